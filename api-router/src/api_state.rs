@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use common::{
     storage::{db::SurrealDbClient, store::StorageManager},
-    utils::config::AppConfig,
+    utils::{config::AppConfig, embedding::EmbeddingProvider},
 };
 
 #[derive(Clone)]
@@ -10,4 +10,5 @@ pub struct ApiState {
     pub db: Arc<SurrealDbClient>,
     pub config: AppConfig,
     pub storage: StorageManager,
+    pub embedding_provider: Arc<EmbeddingProvider>,
 }
@@ -6,7 +6,10 @@ use axum::{
     routing::{get, post},
 };
 use middleware_api_auth::api_auth;
-use routes::{categories::list, ingest::handle, liveness::live, readiness::ready};
+use routes::{
+    action_items, categories::list, facts, ingest::handle, liveness::live, readiness::ready,
+    reingest, suggestions, tasks,
+};
 
 pub mod api_state;
 pub mod error;
@@ -33,6 +36,14 @@ where
             )),
         )
         .route("/categories", get(list))
+        .route("/facts", get(facts::list))
+        .route("/action-items", get(action_items::list))
+        .route("/action-items/{id}/done", post(action_items::set_done))
+        .route("/suggestions", get(suggestions::list))
+        .route("/suggestions/{id}/accept", post(suggestions::accept))
+        .route("/suggestions/{id}/reject", post(suggestions::reject))
+        .route("/content/{id}/reingest", post(reingest::handle))
+        .route("/tasks/{id}/priority", post(tasks::set_priority))
         .route_layer(from_fn_with_state(app_state.clone(), api_auth));
 
     public.merge(protected)
@@ -0,0 +1,80 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+    response::IntoResponse,
+};
+use common::storage::types::{
+    action_item::{ActionItem, ActionItemQuery},
+    entity_fact::{SourceWindow, normalize_date},
+    user::User,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{api_state::ApiState, error::ApiErr};
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ActionItemParams {
+    pub category: Option<String>,
+    /// Only items from documents ingested on or after this `YYYY-MM-DD` date.
+    pub ingested_from: Option<String>,
+    /// Only items from documents ingested on or before this `YYYY-MM-DD` date.
+    pub ingested_to: Option<String>,
+    /// Only items due on or after this `YYYY-MM-DD` date.
+    pub due_from: Option<String>,
+    /// Only items due on or before this `YYYY-MM-DD` date.
+    pub due_to: Option<String>,
+    #[serde(default)]
+    pub include_done: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DoneParams {
+    pub done: bool,
+}
+
+fn parse_due(raw: Option<&str>) -> Result<Option<String>, ApiErr> {
+    match raw.map(str::trim).filter(|raw| !raw.is_empty()) {
+        None => Ok(None),
+        Some(raw) => normalize_date(raw)
+            .map(|date| Some(date.to_string()))
+            .ok_or_else(|| ApiErr::ValidationError(format!("'{raw}' is not a YYYY-MM-DD date"))),
+    }
+}
+
+/// Lists action items extracted from the caller's documents, soonest due first.
+pub async fn list(
+    State(state): State<ApiState>,
+    Extension(user): Extension<User>,
+    Query(params): Query<ActionItemParams>,
+) -> Result<impl IntoResponse, ApiErr> {
+    let query = ActionItemQuery {
+        window: SourceWindow::from_dates(
+            params.category,
+            params.ingested_from.as_deref(),
+            params.ingested_to.as_deref(),
+        )?,
+        due_from: parse_due(params.due_from.as_deref())?,
+        due_to: parse_due(params.due_to.as_deref())?,
+        include_done: params.include_done,
+    };
+    let items = ActionItem::list_for_user(&user.id, &query, &state.db).await?;
+
+    Ok(Json(items))
+}
+
+/// Marks one of the caller's action items as done or open again.
+pub async fn set_done(
+    State(state): State<ApiState>,
+    Extension(user): Extension<User>,
+    Path(id): Path<String>,
+    Json(params): Json<DoneParams>,
+) -> Result<impl IntoResponse, ApiErr> {
+    let item = ActionItem::set_done(&id, &user.id, params.done, &state.db).await?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "id": item.id,
+        "done": item.done,
+    })))
+}
@@ -0,0 +1,60 @@
+use axum::{
+    Extension, Json,
+    extract::{Query, State},
+    response::IntoResponse,
+};
+use common::storage::types::{
+    entity_fact::{EntityAttributeKind, EntityFact, FactQuery, SourceWindow},
+    user::User,
+};
+use serde::Deserialize;
+
+use crate::{api_state::ApiState, error::ApiErr};
+
+#[derive(Debug, Default, Deserialize)]
+pub struct FactParams {
+    /// `date`, `deadline`, `url`, `number`, `amount`, or `person`.
+    pub kind: Option<String>,
+    pub category: Option<String>,
+    /// Only facts from documents ingested on or after this `YYYY-MM-DD` date.
+    pub ingested_from: Option<String>,
+    /// Only facts from documents ingested on or before this `YYYY-MM-DD` date.
+    pub ingested_to: Option<String>,
+    /// Lower bound on the fact value, e.g. the earliest deadline.
+    pub value_from: Option<String>,
+    /// Upper bound on the fact value.
+    pub value_to: Option<String>,
+}
+
+/// Lists typed facts (dates, deadlines, people, amounts, ...) extracted from the
+/// caller's documents.
+pub async fn list(
+    State(state): State<ApiState>,
+    Extension(user): Extension<User>,
+    Query(params): Query<FactParams>,
+) -> Result<impl IntoResponse, ApiErr> {
+    let kind = params
+        .kind
+        .as_deref()
+        .filter(|kind| !kind.trim().is_empty())
+        .map(|kind| {
+            EntityAttributeKind::parse(kind)
+                .ok_or_else(|| ApiErr::ValidationError(format!("unknown fact kind '{kind}'")))
+        })
+        .transpose()?;
+    let window = SourceWindow::from_dates(
+        params.category,
+        params.ingested_from.as_deref(),
+        params.ingested_to.as_deref(),
+    )?;
+
+    let query = FactQuery {
+        window,
+        kind,
+        value_from: params.value_from.filter(|value| !value.trim().is_empty()),
+        value_to: params.value_to.filter(|value| !value.trim().is_empty()),
+    };
+    let facts = EntityFact::find(&user.id, &query, &state.db).await?;
+
+    Ok(Json(facts))
+}
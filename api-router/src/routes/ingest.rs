@@ -3,10 +3,15 @@ use axum_typed_multipart::{FieldData, TryFromMultipart, TypedMultipart};
 use common::{
     error::AppError,
     storage::types::{
-        file_info::FileInfo, ingestion_payload::IngestionPayload, ingestion_task::IngestionTask,
+        file_info::FileInfo,
+        ingestion_payload::IngestionPayload,
+        ingestion_task::{IngestionTask, TaskPriority},
         user::User,
     },
-    utils::ingest_limits::{IngestValidationError, validate_ingest_input},
+    utils::{
+        config::DuplicatePolicy,
+        ingest_limits::{IngestValidationError, validate_ingest_input},
+    },
 };
 use futures::{TryFutureExt, future::try_join_all};
 use serde_json::json;
@@ -20,6 +25,10 @@ pub struct Params {
     pub content: Option<String>,
     pub context: String,
     pub category: String,
+    /// Overrides the configured `ingest_duplicate_policy` (`skip`, `link`, or `force`).
+    pub duplicate_policy: Option<String>,
+    /// Queue priority: `low` for bulk imports, `normal` (default), or `high` for captures.
+    pub priority: Option<String>,
     #[form_data(limit = "20000000")]
     #[form_data(default)]
     pub files: Vec<FieldData<NamedTempFile>>,
@@ -49,6 +58,20 @@ pub async fn handle(
         }
     }
 
+    let duplicate_policy = match input.duplicate_policy.as_deref() {
+        Some(raw) => raw
+            .parse::<DuplicatePolicy>()
+            .map_err(|err| ApiErr::ValidationError(err.to_string()))?,
+        None => state.config.ingest_duplicate_policy,
+    };
+
+    let priority = match input.priority.as_deref() {
+        Some(raw) => raw
+            .parse::<TaskPriority>()
+            .map_err(|err| ApiErr::ValidationError(err.to_string()))?,
+        None => TaskPriority::default(),
+    };
+
     info!(
         user_id = %user_id,
         has_content,
@@ -56,6 +79,7 @@ pub async fn handle(
         context_len = input.context.len(),
         category_len = input.category.len(),
         file_count = input.files.len(),
+        priority = priority.as_str(),
         "Received ingest request"
     );
 
@@ -73,7 +97,27 @@ pub async fn handle(
         user_id.clone(),
     )?;
 
-    IngestionTask::create_all_and_add_to_db(payloads, &user_id, &state.db).await?;
+    let outcomes = IngestionTask::create_all_with_policy(
+        payloads,
+        &user_id,
+        duplicate_policy,
+        priority,
+        &state.db,
+    )
+    .await?;
+
+    let tasks: Vec<_> = outcomes
+        .iter()
+        .map(|outcome| {
+            json!({
+                "task_id": outcome.task().id,
+                "outcome": outcome.as_str(),
+            })
+        })
+        .collect();
 
-    Ok((StatusCode::OK, Json(json!({ "status": "success" }))))
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "status": "success", "tasks": tasks })),
+    ))
 }
@@ -1,4 +1,9 @@
+pub mod action_items;
 pub mod categories;
+pub mod facts;
 pub mod ingest;
 pub mod liveness;
 pub mod readiness;
+pub mod reingest;
+pub mod suggestions;
+pub mod tasks;
@@ -0,0 +1,38 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+    response::IntoResponse,
+};
+use common::storage::types::{
+    ingestion_task::{IngestionTask, TaskPriority},
+    user::User,
+};
+use serde_json::json;
+use tracing::info;
+
+use crate::{api_state::ApiState, error::ApiErr};
+
+/// Queues a pipeline re-run for one stored content item owned by the caller.
+pub async fn handle(
+    State(state): State<ApiState>,
+    Extension(user): Extension<User>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, ApiErr> {
+    let text_content = User::get_and_validate_text_content(&id, &user.id, &state.db).await?;
+
+    let outcome =
+        IngestionTask::create_reingest(&text_content, TaskPriority::Normal, &state.db).await?;
+
+    info!(
+        user_id = %user.id,
+        text_content_id = %id,
+        outcome = outcome.as_str(),
+        "Received re-ingest request"
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "task_id": outcome.task().id,
+        "outcome": outcome.as_str(),
+    })))
+}
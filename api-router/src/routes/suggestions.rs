@@ -0,0 +1,57 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+    response::IntoResponse,
+};
+use common::storage::types::{
+    knowledge_suggestion::{KnowledgeSuggestion, SuggestionEdits, SuggestionStatus},
+    user::User,
+};
+use serde::Deserialize;
+
+use crate::{api_state::ApiState, error::ApiErr};
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SuggestionParams {
+    /// `pending` (default), `accepted`, or `rejected`.
+    #[serde(default)]
+    pub status: SuggestionStatus,
+}
+
+/// Lists AI-suggested entities and relationships staged for the caller's review.
+pub async fn list(
+    State(state): State<ApiState>,
+    Extension(user): Extension<User>,
+    Query(params): Query<SuggestionParams>,
+) -> Result<impl IntoResponse, ApiErr> {
+    let suggestions =
+        KnowledgeSuggestion::list_for_user(&user.id, params.status, &state.db).await?;
+
+    Ok(Json(suggestions))
+}
+
+/// Adds a pending suggestion to the graph. The optional body edits it first.
+pub async fn accept(
+    State(state): State<ApiState>,
+    Extension(user): Extension<User>,
+    Path(id): Path<String>,
+    edits: Option<Json<SuggestionEdits>>,
+) -> Result<impl IntoResponse, ApiErr> {
+    let edits = edits.map(|Json(edits)| edits).unwrap_or_default();
+    let suggestion =
+        KnowledgeSuggestion::accept(&id, &user.id, &edits, &state.db, &state.embedding_provider)
+            .await?;
+
+    Ok(Json(suggestion))
+}
+
+/// Discards a pending suggestion, along with relationships that need a rejected entity.
+pub async fn reject(
+    State(state): State<ApiState>,
+    Extension(user): Extension<User>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, ApiErr> {
+    let suggestion = KnowledgeSuggestion::reject(&id, &user.id, &state.db).await?;
+
+    Ok(Json(suggestion))
+}
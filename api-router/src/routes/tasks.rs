@@ -0,0 +1,48 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+    response::IntoResponse,
+};
+use common::storage::types::{
+    ingestion_task::{IngestionTask, TaskPriority},
+    user::User,
+};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::info;
+
+use crate::{api_state::ApiState, error::ApiErr};
+
+#[derive(Debug, Deserialize)]
+pub struct PriorityParams {
+    /// `low`, `normal`, or `high`.
+    pub priority: String,
+}
+
+/// Moves one of the caller's queued tasks up or down the ingestion queue.
+pub async fn set_priority(
+    State(state): State<ApiState>,
+    Extension(user): Extension<User>,
+    Path(id): Path<String>,
+    Json(params): Json<PriorityParams>,
+) -> Result<impl IntoResponse, ApiErr> {
+    let priority = params
+        .priority
+        .parse::<TaskPriority>()
+        .map_err(|err| ApiErr::ValidationError(err.to_string()))?;
+
+    let task = IngestionTask::set_priority(&id, &user.id, priority, &state.db).await?;
+
+    info!(
+        user_id = %user.id,
+        task_id = %task.id,
+        priority = priority.as_str(),
+        "Updated ingestion task priority"
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "task_id": task.id,
+        "priority": priority.as_str(),
+    })))
+}
@@ -1,6 +1,6 @@
 #![allow(clippy::expect_used)]
 
-use std::sync::Arc;
+use std::{fmt::Write as _, sync::Arc};
 
 use api_router::{api_routes_v1, api_state::ApiState};
 use axum::{
@@ -9,8 +9,24 @@ use axum::{
     http::{Request, StatusCode},
 };
 use common::{
-    storage::{db::SurrealDbClient, store::StorageManager, types::user::User},
-    utils::config::{AppConfig, StorageKind},
+    storage::{
+        db::SurrealDbClient,
+        store::StorageManager,
+        types::{
+            action_item::ActionItem,
+            ingestion_task::{IngestionTask, TaskPriority},
+            knowledge_entity::{KnowledgeEntity, KnowledgeEntityType},
+            knowledge_relationship::KnowledgeRelationship,
+            knowledge_suggestion::KnowledgeSuggestion,
+            system_settings::SystemSettings,
+            text_content::TextContent,
+            user::User,
+        },
+    },
+    utils::{
+        config::{AppConfig, StorageKind},
+        embedding::EmbeddingProvider,
+    },
 };
 use tower::ServiceExt;
 
@@ -32,10 +48,21 @@ async fn build_test_app() -> (Router, Arc<SurrealDbClient>) {
     };
     let storage = StorageManager::new(&config).await.expect("storage manager");
 
+    let embedding_dimensions = usize::try_from(
+        SystemSettings::get_current(&db)
+            .await
+            .expect("system settings")
+            .embedding_dimensions,
+    )
+    .expect("embedding dimensions fit usize");
+
     let state = ApiState {
         db: Arc::clone(&db),
         config,
         storage,
+        embedding_provider: Arc::new(
+            EmbeddingProvider::new_hashed(embedding_dimensions).expect("hashed embeddings"),
+        ),
     };
 
     let router = api_routes_v1(&state).with_state(state);
@@ -157,3 +184,442 @@ async fn authenticated_user_can_list_categories() {
 
     assert_eq!(response.status(), StatusCode::OK);
 }
+
+fn ingest_request(api_key: &str, fields: &[(&str, &str)]) -> Request<Body> {
+    let boundary = "minne-test-boundary";
+    let mut body = String::new();
+    for (name, value) in fields {
+        let _ = write!(
+            body,
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+        );
+    }
+    let _ = write!(body, "--{boundary}--\r\n");
+
+    Request::builder()
+        .method("POST")
+        .uri("/ingest")
+        .header("X-API-Key", api_key)
+        .header(
+            "Content-Type",
+            format!("multipart/form-data; boundary={boundary}"),
+        )
+        .body(Body::from(body))
+        .expect("ingest request")
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn repeated_text_ingest_reports_duplicate_outcome() {
+    let (app, db) = build_test_app().await;
+
+    let user = User::create_new(
+        "api_router_dedup@example.com".to_string(),
+        "test_password".to_string(),
+        &db,
+        "UTC".to_string(),
+        "system".to_string(),
+    )
+    .await
+    .expect("test user");
+    let api_key = User::set_api_key(&user.id, &db).await.expect("api key");
+
+    let fields = [
+        ("content", "The same note submitted twice"),
+        ("context", "capture"),
+        ("category", "notes"),
+    ];
+
+    let first = app
+        .clone()
+        .oneshot(ingest_request(&api_key, &fields))
+        .await
+        .expect("first ingest response");
+    assert_eq!(first.status(), StatusCode::OK);
+    assert!(
+        response_body(first)
+            .await
+            .contains("\"outcome\":\"created\"")
+    );
+
+    let second = app
+        .clone()
+        .oneshot(ingest_request(&api_key, &fields))
+        .await
+        .expect("second ingest response");
+    assert_eq!(second.status(), StatusCode::OK);
+    assert!(
+        response_body(second)
+            .await
+            .contains("\"outcome\":\"linked\"")
+    );
+
+    let mut forced_fields = fields.to_vec();
+    forced_fields.push(("duplicate_policy", "force"));
+    let forced = app
+        .clone()
+        .oneshot(ingest_request(&api_key, &forced_fields))
+        .await
+        .expect("forced ingest response");
+    assert!(
+        response_body(forced)
+            .await
+            .contains("\"outcome\":\"created\"")
+    );
+
+    let mut invalid_fields = fields.to_vec();
+    invalid_fields.push(("duplicate_policy", "sometimes"));
+    let invalid = app
+        .clone()
+        .oneshot(ingest_request(&api_key, &invalid_fields))
+        .await
+        .expect("invalid policy response");
+    assert_eq!(invalid.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn reingest_queues_task_for_owned_content() {
+    let (app, db) = build_test_app().await;
+
+    let user = User::create_new(
+        "api_router_reingest@example.com".to_string(),
+        "test_password".to_string(),
+        &db,
+        "UTC".to_string(),
+        "system".to_string(),
+    )
+    .await
+    .expect("test user");
+    let api_key = User::set_api_key(&user.id, &db).await.expect("api key");
+
+    let content = TextContent::new(
+        "Stored document".to_string(),
+        None,
+        "notes".to_string(),
+        None,
+        None,
+        user.id.clone(),
+    );
+    db.store_item(content.clone()).await.expect("store content");
+
+    let reingest = |id: String| {
+        Request::builder()
+            .method("POST")
+            .uri(format!("/content/{id}/reingest"))
+            .header("X-API-Key", api_key.clone())
+            .body(Body::empty())
+            .expect("reingest request")
+    };
+
+    let first = app
+        .clone()
+        .oneshot(reingest(content.id.clone()))
+        .await
+        .expect("first reingest response");
+    assert_eq!(first.status(), StatusCode::OK);
+    assert!(
+        response_body(first)
+            .await
+            .contains("\"outcome\":\"created\"")
+    );
+
+    let second = app
+        .clone()
+        .oneshot(reingest(content.id.clone()))
+        .await
+        .expect("second reingest response");
+    assert!(
+        response_body(second)
+            .await
+            .contains("\"outcome\":\"linked\"")
+    );
+
+    let missing = app
+        .clone()
+        .oneshot(reingest("missing-content".to_string()))
+        .await
+        .expect("missing reingest response");
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn ingest_priority_is_stored_and_adjustable() {
+    let (app, db) = build_test_app().await;
+
+    let user = User::create_new(
+        "api_router_priority@example.com".to_string(),
+        "test_password".to_string(),
+        &db,
+        "UTC".to_string(),
+        "system".to_string(),
+    )
+    .await
+    .expect("test user");
+    let api_key = User::set_api_key(&user.id, &db).await.expect("api key");
+
+    let response = app
+        .clone()
+        .oneshot(ingest_request(
+            &api_key,
+            &[
+                ("content", "Bulk imported note"),
+                ("context", "import"),
+                ("category", "notes"),
+                ("priority", "low"),
+            ],
+        ))
+        .await
+        .expect("ingest response");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let tasks = User::get_unfinished_ingestion_tasks(&user.id, &db)
+        .await
+        .expect("queued tasks");
+    let task = tasks.first().expect("queued task");
+    assert_eq!(task.priority, TaskPriority::Low.value());
+
+    let set_priority = |priority: &str| {
+        Request::builder()
+            .method("POST")
+            .uri(format!("/tasks/{}/priority", task.id))
+            .header("X-API-Key", api_key.clone())
+            .header("Content-Type", "application/json")
+            .body(Body::from(format!("{{\"priority\":\"{priority}\"}}")))
+            .expect("priority request")
+    };
+
+    let raised = app
+        .clone()
+        .oneshot(set_priority("high"))
+        .await
+        .expect("priority response");
+    assert_eq!(raised.status(), StatusCode::OK);
+    let stored: IngestionTask = db
+        .get_item(&task.id)
+        .await
+        .expect("load task")
+        .expect("task exists");
+    assert_eq!(stored.priority, TaskPriority::High.value());
+
+    let invalid = app
+        .clone()
+        .oneshot(set_priority("urgent"))
+        .await
+        .expect("invalid priority response");
+    assert_eq!(invalid.status(), StatusCode::BAD_REQUEST);
+
+    let bad_ingest = app
+        .clone()
+        .oneshot(ingest_request(
+            &api_key,
+            &[
+                ("content", "Another note"),
+                ("context", "import"),
+                ("category", "notes"),
+                ("priority", "urgent"),
+            ],
+        ))
+        .await
+        .expect("bad ingest response");
+    assert_eq!(bad_ingest.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn action_items_can_be_listed_and_completed() {
+    let (app, db) = build_test_app().await;
+
+    let user = User::create_new(
+        "api_router_actions@example.com".to_string(),
+        "test_password".to_string(),
+        &db,
+        "UTC".to_string(),
+        "system".to_string(),
+    )
+    .await
+    .expect("test user");
+    let api_key = User::set_api_key(&user.id, &db).await.expect("api key");
+
+    let item = ActionItem::new(
+        "meeting-notes".to_string(),
+        user.id.clone(),
+        "Send the budget draft",
+        Some("Ada"),
+        Some("2026-03-31"),
+    )
+    .expect("valid action item");
+    db.store_item(item.clone())
+        .await
+        .expect("store action item");
+
+    let list = |uri: &str| {
+        Request::builder()
+            .uri(uri)
+            .header("X-API-Key", api_key.clone())
+            .body(Body::empty())
+            .expect("list request")
+    };
+
+    let response = app
+        .clone()
+        .oneshot(list("/action-items?due_from=2026-03-01&due_to=2026-03-31"))
+        .await
+        .expect("list response");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(
+        response_body(response)
+            .await
+            .contains("Send the budget draft")
+    );
+
+    let invalid = app
+        .clone()
+        .oneshot(list("/action-items?due_from=soon"))
+        .await
+        .expect("invalid list response");
+    assert_eq!(invalid.status(), StatusCode::BAD_REQUEST);
+
+    let done = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/action-items/{}/done", item.id))
+                .header("X-API-Key", api_key.clone())
+                .header("Content-Type", "application/json")
+                .body(Body::from("{\"done\":true}"))
+                .expect("done request"),
+        )
+        .await
+        .expect("done response");
+    assert_eq!(done.status(), StatusCode::OK);
+
+    let response = app
+        .clone()
+        .oneshot(list("/action-items"))
+        .await
+        .expect("list response");
+    assert_eq!(response_body(response).await, "[]");
+
+    let facts = app
+        .clone()
+        .oneshot(list("/facts?kind=deadline"))
+        .await
+        .expect("facts response");
+    assert_eq!(facts.status(), StatusCode::OK);
+}
+
+/// Stages two entities from a "contract" document and a relationship between them.
+async fn stage_contract_suggestions(
+    db: &SurrealDbClient,
+    user_id: &str,
+) -> (KnowledgeEntity, [String; 3]) {
+    let dimensions = usize::try_from(
+        SystemSettings::get_current(db)
+            .await
+            .expect("system settings")
+            .embedding_dimensions,
+    )
+    .expect("embedding dimensions fit usize");
+
+    let entity = |name: &str| {
+        KnowledgeEntity::new(
+            "contract".to_string(),
+            name.to_string(),
+            format!("{name} from the contract"),
+            KnowledgeEntityType::Custom("Person".to_string()),
+            None,
+            user_id.to_string(),
+        )
+    };
+    let (ada, acme) = (entity("Ada"), entity("Acme"));
+    let staged = [
+        KnowledgeSuggestion::for_entity(ada.clone(), vec![0.1; dimensions]),
+        KnowledgeSuggestion::for_entity(acme.clone(), vec![0.2; dimensions]),
+        KnowledgeSuggestion::for_relationship(KnowledgeRelationship::new(
+            ada.id.clone(),
+            acme.id.clone(),
+            user_id.to_string(),
+            "contract".to_string(),
+            "RelatedTo".to_string(),
+        )),
+    ];
+    for suggestion in &staged {
+        db.store_item(suggestion.clone())
+            .await
+            .expect("store suggestion");
+    }
+
+    (ada, staged.map(|suggestion| suggestion.id))
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn suggestions_can_be_accepted_and_rejected() {
+    let (app, db) = build_test_app().await;
+
+    let user = User::create_new(
+        "api_router_review@example.com".to_string(),
+        "test_password".to_string(),
+        &db,
+        "UTC".to_string(),
+        "system".to_string(),
+    )
+    .await
+    .expect("test user");
+    let api_key = User::set_api_key(&user.id, &db).await.expect("api key");
+    let (ada, [ada_suggestion, acme_suggestion, relationship_suggestion]) =
+        stage_contract_suggestions(&db, &user.id).await;
+
+    let post = |uri: String, body: &'static str| {
+        Request::builder()
+            .method("POST")
+            .uri(uri)
+            .header("X-API-Key", api_key.clone())
+            .header("Content-Type", "application/json")
+            .body(Body::from(body))
+            .expect("post request")
+    };
+
+    let early = app
+        .clone()
+        .oneshot(post(
+            format!("/suggestions/{relationship_suggestion}/accept"),
+            "{}",
+        ))
+        .await
+        .expect("early accept response");
+    assert_eq!(early.status(), StatusCode::BAD_REQUEST);
+
+    let accepted = app
+        .clone()
+        .oneshot(post(
+            format!("/suggestions/{ada_suggestion}/accept"),
+            "{\"name\":\"Ada Lovelace\"}",
+        ))
+        .await
+        .expect("accept response");
+    assert_eq!(accepted.status(), StatusCode::OK);
+    let stored = User::get_and_validate_knowledge_entity(&ada.id, &user.id, &db)
+        .await
+        .expect("accepted entity stored");
+    assert_eq!(stored.name, "Ada Lovelace");
+
+    let rejected = app
+        .clone()
+        .oneshot(post(format!("/suggestions/{acme_suggestion}/reject"), ""))
+        .await
+        .expect("reject response");
+    assert_eq!(rejected.status(), StatusCode::OK);
+
+    let pending = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/suggestions")
+                .header("X-API-Key", api_key.clone())
+                .body(Body::empty())
+                .expect("list request"),
+        )
+        .await
+        .expect("list response");
+    assert_eq!(pending.status(), StatusCode::OK);
+    assert_eq!(response_body(pending).await, "[]");
+}
@@ -40,6 +40,61 @@ impl EmbeddingError {
     }
 }
 
+/// Errors from chat-completion providers.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Error, Debug)]
+pub enum LlmError {
+    #[error("openai error: {0}")]
+    OpenAI(Box<OpenAIError>),
+    #[error("http error: {0}")]
+    Http(Box<reqwest::Error>),
+    #[error("{backend} returned status {status}: {message}")]
+    Status {
+        backend: &'static str,
+        status: u16,
+        message: String,
+    },
+    #[error("malformed provider response: {0}")]
+    Response(String),
+    #[error("unsupported by backend: {0}")]
+    Unsupported(String),
+    #[error("request timed out after {0}s")]
+    Timeout(u64),
+}
+
+impl LlmError {
+    /// Whether the provider rejected the prompt for exceeding the model's context window.
+    #[must_use]
+    pub fn is_context_length(&self) -> bool {
+        let message = match self {
+            Self::OpenAI(err) => err.to_string(),
+            Self::Status { message, .. } | Self::Response(message) => message.clone(),
+            Self::Http(_) | Self::Unsupported(_) | Self::Timeout(_) => return false,
+        }
+        .to_ascii_lowercase();
+        [
+            "context_length",
+            "context length",
+            "context window",
+            "prompt is too long",
+        ]
+        .iter()
+        .any(|marker| message.contains(marker))
+    }
+}
+
+impl From<OpenAIError> for LlmError {
+    fn from(err: OpenAIError) -> Self {
+        Self::OpenAI(Box::new(err))
+    }
+}
+
+impl From<reqwest::Error> for LlmError {
+    fn from(err: reqwest::Error) -> Self {
+        Self::Http(Box::new(err))
+    }
+}
+
 // Core internal errors
 #[allow(clippy::module_name_repetitions)]
 #[derive(Error, Debug)]
@@ -50,6 +105,8 @@ pub enum AppError {
     OpenAI(Box<OpenAIError>),
     #[error("embedding error: {0}")]
     Embedding(#[from] EmbeddingError),
+    #[error("llm error: {0}")]
+    Llm(#[from] LlmError),
     #[error("file error: {0}")]
     File(#[from] FileError),
     #[error("not found: {0}")]
@@ -235,16 +235,6 @@ impl SurrealDbClient {
         E: StoredObject + DeserializeOwned + Clone + Send + Sync,
         Emb: EmbeddingRecord + Send + Sync,
     {
-        // Generic row that works with both `entity_id` and `chunk_id` link
-        // fields via `#[serde(alias)]`.  SurrealDB's `FETCH` resolves the link
-        // server-side so we get the full entity in a single round-trip.
-        #[derive(serde::Deserialize)]
-        struct FetchRow<Ent> {
-            score: f32,
-            #[serde(alias = "entity_id", alias = "chunk_id")]
-            entity: Option<Ent>,
-        }
-
         let link_field = Emb::link_field();
         let sql = format!(
             r#"
@@ -263,31 +253,100 @@ impl SurrealDbClient {
             take = take,
         );
 
-        let mut response = self
+        let response = self
             .client
             .query(sql)
             .bind(("embedding", query_embedding.to_vec()))
             .bind(("user_id", user_id.to_string()))
             .await?;
 
-        response = response.check()?;
-
-        let rows: Vec<FetchRow<E>> = response.take(0)?;
+        fetched_search_results::<E, Emb>(response)
+    }
 
-        let mut results = Vec::with_capacity(rows.len());
-        for r in rows {
-            if let Some(entity) = r.entity {
-                results.push((entity, r.score));
-            } else {
-                tracing::warn!(
-                    "Vector search hit orphaned {} row with missing {link_field}",
-                    Emb::table_name()
-                );
-            }
+    /// Exact similarity ranking of the embeddings belonging to `source_ids`.
+    ///
+    /// Unlike [`Self::vector_search`] this scans the sources' rows instead of querying
+    /// the HNSW index, so every row of a small set of sources is considered.
+    pub async fn vector_search_in_sources<E, Emb>(
+        &self,
+        take: usize,
+        query_embedding: &[f32],
+        source_ids: &[String],
+        user_id: &str,
+    ) -> Result<Vec<(E, f32)>, AppError>
+    where
+        E: StoredObject + DeserializeOwned + Clone + Send + Sync,
+        Emb: EmbeddingRecord + Send + Sync,
+    {
+        if source_ids.is_empty() || take == 0 {
+            return Ok(Vec::new());
         }
 
-        Ok(results)
+        let link_field = Emb::link_field();
+        let sql = format!(
+            r#"
+            SELECT
+                {link_field},
+                vector::similarity::cosine(embedding, $embedding) AS score
+            FROM {emb_table}
+            WHERE user_id = $user_id
+              AND source_id IN $sources
+            ORDER BY score DESC
+            LIMIT $limit
+            FETCH {link_field}
+            "#,
+            link_field = link_field,
+            emb_table = Emb::table_name(),
+        );
+
+        let response = self
+            .client
+            .query(sql)
+            .bind(("embedding", query_embedding.to_vec()))
+            .bind(("sources", source_ids.to_vec()))
+            .bind(("user_id", user_id.to_string()))
+            .bind(("limit", i64::try_from(take).unwrap_or(i64::MAX)))
+            .await?;
+
+        fetched_search_results::<E, Emb>(response)
+    }
+}
+
+/// Turns `SELECT <link>, score ... FETCH <link>` rows into `(entity, score)` pairs,
+/// dropping (and logging) orphaned embeddings whose entity no longer exists.
+#[allow(clippy::result_large_err)]
+fn fetched_search_results<E, Emb>(response: surrealdb::Response) -> Result<Vec<(E, f32)>, AppError>
+where
+    E: DeserializeOwned,
+    Emb: EmbeddingRecord,
+{
+    // Generic row that works with both `entity_id` and `chunk_id` link
+    // fields via `#[serde(alias)]`.  SurrealDB's `FETCH` resolves the link
+    // server-side so we get the full entity in a single round-trip.
+    #[derive(serde::Deserialize)]
+    struct FetchRow<Ent> {
+        score: f32,
+        #[serde(alias = "entity_id", alias = "chunk_id")]
+        entity: Option<Ent>,
     }
+
+    let mut response = response.check()?;
+    let rows: Vec<FetchRow<E>> = response.take(0)?;
+
+    let mut results = Vec::with_capacity(rows.len());
+    for r in rows {
+        if let Some(entity) = r.entity {
+            results.push((entity, r.score));
+        } else {
+            tracing::warn!(
+                "Vector search hit orphaned {} row with missing {}",
+                Emb::table_name(),
+                Emb::link_field()
+            );
+        }
+    }
+
+    Ok(results)
 }
 
 impl Deref for SurrealDbClient {
@@ -0,0 +1,239 @@
+use uuid::Uuid;
+
+use crate::{error::AppError, storage::db::SurrealDbClient, stored_object};
+
+use super::entity_fact::{SourceWindow, normalize_date};
+
+stored_object!(
+    /// A task extracted from ingested content, such as "send the budget to Ada by Friday".
+    ///
+    /// Action items belong to the document they came from and are replaced when it is
+    /// re-ingested.
+    ActionItem, "action_item", {
+    user_id: String,
+    source_id: String,
+    description: String,
+    /// Who is expected to do it, when the content says.
+    #[serde(default)]
+    owner: Option<String>,
+    /// Due date as `YYYY-MM-DD`, when one was stated.
+    #[serde(default)]
+    due_date: Option<String>,
+    #[serde(default)]
+    done: bool
+});
+
+/// Filters for [`ActionItem::list_for_user`].
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Clone, Default)]
+pub struct ActionItemQuery {
+    pub window: SourceWindow,
+    /// Only items due on or after this `YYYY-MM-DD` date.
+    pub due_from: Option<String>,
+    /// Only items due on or before this `YYYY-MM-DD` date.
+    pub due_to: Option<String>,
+    pub include_done: bool,
+}
+
+impl ActionItem {
+    /// Builds an open action item, dropping a blank owner and a due date that is not a
+    /// valid `YYYY-MM-DD` date. Returns `None` for an empty description.
+    #[must_use]
+    pub fn new(
+        source_id: String,
+        user_id: String,
+        description: &str,
+        owner: Option<&str>,
+        due_date: Option<&str>,
+    ) -> Option<Self> {
+        let description = description.trim();
+        if description.is_empty() {
+            return None;
+        }
+
+        let now = Utc::now();
+        Some(Self {
+            id: Uuid::new_v4().to_string(),
+            created_at: now,
+            updated_at: now,
+            user_id,
+            source_id,
+            description: description.to_string(),
+            owner: owner
+                .map(str::trim)
+                .filter(|owner| !owner.is_empty())
+                .map(ToString::to_string),
+            due_date: due_date
+                .and_then(normalize_date)
+                .map(|date| date.to_string()),
+            done: false,
+        })
+    }
+
+    /// Action items of `user_id` matching `query`. Items with a due date come first,
+    /// soonest first, followed by undated ones in the order they were extracted.
+    pub async fn list_for_user(
+        user_id: &str,
+        query: &ActionItemQuery,
+        db: &SurrealDbClient,
+    ) -> Result<Vec<Self>, AppError> {
+        let mut conditions = vec!["user_id = $user_id"];
+        if !query.include_done {
+            conditions.push("done = false");
+        }
+        if query.due_from.is_some() {
+            conditions.push("due_date != NONE AND due_date >= $due_from");
+        }
+        if query.due_to.is_some() {
+            conditions.push("due_date != NONE AND due_date <= $due_to");
+        }
+        conditions.extend(query.window.conditions());
+        let sql = format!(
+            "SELECT * FROM type::table($table) WHERE {} ORDER BY created_at ASC",
+            conditions.join(" AND ")
+        );
+
+        let request = db
+            .client
+            .query(sql)
+            .bind(("table", Self::table_name()))
+            .bind(("user_id", user_id.to_string()))
+            .bind(("due_from", query.due_from.clone()))
+            .bind(("due_to", query.due_to.clone()));
+        let mut items: Vec<Self> = query.window.bind(request).await?.take(0)?;
+
+        items.sort_by(|a, b| match (&a.due_date, &b.due_date) {
+            (Some(a_due), Some(b_due)) => a_due.cmp(b_due),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        });
+
+        Ok(items)
+    }
+
+    /// Action items extracted from one document owned by `user_id`.
+    pub async fn list_for_source(
+        source_id: &str,
+        user_id: &str,
+        db: &SurrealDbClient,
+    ) -> Result<Vec<Self>, AppError> {
+        let items: Vec<Self> = db
+            .client
+            .query(
+                "SELECT * FROM type::table($table)
+                 WHERE source_id = $source_id AND user_id = $user_id ORDER BY created_at ASC",
+            )
+            .bind(("table", Self::table_name()))
+            .bind(("source_id", source_id.to_string()))
+            .bind(("user_id", user_id.to_string()))
+            .await?
+            .take(0)?;
+
+        Ok(items)
+    }
+
+    /// Marks an action item owned by `user_id` as done or open again.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the item does not exist for `user_id`.
+    pub async fn set_done(
+        id: &str,
+        user_id: &str,
+        done: bool,
+        db: &SurrealDbClient,
+    ) -> Result<Self, AppError> {
+        let updated: Option<Self> = db
+            .client
+            .query(
+                "UPDATE type::thing($table, $id) SET done = $done, updated_at = time::now()
+                 WHERE user_id = $user_id RETURN AFTER",
+            )
+            .bind(("table", Self::table_name()))
+            .bind(("id", id.to_string()))
+            .bind(("done", done))
+            .bind(("user_id", user_id.to_string()))
+            .await?
+            .take(0)?;
+
+        updated.ok_or_else(|| AppError::NotFound(format!("action item {id} not found")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used)]
+    use super::*;
+    use crate::test_utils::setup_test_db;
+
+    fn item(source_id: &str, description: &str, due_date: Option<&str>) -> ActionItem {
+        ActionItem::new(
+            source_id.to_string(),
+            "action_user".to_string(),
+            description,
+            None,
+            due_date,
+        )
+        .expect("valid action item")
+    }
+
+    #[test]
+    fn new_normalizes_owner_and_due_date() {
+        let item = ActionItem::new(
+            "source".to_string(),
+            "user".to_string(),
+            "  Send the budget ",
+            Some("  "),
+            Some("2026-02-30"),
+        )
+        .expect("valid action item");
+        assert_eq!(item.description, "Send the budget");
+        assert_eq!(item.owner, None);
+        assert_eq!(item.due_date, None);
+
+        assert!(ActionItem::new("s".into(), "u".into(), " ", None, None).is_none());
+    }
+
+    #[tokio::test]
+    async fn list_for_user_orders_by_due_date_and_hides_done_items() -> anyhow::Result<()> {
+        let db = setup_test_db().await?;
+
+        let undated = item("doc", "Think about hiring", None);
+        let later = item("doc", "Ship release", Some("2026-06-01"));
+        let sooner = item("doc", "Book venue", Some("2026-05-01"));
+        let finished = item("doc", "Write agenda", Some("2026-04-01"));
+        for action in [&undated, &later, &sooner, &finished] {
+            db.store_item(action.clone()).await?;
+        }
+        ActionItem::set_done(&finished.id, "action_user", true, &db).await?;
+
+        let open =
+            ActionItem::list_for_user("action_user", &ActionItemQuery::default(), &db).await?;
+        let descriptions: Vec<&str> = open.iter().map(|i| i.description.as_str()).collect();
+        assert_eq!(
+            descriptions,
+            vec!["Book venue", "Ship release", "Think about hiring"]
+        );
+
+        let in_may = ActionItem::list_for_user(
+            "action_user",
+            &ActionItemQuery {
+                due_from: Some("2026-05-01".to_string()),
+                due_to: Some("2026-05-31".to_string()),
+                include_done: true,
+                ..Default::default()
+            },
+            &db,
+        )
+        .await?;
+        assert_eq!(in_may.len(), 1);
+        assert_eq!(in_may.first().expect("one item").id, sooner.id);
+
+        assert!(matches!(
+            ActionItem::set_done(&sooner.id, "someone_else", true, &db).await,
+            Err(AppError::NotFound(_))
+        ));
+        Ok(())
+    }
+}
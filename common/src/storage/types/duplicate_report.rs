@@ -0,0 +1,553 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    storage::{
+        db::SurrealDbClient,
+        types::{
+            knowledge_entity::KnowledgeEntity,
+            knowledge_entity_embedding::KnowledgeEntityEmbedding, user::User,
+        },
+    },
+    stored_object,
+};
+
+/// Pairs scoring below this are not considered duplicates.
+pub const MIN_DUPLICATE_CONFIDENCE: f32 = 0.82;
+/// Weight of embedding similarity in a pair's confidence; names make up the rest.
+const EMBEDDING_WEIGHT: f32 = 0.6;
+/// Longest description excerpt kept per member in the stored report.
+const MAX_DESCRIPTION_EXCERPT_CHARS: usize = 240;
+/// A report stuck in `Running` for longer than this may be restarted.
+const STALE_SCAN_MINUTES: i64 = 30;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::module_name_repetitions)]
+pub enum DuplicateReportStatus {
+    Running,
+    Complete,
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateGroupStatus {
+    Open,
+    Merged,
+    Dismissed,
+}
+
+/// An entity as it looked when the scan ran.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DuplicateMember {
+    pub entity_id: String,
+    pub name: String,
+    pub entity_type: String,
+    pub description: String,
+}
+
+/// Entities that look like the same thing, oldest first.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DuplicateGroup {
+    pub id: String,
+    /// Mean confidence of the pairs that linked the group, in `0.0..=1.0`.
+    pub confidence: f32,
+    pub embedding_similarity: f32,
+    pub name_similarity: f32,
+    pub members: Vec<DuplicateMember>,
+    pub status: DuplicateGroupStatus,
+}
+
+stored_object!(
+    /// Candidate duplicate entities for one user, rebuilt by a background scan.
+    ///
+    /// There is one report per user, keyed by the user id. Groups the user dismissed
+    /// are kept across scans so they are not suggested again.
+    DuplicateReport, "duplicate_report", {
+    user_id: String,
+    status: DuplicateReportStatus,
+    entity_count: usize,
+    groups: Vec<DuplicateGroup>,
+    /// Entity id sets the user marked as not duplicates.
+    dismissed: Vec<Vec<String>>,
+    error: Option<String>,
+    #[serde(
+        serialize_with = "serialize_option_datetime",
+        deserialize_with = "deserialize_option_datetime",
+        default
+    )]
+    completed_at: Option<DateTime<Utc>>
+});
+
+impl DuplicateReport {
+    pub async fn get_for_user(
+        user_id: &str,
+        db: &SurrealDbClient,
+    ) -> Result<Option<Self>, AppError> {
+        Ok(db.get_item(user_id).await?)
+    }
+
+    /// Whether a scan is in progress and has not gone stale.
+    #[must_use]
+    pub fn is_running(&self) -> bool {
+        self.status == DuplicateReportStatus::Running
+            && Utc::now().signed_duration_since(self.updated_at)
+                < chrono::Duration::minutes(STALE_SCAN_MINUTES)
+    }
+
+    /// Marks the user's report as running and returns it, keeping earlier dismissals.
+    ///
+    /// Returns `None` when a scan is already running, so callers spawn at most one.
+    pub async fn begin_scan(user_id: &str, db: &SurrealDbClient) -> Result<Option<Self>, AppError> {
+        let previous = Self::get_for_user(user_id, db).await?;
+        if previous.as_ref().is_some_and(Self::is_running) {
+            return Ok(None);
+        }
+
+        let now = Utc::now();
+        let report = Self {
+            id: user_id.to_string(),
+            created_at: now,
+            updated_at: now,
+            user_id: user_id.to_string(),
+            status: DuplicateReportStatus::Running,
+            entity_count: 0,
+            groups: previous
+                .as_ref()
+                .map(|report| report.groups.clone())
+                .unwrap_or_default(),
+            dismissed: previous.map(|report| report.dismissed).unwrap_or_default(),
+            error: None,
+            completed_at: None,
+        };
+        db.upsert_item(report.clone()).await?;
+
+        Ok(Some(report))
+    }
+
+    /// Runs the scan for a report returned by [`Self::begin_scan`] and stores the
+    /// outcome, recording failures on the report instead of returning them.
+    pub async fn run_scan(mut self, db: &SurrealDbClient) {
+        match self.scan(db).await {
+            Ok(()) => {
+                info!(
+                    user_id = %self.user_id,
+                    entities = self.entity_count,
+                    groups = self.groups.len(),
+                    "Duplicate entity scan finished"
+                );
+                self.status = DuplicateReportStatus::Complete;
+            }
+            Err(err) => {
+                error!(user_id = %self.user_id, error = %err, "Duplicate entity scan failed");
+                self.status = DuplicateReportStatus::Failed;
+                self.error = Some(err.to_string());
+            }
+        }
+
+        let now = Utc::now();
+        self.updated_at = now;
+        self.completed_at = Some(now);
+        if let Err(err) = db.upsert_item(self.clone()).await {
+            error!(user_id = %self.user_id, error = %err, "Failed to store duplicate report");
+        }
+    }
+
+    async fn scan(&mut self, db: &SurrealDbClient) -> Result<(), AppError> {
+        let entities = User::get_knowledge_entities(&self.user_id, db).await?;
+        let embeddings: Vec<KnowledgeEntityEmbedding> = db
+            .client
+            .query("SELECT * FROM type::table($table) WHERE user_id = $user_id")
+            .bind(("table", KnowledgeEntityEmbedding::table_name()))
+            .bind(("user_id", self.user_id.clone()))
+            .await?
+            .take(0)?;
+        let embeddings: HashMap<String, Vec<f32>> = embeddings
+            .into_iter()
+            .map(|embedding| (embedding.entity_id.key().to_string(), embedding.embedding))
+            .collect();
+
+        self.entity_count = entities.len();
+        let dismissed = self.dismissed.clone();
+        self.groups = tokio::task::spawn_blocking(move || {
+            find_duplicate_groups(&entities, &embeddings, &dismissed)
+        })
+        .await
+        .map_err(|err| AppError::internal(format!("duplicate scan panicked: {err}")))?;
+
+        Ok(())
+    }
+
+    /// Flags a group as handled; dismissed groups are remembered for future scans.
+    pub async fn resolve_group(
+        user_id: &str,
+        group_id: &str,
+        status: DuplicateGroupStatus,
+        db: &SurrealDbClient,
+    ) -> Result<Self, AppError> {
+        let mut report = Self::get_for_user(user_id, db)
+            .await?
+            .ok_or_else(|| AppError::NotFound("duplicate report not found".into()))?;
+        let group = report
+            .groups
+            .iter_mut()
+            .find(|group| group.id == group_id)
+            .ok_or_else(|| AppError::NotFound("duplicate group not found".into()))?;
+
+        group.status = status;
+        if status == DuplicateGroupStatus::Dismissed {
+            let ids = group
+                .members
+                .iter()
+                .map(|member| member.entity_id.clone())
+                .collect();
+            report.dismissed.push(ids);
+        }
+        report.updated_at = Utc::now();
+        db.upsert_item(report.clone()).await?;
+
+        Ok(report)
+    }
+
+    /// Looks up an open group, for review actions.
+    #[must_use]
+    pub fn open_group(&self, group_id: &str) -> Option<&DuplicateGroup> {
+        self.groups
+            .iter()
+            .find(|group| group.id == group_id && group.status == DuplicateGroupStatus::Open)
+    }
+}
+
+struct PairScore {
+    confidence: f32,
+    embedding: f32,
+    name: f32,
+}
+
+/// Clusters entities whose pairwise confidence clears [`MIN_DUPLICATE_CONFIDENCE`].
+///
+/// Compares every pair, so cost grows quadratically with the entity count; it is meant
+/// to run off the request path. Pairs inside a dismissed set are never linked.
+#[must_use]
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::too_many_lines,
+    clippy::implicit_hasher
+)]
+pub fn find_duplicate_groups(
+    entities: &[KnowledgeEntity],
+    embeddings: &HashMap<String, Vec<f32>>,
+    dismissed: &[Vec<String>],
+) -> Vec<DuplicateGroup> {
+    let dismissed: Vec<HashSet<&str>> = dismissed
+        .iter()
+        .map(|ids| ids.iter().map(String::as_str).collect())
+        .collect();
+    let prepared: Vec<(&KnowledgeEntity, String, Option<Vec<f32>>)> = entities
+        .iter()
+        .map(|entity| {
+            let vector = embeddings.get(&entity.id).and_then(|v| unit_vector(v));
+            (entity, normalize_name(&entity.name), vector)
+        })
+        .collect();
+
+    let mut adjacency: HashMap<&str, Vec<&str>> = HashMap::new();
+    let mut pairs: HashMap<(&str, &str), PairScore> = HashMap::new();
+    for (index, (left, left_name, left_vector)) in prepared.iter().enumerate() {
+        for (right, right_name, right_vector) in prepared.iter().skip(index.saturating_add(1)) {
+            let dismissed_pair = dismissed
+                .iter()
+                .any(|ids| ids.contains(left.id.as_str()) && ids.contains(right.id.as_str()));
+            if dismissed_pair {
+                continue;
+            }
+            let Some(score) = score_pair(
+                left_name,
+                left_vector.as_deref(),
+                right_name,
+                right_vector.as_deref(),
+            ) else {
+                continue;
+            };
+            adjacency.entry(&left.id).or_default().push(&right.id);
+            adjacency.entry(&right.id).or_default().push(&left.id);
+            pairs.insert(pair_key(&left.id, &right.id), score);
+        }
+    }
+
+    let by_id: HashMap<&str, &KnowledgeEntity> = entities
+        .iter()
+        .map(|entity| (entity.id.as_str(), entity))
+        .collect();
+    let mut visited: HashSet<&str> = HashSet::new();
+    let mut groups = Vec::new();
+    for entity in entities {
+        if visited.contains(entity.id.as_str()) || !adjacency.contains_key(entity.id.as_str()) {
+            continue;
+        }
+
+        let mut component = Vec::new();
+        let mut queue = VecDeque::from([entity.id.as_str()]);
+        visited.insert(&entity.id);
+        while let Some(id) = queue.pop_front() {
+            component.push(id);
+            for &next in adjacency.get(id).into_iter().flatten() {
+                if visited.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        let scores: Vec<&PairScore> = pairs
+            .iter()
+            .filter(|((a, b), _)| component.contains(a) && component.contains(b))
+            .map(|(_, score)| score)
+            .collect();
+        let mean = |value: fn(&PairScore) -> f32| {
+            scores.iter().map(|score| value(score)).sum::<f32>() / scores.len().max(1) as f32
+        };
+
+        let mut members: Vec<&KnowledgeEntity> = component
+            .iter()
+            .filter_map(|id| by_id.get(id).copied())
+            .collect();
+        members.sort_by_key(|member| member.created_at);
+
+        groups.push(DuplicateGroup {
+            id: Uuid::new_v4().to_string(),
+            confidence: mean(|score| score.confidence),
+            embedding_similarity: mean(|score| score.embedding),
+            name_similarity: mean(|score| score.name),
+            members: members
+                .into_iter()
+                .map(|member| DuplicateMember {
+                    entity_id: member.id.clone(),
+                    name: member.name.clone(),
+                    entity_type: member.entity_type.to_string(),
+                    description: member
+                        .description
+                        .chars()
+                        .take(MAX_DESCRIPTION_EXCERPT_CHARS)
+                        .collect(),
+                })
+                .collect(),
+            status: DuplicateGroupStatus::Open,
+        });
+    }
+
+    groups.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    groups
+}
+
+fn pair_key<'a>(a: &'a str, b: &'a str) -> (&'a str, &'a str) {
+    if a <= b { (a, b) } else { (b, a) }
+}
+
+/// Scores a pair, returning `None` when it does not look like a duplicate.
+///
+/// Identical normalized names always count; otherwise names and embeddings are
+/// blended, falling back to the name alone when either embedding is missing.
+fn score_pair(
+    left_name: &str,
+    left_vector: Option<&[f32]>,
+    right_name: &str,
+    right_vector: Option<&[f32]>,
+) -> Option<PairScore> {
+    let name = name_similarity(left_name, right_name);
+    let embedding = match (left_vector, right_vector) {
+        (Some(left), Some(right)) => Some(
+            left.iter()
+                .zip(right)
+                .map(|(l, r)| l * r)
+                .sum::<f32>()
+                .clamp(0.0, 1.0),
+        ),
+        _ => None,
+    };
+
+    let confidence = embedding.map_or(name, |embedding| {
+        EMBEDDING_WEIGHT * embedding + (1.0 - EMBEDDING_WEIGHT) * name
+    });
+    let exact_name = !left_name.is_empty() && left_name == right_name;
+    (exact_name || confidence >= MIN_DUPLICATE_CONFIDENCE).then_some(PairScore {
+        confidence: if exact_name {
+            confidence.max(MIN_DUPLICATE_CONFIDENCE)
+        } else {
+            confidence
+        },
+        embedding: embedding.unwrap_or(0.0),
+        name,
+    })
+}
+
+fn unit_vector(vector: &[f32]) -> Option<Vec<f32>> {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    (norm > f32::EPSILON).then(|| vector.iter().map(|v| v / norm).collect())
+}
+
+/// Lowercases and collapses punctuation so "Rust-lang" and "rust lang" compare equal.
+fn normalize_name(name: &str) -> String {
+    name.split(|ch: char| !ch.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Dice coefficient over character bigrams of two normalized names.
+#[allow(clippy::cast_precision_loss)]
+fn name_similarity(left: &str, right: &str) -> f32 {
+    if left == right {
+        return if left.is_empty() { 0.0 } else { 1.0 };
+    }
+
+    let bigrams = |name: &str| -> HashMap<(char, char), usize> {
+        let chars: Vec<char> = name.chars().filter(|ch| !ch.is_whitespace()).collect();
+        let mut counts = HashMap::new();
+        for window in chars.windows(2) {
+            if let [a, b] = window {
+                let count: &mut usize = counts.entry((*a, *b)).or_default();
+                *count = count.saturating_add(1);
+            }
+        }
+        counts
+    };
+
+    let left = bigrams(left);
+    let right = bigrams(right);
+    let total = left
+        .values()
+        .sum::<usize>()
+        .saturating_add(right.values().sum::<usize>());
+    if total == 0 {
+        return 0.0;
+    }
+    let shared: usize = left
+        .iter()
+        .map(|(bigram, count)| (*count).min(right.get(bigram).copied().unwrap_or(0)))
+        .sum();
+
+    2.0 * shared as f32 / total as f32
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::must_use_candidate)]
+    use super::*;
+    use crate::storage::types::knowledge_entity::KnowledgeEntityType;
+    use crate::test_utils::setup_test_db;
+
+    fn entity(name: &str) -> KnowledgeEntity {
+        KnowledgeEntity::new(
+            "doc".to_string(),
+            name.to_string(),
+            format!("{name} description"),
+            KnowledgeEntityType::Idea,
+            None,
+            "user-1".to_string(),
+        )
+    }
+
+    #[test]
+    fn name_similarity_scores_near_spellings_highly() {
+        assert!((name_similarity("rust lang", "rust lang") - 1.0).abs() < f32::EPSILON);
+        assert!(name_similarity("postgresql", "postgres sql") > 0.9);
+        assert!(name_similarity("rust", "surrealdb") < 0.3);
+        assert_eq!(normalize_name("Rust-Lang!"), "rust lang");
+    }
+
+    #[test]
+    fn groups_use_embeddings_names_and_dismissals() {
+        let rust = entity("Rust");
+        let rust_lang = entity("rust-lang");
+        let rust_exact = entity("RUST");
+        let surreal = entity("SurrealDB");
+        let surreal_db = entity("Surreal DB");
+        let entities = vec![
+            rust.clone(),
+            rust_lang.clone(),
+            rust_exact.clone(),
+            surreal.clone(),
+            surreal_db.clone(),
+        ];
+        let embeddings = HashMap::from([
+            (rust.id.clone(), vec![1.0, 0.0, 0.0]),
+            (rust_lang.id, vec![0.98, 0.05, 0.0]),
+            (rust_exact.id, vec![0.0, 0.0, 1.0]),
+            (surreal.id.clone(), vec![0.0, 1.0, 0.0]),
+            (surreal_db.id.clone(), vec![0.0, 0.99, 0.1]),
+        ]);
+
+        let groups = find_duplicate_groups(&entities, &embeddings, &[]);
+        assert_eq!(groups.len(), 2);
+        let rust_group = groups
+            .iter()
+            .find(|group| group.members.iter().any(|m| m.entity_id == rust.id))
+            .expect("rust group");
+        assert_eq!(rust_group.members.len(), 3);
+        assert!(rust_group.confidence >= MIN_DUPLICATE_CONFIDENCE);
+        assert!(rust_group.confidence <= 1.0);
+
+        let dismissed = vec![vec![surreal.id, surreal_db.id]];
+        let groups = find_duplicate_groups(&entities, &embeddings, &dismissed);
+        assert_eq!(groups.len(), 1);
+    }
+
+    #[test]
+    fn unrelated_entities_form_no_groups() {
+        let entities = vec![entity("Rust"), entity("Gardening"), entity("Tax return")];
+        assert!(find_duplicate_groups(&entities, &HashMap::new(), &[]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn scan_stores_report_and_remembers_dismissals() -> anyhow::Result<()> {
+        let db = setup_test_db().await?;
+        for name in ["Minne", "minne", "Gardening"] {
+            let mut stored = entity(name);
+            stored.user_id = "user-1".to_string();
+            db.store_item(stored).await?;
+        }
+
+        let report = DuplicateReport::begin_scan("user-1", &db)
+            .await?
+            .expect("no scan running yet");
+        assert!(
+            DuplicateReport::begin_scan("user-1", &db).await?.is_none(),
+            "a second scan waits for the first"
+        );
+        report.run_scan(&db).await;
+
+        let report = DuplicateReport::get_for_user("user-1", &db)
+            .await?
+            .expect("report stored");
+        assert_eq!(report.status, DuplicateReportStatus::Complete);
+        assert_eq!(report.entity_count, 3);
+        let group = report.groups.first().expect("one duplicate group");
+        assert_eq!(group.members.len(), 2);
+
+        let report = DuplicateReport::resolve_group(
+            "user-1",
+            &group.id,
+            DuplicateGroupStatus::Dismissed,
+            &db,
+        )
+        .await?;
+        assert!(report.open_group(&group.id).is_none());
+
+        DuplicateReport::begin_scan("user-1", &db)
+            .await?
+            .expect("previous scan finished")
+            .run_scan(&db)
+            .await;
+        let report = DuplicateReport::get_for_user("user-1", &db)
+            .await?
+            .expect("report stored");
+        assert!(report.groups.is_empty());
+        assert_eq!(report.dismissed.len(), 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,577 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::{engine::any::Any, method::Query};
+
+use crate::{
+    error::AppError,
+    storage::db::SurrealDbClient,
+    utils::serde_helpers::{deserialize_datetime, deserialize_flexible_id, serialize_datetime},
+};
+
+use super::{StoredObject, action_item::ActionItem, knowledge_entity::KnowledgeEntity};
+
+/// Key under [`KnowledgeEntity::metadata`] that holds the entity's typed facts.
+pub const ATTRIBUTES_METADATA_KEY: &str = "attributes";
+
+/// What kind of value a fact holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityAttributeKind {
+    Date,
+    Deadline,
+    Url,
+    Number,
+    Amount,
+    Person,
+}
+
+impl EntityAttributeKind {
+    /// Names of all kinds, as used in the enrichment schema and query strings.
+    #[must_use]
+    pub fn variants() -> &'static [&'static str] {
+        &["date", "deadline", "url", "number", "amount", "person"]
+    }
+
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Date => "date",
+            Self::Deadline => "deadline",
+            Self::Url => "url",
+            Self::Number => "number",
+            Self::Amount => "amount",
+            Self::Person => "person",
+        }
+    }
+
+    /// Looks up a kind by name, ignoring case.
+    #[must_use]
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "date" => Some(Self::Date),
+            "deadline" => Some(Self::Deadline),
+            "url" => Some(Self::Url),
+            "number" => Some(Self::Number),
+            "amount" => Some(Self::Amount),
+            "person" => Some(Self::Person),
+            _ => None,
+        }
+    }
+
+    /// Whether values of this kind are `YYYY-MM-DD` dates.
+    #[must_use]
+    pub fn is_date(self) -> bool {
+        matches!(self, Self::Date | Self::Deadline)
+    }
+}
+
+/// A typed fact about an entity, such as a launch date or a budget.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntityAttribute {
+    pub kind: EntityAttributeKind,
+    /// What the value is, e.g. "launch date" or "budget".
+    pub label: String,
+    /// `YYYY-MM-DD` for dates and deadlines, a plain decimal for numbers and amounts.
+    pub value: String,
+    /// Currency or unit for numbers and amounts.
+    #[serde(default)]
+    pub unit: Option<String>,
+}
+
+impl EntityAttribute {
+    /// Checks and normalizes a proposed fact. Returns `None` when the value does not
+    /// match its kind, so malformed dates or amounts never reach the database.
+    #[must_use]
+    pub fn normalized(self) -> Option<Self> {
+        let label = self.label.trim().to_string();
+        let raw = self.value.trim();
+        if label.is_empty() || raw.is_empty() {
+            return None;
+        }
+
+        let value = match self.kind {
+            EntityAttributeKind::Date | EntityAttributeKind::Deadline => {
+                normalize_date(raw)?.to_string()
+            }
+            EntityAttributeKind::Number | EntityAttributeKind::Amount => {
+                let digits: String = raw.chars().filter(|c| *c != ',' && *c != '_').collect();
+                let parsed = digits.parse::<f64>().ok().filter(|n| n.is_finite())?;
+                parsed.to_string()
+            }
+            EntityAttributeKind::Url => {
+                if !(raw.starts_with("https://") || raw.starts_with("http://")) {
+                    return None;
+                }
+                raw.to_string()
+            }
+            EntityAttributeKind::Person => raw.to_string(),
+        };
+        let unit = match self.kind {
+            EntityAttributeKind::Number | EntityAttributeKind::Amount => self
+                .unit
+                .map(|unit| unit.trim().to_string())
+                .filter(|unit| !unit.is_empty()),
+            _ => None,
+        };
+
+        Some(Self {
+            kind: self.kind,
+            label,
+            value,
+            unit,
+        })
+    }
+
+    /// The facts stored in an entity's metadata; malformed entries are skipped.
+    #[must_use]
+    pub fn from_metadata(metadata: Option<&serde_json::Value>) -> Vec<Self> {
+        metadata
+            .and_then(|metadata| metadata.get(ATTRIBUTES_METADATA_KEY))
+            .and_then(serde_json::Value::as_array)
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(|value| serde_json::from_value(value.clone()).ok())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Entity metadata holding `attributes`, or `None` when there are none.
+    #[must_use]
+    pub fn to_metadata(attributes: &[Self]) -> Option<serde_json::Value> {
+        if attributes.is_empty() {
+            return None;
+        }
+        Some(serde_json::json!({ ATTRIBUTES_METADATA_KEY: attributes }))
+    }
+}
+
+/// Parses a date in `YYYY-MM-DD` form, also accepting a trailing time part.
+#[must_use]
+pub fn normalize_date(raw: &str) -> Option<NaiveDate> {
+    let date = raw.trim().get(..10)?;
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+/// Limits facts and action items to the documents they were extracted from.
+#[derive(Debug, Clone, Default)]
+pub struct SourceWindow {
+    /// Only documents in this category.
+    pub category: Option<String>,
+    /// Only documents ingested at or after this time.
+    pub ingested_after: Option<DateTime<Utc>>,
+    /// Only documents ingested before this time.
+    pub ingested_before: Option<DateTime<Utc>>,
+}
+
+impl SourceWindow {
+    /// Builds a window from optional `YYYY-MM-DD` bounds, both inclusive, as accepted
+    /// by the API and search forms. Blank values are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` if a bound is not a valid date.
+    pub fn from_dates(
+        category: Option<String>,
+        from: Option<&str>,
+        to: Option<&str>,
+    ) -> Result<Self, AppError> {
+        let parse = |raw: Option<&str>| -> Result<Option<NaiveDate>, AppError> {
+            match raw.map(str::trim).filter(|raw| !raw.is_empty()) {
+                None => Ok(None),
+                Some(raw) => normalize_date(raw).map(Some).ok_or_else(|| {
+                    AppError::Validation(format!("'{raw}' is not a YYYY-MM-DD date"))
+                }),
+            }
+        };
+        let start_of = |date: NaiveDate| date.and_time(NaiveTime::MIN).and_utc();
+
+        Ok(Self {
+            category: category.filter(|category| !category.trim().is_empty()),
+            ingested_after: parse(from)?.map(start_of),
+            ingested_before: parse(to)?.and_then(|date| date.succ_opt()).map(start_of),
+        })
+    }
+
+    /// `WHERE` conditions for a table with `source_id`, `user_id` and `created_at`,
+    /// reading `$category`, `$ingested_after` and `$ingested_before`.
+    pub(crate) fn conditions(&self) -> Vec<&'static str> {
+        let mut conditions = Vec::new();
+        if self.ingested_after.is_some() {
+            conditions.push("created_at >= $ingested_after");
+        }
+        if self.ingested_before.is_some() {
+            conditions.push("created_at < $ingested_before");
+        }
+        if self.category.is_some() {
+            conditions.push(
+                "source_id INSIDE (SELECT VALUE record::id(id) FROM text_content \
+                 WHERE user_id = $user_id AND category = $category)",
+            );
+        }
+        conditions
+    }
+
+    /// Binds the values read by [`Self::conditions`].
+    pub(crate) fn bind<'a>(&self, query: Query<'a, Any>) -> Query<'a, Any> {
+        query
+            .bind(("category", self.category.clone()))
+            .bind((
+                "ingested_after",
+                self.ingested_after.map(surrealdb::Datetime::from),
+            ))
+            .bind((
+                "ingested_before",
+                self.ingested_before.map(surrealdb::Datetime::from),
+            ))
+    }
+}
+
+/// Filters for [`EntityFact::find`].
+#[derive(Debug, Clone, Default)]
+pub struct FactQuery {
+    pub window: SourceWindow,
+    pub kind: Option<EntityAttributeKind>,
+    /// Only facts whose value sorts at or after this one, e.g. a `YYYY-MM-DD` date.
+    pub value_from: Option<String>,
+    /// Only facts whose value sorts at or before this one.
+    pub value_to: Option<String>,
+}
+
+/// One typed fact together with the entity it belongs to.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EntityFact {
+    pub entity_id: String,
+    pub entity_name: String,
+    pub source_id: String,
+    #[serde(serialize_with = "serialize_datetime")]
+    pub ingested_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub attribute: EntityAttribute,
+}
+
+#[derive(Deserialize)]
+struct FactRow {
+    #[serde(deserialize_with = "deserialize_flexible_id")]
+    id: String,
+    name: String,
+    source_id: String,
+    #[serde(deserialize_with = "deserialize_datetime")]
+    created_at: DateTime<Utc>,
+    #[serde(default)]
+    metadata: Option<serde_json::Value>,
+}
+
+impl EntityFact {
+    /// Facts of `user_id` matching `query`, ordered by value and then by entity name.
+    pub async fn find(
+        user_id: &str,
+        query: &FactQuery,
+        db: &SurrealDbClient,
+    ) -> Result<Vec<Self>, AppError> {
+        let mut conditions = vec!["user_id = $user_id", "metadata.attributes != NONE"];
+        conditions.extend(query.window.conditions());
+        let sql = format!(
+            "SELECT id, name, source_id, created_at, metadata FROM type::table($table) WHERE {}",
+            conditions.join(" AND ")
+        );
+
+        let request = db
+            .client
+            .query(sql)
+            .bind(("table", KnowledgeEntity::table_name()))
+            .bind(("user_id", user_id.to_string()));
+        let rows: Vec<FactRow> = query.window.bind(request).await?.take(0)?;
+
+        let mut facts: Vec<Self> = rows
+            .iter()
+            .flat_map(Self::from_row)
+            .filter(|fact| query.kind.is_none_or(|kind| fact.attribute.kind == kind))
+            .filter(|fact| {
+                query
+                    .value_from
+                    .as_deref()
+                    .is_none_or(|from| fact.attribute.value.as_str() >= from)
+            })
+            .filter(|fact| {
+                query
+                    .value_to
+                    .as_deref()
+                    .is_none_or(|to| fact.attribute.value.as_str() <= to)
+            })
+            .collect();
+        facts.sort_by(|a, b| {
+            a.attribute
+                .value
+                .cmp(&b.attribute.value)
+                .then_with(|| a.entity_name.cmp(&b.entity_name))
+        });
+
+        Ok(facts)
+    }
+
+    fn from_row(row: &FactRow) -> Vec<Self> {
+        EntityAttribute::from_metadata(row.metadata.as_ref())
+            .into_iter()
+            .map(|attribute| Self {
+                entity_id: row.id.clone(),
+                entity_name: row.name.clone(),
+                source_id: row.source_id.clone(),
+                ingested_at: row.created_at,
+                attribute,
+            })
+            .collect()
+    }
+}
+
+/// Facts and open action items extracted from one document, for chat context.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SourceExtractions {
+    pub facts: Vec<EntityFact>,
+    pub action_items: Vec<ActionItem>,
+}
+
+impl SourceExtractions {
+    /// Loads the facts and open action items of the given sources owned by `user_id`,
+    /// keyed by bare source id. Sources with neither are left out.
+    pub async fn load(
+        db: &SurrealDbClient,
+        user_id: &str,
+        source_ids: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<HashMap<String, Self>, AppError> {
+        let source_ids: HashSet<String> = source_ids
+            .into_iter()
+            .map(|id| id.as_ref().to_string())
+            .collect();
+        if source_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let source_ids: Vec<String> = source_ids.into_iter().collect();
+
+        let mut response = db
+            .client
+            .query(
+                "SELECT id, name, source_id, created_at, metadata FROM type::table($entity_table)
+                 WHERE user_id = $user_id AND source_id INSIDE $source_ids
+                 AND metadata.attributes != NONE",
+            )
+            .query(
+                "SELECT * FROM type::table($action_table)
+                 WHERE user_id = $user_id AND source_id INSIDE $source_ids AND done = false
+                 ORDER BY created_at ASC",
+            )
+            .bind(("entity_table", KnowledgeEntity::table_name()))
+            .bind(("action_table", ActionItem::table_name()))
+            .bind(("user_id", user_id.to_string()))
+            .bind(("source_ids", source_ids))
+            .await?;
+        let rows: Vec<FactRow> = response.take(0)?;
+        let action_items: Vec<ActionItem> = response.take(1)?;
+
+        let mut extractions: HashMap<String, Self> = HashMap::new();
+        for fact in rows.iter().flat_map(EntityFact::from_row) {
+            extractions
+                .entry(fact.source_id.clone())
+                .or_default()
+                .facts
+                .push(fact);
+        }
+        for item in action_items {
+            extractions
+                .entry(item.source_id.clone())
+                .or_default()
+                .action_items
+                .push(item);
+        }
+
+        Ok(extractions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used)]
+    use super::*;
+    use crate::storage::types::knowledge_entity::KnowledgeEntityType;
+    use crate::test_utils::setup_test_db;
+    use chrono::Duration;
+
+    fn attribute(kind: EntityAttributeKind, label: &str, value: &str) -> EntityAttribute {
+        EntityAttribute {
+            kind,
+            label: label.to_string(),
+            value: value.to_string(),
+            unit: None,
+        }
+    }
+
+    #[test]
+    fn normalized_checks_values_against_their_kind() {
+        let deadline = attribute(
+            EntityAttributeKind::Deadline,
+            " report due ",
+            "2026-03-01T12:00",
+        )
+        .normalized()
+        .expect("valid deadline");
+        assert_eq!(deadline.label, "report due");
+        assert_eq!(deadline.value, "2026-03-01");
+
+        let mut budget = attribute(EntityAttributeKind::Amount, "budget", "12,500.50");
+        budget.unit = Some(" EUR ".to_string());
+        let budget = budget.normalized().expect("valid amount");
+        assert_eq!(budget.value, "12500.5");
+        assert_eq!(budget.unit.as_deref(), Some("EUR"));
+
+        assert!(
+            attribute(EntityAttributeKind::Date, "launch", "next tuesday")
+                .normalized()
+                .is_none()
+        );
+        assert!(
+            attribute(EntityAttributeKind::Url, "site", "example.com")
+                .normalized()
+                .is_none()
+        );
+        assert!(
+            attribute(EntityAttributeKind::Person, "", "Ada")
+                .normalized()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn metadata_round_trips_attributes() {
+        let attributes = vec![attribute(EntityAttributeKind::Person, "owner", "Ada")];
+
+        let metadata = EntityAttribute::to_metadata(&attributes);
+
+        assert_eq!(
+            EntityAttribute::from_metadata(metadata.as_ref()),
+            attributes
+        );
+        assert!(EntityAttribute::to_metadata(&[]).is_none());
+        assert!(
+            EntityAttribute::from_metadata(Some(&serde_json::json!({"key": "value"}))).is_empty()
+        );
+    }
+
+    #[test]
+    fn source_window_from_dates_includes_the_end_day() {
+        let window = SourceWindow::from_dates(
+            Some(" ".to_string()),
+            Some("2026-03-01"),
+            Some("2026-03-31"),
+        )
+        .expect("valid dates");
+        assert_eq!(window.category, None);
+        assert_eq!(
+            window.ingested_after.map(|at| at.to_rfc3339()),
+            Some("2026-03-01T00:00:00+00:00".to_string())
+        );
+        assert_eq!(
+            window.ingested_before.map(|at| at.to_rfc3339()),
+            Some("2026-04-01T00:00:00+00:00".to_string())
+        );
+
+        assert!(matches!(
+            SourceWindow::from_dates(None, Some("last month"), None),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn find_filters_by_kind_value_category_and_ingestion_time() -> anyhow::Result<()> {
+        let db = setup_test_db().await?;
+        let user_id = "fact_user";
+
+        let meeting = crate::storage::types::text_content::TextContent::new(
+            "Weekly sync".to_string(),
+            None,
+            "meeting notes".to_string(),
+            None,
+            None,
+            user_id.to_string(),
+        );
+        db.store_item(meeting.clone()).await?;
+
+        let mut launch = KnowledgeEntity::new(
+            meeting.id.clone(),
+            "Launch".to_string(),
+            "Product launch".to_string(),
+            KnowledgeEntityType::Project,
+            EntityAttribute::to_metadata(&[
+                attribute(EntityAttributeKind::Deadline, "launch", "2026-05-01"),
+                attribute(EntityAttributeKind::Person, "owner", "Ada"),
+            ]),
+            user_id.to_string(),
+        );
+        launch.created_at = Utc::now();
+        let mut old = KnowledgeEntity::new(
+            meeting.id.clone(),
+            "Old plan".to_string(),
+            "Superseded".to_string(),
+            KnowledgeEntityType::Project,
+            EntityAttribute::to_metadata(&[attribute(
+                EntityAttributeKind::Deadline,
+                "old plan",
+                "2025-01-01",
+            )]),
+            user_id.to_string(),
+        );
+        old.created_at = Utc::now() - Duration::days(90);
+        let other_category = KnowledgeEntity::new(
+            "elsewhere".to_string(),
+            "Trip".to_string(),
+            "Holiday".to_string(),
+            KnowledgeEntityType::Idea,
+            EntityAttribute::to_metadata(&[attribute(
+                EntityAttributeKind::Deadline,
+                "book flights",
+                "2026-04-01",
+            )]),
+            user_id.to_string(),
+        );
+        db.store_item(launch.clone()).await?;
+        db.store_item(old).await?;
+        db.store_item(other_category).await?;
+
+        let facts = EntityFact::find(
+            user_id,
+            &FactQuery {
+                window: SourceWindow {
+                    category: Some("meeting notes".to_string()),
+                    ingested_after: Some(Utc::now() - Duration::days(30)),
+                    ingested_before: None,
+                },
+                kind: Some(EntityAttributeKind::Deadline),
+                ..Default::default()
+            },
+            &db,
+        )
+        .await?;
+        assert_eq!(facts.len(), 1);
+        let fact = facts.first().expect("one fact");
+        assert_eq!(fact.entity_id, launch.id);
+        assert_eq!(fact.attribute.value, "2026-05-01");
+
+        let due_in_april = EntityFact::find(
+            user_id,
+            &FactQuery {
+                kind: Some(EntityAttributeKind::Deadline),
+                value_from: Some("2026-04-01".to_string()),
+                value_to: Some("2026-04-30".to_string()),
+                ..Default::default()
+            },
+            &db,
+        )
+        .await?;
+        assert_eq!(due_in_april.len(), 1);
+        assert_eq!(due_in_april.first().expect("one fact").entity_name, "Trip");
+
+        Ok(())
+    }
+}
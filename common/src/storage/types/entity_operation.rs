@@ -0,0 +1,791 @@
+use std::collections::HashSet;
+
+use surrealdb::RecordId;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    storage::{
+        db::SurrealDbClient,
+        types::{
+            EmbeddingRecord,
+            knowledge_entity::{KnowledgeEntity, KnowledgeEntityType},
+            knowledge_entity_embedding::KnowledgeEntityEmbedding,
+            knowledge_entity_mention::KnowledgeEntityMention,
+            knowledge_relationship::KnowledgeRelationship,
+            system_settings::SystemSettings,
+            user::User,
+        },
+    },
+    stored_object,
+    utils::embedding::EmbeddingProvider,
+};
+
+/// Which manual cleanup an [`EntityOperation`] recorded.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::module_name_repetitions)]
+pub enum EntityOperationKind {
+    Merge,
+    Split,
+}
+
+/// Rows belonging to a set of entities: the entities themselves, their embeddings,
+/// every `relates_to` edge touching them, and their source mentions.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct EntitySnapshot {
+    pub entities: Vec<KnowledgeEntity>,
+    pub embeddings: Vec<KnowledgeEntityEmbedding>,
+    pub relationships: Vec<KnowledgeRelationship>,
+    pub mentions: Vec<KnowledgeEntityMention>,
+}
+
+/// What a user asked a split to carve out of an existing entity.
+#[derive(Debug, Clone)]
+pub struct SplitRequest {
+    pub name: String,
+    pub description: String,
+    pub entity_type: KnowledgeEntityType,
+    /// Edges of the original entity that should point at the new entity instead.
+    pub relationship_ids: Vec<String>,
+}
+
+stored_object!(
+    /// Undo record for a merge or split of knowledge entities.
+    ///
+    /// `before` holds the affected rows as they were, so undoing deletes whatever the
+    /// operation created and writes the snapshot back.
+    EntityOperation, "entity_operation", {
+    user_id: String,
+    kind: EntityOperationKind,
+    /// The survivor of a merge, or the entity a split was taken from.
+    primary_entity_id: String,
+    /// Entities that only exist because of this operation.
+    created_entity_ids: Vec<String>,
+    /// Short human-readable description shown next to the undo button.
+    summary: String,
+    before: EntitySnapshot,
+    #[serde(
+        serialize_with = "serialize_option_datetime",
+        deserialize_with = "deserialize_option_datetime",
+        default
+    )]
+    undone_at: Option<DateTime<Utc>>
+});
+
+impl EntityOperation {
+    /// Folds `merged_ids` into `survivor_id`.
+    ///
+    /// Edges are re-pointed at the survivor with their metadata (and so their source
+    /// provenance) intact; edges that would become self-loops or duplicates are dropped.
+    /// Descriptions are unioned, mentions move to the survivor, and the survivor is
+    /// re-embedded before the merged entities are deleted.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` when nothing is left to merge, `NotFound`/`Auth`
+    /// for entities the user does not own, and embedding or database errors otherwise.
+    pub async fn merge(
+        survivor_id: &str,
+        merged_ids: &[String],
+        user_id: &str,
+        db: &SurrealDbClient,
+        embedding_provider: &EmbeddingProvider,
+    ) -> Result<Self, AppError> {
+        let mut seen = HashSet::from([survivor_id.to_string()]);
+        let merged_ids: Vec<String> = merged_ids
+            .iter()
+            .filter(|id| seen.insert((*id).clone()))
+            .cloned()
+            .collect();
+        if merged_ids.is_empty() {
+            return Err(AppError::Validation(
+                "select at least one other entity to merge".into(),
+            ));
+        }
+
+        let mut survivor =
+            User::get_and_validate_knowledge_entity(survivor_id, user_id, db).await?;
+        let mut merged = Vec::with_capacity(merged_ids.len());
+        for id in &merged_ids {
+            merged.push(User::get_and_validate_knowledge_entity(id, user_id, db).await?);
+        }
+
+        let mut affected_ids = vec![survivor.id.clone()];
+        affected_ids.extend(merged_ids.iter().cloned());
+        let before = Self::snapshot(&affected_ids, user_id, db).await?;
+
+        let merged_set: HashSet<&str> = merged_ids.iter().map(String::as_str).collect();
+        let repoint = |id: &str| {
+            if merged_set.contains(id) {
+                survivor.id.clone()
+            } else {
+                id.to_string()
+            }
+        };
+
+        let mut edge_keys = HashSet::new();
+        let relationships: Vec<KnowledgeRelationship> = before
+            .relationships
+            .iter()
+            .filter_map(|relationship| {
+                let in_ = repoint(&relationship.in_);
+                let out = repoint(&relationship.out);
+                let collapsed = in_ == out && relationship.in_ != relationship.out;
+                let key = (
+                    in_.clone(),
+                    out.clone(),
+                    relationship.metadata.relationship_type.clone(),
+                );
+                (!collapsed && edge_keys.insert(key)).then(|| KnowledgeRelationship {
+                    in_,
+                    out,
+                    ..relationship.clone()
+                })
+            })
+            .collect();
+
+        let mut mentions: Vec<KnowledgeEntityMention> = before
+            .mentions
+            .iter()
+            .map(|mention| KnowledgeEntityMention {
+                entity_id: repoint(&mention.entity_id),
+                ..mention.clone()
+            })
+            .collect();
+        for entity in std::iter::once(&survivor).chain(merged.iter()) {
+            let mentioned = mentions
+                .iter()
+                .any(|mention| mention.source_id == entity.source_id);
+            if !mentioned {
+                mentions.push(KnowledgeEntityMention::new(
+                    survivor.id.clone(),
+                    entity.source_id.clone(),
+                    user_id.to_string(),
+                    entity.name.clone(),
+                ));
+            }
+        }
+
+        survivor.description = union_descriptions(
+            std::iter::once(survivor.description.as_str())
+                .chain(merged.iter().map(|entity| entity.description.as_str())),
+        );
+        survivor.updated_at = Utc::now();
+        let embedding = embed_entity(&survivor, db, embedding_provider).await?;
+
+        let names: Vec<&str> = merged.iter().map(|entity| entity.name.as_str()).collect();
+        let operation = Self::new(
+            user_id,
+            EntityOperationKind::Merge,
+            &survivor.id,
+            Vec::new(),
+            format!("Merged {} into {}", names.join(", "), survivor.name),
+            before,
+        );
+
+        let after = EntitySnapshot {
+            embeddings: vec![embedding],
+            entities: vec![survivor],
+            relationships,
+            mentions,
+        };
+        replace_entities(db, user_id, &affected_ids, &after, &operation).await?;
+
+        Ok(operation)
+    }
+
+    /// Carves a new entity out of `entity_id`, moving the selected edges over to it.
+    ///
+    /// The new entity keeps the original's source so provenance is unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` for an empty name or edges that do not touch the
+    /// entity, `NotFound`/`Auth` for entities the user does not own, and embedding or
+    /// database errors otherwise.
+    pub async fn split(
+        entity_id: &str,
+        request: SplitRequest,
+        user_id: &str,
+        db: &SurrealDbClient,
+        embedding_provider: &EmbeddingProvider,
+    ) -> Result<Self, AppError> {
+        let name = request.name.trim();
+        if name.is_empty() {
+            return Err(AppError::Validation("the new entity needs a name".into()));
+        }
+
+        let original = User::get_and_validate_knowledge_entity(entity_id, user_id, db).await?;
+        let affected_ids = vec![original.id.clone()];
+        let before = Self::snapshot(&affected_ids, user_id, db).await?;
+
+        let created = KnowledgeEntity::new(
+            original.source_id.clone(),
+            name.to_string(),
+            request.description.trim().to_string(),
+            request.entity_type,
+            None,
+            user_id.to_string(),
+        );
+        let embedding = embed_entity(&created, db, embedding_provider).await?;
+
+        let moved: HashSet<&str> = request
+            .relationship_ids
+            .iter()
+            .map(String::as_str)
+            .collect();
+        let known: HashSet<&str> = before
+            .relationships
+            .iter()
+            .map(|relationship| relationship.id.as_str())
+            .collect();
+        if !moved.is_subset(&known) {
+            return Err(AppError::Validation(
+                "only relationships of the entity being split can be moved".into(),
+            ));
+        }
+
+        let relationships: Vec<KnowledgeRelationship> = before
+            .relationships
+            .iter()
+            .map(|relationship| {
+                if !moved.contains(relationship.id.as_str()) {
+                    return relationship.clone();
+                }
+                let swap = |id: &str| {
+                    if id == original.id {
+                        created.id.clone()
+                    } else {
+                        id.to_string()
+                    }
+                };
+                KnowledgeRelationship {
+                    in_: swap(&relationship.in_),
+                    out: swap(&relationship.out),
+                    ..relationship.clone()
+                }
+            })
+            .collect();
+
+        let mut mentions = before.mentions.clone();
+        mentions.push(KnowledgeEntityMention::new(
+            created.id.clone(),
+            created.source_id.clone(),
+            user_id.to_string(),
+            created.name.clone(),
+        ));
+
+        let operation = Self::new(
+            user_id,
+            EntityOperationKind::Split,
+            &original.id,
+            vec![created.id.clone()],
+            format!("Split {} out of {}", created.name, original.name),
+            before.clone(),
+        );
+
+        let mut embeddings = before.embeddings;
+        embeddings.push(embedding);
+        let after = EntitySnapshot {
+            entities: vec![original, created],
+            embeddings,
+            relationships,
+            mentions,
+        };
+        replace_entities(db, user_id, &affected_ids, &after, &operation).await?;
+
+        Ok(operation)
+    }
+
+    /// Reverts an operation by deleting what it created and restoring its snapshot.
+    ///
+    /// Only the user's latest operation that is still in effect can be undone, so an
+    /// undo never overwrites a later merge or split of the same entities.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` for unknown operations, `AppError::Validation` if it
+    /// was already undone or is not the latest one, and database errors otherwise.
+    pub async fn undo(id: &str, user_id: &str, db: &SurrealDbClient) -> Result<Self, AppError> {
+        let mut operation: Self = db
+            .get_item(id)
+            .await?
+            .filter(|operation: &Self| operation.user_id == user_id)
+            .ok_or_else(|| AppError::NotFound("entity operation not found".into()))?;
+
+        if operation.undone_at.is_some() {
+            return Err(AppError::Validation(
+                "this operation was already undone".into(),
+            ));
+        }
+        let latest = Self::latest_undoable(user_id, db).await?;
+        if latest.as_ref().map(|latest| latest.id.as_str()) != Some(operation.id.as_str()) {
+            return Err(AppError::Validation(
+                "only the most recent merge or split can be undone".into(),
+            ));
+        }
+
+        let mut affected_ids: Vec<String> = operation
+            .before
+            .entities
+            .iter()
+            .map(|entity| entity.id.clone())
+            .collect();
+        affected_ids.extend(operation.created_entity_ids.iter().cloned());
+
+        let now = Utc::now();
+        operation.undone_at = Some(now);
+        operation.updated_at = now;
+        let before = operation.before.clone();
+        replace_entities(db, user_id, &affected_ids, &before, &operation).await?;
+
+        Ok(operation)
+    }
+
+    /// The user's most recent operation that has not been undone.
+    pub async fn latest_undoable(
+        user_id: &str,
+        db: &SurrealDbClient,
+    ) -> Result<Option<Self>, AppError> {
+        let operations: Vec<Self> = db
+            .client
+            .query(
+                "SELECT * FROM type::table($table)
+                 WHERE user_id = $user_id AND undone_at = NONE
+                 ORDER BY created_at DESC LIMIT 1",
+            )
+            .bind(("table", Self::table_name()))
+            .bind(("user_id", user_id.to_string()))
+            .await?
+            .take(0)?;
+
+        Ok(operations.into_iter().next())
+    }
+
+    fn new(
+        user_id: &str,
+        kind: EntityOperationKind,
+        primary_entity_id: &str,
+        created_entity_ids: Vec<String>,
+        summary: String,
+        before: EntitySnapshot,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            created_at: now,
+            updated_at: now,
+            user_id: user_id.to_string(),
+            kind,
+            primary_entity_id: primary_entity_id.to_string(),
+            created_entity_ids,
+            summary,
+            before,
+            undone_at: None,
+        }
+    }
+
+    async fn snapshot(
+        entity_ids: &[String],
+        user_id: &str,
+        db: &SurrealDbClient,
+    ) -> Result<EntitySnapshot, AppError> {
+        let records = entity_records(entity_ids);
+        let mut response = db
+            .client
+            .query(
+                "SELECT * FROM knowledge_entity WHERE id IN $records AND user_id = $user_id;
+                 SELECT * FROM knowledge_entity_embedding WHERE entity_id IN $records;
+                 SELECT * FROM relates_to
+                    WHERE metadata.user_id = $user_id AND (`in` IN $records OR out IN $records);
+                 SELECT * FROM knowledge_entity_mention
+                    WHERE user_id = $user_id AND entity_id IN $ids
+                    ORDER BY created_at ASC;",
+            )
+            .bind(("records", records))
+            .bind(("ids", entity_ids.to_vec()))
+            .bind(("user_id", user_id.to_string()))
+            .await?;
+
+        Ok(EntitySnapshot {
+            entities: response.take(0)?,
+            embeddings: response.take(1)?,
+            relationships: response.take(2)?,
+            mentions: response.take(3)?,
+        })
+    }
+}
+
+fn entity_records(entity_ids: &[String]) -> Vec<RecordId> {
+    entity_ids
+        .iter()
+        .map(|id| RecordId::from_table_key(KnowledgeEntity::table_name(), id))
+        .collect()
+}
+
+/// Joins distinct, non-empty descriptions in order, one paragraph each.
+fn union_descriptions<'a>(descriptions: impl Iterator<Item = &'a str>) -> String {
+    let mut seen = HashSet::new();
+    descriptions
+        .map(str::trim)
+        .filter(|description| !description.is_empty() && seen.insert(description.to_lowercase()))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+async fn embed_entity(
+    entity: &KnowledgeEntity,
+    db: &SurrealDbClient,
+    embedding_provider: &EmbeddingProvider,
+) -> Result<KnowledgeEntityEmbedding, AppError> {
+    let input = KnowledgeEntity::embedding_input_text(
+        &entity.name,
+        &entity.description,
+        &entity.entity_type,
+    );
+    let embedding = embedding_provider.embed(&input).await?;
+
+    let settings = SystemSettings::get_current(db).await?;
+    KnowledgeEntityEmbedding::validate_dimension(
+        &embedding,
+        settings.embedding_dimensions as usize,
+    )?;
+
+    Ok(KnowledgeEntityEmbedding::new(
+        &entity.id,
+        entity.source_id.clone(),
+        embedding,
+        entity.user_id.clone(),
+        KnowledgeEntity::table_name(),
+    ))
+}
+
+/// Swaps every row belonging to `entity_ids` for `state` and stores `operation`,
+/// all in one transaction.
+async fn replace_entities(
+    db: &SurrealDbClient,
+    user_id: &str,
+    entity_ids: &[String],
+    state: &EntitySnapshot,
+    operation: &EntityOperation,
+) -> Result<(), AppError> {
+    let mut query = String::from(
+        "BEGIN TRANSACTION;
+         DELETE relates_to WHERE `in` IN $records OR out IN $records;
+         DELETE knowledge_entity_mention WHERE user_id = $user_id AND entity_id IN $ids;
+         FOR $id IN $ids {
+             DELETE type::thing('knowledge_entity_embedding', $id);
+             DELETE type::thing('knowledge_entity', $id);
+         };",
+    );
+    if !state.entities.is_empty() {
+        query.push_str("\nINSERT INTO knowledge_entity $entities;");
+    }
+    if !state.embeddings.is_empty() {
+        query.push_str("\nINSERT INTO knowledge_entity_embedding $embeddings;");
+    }
+    if !state.mentions.is_empty() {
+        query.push_str("\nINSERT INTO knowledge_entity_mention $mentions;");
+    }
+    if !state.relationships.is_empty() {
+        query.push_str(
+            r"
+FOR $relationship IN $relationships {
+    LET $in_node = type::thing('knowledge_entity', $relationship.`in`);
+    LET $out_node = type::thing('knowledge_entity', $relationship.out);
+    RELATE $in_node->relates_to->$out_node CONTENT {
+        id: type::thing('relates_to', $relationship.id),
+        metadata: $relationship.metadata
+    };
+};",
+        );
+    }
+    query.push_str(
+        "\nUPSERT type::thing('entity_operation', $operation_id) CONTENT $operation;
+         COMMIT TRANSACTION;",
+    );
+
+    db.client
+        .query(query)
+        .bind(("records", entity_records(entity_ids)))
+        .bind(("ids", entity_ids.to_vec()))
+        .bind(("user_id", user_id.to_string()))
+        .bind(("entities", state.entities.clone()))
+        .bind(("embeddings", state.embeddings.clone()))
+        .bind(("mentions", state.mentions.clone()))
+        .bind(("relationships", state.relationships.clone()))
+        .bind(("operation_id", operation.id.clone()))
+        .bind(("operation", operation.clone()))
+        .await?
+        .check()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::must_use_candidate)]
+    use super::*;
+    use crate::test_utils::setup_test_db_with_embedding_dimension;
+
+    const USER: &str = "user-1";
+
+    async fn store_entity(
+        db: &SurrealDbClient,
+        provider: &EmbeddingProvider,
+        name: &str,
+        description: &str,
+        source_id: &str,
+    ) -> anyhow::Result<KnowledgeEntity> {
+        let entity = KnowledgeEntity::new(
+            source_id.to_string(),
+            name.to_string(),
+            description.to_string(),
+            KnowledgeEntityType::Project,
+            None,
+            USER.to_string(),
+        );
+        let embedding = embed_entity(&entity, db, provider).await?;
+        KnowledgeEntity::store_with_embedding(entity.clone(), embedding.embedding, 3, db).await?;
+        db.store_item(KnowledgeEntityMention::new(
+            entity.id.clone(),
+            source_id.to_string(),
+            USER.to_string(),
+            name.to_string(),
+        ))
+        .await?;
+        Ok(entity)
+    }
+
+    async fn relate(
+        db: &SurrealDbClient,
+        in_: &KnowledgeEntity,
+        out: &KnowledgeEntity,
+        source_id: &str,
+    ) -> anyhow::Result<KnowledgeRelationship> {
+        let relationship = KnowledgeRelationship::new(
+            in_.id.clone(),
+            out.id.clone(),
+            USER.to_string(),
+            source_id.to_string(),
+            "RelatedTo".to_string(),
+        );
+        relationship.clone().store_relationship(db).await?;
+        Ok(relationship)
+    }
+
+    async fn relationships(db: &SurrealDbClient) -> anyhow::Result<Vec<KnowledgeRelationship>> {
+        Ok(User::get_knowledge_relationships(USER, db).await?)
+    }
+
+    #[tokio::test]
+    async fn merge_repoints_edges_and_undo_restores_everything() -> anyhow::Result<()> {
+        let db = setup_test_db_with_embedding_dimension(3).await?;
+        let provider = EmbeddingProvider::new_hashed(3)?;
+
+        let survivor = store_entity(&db, &provider, "Minne", "A notes app", "doc-1").await?;
+        let duplicate =
+            store_entity(&db, &provider, "minne app", "Written in Rust", "doc-2").await?;
+        let other = store_entity(&db, &provider, "SurrealDB", "A database", "doc-2").await?;
+        relate(&db, &duplicate, &other, "doc-2").await?;
+        relate(&db, &survivor, &duplicate, "doc-1").await?;
+
+        let before = EntityOperation::snapshot(
+            &[survivor.id.clone(), duplicate.id.clone(), other.id.clone()],
+            USER,
+            &db,
+        )
+        .await?;
+
+        let operation = EntityOperation::merge(
+            &survivor.id,
+            std::slice::from_ref(&duplicate.id),
+            USER,
+            &db,
+            &provider,
+        )
+        .await?;
+        assert_eq!(operation.kind, EntityOperationKind::Merge);
+
+        assert!(
+            db.get_item::<KnowledgeEntity>(&duplicate.id)
+                .await?
+                .is_none()
+        );
+        assert!(
+            db.get_item::<KnowledgeEntityEmbedding>(&duplicate.id)
+                .await?
+                .is_none()
+        );
+        let merged: KnowledgeEntity = db
+            .get_item(&survivor.id)
+            .await?
+            .expect("survivor still exists");
+        assert_eq!(merged.description, "A notes app\n\nWritten in Rust");
+
+        // The survivor->duplicate edge collapses; the duplicate->other edge moves over
+        // and keeps the source it came from.
+        let edges = relationships(&db).await?;
+        assert_eq!(edges.len(), 1);
+        let edge = edges.first().expect("one edge");
+        assert_eq!(edge.in_, survivor.id);
+        assert_eq!(edge.out, other.id);
+        assert_eq!(edge.metadata.source_id, "doc-2");
+
+        let mentions = KnowledgeEntityMention::for_entity(&survivor.id, USER, &db).await?;
+        assert_eq!(mentions.len(), 2);
+
+        let undone = EntityOperation::undo(&operation.id, USER, &db).await?;
+        assert!(undone.undone_at.is_some());
+
+        let after = EntityOperation::snapshot(
+            &[survivor.id.clone(), duplicate.id.clone(), other.id.clone()],
+            USER,
+            &db,
+        )
+        .await?;
+        assert_eq!(after.entities.len(), before.entities.len());
+        assert_eq!(after.embeddings.len(), before.embeddings.len());
+        assert_eq!(after.mentions.len(), before.mentions.len());
+        let mut restored_edges: Vec<String> = after
+            .relationships
+            .iter()
+            .map(|edge| edge.id.clone())
+            .collect();
+        let mut original_edges: Vec<String> = before
+            .relationships
+            .iter()
+            .map(|edge| edge.id.clone())
+            .collect();
+        restored_edges.sort();
+        original_edges.sort();
+        assert_eq!(restored_edges, original_edges);
+        let restored: KnowledgeEntity =
+            db.get_item(&survivor.id).await?.expect("survivor restored");
+        assert_eq!(restored.description, "A notes app");
+
+        let again = EntityOperation::undo(&operation.id, USER, &db).await;
+        assert!(matches!(again, Err(AppError::Validation(_))));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn split_moves_selected_edges_and_undo_removes_new_entity() -> anyhow::Result<()> {
+        let db = setup_test_db_with_embedding_dimension(3).await?;
+        let provider = EmbeddingProvider::new_hashed(3)?;
+
+        let mercury =
+            store_entity(&db, &provider, "Mercury", "Planet and element", "doc-1").await?;
+        let sun = store_entity(&db, &provider, "Sun", "A star", "doc-1").await?;
+        let lab = store_entity(&db, &provider, "Chemistry lab", "Where we test", "doc-1").await?;
+        relate(&db, &mercury, &sun, "doc-1").await?;
+        let moved = relate(&db, &lab, &mercury, "doc-1").await?;
+
+        let operation = EntityOperation::split(
+            &mercury.id,
+            SplitRequest {
+                name: "Mercury (element)".to_string(),
+                description: "Chemical element Hg".to_string(),
+                entity_type: KnowledgeEntityType::Idea,
+                relationship_ids: vec![moved.id.clone()],
+            },
+            USER,
+            &db,
+            &provider,
+        )
+        .await?;
+        let created_id = operation
+            .created_entity_ids
+            .first()
+            .expect("split creates an entity")
+            .clone();
+
+        let created: KnowledgeEntity = db.get_item(&created_id).await?.expect("created entity");
+        assert_eq!(created.source_id, "doc-1");
+        assert!(
+            db.get_item::<KnowledgeEntityEmbedding>(&created_id)
+                .await?
+                .is_some()
+        );
+        let edges = relationships(&db).await?;
+        let moved_edge = edges
+            .iter()
+            .find(|edge| edge.id == moved.id)
+            .expect("moved edge kept its id");
+        assert_eq!(moved_edge.in_, lab.id);
+        assert_eq!(moved_edge.out, created_id);
+        assert!(
+            edges
+                .iter()
+                .any(|edge| edge.in_ == mercury.id && edge.out == sun.id)
+        );
+
+        EntityOperation::undo(&operation.id, USER, &db).await?;
+        assert!(db.get_item::<KnowledgeEntity>(&created_id).await?.is_none());
+        assert!(
+            KnowledgeEntityMention::for_entity(&created_id, USER, &db)
+                .await?
+                .is_empty()
+        );
+        let edges = relationships(&db).await?;
+        assert_eq!(edges.len(), 2);
+        assert!(
+            edges
+                .iter()
+                .any(|edge| edge.in_ == lab.id && edge.out == mercury.id)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn only_latest_operation_can_be_undone() -> anyhow::Result<()> {
+        let db = setup_test_db_with_embedding_dimension(3).await?;
+        let provider = EmbeddingProvider::new_hashed(3)?;
+
+        let a = store_entity(&db, &provider, "A", "", "doc-1").await?;
+        let b = store_entity(&db, &provider, "B", "", "doc-2").await?;
+        let c = store_entity(&db, &provider, "C", "", "doc-3").await?;
+
+        let first =
+            EntityOperation::merge(&a.id, std::slice::from_ref(&b.id), USER, &db, &provider)
+                .await?;
+        let second =
+            EntityOperation::merge(&a.id, std::slice::from_ref(&c.id), USER, &db, &provider)
+                .await?;
+
+        let blocked = EntityOperation::undo(&first.id, USER, &db).await;
+        assert!(matches!(blocked, Err(AppError::Validation(_))));
+
+        EntityOperation::undo(&second.id, USER, &db).await?;
+        EntityOperation::undo(&first.id, USER, &db).await?;
+        assert!(db.get_item::<KnowledgeEntity>(&b.id).await?.is_some());
+        assert!(db.get_item::<KnowledgeEntity>(&c.id).await?.is_some());
+        assert!(EntityOperation::latest_undoable(USER, &db).await?.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn merge_rejects_foreign_and_empty_selections() -> anyhow::Result<()> {
+        let db = setup_test_db_with_embedding_dimension(3).await?;
+        let provider = EmbeddingProvider::new_hashed(3)?;
+        let a = store_entity(&db, &provider, "A", "", "doc-1").await?;
+        let b = store_entity(&db, &provider, "B", "", "doc-2").await?;
+
+        let empty =
+            EntityOperation::merge(&a.id, std::slice::from_ref(&a.id), USER, &db, &provider).await;
+        assert!(matches!(empty, Err(AppError::Validation(_))));
+
+        let foreign = EntityOperation::merge(
+            &a.id,
+            std::slice::from_ref(&b.id),
+            "someone-else",
+            &db,
+            &provider,
+        )
+        .await;
+        assert!(matches!(foreign, Err(AppError::Auth(_))));
+        assert!(db.get_item::<KnowledgeEntity>(&b.id).await?.is_some());
+        Ok(())
+    }
+}
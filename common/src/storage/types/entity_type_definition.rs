@@ -0,0 +1,314 @@
+use chrono::Utc as ChronoUtc;
+use uuid::Uuid;
+
+use crate::{error::AppError, storage::db::SurrealDbClient, stored_object};
+
+use super::knowledge_entity::KnowledgeEntityType;
+
+/// Longest accepted name for a user-defined entity type.
+pub const MAX_ENTITY_TYPE_NAME_CHARS: usize = 40;
+/// Longest accepted description for a user-defined entity type.
+pub const MAX_ENTITY_TYPE_DESCRIPTION_CHARS: usize = 400;
+/// Color used when a type is created without one.
+pub const DEFAULT_ENTITY_TYPE_COLOR: &str = "#64748B";
+
+stored_object!(EntityTypeDefinition, "entity_type_definition", {
+    user_id: String,
+    name: String,
+    description: String,
+    color: String
+});
+
+impl EntityTypeDefinition {
+    /// Validates and builds a definition; see [`Self::create`] for the rules.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` for an invalid name, description, or color.
+    pub fn new(
+        user_id: String,
+        name: &str,
+        description: &str,
+        color: Option<&str>,
+    ) -> Result<Self, AppError> {
+        let name = validate_name(name)?;
+        let description = description.trim();
+        if description.chars().count() > MAX_ENTITY_TYPE_DESCRIPTION_CHARS {
+            return Err(AppError::Validation(format!(
+                "entity type description must be at most {MAX_ENTITY_TYPE_DESCRIPTION_CHARS} characters"
+            )));
+        }
+        let color = match color.map(str::trim).filter(|c| !c.is_empty()) {
+            Some(color) => normalize_color(color)?,
+            None => DEFAULT_ENTITY_TYPE_COLOR.to_string(),
+        };
+
+        let now = ChronoUtc::now();
+        Ok(Self {
+            id: Uuid::new_v4().to_string(),
+            created_at: now,
+            updated_at: now,
+            user_id,
+            name,
+            description: description.to_string(),
+            color,
+        })
+    }
+
+    /// The entity type this definition stands for.
+    #[must_use]
+    pub fn entity_type(&self) -> KnowledgeEntityType {
+        KnowledgeEntityType::Custom(self.name.clone())
+    }
+
+    /// All definitions owned by `user_id`, sorted by name.
+    pub async fn list_for_user(user_id: &str, db: &SurrealDbClient) -> Result<Vec<Self>, AppError> {
+        let definitions: Vec<Self> = db
+            .client
+            .query("SELECT * FROM type::table($table) WHERE user_id = $user_id ORDER BY name ASC")
+            .bind(("table", Self::table_name()))
+            .bind(("user_id", user_id.to_string()))
+            .await?
+            .take(0)?;
+
+        Ok(definitions)
+    }
+
+    /// Built-in type names followed by the user's own, as offered in forms and prompts.
+    pub async fn type_names_for_user(
+        user_id: &str,
+        db: &SurrealDbClient,
+    ) -> Result<Vec<String>, AppError> {
+        let mut names: Vec<String> = KnowledgeEntityType::variants()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        names.extend(
+            Self::list_for_user(user_id, db)
+                .await?
+                .into_iter()
+                .map(|definition| definition.name),
+        );
+        Ok(names)
+    }
+
+    /// Store a new type for `user_id`.
+    ///
+    /// Names are trimmed, must be 1–40 characters of letters, digits, spaces, `-`
+    /// or `_`, and may not repeat a built-in or existing type (ignoring case).
+    /// Colors are `#rrggbb` hex values.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` for invalid input or a duplicate name, and
+    /// `AppError::Database` if the store fails.
+    pub async fn create(
+        user_id: &str,
+        name: &str,
+        description: &str,
+        color: Option<&str>,
+        db: &SurrealDbClient,
+    ) -> Result<Self, AppError> {
+        let definition = Self::new(user_id.to_string(), name, description, color)?;
+
+        let taken = Self::list_for_user(user_id, db)
+            .await?
+            .iter()
+            .any(|existing| existing.name.eq_ignore_ascii_case(&definition.name));
+        if taken {
+            return Err(AppError::Validation(format!(
+                "entity type '{}' already exists",
+                definition.name
+            )));
+        }
+
+        db.store_item(definition)
+            .await?
+            .ok_or_else(|| AppError::internal("entity type store returned no record"))
+    }
+
+    /// Update the description and color of a type. Existing entities are untouched.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the type does not exist for `user_id`,
+    /// `AppError::Validation` for invalid input, and `AppError::Database` on failure.
+    pub async fn update(
+        id: &str,
+        user_id: &str,
+        description: &str,
+        color: Option<&str>,
+        db: &SurrealDbClient,
+    ) -> Result<Self, AppError> {
+        let existing = Self::get_owned(id, user_id, db).await?;
+        let validated = Self::new(
+            user_id.to_string(),
+            &existing.name,
+            description,
+            color.or(Some(existing.color.as_str())),
+        )?;
+
+        let updated = Self {
+            description: validated.description,
+            color: validated.color,
+            updated_at: ChronoUtc::now(),
+            ..existing
+        };
+        db.client
+            .query(
+                "UPDATE type::thing($table, $id)
+                 SET description = $description, color = $color, updated_at = time::now();",
+            )
+            .bind(("table", Self::table_name()))
+            .bind(("id", updated.id.clone()))
+            .bind(("description", updated.description.clone()))
+            .bind(("color", updated.color.clone()))
+            .await?
+            .check()?;
+
+        Ok(updated)
+    }
+
+    /// Delete a type definition. Entities already tagged with it keep the name.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the type does not exist for `user_id`, and
+    /// `AppError::Database` if the delete fails.
+    pub async fn delete(id: &str, user_id: &str, db: &SurrealDbClient) -> Result<(), AppError> {
+        let definition = Self::get_owned(id, user_id, db).await?;
+        db.delete_item::<Self>(&definition.id).await?;
+        Ok(())
+    }
+
+    async fn get_owned(id: &str, user_id: &str, db: &SurrealDbClient) -> Result<Self, AppError> {
+        db.get_item::<Self>(id)
+            .await?
+            .filter(|definition| definition.user_id == user_id)
+            .ok_or_else(|| AppError::NotFound("entity type not found".to_string()))
+    }
+}
+
+fn validate_name(raw: &str) -> Result<String, AppError> {
+    let name = raw.split_whitespace().collect::<Vec<_>>().join(" ");
+    if name.is_empty() {
+        return Err(AppError::Validation(
+            "entity type name is required".to_string(),
+        ));
+    }
+    if name.chars().count() > MAX_ENTITY_TYPE_NAME_CHARS {
+        return Err(AppError::Validation(format!(
+            "entity type name must be at most {MAX_ENTITY_TYPE_NAME_CHARS} characters"
+        )));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'))
+    {
+        return Err(AppError::Validation(
+            "entity type names may only contain letters, digits, spaces, '-' and '_'".to_string(),
+        ));
+    }
+    if KnowledgeEntityType::builtin(&name).is_some() {
+        return Err(AppError::Validation(format!(
+            "'{name}' is a built-in entity type"
+        )));
+    }
+    Ok(name)
+}
+
+fn normalize_color(raw: &str) -> Result<String, AppError> {
+    let hex = raw.strip_prefix('#').unwrap_or(raw);
+    if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        Ok(format!("#{}", hex.to_ascii_uppercase()))
+    } else {
+        Err(AppError::Validation(format!(
+            "'{raw}' is not a color; use #rrggbb"
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::must_use_candidate)]
+    use super::*;
+    use crate::test_utils::setup_test_db;
+
+    #[test]
+    fn test_new_validates_name_and_color() {
+        let definition = EntityTypeDefinition::new(
+            "user".to_string(),
+            "  Research   Paper ",
+            "Published papers",
+            Some("#a1b2c3"),
+        )
+        .expect("valid definition");
+        assert_eq!(definition.name, "Research Paper");
+        assert_eq!(definition.color, "#A1B2C3");
+        assert_eq!(
+            definition.entity_type(),
+            KnowledgeEntityType::Custom("Research Paper".to_string())
+        );
+
+        let defaulted = EntityTypeDefinition::new("user".to_string(), "Person", "", None)
+            .expect("default color");
+        assert_eq!(defaulted.color, DEFAULT_ENTITY_TYPE_COLOR);
+
+        for (name, color) in [
+            ("", None),
+            ("document", None),
+            ("Bad/Name", None),
+            ("Person", Some("red")),
+        ] {
+            assert!(
+                matches!(
+                    EntityTypeDefinition::new("user".to_string(), name, "", color),
+                    Err(AppError::Validation(_))
+                ),
+                "{name:?} / {color:?} should be rejected"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_list_update_and_delete() -> anyhow::Result<()> {
+        let db = setup_test_db().await?;
+
+        let person =
+            EntityTypeDefinition::create("user-a", "Person", "People", Some("#ff0000"), &db)
+                .await?;
+        EntityTypeDefinition::create("user-a", "Decision", "Decisions", None, &db).await?;
+        EntityTypeDefinition::create("user-b", "Person", "Other user", None, &db).await?;
+
+        let duplicate = EntityTypeDefinition::create("user-a", "person", "", None, &db).await;
+        assert!(matches!(duplicate, Err(AppError::Validation(_))));
+
+        let names = EntityTypeDefinition::type_names_for_user("user-a", &db).await?;
+        assert_eq!(
+            names,
+            vec![
+                "Idea",
+                "Project",
+                "Document",
+                "Page",
+                "TextSnippet",
+                "Decision",
+                "Person"
+            ]
+        );
+
+        let updated =
+            EntityTypeDefinition::update(&person.id, "user-a", "Humans", Some("#00ff00"), &db)
+                .await?;
+        assert_eq!(updated.description, "Humans");
+        assert_eq!(updated.color, "#00FF00");
+
+        let foreign = EntityTypeDefinition::delete(&person.id, "user-b", &db).await;
+        assert!(matches!(foreign, Err(AppError::NotFound(_))));
+
+        EntityTypeDefinition::delete(&person.id, "user-a", &db).await?;
+        let remaining = EntityTypeDefinition::list_for_user("user-a", &db).await?;
+        assert_eq!(remaining.len(), 1);
+        Ok(())
+    }
+}
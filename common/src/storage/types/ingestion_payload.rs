@@ -1,6 +1,10 @@
 #![allow(clippy::result_large_err)]
-use crate::{error::AppError, storage::types::file_info::FileInfo};
+use crate::{
+    error::AppError,
+    storage::types::{file_info::FileInfo, text_content::TextContent},
+};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use tracing::info;
 use url::Url;
 
@@ -24,6 +28,13 @@ pub enum IngestionPayload {
         category: String,
         user_id: String,
     },
+    /// Re-run the pipeline over an already stored `TextContent`, reusing its text.
+    Reingest {
+        text_content_id: String,
+        /// Display label captured when the re-ingest was queued.
+        label: String,
+        user_id: String,
+    },
 }
 
 impl Default for IngestionPayload {
@@ -150,6 +161,52 @@ impl IngestionPayload {
         Ok(object_list)
     }
 
+    /// Payload that re-enriches `content` in place, keeping its id.
+    #[must_use]
+    pub fn reingest(content: &TextContent) -> Self {
+        Self::Reingest {
+            text_content_id: content.id.clone(),
+            label: content.source_label(),
+            user_id: content.user_id.clone(),
+        }
+    }
+
+    /// Owner of the submitted content.
+    #[must_use]
+    pub fn user_id(&self) -> &str {
+        match self {
+            Self::Url { user_id, .. }
+            | Self::Text { user_id, .. }
+            | Self::File { user_id, .. }
+            | Self::Reingest { user_id, .. } => user_id,
+        }
+    }
+
+    /// Key used to detect repeated text and URL submissions for the same user.
+    ///
+    /// Text payloads hash their whitespace-normalized body; URL payloads use the
+    /// canonical URL (see [`canonical_url`]). Re-ingests key on the target content so
+    /// only one can be in flight. Files return `None` because they are already
+    /// deduplicated by SHA-256 in [`FileInfo`].
+    #[must_use]
+    pub fn dedup_key(&self) -> Option<String> {
+        match self {
+            Self::Text { text, .. } => {
+                let normalized = normalize_text(text);
+                if normalized.is_empty() {
+                    return None;
+                }
+                let digest = Sha256::digest(normalized.as_bytes());
+                Some(format!("text:{digest:x}"))
+            }
+            Self::Url { url, .. } => Some(format!("url:{}", canonical_url(url))),
+            Self::Reingest {
+                text_content_id, ..
+            } => Some(format!("reingest:{text_content_id}")),
+            Self::File { .. } => None,
+        }
+    }
+
     fn parse_content(content: Option<String>) -> ParsedContent {
         let Some(input_content) = content else {
             return ParsedContent::Skip;
@@ -166,6 +223,53 @@ impl IngestionPayload {
     }
 }
 
+/// Query parameters that only carry tracking data and never change the page.
+const TRACKING_QUERY_PARAMS: [&str; 4] = ["fbclid", "gclid", "mc_cid", "mc_eid"];
+
+/// Collapses whitespace runs to single spaces and trims the ends.
+fn normalize_text(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn is_tracking_param(key: &str) -> bool {
+    key.starts_with("utm_") || TRACKING_QUERY_PARAMS.contains(&key)
+}
+
+/// Canonical form of a URL for duplicate matching.
+///
+/// Drops the fragment, `utm_*` and other tracking parameters, sorts the remaining
+/// query pairs, and strips a trailing slash from non-root paths. The host is
+/// already lowercased by the parser. Unparseable input is returned trimmed.
+#[must_use]
+pub fn canonical_url(raw: &str) -> String {
+    let Ok(mut url) = Url::parse(raw.trim()) else {
+        return raw.trim().to_string();
+    };
+
+    url.set_fragment(None);
+
+    let mut pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(key, _)| !is_tracking_param(key))
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    pairs.sort();
+
+    if pairs.is_empty() {
+        url.set_query(None);
+    } else {
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+
+    let path = url.path();
+    if path.len() > 1 && path.ends_with('/') {
+        let trimmed = path.trim_end_matches('/').to_string();
+        url.set_path(&trimmed);
+    }
+
+    url.to_string()
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::expect_used, clippy::must_use_candidate)]
@@ -522,4 +626,70 @@ mod tests {
         assert!(matches!(result.get(1), Some(IngestionPayload::File { .. })));
         Ok(())
     }
+
+    #[test]
+    fn test_dedup_key_ignores_whitespace_differences() {
+        let first = IngestionPayload::Text {
+            text: "  Hello   world\n\nagain ".to_string(),
+            context: "a".to_string(),
+            category: "a".to_string(),
+            user_id: "user123".to_string(),
+        };
+        let second = IngestionPayload::Text {
+            text: "Hello world again".to_string(),
+            context: "b".to_string(),
+            category: "b".to_string(),
+            user_id: "user123".to_string(),
+        };
+
+        let key = first.dedup_key().expect("text payload has a key");
+        assert!(key.starts_with("text:"));
+        assert_eq!(first.dedup_key(), second.dedup_key());
+    }
+
+    #[test]
+    fn test_dedup_key_for_urls_uses_canonical_form() {
+        let tracked = IngestionPayload::Url {
+            url: "https://Example.com/post/?utm_source=x&b=2&a=1#section".to_string(),
+            context: String::new(),
+            category: String::new(),
+            user_id: "user123".to_string(),
+        };
+        let clean = IngestionPayload::Url {
+            url: "https://example.com/post?a=1&b=2".to_string(),
+            context: String::new(),
+            category: String::new(),
+            user_id: "user123".to_string(),
+        };
+
+        assert_eq!(
+            tracked.dedup_key().as_deref(),
+            Some("url:https://example.com/post?a=1&b=2")
+        );
+        assert_eq!(tracked.dedup_key(), clean.dedup_key());
+    }
+
+    #[test]
+    fn test_dedup_key_skips_files() {
+        let payload = IngestionPayload::File {
+            file_info: MockFileInfo {
+                id: "file1".to_string(),
+            }
+            .into(),
+            context: String::new(),
+            category: String::new(),
+            user_id: "user123".to_string(),
+        };
+
+        assert!(payload.dedup_key().is_none());
+    }
+
+    #[test]
+    fn test_canonical_url_keeps_root_slash() {
+        assert_eq!(canonical_url("https://example.com"), "https://example.com/");
+        assert_eq!(
+            canonical_url("https://example.com/?fbclid=abc"),
+            "https://example.com/"
+        );
+    }
 }
@@ -1,17 +1,25 @@
-use std::{sync::Arc, time::Duration};
+use std::{collections::HashMap, str::FromStr, sync::Arc, time::Duration};
 
 use chrono::Duration as ChronoDuration;
 use futures::future::try_join_all;
 use state_machines::state_machine;
 use surrealdb::sql::Datetime as SurrealDatetime;
+use thiserror::Error;
+use tracing::info;
 use uuid::Uuid;
 
-use crate::{error::AppError, storage::db::SurrealDbClient, stored_object};
+use crate::{
+    error::AppError, storage::db::SurrealDbClient, stored_object, utils::config::DuplicatePolicy,
+};
 
-use super::ingestion_payload::IngestionPayload;
+use super::{ingestion_payload::IngestionPayload, text_content::TextContent};
 
 pub const MAX_ATTEMPTS: u32 = 3;
 pub const DEFAULT_LEASE_SECS: i64 = 300;
+/// Lease for URL payloads, which render the page in a headless browser first.
+pub const URL_LEASE_SECS: i64 = 600;
+/// Lease for PDFs and audio/video files, which go through vision or transcription.
+pub const LONG_LEASE_SECS: i64 = 1_800;
 pub const DEFAULT_PRIORITY: i32 = 0;
 
 #[derive(Debug, Default, Clone, Copy, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
@@ -69,6 +77,72 @@ impl TaskState {
     }
 }
 
+/// Error returned when parsing a task priority name.
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("unknown task priority '{input}': expected 'low', 'normal', or 'high'")]
+pub struct ParseTaskPriorityError {
+    /// The unrecognized input string.
+    pub input: String,
+}
+
+/// Named queue priorities; higher values are claimed first.
+#[derive(Debug, Default, Clone, Copy, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskPriority {
+    /// Bulk work such as imports and library-wide re-ingests.
+    Low,
+    /// Regular submissions (default).
+    #[default]
+    Normal,
+    /// Interactive captures the user is waiting on.
+    High,
+}
+
+impl TaskPriority {
+    #[must_use]
+    pub fn value(self) -> i32 {
+        match self {
+            Self::Low => -10,
+            Self::Normal => DEFAULT_PRIORITY,
+            Self::High => 10,
+        }
+    }
+
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Normal => "normal",
+            Self::High => "high",
+        }
+    }
+
+    /// Maps a stored priority value back to the nearest named level.
+    #[must_use]
+    pub fn from_value(value: i32) -> Self {
+        match value.cmp(&DEFAULT_PRIORITY) {
+            std::cmp::Ordering::Less => Self::Low,
+            std::cmp::Ordering::Equal => Self::Normal,
+            std::cmp::Ordering::Greater => Self::High,
+        }
+    }
+}
+
+impl FromStr for TaskPriority {
+    type Err = ParseTaskPriorityError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "low" => Ok(Self::Low),
+            "normal" => Ok(Self::Normal),
+            "high" => Ok(Self::High),
+            other => Err(ParseTaskPriorityError {
+                input: other.to_string(),
+            }),
+        }
+    }
+}
+
 /// Information about an error that occurred during task processing.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq, Default)]
 pub struct TaskErrorInfo {
@@ -154,6 +228,66 @@ fn worker_id_for_bind(worker_id: Option<&String>) -> String {
     worker_id.cloned().unwrap_or_default()
 }
 
+/// Per-user record of when a worker last claimed one of the user's tasks.
+const USER_CURSOR_TABLE: &str = "ingestion_user_cursor";
+
+/// `WHERE` conditions for tasks a worker may claim right now; expects
+/// `$candidate_states`, `$sticky_states`, and `$now` to be bound.
+macro_rules! claimable_task_filter {
+    () => {
+        r#"
+                  state IN $candidate_states
+                  AND scheduled_at <= $now
+                  AND (
+                        attempts < max_attempts
+                        OR state IN $sticky_states
+                  )
+                  AND (
+                        locked_at = NONE
+                        OR time::unix($now) - time::unix(locked_at) >= lease_duration_secs
+                  )"#
+    };
+}
+
+fn claim_candidate_states() -> Vec<&'static str> {
+    vec![
+        TaskState::Pending.as_str(),
+        TaskState::Failed.as_str(),
+        TaskState::Reserved.as_str(),
+        TaskState::Processing.as_str(),
+    ]
+}
+
+fn leased_states() -> Vec<&'static str> {
+    vec![TaskState::Reserved.as_str(), TaskState::Processing.as_str()]
+}
+
+/// Round-robin choice among users with ready work: highest priority first, then
+/// the user served longest ago (never-served users first), then by id for stability.
+fn pick_next_user(
+    ready: impl IntoIterator<Item = (String, i32)>,
+    served_at: &HashMap<String, i64>,
+) -> Option<String> {
+    let mut top_priority = i32::MIN;
+    let mut candidates: Vec<String> = Vec::new();
+    for (user_id, priority) in ready {
+        if priority > top_priority {
+            top_priority = priority;
+            candidates.clear();
+        }
+        if priority == top_priority && !candidates.contains(&user_id) {
+            candidates.push(user_id);
+        }
+    }
+
+    candidates.into_iter().min_by(|a, b| {
+        served_at
+            .get(a)
+            .cmp(&served_at.get(b))
+            .then_with(|| a.cmp(b))
+    })
+}
+
 stored_object!(IngestionTask, "ingestion_task", {
     content: IngestionPayload,
     state: TaskState,
@@ -178,13 +312,60 @@ stored_object!(IngestionTask, "ingestion_task", {
         default
     )]
     last_error_at: Option<chrono::DateTime<chrono::Utc>>,
-    priority: i32
+    priority: i32,
+    /// Content fingerprint for text/URL payloads (see [`IngestionPayload::dedup_key`]).
+    #[serde(default)]
+    dedup_key: Option<String>
 });
 
+/// What happened when a payload was queued under a [`DuplicatePolicy`].
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(tag = "outcome", content = "task", rename_all = "lowercase")]
+pub enum EnqueueOutcome {
+    /// A new task was stored.
+    Created(IngestionTask),
+    /// The payload matched an earlier task, which is returned for the caller to follow.
+    Linked(IngestionTask),
+    /// The payload matched an earlier task and was dropped.
+    Skipped(IngestionTask),
+}
+
+impl EnqueueOutcome {
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Created(_) => "created",
+            Self::Linked(_) => "linked",
+            Self::Skipped(_) => "skipped",
+        }
+    }
+
+    /// The newly created task, or the existing task the payload duplicated.
+    #[must_use]
+    pub fn task(&self) -> &IngestionTask {
+        match self {
+            Self::Created(task) | Self::Linked(task) | Self::Skipped(task) => task,
+        }
+    }
+
+    #[must_use]
+    pub fn into_task(self) -> IngestionTask {
+        match self {
+            Self::Created(task) | Self::Linked(task) | Self::Skipped(task) => task,
+        }
+    }
+
+    #[must_use]
+    pub fn is_created(&self) -> bool {
+        matches!(self, Self::Created(_))
+    }
+}
+
 impl IngestionTask {
     #[must_use]
     pub fn new(content: IngestionPayload, user_id: String) -> Self {
         let now = chrono::Utc::now();
+        let dedup_key = content.dedup_key();
 
         Self {
             id: Uuid::new_v4().to_string(),
@@ -201,11 +382,18 @@ impl IngestionTask {
             error_message: None,
             last_error_at: None,
             priority: DEFAULT_PRIORITY,
+            dedup_key,
             created_at: now,
             updated_at: now,
         }
     }
 
+    #[must_use]
+    pub fn with_priority(mut self, priority: TaskPriority) -> Self {
+        self.priority = priority.value();
+        self
+    }
+
     #[must_use]
     pub fn can_retry(&self) -> bool {
         self.attempts < self.max_attempts
@@ -226,6 +414,32 @@ impl IngestionTask {
         Duration::from_secs(u64::try_from(self.lease_duration_secs.max(0)).unwrap_or(0))
     }
 
+    /// How long a worker should hold the lease for this payload.
+    ///
+    /// Workers claim with [`DEFAULT_LEASE_SECS`] and then extend the lease to this
+    /// estimate; the heartbeat keeps it alive beyond that for unusually slow runs.
+    #[must_use]
+    pub fn estimated_lease(payload: &IngestionPayload) -> Duration {
+        let secs = match payload {
+            IngestionPayload::Text { .. } | IngestionPayload::Reingest { .. } => DEFAULT_LEASE_SECS,
+            IngestionPayload::Url { .. } => URL_LEASE_SECS,
+            IngestionPayload::File { file_info, .. } => {
+                let mime = file_info.mime_type.as_str();
+                if mime == "application/pdf"
+                    || mime.starts_with("audio/")
+                    || mime.starts_with("video/")
+                {
+                    LONG_LEASE_SECS
+                } else if mime.starts_with("image/") {
+                    URL_LEASE_SECS
+                } else {
+                    DEFAULT_LEASE_SECS
+                }
+            }
+        };
+        Duration::from_secs(u64::try_from(secs).unwrap_or(0))
+    }
+
     /// Create a new task and immediately persist it to the database.
     ///
     /// # Errors
@@ -271,33 +485,292 @@ impl IngestionTask {
         .await
     }
 
+    /// Find an earlier task for the same user whose payload has `dedup_key`.
+    ///
+    /// Cancelled and dead-lettered tasks never count. Succeeded tasks only count
+    /// while their `TextContent` still exists, so deleted content can be re-ingested.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` if a query fails.
+    pub async fn find_duplicate(
+        user_id: &str,
+        dedup_key: &str,
+        db: &SurrealDbClient,
+    ) -> Result<Option<IngestionTask>, AppError> {
+        let candidates: Vec<IngestionTask> = db
+            .query(
+                "SELECT * FROM type::table($table)
+                 WHERE user_id = $user_id
+                   AND dedup_key = $dedup_key
+                   AND state NOT IN $ignored_states
+                 ORDER BY created_at DESC",
+            )
+            .bind(("table", Self::table_name()))
+            .bind(("user_id", user_id.to_string()))
+            .bind(("dedup_key", dedup_key.to_string()))
+            .bind((
+                "ignored_states",
+                vec![
+                    TaskState::Cancelled.as_str(),
+                    TaskState::DeadLetter.as_str(),
+                ],
+            ))
+            .await?
+            .take(0)?;
+
+        for candidate in candidates {
+            if candidate.state != TaskState::Succeeded
+                || db.get_item::<TextContent>(&candidate.id).await?.is_some()
+            {
+                return Ok(Some(candidate));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Queue a payload at `priority` unless `policy` says an identical earlier
+    /// submission wins.
+    ///
+    /// File payloads and [`DuplicatePolicy::Force`] always create a task. The check
+    /// is best effort: two identical requests racing each other may both be queued.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` if the lookup or store fails.
+    pub async fn create_with_policy(
+        content: IngestionPayload,
+        user_id: impl AsRef<str>,
+        policy: DuplicatePolicy,
+        priority: TaskPriority,
+        db: &SurrealDbClient,
+    ) -> Result<EnqueueOutcome, AppError> {
+        let user_id = user_id.as_ref();
+
+        if policy != DuplicatePolicy::Force
+            && let Some(dedup_key) = content.dedup_key()
+            && let Some(existing) = Self::find_duplicate(user_id, &dedup_key, db).await?
+        {
+            info!(
+                user_id = %user_id,
+                existing_task_id = %existing.id,
+                policy = policy.as_str(),
+                "Duplicate ingestion payload detected"
+            );
+            return Ok(match policy {
+                DuplicatePolicy::Skip => EnqueueOutcome::Skipped(existing),
+                DuplicatePolicy::Link | DuplicatePolicy::Force => EnqueueOutcome::Linked(existing),
+            });
+        }
+
+        let task = Self::new(content, user_id.to_string()).with_priority(priority);
+        db.store_item(task)
+            .await?
+            .map(EnqueueOutcome::Created)
+            .ok_or_else(|| AppError::internal("ingestion task store returned no record"))
+    }
+
+    /// Multi-payload variant of [`Self::create_with_policy`], preserving input order.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first [`AppError`] from any failed lookup or store.
+    pub async fn create_all_with_policy(
+        contents: Vec<IngestionPayload>,
+        user_id: impl AsRef<str>,
+        policy: DuplicatePolicy,
+        priority: TaskPriority,
+        db: &SurrealDbClient,
+    ) -> Result<Vec<EnqueueOutcome>, AppError> {
+        if contents.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let user_id = Arc::new(user_id.as_ref().to_string());
+        let db = db.clone();
+
+        try_join_all(contents.into_iter().map(|content| {
+            let user_id = Arc::clone(&user_id);
+            let db = db.clone();
+            async move {
+                Self::create_with_policy(content, user_id.as_ref(), policy, priority, &db).await
+            }
+        }))
+        .await
+    }
+
+    /// Queue a pipeline re-run over stored `content` with the current prompts and models.
+    ///
+    /// A re-ingest of the same content that is still in flight is returned as
+    /// [`EnqueueOutcome::Linked`] instead of being queued twice.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` if the lookup or store fails.
+    pub async fn create_reingest(
+        content: &TextContent,
+        priority: TaskPriority,
+        db: &SurrealDbClient,
+    ) -> Result<EnqueueOutcome, AppError> {
+        Self::create_with_policy(
+            IngestionPayload::reingest(content),
+            &content.user_id,
+            DuplicatePolicy::Link,
+            priority,
+            db,
+        )
+        .await
+    }
+
+    /// Queue a re-ingest for every stored `TextContent`, across all users.
+    ///
+    /// Tasks are created one after another so large libraries do not flood the
+    /// database, and at [`TaskPriority::Low`] so they yield to new captures.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first [`AppError`] from loading content or creating a task.
+    pub async fn create_reingest_for_all(
+        db: &SurrealDbClient,
+    ) -> Result<Vec<EnqueueOutcome>, AppError> {
+        let contents: Vec<TextContent> = db.get_all_stored_items().await?;
+        let mut outcomes = Vec::with_capacity(contents.len());
+
+        for content in &contents {
+            outcomes.push(Self::create_reingest(content, TaskPriority::Low, db).await?);
+        }
+
+        info!(
+            content_count = contents.len(),
+            queued = outcomes
+                .iter()
+                .filter(|outcome| outcome.is_created())
+                .count(),
+            "Queued bulk re-ingest"
+        );
+
+        Ok(outcomes)
+    }
+
     /// Claim the next ready task for processing.
     ///
     /// Atomically reserves a task by transitioning it from a candidate state to `Reserved`.
+    /// The highest priority with ready work goes first. Among users with work at that
+    /// priority, the one served longest ago is picked, so one user's large import
+    /// cannot hold back everyone else; each user's own tasks still run oldest first.
     /// Returns `Ok(None)` if no task is ready to claim.
     ///
     /// # Errors
     ///
-    /// Returns `AppError::Database` if the update query fails.
+    /// Returns `AppError::Database` if a query fails.
     pub async fn claim_next_ready(
         db: &SurrealDbClient,
         worker_id: &str,
         now: chrono::DateTime<chrono::Utc>,
         lease_duration: Duration,
     ) -> Result<Option<IngestionTask>, AppError> {
-        const CLAIM_QUERY: &str = r#"
+        debug_assert!(lifecycle::pending().reserve().is_ok());
+        debug_assert!(lifecycle::pending().reserve().is_ok_and(|m| {
+            m.start_processing()
+                .is_ok_and(|m| m.fail().is_ok_and(|m| m.reserve().is_ok()))
+        }));
+
+        let next_user = Self::next_user_to_serve(db, now).await?;
+        let mut claimed = match next_user.as_deref() {
+            Some(user_id) => {
+                Self::claim_ready_task(db, worker_id, now, lease_duration, Some(user_id)).await?
+            }
+            None => None,
+        };
+        if claimed.is_none() && next_user.is_some() {
+            // Another worker took that user's last ready task in the meantime.
+            claimed = Self::claim_ready_task(db, worker_id, now, lease_duration, None).await?;
+        }
+
+        if let Some(task) = &claimed {
+            Self::record_user_served(db, &task.user_id, now).await?;
+        }
+        Ok(claimed)
+    }
+
+    /// Picks the user whose ready work should be claimed next (see [`Self::claim_next_ready`]).
+    async fn next_user_to_serve(
+        db: &SurrealDbClient,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Option<String>, AppError> {
+        const READY_GROUPS_QUERY: &str = concat!(
+            "SELECT user_id, priority FROM type::table($table) WHERE ",
+            claimable_task_filter!(),
+            " GROUP BY user_id, priority;"
+        );
+        const SERVED_QUERY: &str = "
+            SELECT user_id, time::nano(last_claimed_at) AS served_at
+            FROM type::table($cursor_table)
+            WHERE user_id IN $user_ids;
+        ";
+
+        #[derive(serde::Deserialize)]
+        struct ReadyGroup {
+            user_id: String,
+            priority: i32,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct ServedAt {
+            user_id: String,
+            served_at: i64,
+        }
+
+        let groups: Vec<ReadyGroup> = db
+            .client
+            .query(READY_GROUPS_QUERY)
+            .bind(("table", Self::table_name()))
+            .bind(("candidate_states", claim_candidate_states()))
+            .bind(("sticky_states", leased_states()))
+            .bind(("now", SurrealDatetime::from(now)))
+            .await?
+            .take(0)?;
+        if groups.is_empty() {
+            return Ok(None);
+        }
+
+        let user_ids: Vec<String> = groups.iter().map(|group| group.user_id.clone()).collect();
+        let served: Vec<ServedAt> = db
+            .client
+            .query(SERVED_QUERY)
+            .bind(("cursor_table", USER_CURSOR_TABLE))
+            .bind(("user_ids", user_ids))
+            .await?
+            .take(0)?;
+        let served: HashMap<String, i64> = served
+            .into_iter()
+            .map(|entry| (entry.user_id, entry.served_at))
+            .collect();
+
+        Ok(pick_next_user(
+            groups
+                .into_iter()
+                .map(|group| (group.user_id, group.priority)),
+            &served,
+        ))
+    }
+
+    async fn claim_ready_task(
+        db: &SurrealDbClient,
+        worker_id: &str,
+        now: chrono::DateTime<chrono::Utc>,
+        lease_duration: Duration,
+        user_id: Option<&str>,
+    ) -> Result<Option<IngestionTask>, AppError> {
+        const CLAIM_QUERY: &str = concat!(
+            r#"
             UPDATE (
                 SELECT * FROM type::table($table)
-                WHERE state IN $candidate_states
-                  AND scheduled_at <= $now
-                  AND (
-                        attempts < max_attempts
-                        OR state IN $sticky_states
-                  )
-                  AND (
-                        locked_at = NONE
-                        OR time::unix($now) - time::unix(locked_at) >= lease_duration_secs
-                  )
+                WHERE "#,
+            claimable_task_filter!(),
+            r#"
+                  AND ($any_user OR user_id = $user_id)
                 ORDER BY priority DESC, scheduled_at ASC, created_at ASC
                 LIMIT 1
             )
@@ -312,35 +785,21 @@ impl IngestionTask {
                 lease_duration_secs = $lease_secs,
                 updated_at = $now
             RETURN *;
-        "#;
-
-        debug_assert!(lifecycle::pending().reserve().is_ok());
-        debug_assert!(lifecycle::pending().reserve().is_ok_and(|m| {
-            m.start_processing()
-                .is_ok_and(|m| m.fail().is_ok_and(|m| m.reserve().is_ok()))
-        }));
+        "#
+        );
 
         let mut result = db
             .client
             .query(CLAIM_QUERY)
             .bind(("table", Self::table_name()))
-            .bind((
-                "candidate_states",
-                vec![
-                    TaskState::Pending.as_str(),
-                    TaskState::Failed.as_str(),
-                    TaskState::Reserved.as_str(),
-                    TaskState::Processing.as_str(),
-                ],
-            ))
-            .bind((
-                "sticky_states",
-                vec![TaskState::Reserved.as_str(), TaskState::Processing.as_str()],
-            ))
+            .bind(("candidate_states", claim_candidate_states()))
+            .bind(("sticky_states", leased_states()))
             .bind((
                 "increment_states",
                 vec![TaskState::Pending.as_str(), TaskState::Failed.as_str()],
             ))
+            .bind(("any_user", user_id.is_none()))
+            .bind(("user_id", user_id.unwrap_or_default().to_string()))
             .bind(("reserved_state", TaskState::Reserved.as_str()))
             .bind(("now", SurrealDatetime::from(now)))
             .bind(("worker_id", worker_id.to_string()))
@@ -354,6 +813,113 @@ impl IngestionTask {
         Ok(task)
     }
 
+    async fn record_user_served(
+        db: &SurrealDbClient,
+        user_id: &str,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), AppError> {
+        db.client
+            .query(
+                "UPSERT type::thing($cursor_table, $user_id)
+                 SET user_id = $user_id, last_claimed_at = $now;",
+            )
+            .bind(("cursor_table", USER_CURSOR_TABLE))
+            .bind(("user_id", user_id.to_string()))
+            .bind(("now", SurrealDatetime::from(now)))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// Change the priority of a task that is still waiting in the queue.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the task does not exist or belongs to another
+    /// user, `AppError::Validation` if it is already running or finished, and
+    /// `AppError::Database` if the update fails.
+    pub async fn set_priority(
+        task_id: &str,
+        user_id: &str,
+        priority: TaskPriority,
+        db: &SurrealDbClient,
+    ) -> Result<IngestionTask, AppError> {
+        let task = db
+            .get_item::<IngestionTask>(task_id)
+            .await?
+            .filter(|task| task.user_id == user_id)
+            .ok_or_else(|| AppError::NotFound(format!("ingestion task {task_id}")))?;
+
+        let mut result = db
+            .client
+            .query(
+                "UPDATE type::thing($table, $id)
+                 SET priority = $priority, updated_at = $now
+                 WHERE state IN $queued_states
+                 RETURN *;",
+            )
+            .bind(("table", Self::table_name()))
+            .bind(("id", task.id.clone()))
+            .bind(("priority", priority.value()))
+            .bind((
+                "queued_states",
+                vec![TaskState::Pending.as_str(), TaskState::Failed.as_str()],
+            ))
+            .bind(("now", SurrealDatetime::from(chrono::Utc::now())))
+            .await?;
+
+        let updated: Option<IngestionTask> = result.take(0)?;
+        updated.ok_or_else(|| {
+            AppError::Validation(format!(
+                "task is {} and can no longer be reprioritized",
+                task.state.display_label().to_lowercase()
+            ))
+        })
+    }
+
+    /// Extend the lease on a task that `worker_id` still holds.
+    ///
+    /// The lease restarts at `now` and lasts `lease_duration`. Returns `false` when
+    /// the task is no longer reserved or processing by this worker, e.g. because
+    /// the lease lapsed and another worker claimed it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Database` if the update query fails.
+    pub async fn renew_lease(
+        db: &SurrealDbClient,
+        task_id: &str,
+        worker_id: &str,
+        now: chrono::DateTime<chrono::Utc>,
+        lease_duration: Duration,
+    ) -> Result<bool, AppError> {
+        const RENEW_QUERY: &str = r#"
+            UPDATE type::thing($table, $id)
+            SET locked_at = $now,
+                lease_duration_secs = $lease_secs,
+                updated_at = $now
+            WHERE worker_id = $worker_id AND state IN $leased_states
+            RETURN *;
+        "#;
+
+        let mut result = db
+            .client
+            .query(RENEW_QUERY)
+            .bind(("table", Self::table_name()))
+            .bind(("id", task_id.to_string()))
+            .bind(("worker_id", worker_id.to_string()))
+            .bind(("leased_states", leased_states()))
+            .bind(("now", SurrealDatetime::from(now)))
+            .bind((
+                "lease_secs",
+                i64::try_from(lease_duration.as_secs()).unwrap_or(i64::MAX),
+            ))
+            .await?;
+
+        let renewed: Option<IngestionTask> = result.take(0)?;
+        Ok(renewed.is_some())
+    }
+
     /// Transition this task from `Reserved` to `Processing`.
     ///
     /// # Errors
@@ -630,7 +1196,7 @@ mod tests {
     use anyhow::{self, Context};
 
     use super::*;
-    use crate::storage::types::ingestion_payload::IngestionPayload;
+    use crate::storage::types::{file_info::FileInfo, ingestion_payload::IngestionPayload};
     use crate::test_utils::setup_test_db;
 
     fn create_payload(user_id: &str) -> IngestionPayload {
@@ -748,7 +1314,7 @@ mod tests {
 
         let worker_id = "worker-1";
         let now = chrono::Utc::now();
-        let claimed = IngestionTask::claim_next_ready(&db, worker_id, now, Duration::from_secs(60))
+        let claimed = IngestionTask::claim_next_ready(&db, worker_id, now, Duration::from_mins(1))
             .await
             .with_context(|| "claim".to_string())?
             .with_context(|| "task claimed".to_string())?;
@@ -784,7 +1350,7 @@ mod tests {
 
         let worker_id = "worker-dead";
         let now = chrono::Utc::now();
-        let claimed = IngestionTask::claim_next_ready(&db, worker_id, now, Duration::from_secs(60))
+        let claimed = IngestionTask::claim_next_ready(&db, worker_id, now, Duration::from_mins(1))
             .await
             .with_context(|| "claim".to_string())?
             .with_context(|| "claimed".to_string())?;
@@ -908,4 +1474,387 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_new_task_records_dedup_key() {
+        let payload = create_payload("user123");
+        let task = IngestionTask::new(payload.clone(), "user123".to_string());
+
+        assert!(task.dedup_key.is_some());
+        assert_eq!(task.dedup_key, payload.dedup_key());
+    }
+
+    #[tokio::test]
+    async fn test_create_with_policy_links_and_skips_duplicates() -> anyhow::Result<()> {
+        let db = memory_db().await?;
+        let user_id = "user123";
+
+        let first = IngestionTask::create_with_policy(
+            create_payload(user_id),
+            user_id,
+            DuplicatePolicy::Link,
+            TaskPriority::Normal,
+            &db,
+        )
+        .await?;
+        assert!(first.is_created());
+
+        let linked = IngestionTask::create_with_policy(
+            create_payload(user_id),
+            user_id,
+            DuplicatePolicy::Link,
+            TaskPriority::Normal,
+            &db,
+        )
+        .await?;
+        assert_eq!(linked.as_str(), "linked");
+        assert_eq!(linked.task().id, first.task().id);
+
+        let skipped = IngestionTask::create_with_policy(
+            create_payload(user_id),
+            user_id,
+            DuplicatePolicy::Skip,
+            TaskPriority::Normal,
+            &db,
+        )
+        .await?;
+        assert_eq!(skipped.as_str(), "skipped");
+        assert_eq!(skipped.task().id, first.task().id);
+
+        let forced = IngestionTask::create_with_policy(
+            create_payload(user_id),
+            user_id,
+            DuplicatePolicy::Force,
+            TaskPriority::Normal,
+            &db,
+        )
+        .await?;
+        assert!(forced.is_created());
+        assert_ne!(forced.task().id, first.task().id);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_create_with_policy_is_scoped_per_user_and_ignores_cancelled() -> anyhow::Result<()>
+    {
+        let db = memory_db().await?;
+
+        let first = IngestionTask::create_with_policy(
+            create_payload("user-a"),
+            "user-a",
+            DuplicatePolicy::Skip,
+            TaskPriority::Normal,
+            &db,
+        )
+        .await?
+        .into_task();
+
+        let other_user = IngestionTask::create_with_policy(
+            create_payload("user-b"),
+            "user-b",
+            DuplicatePolicy::Skip,
+            TaskPriority::Normal,
+            &db,
+        )
+        .await?;
+        assert!(other_user.is_created());
+
+        first.mark_cancelled(&db).await?;
+
+        let again = IngestionTask::create_with_policy(
+            create_payload("user-a"),
+            "user-a",
+            DuplicatePolicy::Skip,
+            TaskPriority::Normal,
+            &db,
+        )
+        .await?;
+        assert!(again.is_created());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_succeeded_duplicate_requires_existing_content() -> anyhow::Result<()> {
+        let db = memory_db().await?;
+        let user_id = "user123";
+        let task = IngestionTask::new(create_payload(user_id), user_id.to_string());
+        db.store_item(task.clone()).await?;
+
+        let claimed = IngestionTask::claim_next_ready(
+            &db,
+            "worker-dedup",
+            chrono::Utc::now(),
+            Duration::from_mins(1),
+        )
+        .await?
+        .context("task claimed")?;
+        claimed
+            .mark_processing(&db)
+            .await?
+            .mark_succeeded(&db)
+            .await?;
+
+        let dedup_key = task.dedup_key.clone().context("dedup key")?;
+        assert!(
+            IngestionTask::find_duplicate(user_id, &dedup_key, &db)
+                .await?
+                .is_none(),
+            "succeeded task without content should not block re-ingest"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_create_reingest_links_while_in_flight() -> anyhow::Result<()> {
+        let db = memory_db().await?;
+        let content = TextContent::new(
+            "Stored text".to_string(),
+            None,
+            "notes".to_string(),
+            None,
+            None,
+            "user123".to_string(),
+        );
+        db.store_item(content.clone()).await?;
+
+        let first = IngestionTask::create_reingest(&content, TaskPriority::Normal, &db).await?;
+        assert!(first.is_created());
+        match &first.task().content {
+            IngestionPayload::Reingest {
+                text_content_id,
+                user_id,
+                ..
+            } => {
+                assert_eq!(text_content_id, &content.id);
+                assert_eq!(user_id, "user123");
+            }
+            other => anyhow::bail!("expected reingest payload, got {other:?}"),
+        }
+
+        let second = IngestionTask::create_reingest(&content, TaskPriority::Normal, &db).await?;
+        assert_eq!(second.as_str(), "linked");
+        assert_eq!(second.task().id, first.task().id);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_create_reingest_for_all_covers_every_user() -> anyhow::Result<()> {
+        let db = memory_db().await?;
+        for user_id in ["user-a", "user-b"] {
+            db.store_item(TextContent::new(
+                format!("text for {user_id}"),
+                None,
+                "notes".to_string(),
+                None,
+                None,
+                user_id.to_string(),
+            ))
+            .await?;
+        }
+
+        let outcomes = IngestionTask::create_reingest_for_all(&db).await?;
+
+        assert_eq!(outcomes.len(), 2);
+        assert!(outcomes.iter().all(EnqueueOutcome::is_created));
+        assert!(
+            outcomes
+                .iter()
+                .all(|outcome| outcome.task().priority == TaskPriority::Low.value()),
+            "bulk re-ingests queue behind regular submissions"
+        );
+        let mut owners: Vec<&str> = outcomes
+            .iter()
+            .map(|outcome| outcome.task().user_id.as_str())
+            .collect();
+        owners.sort_unstable();
+        assert_eq!(owners, vec!["user-a", "user-b"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_renew_lease_only_for_owning_worker() -> anyhow::Result<()> {
+        let db = memory_db().await?;
+        let user_id = "user123";
+        let task = IngestionTask::new(create_payload(user_id), user_id.to_string());
+        db.store_item(task.clone()).await?;
+
+        let claimed_at = chrono::Utc::now();
+        let claimed =
+            IngestionTask::claim_next_ready(&db, "worker-a", claimed_at, Duration::from_mins(1))
+                .await?
+                .context("task claimed")?;
+
+        let renewed_at = claimed_at + ChronoDuration::seconds(30);
+        let extended = Duration::from_mins(10);
+        assert!(
+            IngestionTask::renew_lease(&db, &claimed.id, "worker-a", renewed_at, extended).await?
+        );
+        assert!(
+            !IngestionTask::renew_lease(&db, &claimed.id, "worker-b", renewed_at, extended).await?
+        );
+
+        let stored: IngestionTask = db.get_item(&claimed.id).await?.context("task stored")?;
+        assert_eq!(
+            stored.locked_at.map(|at| at.timestamp()),
+            Some(renewed_at.timestamp())
+        );
+        assert_eq!(stored.lease_duration(), extended);
+
+        let processing = claimed.mark_processing(&db).await?;
+        let succeeded = processing.mark_succeeded(&db).await?;
+        assert!(
+            !IngestionTask::renew_lease(&db, &succeeded.id, "worker-a", renewed_at, extended)
+                .await?,
+            "finished tasks have no lease to renew"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_estimated_lease_scales_with_payload_kind() {
+        let user_id = "user123";
+        let file = |mime: &str| IngestionPayload::File {
+            file_info: FileInfo {
+                id: "file1".into(),
+                created_at: chrono::Utc::now(),
+                updated_at: chrono::Utc::now(),
+                sha256: "hash".into(),
+                path: "/tmp/file".into(),
+                file_name: "file".into(),
+                mime_type: mime.into(),
+                user_id: user_id.into(),
+            },
+            context: String::new(),
+            category: "notes".into(),
+            user_id: user_id.into(),
+        };
+        let secs = |payload: &IngestionPayload| IngestionTask::estimated_lease(payload).as_secs();
+
+        assert_eq!(secs(&create_payload(user_id)), 300);
+        assert_eq!(
+            secs(&IngestionPayload::Url {
+                url: "https://example.com".into(),
+                context: String::new(),
+                category: "notes".into(),
+                user_id: user_id.into(),
+            }),
+            600
+        );
+        assert_eq!(secs(&file("application/pdf")), 1_800);
+        assert_eq!(secs(&file("audio/mpeg")), 1_800);
+        assert_eq!(secs(&file("image/png")), 600);
+        assert_eq!(secs(&file("text/plain")), 300);
+    }
+
+    #[tokio::test]
+    async fn test_claim_round_robins_between_users() -> anyhow::Result<()> {
+        let db = memory_db().await?;
+        let start = chrono::Utc::now();
+        for age_secs in [10, 9, 8] {
+            let mut task = IngestionTask::new(create_payload("user-a"), "user-a".to_string());
+            task.created_at = start - ChronoDuration::seconds(age_secs);
+            task.scheduled_at = task.created_at;
+            db.store_item(task).await?;
+        }
+        let mut late = IngestionTask::new(create_payload("user-b"), "user-b".to_string());
+        late.scheduled_at = start;
+        db.store_item(late).await?;
+
+        let mut owners = Vec::new();
+        for offset in 1..=4 {
+            let now = start + ChronoDuration::seconds(offset);
+            let claimed =
+                IngestionTask::claim_next_ready(&db, "worker-fair", now, Duration::from_mins(1))
+                    .await?
+                    .context("task claimed")?;
+            owners.push(claimed.user_id);
+        }
+
+        assert_eq!(owners, vec!["user-a", "user-b", "user-a", "user-a"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_claim_prefers_higher_priority() -> anyhow::Result<()> {
+        let db = memory_db().await?;
+        let bulk = IngestionTask::new(create_payload("user-a"), "user-a".to_string())
+            .with_priority(TaskPriority::Low);
+        db.store_item(bulk.clone()).await?;
+        let normal = IngestionTask::new(create_payload("user-a"), "user-a".to_string());
+        db.store_item(normal.clone()).await?;
+        let capture = IngestionTask::new(create_payload("user-b"), "user-b".to_string())
+            .with_priority(TaskPriority::High);
+        db.store_item(capture.clone()).await?;
+
+        let now = chrono::Utc::now() + ChronoDuration::seconds(1);
+        let mut claimed_ids = Vec::new();
+        for _ in 0..3 {
+            let claimed =
+                IngestionTask::claim_next_ready(&db, "worker-prio", now, Duration::from_mins(1))
+                    .await?
+                    .context("task claimed")?;
+            claimed_ids.push(claimed.id);
+        }
+
+        assert_eq!(claimed_ids, vec![capture.id, normal.id, bulk.id]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pick_next_user_prefers_least_recently_served() {
+        let ready = vec![
+            ("user-a".to_string(), 0),
+            ("user-b".to_string(), 0),
+            ("user-c".to_string(), -10),
+        ];
+        let mut served = HashMap::new();
+        assert_eq!(
+            pick_next_user(ready.clone(), &served).as_deref(),
+            Some("user-a")
+        );
+
+        served.insert("user-a".to_string(), 20);
+        served.insert("user-b".to_string(), 10);
+        assert_eq!(pick_next_user(ready, &served).as_deref(), Some("user-b"));
+
+        assert_eq!(pick_next_user(Vec::new(), &served), None);
+    }
+
+    #[tokio::test]
+    async fn test_set_priority_only_for_owner_and_queued_tasks() -> anyhow::Result<()> {
+        let db = memory_db().await?;
+        let task = IngestionTask::new(create_payload("user-a"), "user-a".to_string());
+        db.store_item(task.clone()).await?;
+
+        let raised =
+            IngestionTask::set_priority(&task.id, "user-a", TaskPriority::High, &db).await?;
+        assert_eq!(raised.priority, TaskPriority::High.value());
+
+        let foreign = IngestionTask::set_priority(&task.id, "user-b", TaskPriority::Low, &db).await;
+        assert!(matches!(foreign, Err(AppError::NotFound(_))));
+
+        IngestionTask::claim_next_ready(
+            &db,
+            "worker-prio",
+            chrono::Utc::now(),
+            Duration::from_mins(1),
+        )
+        .await?
+        .context("task claimed")?;
+        let running = IngestionTask::set_priority(&task.id, "user-a", TaskPriority::Low, &db).await;
+        assert!(matches!(running, Err(AppError::Validation(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_task_priority_parses_names() {
+        assert_eq!("HIGH".parse::<TaskPriority>(), Ok(TaskPriority::High));
+        assert_eq!(" low ".parse::<TaskPriority>(), Ok(TaskPriority::Low));
+        assert!("urgent".parse::<TaskPriority>().is_err());
+        assert_eq!(
+            TaskPriority::from_value(TaskPriority::Low.value()),
+            TaskPriority::Low
+        );
+        assert_eq!(TaskPriority::default().value(), DEFAULT_PRIORITY);
+    }
 }
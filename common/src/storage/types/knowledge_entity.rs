@@ -17,45 +17,93 @@ use crate::{
 use tracing::{error, info};
 use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+/// Type of a knowledge entity.
+///
+/// Stored as its plain name. Names that are not built in become [`Self::Custom`], so
+/// types users define (see [`EntityTypeDefinition`]) survive a round trip.
+///
+/// [`EntityTypeDefinition`]: crate::storage::types::entity_type_definition::EntityTypeDefinition
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum KnowledgeEntityType {
     Idea,
     Project,
     Document,
     Page,
     TextSnippet,
-    // Add more types as needed
+    /// A user-defined type, holding its display name.
+    Custom(String),
 }
 impl KnowledgeEntityType {
+    /// Names of the built-in types.
     #[must_use]
     pub fn variants() -> &'static [&'static str] {
         &["Idea", "Project", "Document", "Page", "TextSnippet"]
     }
 
     #[must_use]
-    pub const fn as_str(self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             Self::Idea => "Idea",
             Self::Project => "Project",
             Self::Document => "Document",
             Self::Page => "Page",
             Self::TextSnippet => "TextSnippet",
+            Self::Custom(name) => name,
+        }
+    }
+
+    /// Looks up a built-in type by name, ignoring case.
+    #[must_use]
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "idea" => Some(Self::Idea),
+            "project" => Some(Self::Project),
+            "document" => Some(Self::Document),
+            "page" => Some(Self::Page),
+            "textsnippet" => Some(Self::TextSnippet),
+            _ => None,
         }
     }
 }
 
 impl From<String> for KnowledgeEntityType {
     fn from(s: String) -> Self {
-        match s.to_lowercase().as_str() {
-            "idea" => KnowledgeEntityType::Idea,
-            "project" => KnowledgeEntityType::Project,
-            "page" => KnowledgeEntityType::Page,
-            "textsnippet" => KnowledgeEntityType::TextSnippet,
-            _ => KnowledgeEntityType::Document, // Default case
+        if let Some(builtin) = Self::builtin(&s) {
+            return builtin;
+        }
+        let trimmed = s.trim();
+        if trimmed.is_empty() {
+            KnowledgeEntityType::Document
+        } else {
+            KnowledgeEntityType::Custom(trimmed.to_string())
         }
     }
 }
 
+impl std::fmt::Display for KnowledgeEntityType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for KnowledgeEntityType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for KnowledgeEntityType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        String::deserialize(deserializer).map(Self::from)
+    }
+}
+
 /// Search result including hydrated entity.
 #[allow(clippy::module_name_repetitions)]
 #[derive(Debug, Clone, PartialEq)]
@@ -70,7 +118,10 @@ stored_object!(KnowledgeEntity, "knowledge_entity", {
     description: String,
     entity_type: KnowledgeEntityType,
     metadata: Option<serde_json::Value>,
-    user_id: String
+    user_id: String,
+    /// Tag names from the user's vocabulary (see [`super::tag::Tag`]).
+    #[serde(default)]
+    tags: Vec<String>
 });
 
 impl HasEmbedding for KnowledgeEntity {
@@ -106,6 +157,7 @@ impl KnowledgeEntity {
             entity_type,
             metadata,
             user_id,
+            tags: Vec::new(),
         }
     }
 
@@ -114,7 +166,7 @@ impl KnowledgeEntity {
     pub fn embedding_input_text(
         name: &str,
         description: &str,
-        entity_type: KnowledgeEntityType,
+        entity_type: &KnowledgeEntityType,
     ) -> String {
         let mut out = String::with_capacity(
             name.len()
@@ -152,6 +204,8 @@ impl KnowledgeEntity {
             #[serde(default)]
             metadata: Option<serde_json::Value>,
             user_id: String,
+            #[serde(default)]
+            tags: Vec<String>,
             score: f32,
         }
 
@@ -168,6 +222,7 @@ impl KnowledgeEntity {
                 entity_type,
                 metadata,
                 user_id,
+                tags,
                 (
                     IF search::score(0) != NONE THEN search::score(0) ELSE 0 END +
                     IF search::score(1) != NONE THEN search::score(1) ELSE 0 END
@@ -205,6 +260,7 @@ impl KnowledgeEntity {
                     entity_type: row.entity_type,
                     metadata: row.metadata,
                     user_id: row.user_id,
+                    tags: row.tags,
                 },
                 score: row.score,
             })
@@ -240,6 +296,34 @@ impl KnowledgeEntity {
         Ok(entities)
     }
 
+    /// Fetch the knowledge entities with the given ids owned by a user, in no particular order.
+    ///
+    /// Unknown ids and entities of other users are skipped.
+    pub async fn find_by_ids(
+        db: &SurrealDbClient,
+        ids: &[String],
+        user_id: &str,
+    ) -> Result<Vec<KnowledgeEntity>, AppError> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let records: Vec<surrealdb::RecordId> = ids
+            .iter()
+            .map(|id| surrealdb::RecordId::from_table_key(Self::table_name(), id))
+            .collect();
+        let entities: Vec<KnowledgeEntity> = db
+            .client
+            .query("SELECT * FROM type::table($table) WHERE id IN $records AND user_id = $user_id")
+            .bind(("table", Self::table_name()))
+            .bind(("records", records))
+            .bind(("user_id", user_id.to_owned()))
+            .await?
+            .take(0)?;
+
+        Ok(entities)
+    }
+
     pub async fn delete_by_source_id(
         source_id: &str,
         db: &SurrealDbClient,
@@ -285,7 +369,7 @@ impl KnowledgeEntity {
         db_client: &SurrealDbClient,
         embedding_provider: &EmbeddingProvider,
     ) -> Result<(), AppError> {
-        let embedding_input = Self::embedding_input_text(name, description, *entity_type);
+        let embedding_input = Self::embedding_input_text(name, description, entity_type);
         let embedding = embedding_provider.embed(&embedding_input).await?;
 
         let entity: KnowledgeEntity = db_client
@@ -381,7 +465,7 @@ impl KnowledgeEntity {
                     Self::embedding_input_text(
                         &entity.name,
                         &entity.description,
-                        entity.entity_type,
+                        &entity.entity_type,
                     )
                 })
                 .collect();
@@ -506,7 +590,7 @@ mod tests {
         let text = KnowledgeEntity::embedding_input_text(
             "Alpha",
             "Beta",
-            KnowledgeEntityType::TextSnippet,
+            &KnowledgeEntityType::TextSnippet,
         );
         assert_eq!(text, "name: Alpha, description: Beta, type: TextSnippet");
     }
@@ -526,7 +610,7 @@ mod tests {
             source_id.clone(),
             name.clone(),
             description.clone(),
-            entity_type,
+            entity_type.clone(),
             metadata.clone(),
             user_id.clone(),
         );
@@ -575,13 +659,33 @@ mod tests {
         );
 
         assert_eq!(
-            KnowledgeEntityType::from("unknown".to_string()),
+            KnowledgeEntityType::from("  Person ".to_string()),
+            KnowledgeEntityType::Custom("Person".to_string())
+        );
+        assert_eq!(
+            KnowledgeEntityType::from("   ".to_string()),
             KnowledgeEntityType::Document
         );
 
         Ok(())
     }
 
+    #[test]
+    fn test_knowledge_entity_type_serializes_as_plain_name() -> anyhow::Result<()> {
+        let custom = KnowledgeEntityType::Custom("Decision".to_string());
+        assert_eq!(serde_json::to_value(&custom)?, json!("Decision"));
+        assert_eq!(
+            serde_json::to_value(KnowledgeEntityType::TextSnippet)?,
+            json!("TextSnippet")
+        );
+
+        let parsed: KnowledgeEntityType = serde_json::from_value(json!("Decision"))?;
+        assert_eq!(parsed, custom);
+        let parsed: KnowledgeEntityType = serde_json::from_value(json!("Idea"))?;
+        assert_eq!(parsed, KnowledgeEntityType::Idea);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_knowledge_entity_variants() -> anyhow::Result<()> {
         let variants = KnowledgeEntityType::variants();
@@ -607,7 +711,7 @@ mod tests {
             source_id.clone(),
             "Entity 1".to_string(),
             "Description 1".to_string(),
-            entity_type,
+            entity_type.clone(),
             None,
             user_id.clone(),
         );
@@ -616,7 +720,7 @@ mod tests {
             source_id.clone(),
             "Entity 2".to_string(),
             "Description 2".to_string(),
-            entity_type,
+            entity_type.clone(),
             None,
             user_id.clone(),
         );
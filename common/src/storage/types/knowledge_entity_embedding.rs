@@ -112,6 +112,7 @@ mod tests {
             entity_type: KnowledgeEntityType::Document,
             metadata: None,
             user_id: user_id.to_owned(),
+            tags: Vec::new(),
         }
     }
 
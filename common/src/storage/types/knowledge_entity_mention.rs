@@ -0,0 +1,184 @@
+use std::collections::{HashMap, HashSet};
+
+use uuid::Uuid;
+
+use crate::{error::AppError, storage::db::SurrealDbClient, stored_object};
+
+stored_object!(
+    /// Records that a source document mentions a knowledge entity.
+    ///
+    /// Canonical entities are shared across documents; a mention ties one of them to
+    /// each source that produced it, under the name that source used. Deleting a
+    /// document removes its mentions, and the entity only goes once no mentions remain.
+    KnowledgeEntityMention, "knowledge_entity_mention", {
+    entity_id: String,
+    source_id: String,
+    user_id: String,
+    /// Name the entity had in this source; differs from the entity name for aliases.
+    surface_name: String
+});
+
+impl KnowledgeEntityMention {
+    #[must_use]
+    pub fn new(
+        entity_id: String,
+        source_id: String,
+        user_id: String,
+        surface_name: String,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            created_at: now,
+            updated_at: now,
+            entity_id,
+            source_id,
+            user_id,
+            surface_name,
+        }
+    }
+
+    /// All mentions of one entity, oldest first.
+    pub async fn for_entity(
+        entity_id: &str,
+        user_id: &str,
+        db: &SurrealDbClient,
+    ) -> Result<Vec<Self>, AppError> {
+        let mentions: Vec<Self> = db
+            .client
+            .query(
+                "SELECT * FROM type::table($table)
+                 WHERE entity_id = $entity_id AND user_id = $user_id
+                 ORDER BY created_at ASC",
+            )
+            .bind(("table", Self::table_name()))
+            .bind(("entity_id", entity_id.to_string()))
+            .bind(("user_id", user_id.to_string()))
+            .await?
+            .take(0)?;
+
+        Ok(mentions)
+    }
+
+    /// Distinct names other sources used for an entity, excluding `canonical_name`.
+    pub async fn aliases(
+        entity_id: &str,
+        canonical_name: &str,
+        user_id: &str,
+        db: &SurrealDbClient,
+    ) -> Result<Vec<String>, AppError> {
+        let mut seen = HashSet::from([canonical_name.trim().to_lowercase()]);
+        let aliases = Self::for_entity(entity_id, user_id, db)
+            .await?
+            .into_iter()
+            .map(|mention| mention.surface_name.trim().to_string())
+            .filter(|name| !name.is_empty() && seen.insert(name.to_lowercase()))
+            .collect();
+
+        Ok(aliases)
+    }
+
+    /// Source ids mentioning each of `entity_ids`.
+    pub async fn sources_by_entity(
+        entity_ids: &[String],
+        user_id: &str,
+        db: &SurrealDbClient,
+    ) -> Result<HashMap<String, HashSet<String>>, AppError> {
+        #[derive(Deserialize)]
+        struct Row {
+            entity_id: String,
+            source_id: String,
+        }
+
+        if entity_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let rows: Vec<Row> = db
+            .client
+            .query(
+                "SELECT entity_id, source_id FROM type::table($table)
+                 WHERE user_id = $user_id AND entity_id IN $entity_ids",
+            )
+            .bind(("table", Self::table_name()))
+            .bind(("user_id", user_id.to_string()))
+            .bind(("entity_ids", entity_ids.to_vec()))
+            .await?
+            .take(0)?;
+
+        let mut sources: HashMap<String, HashSet<String>> = HashMap::new();
+        for row in rows {
+            sources
+                .entry(row.entity_id)
+                .or_default()
+                .insert(row.source_id);
+        }
+        Ok(sources)
+    }
+
+    /// Removes every mention of an entity, e.g. when the entity itself is deleted.
+    pub async fn delete_for_entity(
+        entity_id: &str,
+        user_id: &str,
+        db: &SurrealDbClient,
+    ) -> Result<(), AppError> {
+        db.client
+            .query("DELETE type::table($table) WHERE entity_id = $entity_id AND user_id = $user_id")
+            .bind(("table", Self::table_name()))
+            .bind(("entity_id", entity_id.to_string()))
+            .bind(("user_id", user_id.to_string()))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::setup_test_db;
+
+    #[tokio::test]
+    async fn test_aliases_skip_canonical_name_and_duplicates() -> anyhow::Result<()> {
+        let db = setup_test_db().await?;
+        for (source, name) in [
+            ("doc-1", "Rust"),
+            ("doc-2", "rust-lang"),
+            ("doc-3", "Rust-Lang"),
+            ("doc-4", "RUST"),
+        ] {
+            db.store_item(KnowledgeEntityMention::new(
+                "entity-1".to_string(),
+                source.to_string(),
+                "user-1".to_string(),
+                name.to_string(),
+            ))
+            .await?;
+        }
+
+        let aliases = KnowledgeEntityMention::aliases("entity-1", "Rust", "user-1", &db).await?;
+        assert_eq!(aliases.len(), 1);
+        assert_eq!(
+            aliases.first().map(|alias| alias.to_lowercase()),
+            Some("rust-lang".to_string())
+        );
+
+        let sources = KnowledgeEntityMention::sources_by_entity(
+            &["entity-1".to_string(), "entity-2".to_string()],
+            "user-1",
+            &db,
+        )
+        .await?;
+        assert_eq!(sources.get("entity-1").map(HashSet::len), Some(4));
+        assert!(!sources.contains_key("entity-2"));
+
+        KnowledgeEntityMention::delete_for_entity("entity-1", "user-1", &db).await?;
+        assert!(
+            KnowledgeEntityMention::for_entity("entity-1", "user-1", &db)
+                .await?
+                .is_empty()
+        );
+        Ok(())
+    }
+}
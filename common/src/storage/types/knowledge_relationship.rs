@@ -1,16 +1,51 @@
+use std::collections::HashMap;
+
+use crate::storage::types::StoredObject;
+use crate::storage::types::knowledge_entity::KnowledgeEntity;
+use crate::storage::types::relationship_type_definition::RelationshipTypeCatalog;
 use crate::storage::types::user::User;
 use crate::utils::serde_helpers::deserialize_flexible_id;
 use crate::{error::AppError, storage::db::SurrealDbClient};
 use serde::{Deserialize, Serialize};
+use surrealdb::RecordId;
 use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Weight of relationships stored without one, including all created before weights.
+pub const DEFAULT_RELATIONSHIP_WEIGHT: f32 = 1.0;
+
+const fn default_weight() -> f32 {
+    DEFAULT_RELATIONSHIP_WEIGHT
+}
+
+/// Clamps a weight to `0.0..=1.0`; non-finite values become the default.
+#[must_use]
+pub fn normalize_weight(weight: f32) -> f32 {
+    if weight.is_finite() {
+        weight.clamp(0.0, 1.0)
+    } else {
+        DEFAULT_RELATIONSHIP_WEIGHT
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct RelationshipMetadata {
     pub user_id: String,
     pub source_id: String,
     pub relationship_type: String,
+    /// Confidence in the relationship, from `0.0` to `1.0`.
+    #[serde(default = "default_weight")]
+    pub weight: f32,
 }
-#[derive(Debug, Serialize, Deserialize, Clone)]
+
+/// Outcome of [`KnowledgeRelationship::canonicalize_types`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RelationshipTypeCleanup {
+    /// Relationships renamed or flipped onto a managed type.
+    pub updated: usize,
+    /// Types matching no managed type, with how many relationships use each.
+    pub unknown: Vec<(String, usize)>,
+}
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct KnowledgeRelationship {
     #[serde(deserialize_with = "deserialize_flexible_id")]
     pub id: String,
@@ -38,10 +73,18 @@ impl KnowledgeRelationship {
                 user_id,
                 source_id,
                 relationship_type,
+                weight: DEFAULT_RELATIONSHIP_WEIGHT,
             },
         }
     }
 
+    /// Sets the confidence of the relationship, see [`normalize_weight`].
+    #[must_use]
+    pub fn with_weight(mut self, weight: f32) -> Self {
+        self.metadata.weight = normalize_weight(weight);
+        self
+    }
+
     pub async fn store_relationship(self, db_client: &SurrealDbClient) -> Result<(), AppError> {
         User::get_and_validate_knowledge_entity(&self.in_, &self.metadata.user_id, db_client)
             .await?;
@@ -57,6 +100,7 @@ impl KnowledgeRelationship {
                     user_id,
                     source_id,
                     relationship_type,
+                    weight,
                 },
         } = self;
 
@@ -71,7 +115,8 @@ impl KnowledgeRelationship {
                 RELATE $in_entity->$relation->$out_entity SET
                     metadata.user_id = $user_id,
                     metadata.source_id = $source_id,
-                    metadata.relationship_type = $relationship_type;
+                    metadata.relationship_type = $relationship_type,
+                    metadata.weight = $weight;
                 COMMIT TRANSACTION;"#,
             )
             .bind(("rel_id", id))
@@ -80,6 +125,7 @@ impl KnowledgeRelationship {
             .bind(("user_id", user_id))
             .bind(("source_id", source_id))
             .bind(("relationship_type", relationship_type))
+            .bind(("weight", normalize_weight(weight)))
             .await
             .map_err(AppError::from)?
             .check()
@@ -145,6 +191,124 @@ impl KnowledgeRelationship {
             Err(AppError::NotFound(format!("Relationship {id} not found")))
         }
     }
+
+    /// Sets the weight of a relationship owned by `user_id`, see [`normalize_weight`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the relationship does not exist for `user_id`.
+    pub async fn set_weight(
+        id: &str,
+        user_id: &str,
+        weight: f32,
+        db_client: &SurrealDbClient,
+    ) -> Result<(), AppError> {
+        let updated: Vec<Self> = db_client
+            .client
+            .query(
+                "UPDATE type::thing('relates_to', $id) SET metadata.weight = $weight
+                 WHERE metadata.user_id = $user_id RETURN AFTER",
+            )
+            .bind(("id", id.to_owned()))
+            .bind(("weight", normalize_weight(weight)))
+            .bind(("user_id", user_id.to_owned()))
+            .await?
+            .take(0)?;
+
+        if updated.is_empty() {
+            return Err(AppError::NotFound(format!("Relationship {id} not found")));
+        }
+        Ok(())
+    }
+
+    /// Relationships of `user_id` touching any of `entity_ids`, in either direction.
+    ///
+    /// An empty `relationship_types` matches every type.
+    pub async fn find_adjacent(
+        entity_ids: &[String],
+        user_id: &str,
+        relationship_types: &[String],
+        db_client: &SurrealDbClient,
+    ) -> Result<Vec<Self>, AppError> {
+        if entity_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let records: Vec<RecordId> = entity_ids
+            .iter()
+            .map(|id| RecordId::from_table_key(KnowledgeEntity::table_name(), id))
+            .collect();
+        let relationships: Vec<Self> = db_client
+            .client
+            .query(
+                "SELECT * FROM relates_to
+                 WHERE metadata.user_id = $user_id
+                   AND (`in` IN $records OR out IN $records)
+                   AND ($types = [] OR metadata.relationship_type IN $types)",
+            )
+            .bind(("records", records))
+            .bind(("user_id", user_id.to_owned()))
+            .bind(("types", relationship_types.to_vec()))
+            .await?
+            .take(0)?;
+
+        Ok(relationships)
+    }
+
+    /// Rewrites the relationships of `user_id` onto the managed types in `catalog`:
+    /// spelling variants are renamed and relationships named by an inverse are flipped.
+    /// Relationships whose type matches nothing are left alone and reported.
+    pub async fn canonicalize_types(
+        user_id: &str,
+        catalog: &RelationshipTypeCatalog,
+        db_client: &SurrealDbClient,
+    ) -> Result<RelationshipTypeCleanup, AppError> {
+        let relationships: Vec<Self> = db_client
+            .client
+            .query("SELECT * FROM relates_to WHERE metadata.user_id = $user_id")
+            .bind(("user_id", user_id.to_owned()))
+            .await?
+            .take(0)?;
+
+        let mut cleanup = RelationshipTypeCleanup::default();
+        let mut unknown: HashMap<String, usize> = HashMap::new();
+        for relationship in relationships {
+            let Some(resolved) = catalog.resolve(&relationship.metadata.relationship_type) else {
+                let count = unknown
+                    .entry(relationship.metadata.relationship_type)
+                    .or_insert(0);
+                *count = count.saturating_add(1);
+                continue;
+            };
+            if !resolved.reversed && resolved.name == relationship.metadata.relationship_type {
+                continue;
+            }
+
+            let (in_, out) = if resolved.reversed {
+                (relationship.out, relationship.in_)
+            } else {
+                (relationship.in_, relationship.out)
+            };
+            Self {
+                id: relationship.id,
+                in_,
+                out,
+                metadata: RelationshipMetadata {
+                    relationship_type: resolved.name,
+                    ..relationship.metadata
+                },
+            }
+            .store_relationship(db_client)
+            .await?;
+            cleanup.updated = cleanup.updated.saturating_add(1);
+        }
+
+        cleanup.unknown = unknown.into_iter().collect();
+        cleanup
+            .unknown
+            .sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        Ok(cleanup)
+    }
 }
 
 #[cfg(test)]
@@ -333,6 +497,82 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_canonicalize_types_renames_and_flips_variants() -> anyhow::Result<()> {
+        let db = setup_test_db().await?;
+        let user_id = "user123";
+
+        let paper = create_test_entity("Paper", user_id, &db).await?;
+        let book = create_test_entity("Book", user_id, &db).await?;
+
+        let variant = KnowledgeRelationship::new(
+            paper.clone(),
+            book.clone(),
+            user_id.to_string(),
+            "source123".to_string(),
+            "is related to".to_string(),
+        )
+        .with_weight(0.4);
+        let inverse = KnowledgeRelationship::new(
+            paper.clone(),
+            book.clone(),
+            user_id.to_string(),
+            "source123".to_string(),
+            "referenced_by".to_string(),
+        );
+        let unknown = KnowledgeRelationship::new(
+            paper,
+            book.clone(),
+            user_id.to_string(),
+            "source123".to_string(),
+            "WorksAt".to_string(),
+        );
+        for relationship in [&variant, &inverse, &unknown] {
+            relationship.clone().store_relationship(&db).await?;
+        }
+
+        let cleanup = KnowledgeRelationship::canonicalize_types(
+            user_id,
+            &RelationshipTypeCatalog::builtin(),
+            &db,
+        )
+        .await?;
+        assert_eq!(cleanup.updated, 2);
+        assert_eq!(cleanup.unknown, vec![("WorksAt".to_string(), 1)]);
+
+        let renamed = get_relationship_by_id(&variant.id, &db)
+            .await
+            .expect("renamed relationship");
+        assert_eq!(renamed.metadata.relationship_type, "RelatedTo");
+        assert!((renamed.metadata.weight - 0.4).abs() < f32::EPSILON);
+
+        let flipped = get_relationship_by_id(&inverse.id, &db)
+            .await
+            .expect("flipped relationship");
+        assert_eq!(flipped.metadata.relationship_type, "References");
+        assert_eq!(flipped.in_, book);
+        assert!((flipped.metadata.weight - DEFAULT_RELATIONSHIP_WEIGHT).abs() < f32::EPSILON);
+
+        KnowledgeRelationship::set_weight(&flipped.id, user_id, 0.25, &db).await?;
+        let reweighted = get_relationship_by_id(&flipped.id, &db)
+            .await
+            .expect("reweighted relationship");
+        assert!((reweighted.metadata.weight - 0.25).abs() < f32::EPSILON);
+        assert!(matches!(
+            KnowledgeRelationship::set_weight(&flipped.id, "other-user", 0.5, &db).await,
+            Err(AppError::NotFound(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_normalize_weight_clamps_and_defaults() {
+        assert!((normalize_weight(1.7) - 1.0).abs() < f32::EPSILON);
+        assert!(normalize_weight(-0.2).abs() < f32::EPSILON);
+        assert!((normalize_weight(f32::NAN) - DEFAULT_RELATIONSHIP_WEIGHT).abs() < f32::EPSILON);
+    }
+
     #[tokio::test]
     async fn test_store_and_delete_relationship() -> anyhow::Result<()> {
         let db = setup_test_db().await?;
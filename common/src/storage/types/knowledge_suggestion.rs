@@ -0,0 +1,529 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    storage::{
+        db::SurrealDbClient,
+        types::{
+            knowledge_entity::{KnowledgeEntity, KnowledgeEntityType},
+            knowledge_entity_mention::KnowledgeEntityMention,
+            knowledge_relationship::{KnowledgeRelationship, normalize_weight},
+            relationship_type_definition::RelationshipTypeCatalog,
+            system_settings::SystemSettings,
+        },
+    },
+    stored_object,
+    utils::embedding::EmbeddingProvider,
+};
+
+/// What a [`KnowledgeSuggestion`] proposes to add to the graph.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionKind {
+    Entity,
+    Relationship,
+}
+
+/// Review state of a [`KnowledgeSuggestion`].
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionStatus {
+    #[default]
+    Pending,
+    Accepted,
+    Rejected,
+}
+
+impl SuggestionStatus {
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Accepted => "accepted",
+            Self::Rejected => "rejected",
+        }
+    }
+}
+
+stored_object!(
+    /// An entity or relationship proposed by enrichment and held back from the graph
+    /// until the user accepts it.
+    ///
+    /// Suggestions belong to the document they came from and are replaced when it is
+    /// re-ingested. Staged entities keep the id they will be stored under, so
+    /// relationship suggestions can point at them before they are accepted.
+    KnowledgeSuggestion, "knowledge_suggestion", {
+    user_id: String,
+    source_id: String,
+    kind: SuggestionKind,
+    #[serde(default)]
+    status: SuggestionStatus,
+    #[serde(default)]
+    entity: Option<KnowledgeEntity>,
+    /// Embedding computed for `entity` during ingestion.
+    #[serde(default)]
+    embedding: Option<Vec<f32>>,
+    #[serde(default)]
+    relationship: Option<KnowledgeRelationship>
+});
+
+/// Changes a reviewer made before accepting a suggestion. Fields that do not apply to
+/// the suggestion's kind are ignored.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SuggestionEdits {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub entity_type: Option<String>,
+    pub relationship_type: Option<String>,
+    pub weight: Option<f32>,
+}
+
+fn non_blank(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|value| !value.is_empty())
+}
+
+impl KnowledgeSuggestion {
+    fn staged(source_id: &str, user_id: &str, kind: SuggestionKind) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            created_at: now,
+            updated_at: now,
+            user_id: user_id.to_string(),
+            source_id: source_id.to_string(),
+            kind,
+            status: SuggestionStatus::Pending,
+            entity: None,
+            embedding: None,
+            relationship: None,
+        }
+    }
+
+    /// Stages a proposed entity together with its embedding.
+    #[must_use]
+    pub fn for_entity(entity: KnowledgeEntity, embedding: Vec<f32>) -> Self {
+        let staged = Self::staged(&entity.source_id, &entity.user_id, SuggestionKind::Entity);
+        Self {
+            entity: Some(entity),
+            embedding: Some(embedding),
+            ..staged
+        }
+    }
+
+    /// Stages a proposed relationship.
+    #[must_use]
+    pub fn for_relationship(relationship: KnowledgeRelationship) -> Self {
+        let staged = Self::staged(
+            &relationship.metadata.source_id,
+            &relationship.metadata.user_id,
+            SuggestionKind::Relationship,
+        );
+        Self {
+            relationship: Some(relationship),
+            ..staged
+        }
+    }
+
+    /// Suggestions of `user_id` in `status`, grouped by document and oldest first.
+    pub async fn list_for_user(
+        user_id: &str,
+        status: SuggestionStatus,
+        db: &SurrealDbClient,
+    ) -> Result<Vec<Self>, AppError> {
+        let suggestions: Vec<Self> = db
+            .client
+            .query(
+                "SELECT * FROM type::table($table)
+                 WHERE user_id = $user_id AND status = $status
+                 ORDER BY source_id ASC, kind ASC, created_at ASC",
+            )
+            .bind(("table", Self::table_name()))
+            .bind(("user_id", user_id.to_string()))
+            .bind(("status", status.as_str()))
+            .await?
+            .take(0)?;
+
+        Ok(suggestions)
+    }
+
+    /// Number of suggestions of `user_id` awaiting review.
+    pub async fn pending_count(user_id: &str, db: &SurrealDbClient) -> Result<usize, AppError> {
+        #[derive(Deserialize)]
+        struct Row {
+            count: usize,
+        }
+
+        let row: Option<Row> = db
+            .client
+            .query(
+                "SELECT count() AS count FROM type::table($table)
+                 WHERE user_id = $user_id AND status = 'pending' GROUP ALL",
+            )
+            .bind(("table", Self::table_name()))
+            .bind(("user_id", user_id.to_string()))
+            .await?
+            .take(0)?;
+
+        Ok(row.map_or(0, |row| row.count))
+    }
+
+    /// Display names for the endpoints of relationship suggestions, taken from the entity
+    /// suggestions among `suggestions` first and the user's graph otherwise.
+    pub async fn endpoint_names(
+        suggestions: &[Self],
+        user_id: &str,
+        db: &SurrealDbClient,
+    ) -> Result<HashMap<String, String>, AppError> {
+        #[derive(Deserialize)]
+        struct NameRow {
+            id: String,
+            name: String,
+        }
+
+        let mut names: HashMap<String, String> = suggestions
+            .iter()
+            .filter_map(|suggestion| suggestion.entity.as_ref())
+            .map(|entity| (entity.id.clone(), entity.name.clone()))
+            .collect();
+
+        let missing: Vec<String> = suggestions
+            .iter()
+            .filter_map(|suggestion| suggestion.relationship.as_ref())
+            .flat_map(|relationship| [relationship.in_.clone(), relationship.out.clone()])
+            .filter(|id| !names.contains_key(id))
+            .collect();
+        if !missing.is_empty() {
+            let rows: Vec<NameRow> = db
+                .client
+                .query(
+                    "SELECT record::id(id) AS id, name FROM type::table($table)
+                     WHERE user_id = $user_id AND record::id(id) IN $ids",
+                )
+                .bind(("table", KnowledgeEntity::table_name()))
+                .bind(("user_id", user_id.to_string()))
+                .bind(("ids", missing))
+                .await?
+                .take(0)?;
+            names.extend(rows.into_iter().map(|row| (row.id, row.name)));
+        }
+
+        Ok(names)
+    }
+
+    async fn get_pending(id: &str, user_id: &str, db: &SurrealDbClient) -> Result<Self, AppError> {
+        let suggestion: Self = db
+            .get_item(id)
+            .await?
+            .filter(|suggestion: &Self| suggestion.user_id == user_id)
+            .ok_or_else(|| AppError::NotFound("suggestion not found".into()))?;
+
+        if suggestion.status != SuggestionStatus::Pending {
+            return Err(AppError::Validation(format!(
+                "this suggestion was already {}",
+                suggestion.status.as_str()
+            )));
+        }
+
+        Ok(suggestion)
+    }
+
+    async fn set_status(
+        mut self,
+        status: SuggestionStatus,
+        db: &SurrealDbClient,
+    ) -> Result<Self, AppError> {
+        self.status = status;
+        self.updated_at = Utc::now();
+        db.upsert_item(self.clone()).await?;
+        Ok(self)
+    }
+
+    /// Adds a pending suggestion to the graph, with `edits` applied, and marks it
+    /// accepted.
+    ///
+    /// Edited entity text is embedded again. A relationship can only be accepted once
+    /// both entities it links are in the graph.
+    pub async fn accept(
+        id: &str,
+        user_id: &str,
+        edits: &SuggestionEdits,
+        db: &SurrealDbClient,
+        embedding_provider: &EmbeddingProvider,
+    ) -> Result<Self, AppError> {
+        let mut suggestion = Self::get_pending(id, user_id, db).await?;
+
+        match suggestion.kind {
+            SuggestionKind::Entity => {
+                let entity = suggestion.entity.take().ok_or_else(|| {
+                    AppError::InternalError("entity suggestion without entity".into())
+                })?;
+                let embedding = suggestion.embedding.take();
+                let (entity, embedding) =
+                    Self::accept_entity(entity, embedding, edits, db, embedding_provider).await?;
+                suggestion.entity = Some(entity);
+                suggestion.embedding = Some(embedding);
+            }
+            SuggestionKind::Relationship => {
+                let relationship = suggestion.relationship.take().ok_or_else(|| {
+                    AppError::InternalError("relationship suggestion without relationship".into())
+                })?;
+                suggestion.relationship =
+                    Some(Self::accept_relationship(relationship, edits, db).await?);
+            }
+        }
+
+        suggestion.set_status(SuggestionStatus::Accepted, db).await
+    }
+
+    async fn accept_entity(
+        mut entity: KnowledgeEntity,
+        embedding: Option<Vec<f32>>,
+        edits: &SuggestionEdits,
+        db: &SurrealDbClient,
+        embedding_provider: &EmbeddingProvider,
+    ) -> Result<(KnowledgeEntity, Vec<f32>), AppError> {
+        let mut edited = false;
+        if let Some(name) = non_blank(edits.name.as_deref()).filter(|name| *name != entity.name) {
+            entity.name = name.to_string();
+            edited = true;
+        }
+        if let Some(description) = edits
+            .description
+            .as_deref()
+            .map(str::trim)
+            .filter(|description| *description != entity.description)
+        {
+            entity.description = description.to_string();
+            edited = true;
+        }
+        if let Some(entity_type) = non_blank(edits.entity_type.as_deref())
+            .map(|entity_type| KnowledgeEntityType::from(entity_type.to_string()))
+            .filter(|entity_type| *entity_type != entity.entity_type)
+        {
+            entity.entity_type = entity_type;
+            edited = true;
+        }
+
+        let embedding = match embedding {
+            Some(embedding) if !edited => embedding,
+            _ => {
+                let input = KnowledgeEntity::embedding_input_text(
+                    &entity.name,
+                    &entity.description,
+                    &entity.entity_type,
+                );
+                embedding_provider.embed(&input).await?
+            }
+        };
+
+        let now = Utc::now();
+        entity.created_at = now;
+        entity.updated_at = now;
+        let settings = SystemSettings::get_current(db).await?;
+        let dimensions = usize::try_from(settings.embedding_dimensions).map_err(|_| {
+            AppError::InternalError(
+                "system_settings.embedding_dimensions exceeds usize::MAX".into(),
+            )
+        })?;
+        KnowledgeEntity::store_with_embedding(entity.clone(), embedding.clone(), dimensions, db)
+            .await?;
+        db.store_item(KnowledgeEntityMention::new(
+            entity.id.clone(),
+            entity.source_id.clone(),
+            entity.user_id.clone(),
+            entity.name.clone(),
+        ))
+        .await?;
+
+        Ok((entity, embedding))
+    }
+
+    async fn accept_relationship(
+        mut relationship: KnowledgeRelationship,
+        edits: &SuggestionEdits,
+        db: &SurrealDbClient,
+    ) -> Result<KnowledgeRelationship, AppError> {
+        let user_id = relationship.metadata.user_id.clone();
+        if let Some(raw) = non_blank(edits.relationship_type.as_deref()) {
+            let catalog = RelationshipTypeCatalog::for_user(&user_id, db).await?;
+            let resolved = catalog.resolve(raw).ok_or_else(|| {
+                AppError::Validation(format!("'{raw}' is not one of your relationship types"))
+            })?;
+            if resolved.reversed {
+                std::mem::swap(&mut relationship.in_, &mut relationship.out);
+            }
+            relationship.metadata.relationship_type = resolved.name;
+        }
+        if let Some(weight) = edits.weight {
+            relationship.metadata.weight = normalize_weight(weight);
+        }
+
+        for entity_id in [&relationship.in_, &relationship.out] {
+            let stored: Option<KnowledgeEntity> = db.get_item(entity_id).await?;
+            if stored.is_none_or(|entity| entity.user_id != user_id) {
+                return Err(AppError::Validation(
+                    "accept the entities this relationship links first".into(),
+                ));
+            }
+        }
+
+        relationship.clone().store_relationship(db).await?;
+        Ok(relationship)
+    }
+
+    /// Marks a pending suggestion rejected. Rejecting an entity also rejects the pending
+    /// relationships that depend on it.
+    pub async fn reject(id: &str, user_id: &str, db: &SurrealDbClient) -> Result<Self, AppError> {
+        let suggestion = Self::get_pending(id, user_id, db).await?;
+
+        if let Some(entity) = &suggestion.entity {
+            db.client
+                .query(
+                    "UPDATE type::table($table)
+                     SET status = 'rejected', updated_at = time::now()
+                     WHERE user_id = $user_id AND status = 'pending' AND kind = 'relationship'
+                        AND (relationship.`in` = $entity_id OR relationship.out = $entity_id)",
+                )
+                .bind(("table", Self::table_name()))
+                .bind(("user_id", user_id.to_string()))
+                .bind(("entity_id", entity.id.clone()))
+                .await?
+                .check()?;
+        }
+
+        suggestion.set_status(SuggestionStatus::Rejected, db).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used)]
+    use super::*;
+    use crate::{storage::types::user::User, test_utils::setup_test_db_with_embedding_dimension};
+
+    const USER: &str = "review-user";
+
+    fn proposed_entity(name: &str) -> KnowledgeEntity {
+        KnowledgeEntity::new(
+            "doc-1".to_string(),
+            name.to_string(),
+            format!("{name} description"),
+            KnowledgeEntityType::Project,
+            None,
+            USER.to_string(),
+        )
+    }
+
+    async fn stage(
+        db: &SurrealDbClient,
+        suggestions: Vec<KnowledgeSuggestion>,
+    ) -> anyhow::Result<Vec<KnowledgeSuggestion>> {
+        for suggestion in &suggestions {
+            db.store_item(suggestion.clone()).await?;
+        }
+        Ok(suggestions)
+    }
+
+    #[tokio::test]
+    async fn accepting_adds_entities_and_relationships_to_the_graph() -> anyhow::Result<()> {
+        let db = setup_test_db_with_embedding_dimension(3).await?;
+        let provider = EmbeddingProvider::new_hashed(3)?;
+        let minne = proposed_entity("Minne");
+        let surreal = proposed_entity("SurrealDB");
+        let relationship = KnowledgeRelationship::new(
+            minne.id.clone(),
+            surreal.id.clone(),
+            USER.to_string(),
+            "doc-1".to_string(),
+            "RelatedTo".to_string(),
+        );
+        let staged = stage(
+            &db,
+            vec![
+                KnowledgeSuggestion::for_entity(minne.clone(), vec![0.1; 3]),
+                KnowledgeSuggestion::for_entity(surreal.clone(), vec![0.2; 3]),
+                KnowledgeSuggestion::for_relationship(relationship),
+            ],
+        )
+        .await?;
+        let [minne_id, surreal_id, relationship_id] =
+            [0, 1, 2].map(|index| staged.get(index).expect("staged").id.clone());
+
+        let edits = SuggestionEdits::default();
+        let early =
+            KnowledgeSuggestion::accept(&relationship_id, USER, &edits, &db, &provider).await;
+        assert!(matches!(early, Err(AppError::Validation(_))));
+
+        let renamed = SuggestionEdits {
+            name: Some("Minne App".to_string()),
+            ..SuggestionEdits::default()
+        };
+        KnowledgeSuggestion::accept(&minne_id, USER, &renamed, &db, &provider).await?;
+        KnowledgeSuggestion::accept(&surreal_id, USER, &edits, &db, &provider).await?;
+        let typed = SuggestionEdits {
+            relationship_type: Some("similar to".to_string()),
+            weight: Some(0.4),
+            ..SuggestionEdits::default()
+        };
+        let accepted =
+            KnowledgeSuggestion::accept(&relationship_id, USER, &typed, &db, &provider).await?;
+        assert_eq!(accepted.status, SuggestionStatus::Accepted);
+
+        let entity = User::get_and_validate_knowledge_entity(&minne.id, USER, &db).await?;
+        assert_eq!(entity.name, "Minne App");
+        let relationships = User::get_knowledge_relationships(USER, &db).await?;
+        let stored = relationships.first().expect("relationship stored");
+        assert_eq!(stored.metadata.relationship_type, "SimilarTo");
+        assert!((stored.metadata.weight - 0.4).abs() < f32::EPSILON);
+
+        let again = KnowledgeSuggestion::accept(&minne_id, USER, &edits, &db, &provider).await;
+        assert!(matches!(again, Err(AppError::Validation(_))));
+        assert_eq!(KnowledgeSuggestion::pending_count(USER, &db).await?, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn rejecting_an_entity_rejects_its_relationships() -> anyhow::Result<()> {
+        let db = setup_test_db_with_embedding_dimension(3).await?;
+        let minne = proposed_entity("Minne");
+        let other = proposed_entity("Other");
+        let staged = stage(
+            &db,
+            vec![
+                KnowledgeSuggestion::for_entity(minne.clone(), vec![0.1; 3]),
+                KnowledgeSuggestion::for_entity(other.clone(), vec![0.2; 3]),
+                KnowledgeSuggestion::for_relationship(KnowledgeRelationship::new(
+                    other.id.clone(),
+                    minne.id.clone(),
+                    USER.to_string(),
+                    "doc-1".to_string(),
+                    "RelatedTo".to_string(),
+                )),
+            ],
+        )
+        .await?;
+        let minne_id = staged.first().expect("staged").id.clone();
+
+        KnowledgeSuggestion::reject(&minne_id, USER, &db).await?;
+
+        let pending =
+            KnowledgeSuggestion::list_for_user(USER, SuggestionStatus::Pending, &db).await?;
+        assert_eq!(pending.len(), 1);
+        assert_eq!(
+            pending
+                .first()
+                .and_then(|s| s.entity.as_ref())
+                .map(|e| e.name.as_str()),
+            Some("Other")
+        );
+        assert!(User::get_knowledge_entities(USER, &db).await?.is_empty());
+        let rejected =
+            KnowledgeSuggestion::list_for_user(USER, SuggestionStatus::Rejected, &db).await?;
+        assert_eq!(rejected.len(), 2);
+
+        Ok(())
+    }
+}
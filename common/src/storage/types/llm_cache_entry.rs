@@ -0,0 +1,104 @@
+use crate::{error::AppError, storage::db::SurrealDbClient, stored_object};
+
+stored_object!(
+    /// A cached LLM completion; the id is the digest of the route and request it answers.
+    LlmCacheEntry, "llm_cache_entry", {
+    model: String,
+    content: String,
+    /// `None` keeps the entry until it is purged.
+    #[serde(
+        serialize_with = "serialize_option_datetime",
+        deserialize_with = "deserialize_option_datetime",
+        default
+    )]
+    expires_at: Option<DateTime<Utc>>
+});
+
+#[derive(Deserialize)]
+struct CountResult {
+    count: u64,
+}
+
+impl LlmCacheEntry {
+    #[must_use]
+    pub fn new(key: &str, model: &str, content: &str, expires_at: Option<DateTime<Utc>>) -> Self {
+        let now = Utc::now();
+        Self {
+            id: key.to_string(),
+            created_at: now,
+            updated_at: now,
+            model: model.to_string(),
+            content: content.to_string(),
+            expires_at,
+        }
+    }
+
+    #[must_use]
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    /// The entry stored under `key`, unless it has expired.
+    pub async fn get_fresh(key: &str, db: &SurrealDbClient) -> Result<Option<Self>, AppError> {
+        let entry: Option<Self> = db.get_item(key).await?;
+        Ok(entry.filter(|entry| !entry.is_expired(Utc::now())))
+    }
+
+    /// Deletes every entry, returning how many there were.
+    pub async fn purge_all(db: &SurrealDbClient) -> Result<u64, AppError> {
+        let mut response = db
+            .client
+            .query("SELECT count() AS count FROM type::table($table) GROUP ALL")
+            .query("DELETE type::table($table)")
+            .bind(("table", Self::table_name()))
+            .await?;
+        let counted: Option<CountResult> = response.take(0)?;
+        Ok(counted.map_or(0, |counted| counted.count))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used)]
+
+    use chrono::Duration;
+
+    use super::*;
+    use crate::test_utils::setup_test_db;
+
+    #[tokio::test]
+    async fn expired_entries_are_missed_and_purge_removes_all() {
+        let db = setup_test_db().await.expect("test db");
+        let later = Some(Utc::now() + Duration::hours(1));
+        let earlier = Some(Utc::now() - Duration::hours(1));
+        db.store_item(LlmCacheEntry::new("fresh", "gpt-4o-mini", "{}", later))
+            .await
+            .expect("fresh entry");
+        db.store_item(LlmCacheEntry::new("stale", "gpt-4o-mini", "{}", earlier))
+            .await
+            .expect("stale entry");
+        db.store_item(LlmCacheEntry::new("kept", "gpt-4o-mini", "{}", None))
+            .await
+            .expect("entry without expiry");
+
+        let fresh = LlmCacheEntry::get_fresh("fresh", &db)
+            .await
+            .expect("lookup");
+        assert_eq!(fresh.map(|entry| entry.content).as_deref(), Some("{}"));
+        assert!(
+            LlmCacheEntry::get_fresh("stale", &db)
+                .await
+                .expect("lookup")
+                .is_none()
+        );
+        assert!(
+            LlmCacheEntry::get_fresh("kept", &db)
+                .await
+                .expect("lookup")
+                .is_some()
+        );
+
+        assert_eq!(LlmCacheEntry::purge_all(&db).await.expect("purge"), 3);
+        assert_eq!(LlmCacheEntry::purge_all(&db).await.expect("purge"), 0);
+    }
+}
@@ -0,0 +1,319 @@
+use std::collections::BTreeMap;
+
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    storage::{
+        db::SurrealDbClient,
+        types::system_settings::{ModelRole, ModelRoute, SystemSettings},
+    },
+    stored_object,
+    utils::config::LlmBackend,
+};
+
+/// Days of usage shown in the admin panel and on the account page.
+pub const USAGE_REPORT_DAYS: i64 = 30;
+
+/// What an LLM call was made for.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageRole {
+    Ingestion,
+    Chat,
+    Vision,
+    Transcription,
+}
+
+impl UsageRole {
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ingestion => "ingestion",
+            Self::Chat => "chat",
+            Self::Vision => "vision",
+            Self::Transcription => "transcription",
+        }
+    }
+}
+
+impl From<ModelRole> for UsageRole {
+    fn from(role: ModelRole) -> Self {
+        match role {
+            ModelRole::Query => Self::Chat,
+            ModelRole::Processing => Self::Ingestion,
+            ModelRole::ImageProcessing => Self::Vision,
+        }
+    }
+}
+
+stored_object!(
+    /// Tokens spent by one LLM call.
+    ///
+    /// Prices are not stored; costs are estimated from the admin price table when usage is
+    /// reported, so correcting a price corrects past totals too.
+    LlmUsage, "llm_usage", {
+    user_id: String,
+    role: UsageRole,
+    backend: LlmBackend,
+    model: String,
+    prompt_tokens: u64,
+    completion_tokens: u64,
+    /// Ingestion source or conversation the call was made for.
+    #[serde(default)]
+    reference_id: Option<String>
+});
+
+/// Token counts of one user, UTC day and model.
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+pub struct UsageBucket {
+    pub user_id: String,
+    pub day: String,
+    pub model: String,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub calls: u64,
+}
+
+/// Summed usage under one key (a user id or a day) with its estimated cost in USD.
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct UsageTotal {
+    pub key: String,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub calls: u64,
+    pub cost: f64,
+}
+
+impl UsageTotal {
+    fn add(&mut self, bucket: &UsageBucket, cost: f64) {
+        self.prompt_tokens = self.prompt_tokens.saturating_add(bucket.prompt_tokens);
+        self.completion_tokens = self
+            .completion_tokens
+            .saturating_add(bucket.completion_tokens);
+        self.calls = self.calls.saturating_add(bucket.calls);
+        self.cost += cost;
+    }
+}
+
+/// Per-user and per-day usage totals, newest day first.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UsageReport {
+    pub total: UsageTotal,
+    pub per_user: Vec<UsageTotal>,
+    pub per_day: Vec<UsageTotal>,
+    /// Models that were used but have no price, so their tokens add nothing to the cost.
+    pub unpriced_models: Vec<String>,
+}
+
+impl UsageReport {
+    /// Sums `buckets`, pricing each with `settings.model_prices`.
+    #[must_use]
+    pub fn from_buckets(buckets: &[UsageBucket], settings: &SystemSettings) -> Self {
+        let mut total = UsageTotal::default();
+        let mut per_user: BTreeMap<&str, UsageTotal> = BTreeMap::new();
+        let mut per_day: BTreeMap<&str, UsageTotal> = BTreeMap::new();
+        let mut unpriced_models: Vec<String> = Vec::new();
+
+        for bucket in buckets {
+            let price = settings.price_for(&bucket.model);
+            if price.is_none() && !unpriced_models.contains(&bucket.model) {
+                unpriced_models.push(bucket.model.clone());
+            }
+            let cost = price.map_or(0.0, |price| {
+                price.cost(bucket.prompt_tokens, bucket.completion_tokens)
+            });
+            total.add(bucket, cost);
+            per_user
+                .entry(&bucket.user_id)
+                .or_default()
+                .add(bucket, cost);
+            per_day.entry(&bucket.day).or_default().add(bucket, cost);
+        }
+
+        let keyed = |(key, total): (&str, UsageTotal)| UsageTotal {
+            key: key.to_string(),
+            ..total
+        };
+        let mut per_user: Vec<UsageTotal> = per_user.into_iter().map(keyed).collect();
+        per_user.sort_by(|a, b| {
+            b.prompt_tokens
+                .saturating_add(b.completion_tokens)
+                .cmp(&a.prompt_tokens.saturating_add(a.completion_tokens))
+        });
+        unpriced_models.sort();
+
+        Self {
+            total,
+            per_user,
+            per_day: per_day.into_iter().rev().map(keyed).collect(),
+            unpriced_models,
+        }
+    }
+}
+
+impl LlmUsage {
+    #[must_use]
+    pub fn new(
+        user_id: &str,
+        reference_id: Option<&str>,
+        role: UsageRole,
+        route: &ModelRoute,
+        prompt_tokens: u64,
+        completion_tokens: u64,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            created_at: now,
+            updated_at: now,
+            user_id: user_id.to_string(),
+            role,
+            backend: route.backend,
+            model: route.model.clone(),
+            prompt_tokens,
+            completion_tokens,
+            reference_id: reference_id.map(ToString::to_string),
+        }
+    }
+
+    /// Usage since `since` grouped by user, UTC day and model, optionally for one user.
+    pub async fn buckets_since(
+        db: &SurrealDbClient,
+        user_id: Option<&str>,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<UsageBucket>, AppError> {
+        let user_filter = if user_id.is_some() {
+            "AND user_id = $user_id"
+        } else {
+            ""
+        };
+        let buckets: Vec<UsageBucket> = db
+            .client
+            .query(format!(
+                "SELECT user_id, time::format(created_at, '%Y-%m-%d') AS day, model,
+                    math::sum(prompt_tokens) AS prompt_tokens,
+                    math::sum(completion_tokens) AS completion_tokens,
+                    count() AS calls
+                 FROM type::table($table)
+                 WHERE created_at >= $since {user_filter}
+                 GROUP BY user_id, day, model"
+            ))
+            .bind(("table", Self::table_name()))
+            .bind(("since", surrealdb::Datetime::from(since)))
+            .bind(("user_id", user_id.map(ToString::to_string)))
+            .await?
+            .take(0)?;
+        Ok(buckets)
+    }
+
+    /// Priced per-user and per-day totals since `since`, optionally for one user.
+    pub async fn report_since(
+        db: &SurrealDbClient,
+        user_id: Option<&str>,
+        since: DateTime<Utc>,
+    ) -> Result<UsageReport, AppError> {
+        let (buckets, settings) = tokio::try_join!(
+            Self::buckets_since(db, user_id, since),
+            SystemSettings::get_current(db)
+        )?;
+        Ok(UsageReport::from_buckets(&buckets, &settings))
+    }
+
+    /// Report covering the last [`USAGE_REPORT_DAYS`] days, optionally for one user.
+    pub async fn recent_report(
+        db: &SurrealDbClient,
+        user_id: Option<&str>,
+    ) -> Result<UsageReport, AppError> {
+        let since = Utc::now()
+            .checked_sub_signed(chrono::Duration::days(USAGE_REPORT_DAYS))
+            .unwrap_or_else(Utc::now);
+        Self::report_since(db, user_id, since).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used)]
+
+    use chrono::Duration;
+
+    use super::*;
+    use crate::{
+        storage::types::system_settings::{ModelPrice, SystemSettingsPatch},
+        test_utils::setup_test_db,
+    };
+
+    #[tokio::test]
+    async fn report_sums_usage_per_user_and_day_and_prices_known_models() {
+        let db = setup_test_db().await.expect("test db");
+        SystemSettingsPatch {
+            model_prices: Some(ModelPrice::parse_lines("gpt-4o-mini = 1, 2").expect("prices")),
+            ..Default::default()
+        }
+        .apply(&db)
+        .await
+        .expect("prices stored");
+
+        let priced = ModelRoute::new(LlmBackend::OpenAI, "gpt-4o-mini");
+        let local = ModelRoute::new(LlmBackend::Ollama, "llama3.1");
+        let mut yesterday = LlmUsage::new(
+            "alice",
+            Some("conv-1"),
+            UsageRole::Chat,
+            &priced,
+            500_000,
+            0,
+        );
+        yesterday.created_at -= Duration::days(1);
+        let records = [
+            LlmUsage::new(
+                "alice",
+                Some("src-1"),
+                UsageRole::Ingestion,
+                &priced,
+                1_000_000,
+                250_000,
+            ),
+            LlmUsage::new(
+                "alice",
+                Some("src-1"),
+                UsageRole::Ingestion,
+                &priced,
+                1_000_000,
+                250_000,
+            ),
+            LlmUsage::new("bob", None, UsageRole::Vision, &local, 10, 5),
+            yesterday,
+        ];
+        for record in records {
+            db.store_item(record).await.expect("usage stored");
+        }
+
+        let since = Utc::now() - Duration::days(30);
+        let report = LlmUsage::report_since(&db, None, since)
+            .await
+            .expect("report");
+
+        assert_eq!(report.total.calls, 4);
+        assert_eq!(report.total.prompt_tokens, 2_500_010);
+        assert!((report.total.cost - 3.5).abs() < 1e-9);
+        assert_eq!(report.unpriced_models, vec!["llama3.1".to_string()]);
+        assert_eq!(report.per_day.len(), 2);
+        assert_eq!(
+            report.per_day.first().map(|day| day.calls),
+            Some(3),
+            "newest day first"
+        );
+        assert_eq!(
+            report.per_user.first().map(|user| user.key.as_str()),
+            Some("alice")
+        );
+
+        let bob = LlmUsage::report_since(&db, Some("bob"), since)
+            .await
+            .expect("bob's report");
+        assert_eq!(bob.total.calls, 1);
+        assert_eq!(bob.per_user.len(), 1);
+    }
+}
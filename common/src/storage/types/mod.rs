@@ -1,18 +1,30 @@
 #![allow(clippy::unsafe_derive_deserialize)]
 #![allow(async_fn_in_trait)]
 use serde::{Deserialize, Serialize};
+pub mod action_item;
 pub mod analytics;
 pub mod conversation;
+pub mod duplicate_report;
+pub mod entity_fact;
+pub mod entity_operation;
+pub mod entity_type_definition;
 pub mod file_info;
 pub mod ingestion_payload;
 pub mod ingestion_task;
 pub mod knowledge_entity;
 pub mod knowledge_entity_embedding;
+pub mod knowledge_entity_mention;
 pub mod knowledge_relationship;
+pub mod knowledge_suggestion;
+pub mod llm_cache_entry;
+pub mod llm_usage;
 pub mod message;
+pub mod relationship_type_definition;
 pub mod scratchpad;
+pub mod settings_version;
 pub mod system_prompts;
 pub mod system_settings;
+pub mod tag;
 pub mod text_chunk;
 pub mod text_chunk_embedding;
 pub mod text_content;
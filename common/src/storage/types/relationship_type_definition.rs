@@ -0,0 +1,454 @@
+use chrono::Utc as ChronoUtc;
+use uuid::Uuid;
+
+use crate::{error::AppError, storage::db::SurrealDbClient, stored_object};
+
+/// Type given to relationships whose proposed type is unknown or blank.
+pub const DEFAULT_RELATIONSHIP_TYPE: &str = "RelatedTo";
+/// Longest accepted name (or inverse name) for a user-defined relationship type.
+pub const MAX_RELATIONSHIP_TYPE_NAME_CHARS: usize = 40;
+/// Longest accepted description for a user-defined relationship type.
+pub const MAX_RELATIONSHIP_TYPE_DESCRIPTION_CHARS: usize = 400;
+
+struct BuiltinRelationshipType {
+    name: &'static str,
+    inverse_name: Option<&'static str>,
+    directed: bool,
+    description: &'static str,
+    /// Spellings seen in existing graphs, compared by [`type_key`].
+    aliases: &'static [&'static str],
+}
+
+const BUILTIN_RELATIONSHIP_TYPES: &[BuiltinRelationshipType] = &[
+    BuiltinRelationshipType {
+        name: "RelatedTo",
+        inverse_name: None,
+        directed: false,
+        description: "A general connection, used when no more specific type fits.",
+        aliases: &[
+            "relates_to",
+            "related",
+            "related with",
+            "connected to",
+            "associated with",
+            "linked to",
+        ],
+    },
+    BuiltinRelationshipType {
+        name: "RelevantTo",
+        inverse_name: None,
+        directed: true,
+        description: "The source matters for, or informs, the target.",
+        aliases: &["relevant", "applies to"],
+    },
+    BuiltinRelationshipType {
+        name: "SimilarTo",
+        inverse_name: None,
+        directed: false,
+        description: "The two entities are alike or interchangeable.",
+        aliases: &["similar", "resembles", "like", "comparable to"],
+    },
+    BuiltinRelationshipType {
+        name: "References",
+        inverse_name: Some("ReferencedBy"),
+        directed: true,
+        description: "The source cites, mentions or links to the target.",
+        aliases: &["refers to", "reference", "cites", "mentions"],
+    },
+];
+
+stored_object!(
+    /// A relationship type a user added to the built-in ones.
+    RelationshipTypeDefinition, "relationship_type_definition", {
+    user_id: String,
+    name: String,
+    /// Name of the type read from target to source, e.g. `PartOf` for `HasPart`.
+    #[serde(default)]
+    inverse_name: Option<String>,
+    /// Whether `A name B` differs from `B name A`.
+    #[serde(default)]
+    directed: bool,
+    description: String
+});
+
+/// A relationship type as offered to users and the enrichment prompt.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RelationshipType {
+    pub name: String,
+    pub inverse_name: Option<String>,
+    pub directed: bool,
+    pub description: String,
+    /// Id of the user's definition; `None` for built-in types.
+    pub definition_id: Option<String>,
+    #[serde(skip)]
+    aliases: Vec<String>,
+}
+
+impl From<RelationshipTypeDefinition> for RelationshipType {
+    fn from(definition: RelationshipTypeDefinition) -> Self {
+        Self {
+            name: definition.name,
+            inverse_name: definition.inverse_name,
+            directed: definition.directed,
+            description: definition.description,
+            definition_id: Some(definition.id),
+            aliases: Vec::new(),
+        }
+    }
+}
+
+/// A free-form type name matched to a managed type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedRelationshipType {
+    pub name: String,
+    /// The input named the inverse, so source and target must be swapped.
+    pub reversed: bool,
+}
+
+/// The built-in relationship types followed by a user's own.
+#[derive(Debug, Clone, Serialize)]
+pub struct RelationshipTypeCatalog {
+    types: Vec<RelationshipType>,
+}
+
+impl RelationshipTypeCatalog {
+    /// The catalog of a user without definitions of their own.
+    #[must_use]
+    pub fn builtin() -> Self {
+        let types = BUILTIN_RELATIONSHIP_TYPES
+            .iter()
+            .map(|builtin| RelationshipType {
+                name: builtin.name.to_string(),
+                inverse_name: builtin.inverse_name.map(ToString::to_string),
+                directed: builtin.directed,
+                description: builtin.description.to_string(),
+                definition_id: None,
+                aliases: builtin
+                    .aliases
+                    .iter()
+                    .map(|alias| type_key(alias))
+                    .collect(),
+            })
+            .collect();
+        Self { types }
+    }
+
+    /// Built-in types plus the definitions of `user_id`.
+    pub async fn for_user(user_id: &str, db: &SurrealDbClient) -> Result<Self, AppError> {
+        let mut catalog = Self::builtin();
+        catalog.types.extend(
+            RelationshipTypeDefinition::list_for_user(user_id, db)
+                .await?
+                .into_iter()
+                .map(RelationshipType::from),
+        );
+        Ok(catalog)
+    }
+
+    #[must_use]
+    pub fn types(&self) -> &[RelationshipType] {
+        &self.types
+    }
+
+    #[must_use]
+    pub fn names(&self) -> Vec<String> {
+        self.types.iter().map(|kind| kind.name.clone()).collect()
+    }
+
+    /// The managed type called `name`, ignoring case and punctuation.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&RelationshipType> {
+        let key = type_key(name);
+        self.types.iter().find(|kind| type_key(&kind.name) == key)
+    }
+
+    /// Matches a free-form type such as `"is related to"` or `"referenced_by"` against
+    /// the names, inverse names and known spellings of the managed types. A leading
+    /// "is" is ignored.
+    #[must_use]
+    pub fn resolve(&self, raw: &str) -> Option<ResolvedRelationshipType> {
+        let key = type_key(raw);
+        if key.is_empty() {
+            return None;
+        }
+        let stripped = key.strip_prefix("is").filter(|rest| !rest.is_empty());
+
+        [Some(key.as_str()), stripped]
+            .into_iter()
+            .flatten()
+            .find_map(|candidate| self.resolve_key(candidate))
+    }
+
+    /// [`Self::resolve`], falling back to [`DEFAULT_RELATIONSHIP_TYPE`] for unknown types.
+    #[must_use]
+    pub fn canonicalize(&self, raw: &str) -> ResolvedRelationshipType {
+        self.resolve(raw)
+            .unwrap_or_else(|| ResolvedRelationshipType {
+                name: DEFAULT_RELATIONSHIP_TYPE.to_string(),
+                reversed: false,
+            })
+    }
+
+    fn resolve_key(&self, key: &str) -> Option<ResolvedRelationshipType> {
+        self.types.iter().find_map(|kind| {
+            let resolved = |reversed| {
+                Some(ResolvedRelationshipType {
+                    name: kind.name.clone(),
+                    reversed,
+                })
+            };
+            if type_key(&kind.name) == key || kind.aliases.iter().any(|alias| alias == key) {
+                return resolved(false);
+            }
+            match &kind.inverse_name {
+                Some(inverse) if type_key(inverse) == key => resolved(kind.directed),
+                _ => None,
+            }
+        })
+    }
+
+    fn is_taken(&self, name: &str) -> bool {
+        let key = type_key(name);
+        self.types.iter().any(|kind| {
+            type_key(&kind.name) == key
+                || kind.aliases.contains(&key)
+                || kind
+                    .inverse_name
+                    .as_deref()
+                    .is_some_and(|inverse| type_key(inverse) == key)
+        })
+    }
+}
+
+impl RelationshipTypeDefinition {
+    /// All definitions owned by `user_id`, sorted by name.
+    pub async fn list_for_user(user_id: &str, db: &SurrealDbClient) -> Result<Vec<Self>, AppError> {
+        let definitions: Vec<Self> = db
+            .client
+            .query("SELECT * FROM type::table($table) WHERE user_id = $user_id ORDER BY name ASC")
+            .bind(("table", Self::table_name()))
+            .bind(("user_id", user_id.to_string()))
+            .await?
+            .take(0)?;
+
+        Ok(definitions)
+    }
+
+    /// Store a new relationship type for `user_id`.
+    ///
+    /// Names are turned into `CamelCase` and must be 1–40 characters long. Neither the
+    /// name nor the inverse name may repeat a built-in or existing type, inverse, or
+    /// known spelling. Undirected types have no inverse.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` for invalid input or a duplicate name, and
+    /// `AppError::Database` if the store fails.
+    pub async fn create(
+        user_id: &str,
+        name: &str,
+        inverse_name: Option<&str>,
+        directed: bool,
+        description: &str,
+        db: &SurrealDbClient,
+    ) -> Result<Self, AppError> {
+        let name = validate_name(name)?;
+        let inverse_name =
+            match inverse_name.filter(|inverse| directed && !inverse.trim().is_empty()) {
+                Some(inverse) => Some(validate_name(inverse)?),
+                None => None,
+            };
+        let description = description.trim();
+        if description.chars().count() > MAX_RELATIONSHIP_TYPE_DESCRIPTION_CHARS {
+            return Err(AppError::Validation(format!(
+                "relationship type description must be at most {MAX_RELATIONSHIP_TYPE_DESCRIPTION_CHARS} characters"
+            )));
+        }
+
+        let catalog = RelationshipTypeCatalog::for_user(user_id, db).await?;
+        for taken in std::iter::once(&name).chain(inverse_name.as_ref()) {
+            if catalog.is_taken(taken) {
+                return Err(AppError::Validation(format!(
+                    "relationship type '{taken}' already exists"
+                )));
+            }
+        }
+        if inverse_name.as_deref().map(type_key) == Some(type_key(&name)) {
+            return Err(AppError::Validation(
+                "a relationship type cannot be its own inverse".to_string(),
+            ));
+        }
+
+        let now = ChronoUtc::now();
+        let definition = Self {
+            id: Uuid::new_v4().to_string(),
+            created_at: now,
+            updated_at: now,
+            user_id: user_id.to_string(),
+            name,
+            inverse_name,
+            directed,
+            description: description.to_string(),
+        };
+        db.store_item(definition)
+            .await?
+            .ok_or_else(|| AppError::internal("relationship type store returned no record"))
+    }
+
+    /// Delete a type definition. Relationships already using it keep the name.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the type does not exist for `user_id`, and
+    /// `AppError::Database` if the delete fails.
+    pub async fn delete(id: &str, user_id: &str, db: &SurrealDbClient) -> Result<(), AppError> {
+        let definition = db
+            .get_item::<Self>(id)
+            .await?
+            .filter(|definition| definition.user_id == user_id)
+            .ok_or_else(|| AppError::NotFound("relationship type not found".to_string()))?;
+        db.delete_item::<Self>(&definition.id).await?;
+        Ok(())
+    }
+}
+
+/// Lowercase letters and digits only, so `relates_to` and `Relates To` compare equal.
+fn type_key(raw: &str) -> String {
+    raw.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// `"part of"` becomes `"PartOf"`; names already in `CamelCase` are kept.
+fn camel_case(raw: &str) -> String {
+    let mut result = String::new();
+    for segment in raw
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|segment| !segment.is_empty())
+    {
+        let mut chars = segment.chars();
+        if let Some(first) = chars.next() {
+            result.extend(first.to_uppercase());
+            result.extend(chars);
+        }
+    }
+    result
+}
+
+fn validate_name(raw: &str) -> Result<String, AppError> {
+    let name = camel_case(raw);
+    if name.is_empty() {
+        return Err(AppError::Validation(
+            "relationship type name is required".to_string(),
+        ));
+    }
+    if name.chars().count() > MAX_RELATIONSHIP_TYPE_NAME_CHARS {
+        return Err(AppError::Validation(format!(
+            "relationship type name must be at most {MAX_RELATIONSHIP_TYPE_NAME_CHARS} characters"
+        )));
+    }
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(
+        clippy::expect_used,
+        clippy::must_use_candidate,
+        clippy::unnecessary_wraps
+    )]
+    use super::*;
+    use crate::test_utils::setup_test_db;
+
+    fn resolved(name: &str, reversed: bool) -> Option<ResolvedRelationshipType> {
+        Some(ResolvedRelationshipType {
+            name: name.to_string(),
+            reversed,
+        })
+    }
+
+    #[test]
+    fn resolve_folds_spelling_variants_onto_managed_types() {
+        let catalog = RelationshipTypeCatalog::builtin();
+
+        for raw in ["relates_to", "RelatedTo", "is related to", "RELATED"] {
+            assert_eq!(catalog.resolve(raw), resolved("RelatedTo", false), "{raw}");
+        }
+        assert_eq!(
+            catalog.resolve("is similar to"),
+            resolved("SimilarTo", false)
+        );
+        assert_eq!(
+            catalog.resolve("referenced_by"),
+            resolved("References", true)
+        );
+        assert_eq!(catalog.resolve("Works at"), None);
+        assert_eq!(
+            catalog.canonicalize("Works at"),
+            ResolvedRelationshipType {
+                name: DEFAULT_RELATIONSHIP_TYPE.to_string(),
+                reversed: false,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn create_validates_and_extends_the_catalog() -> anyhow::Result<()> {
+        let db = setup_test_db().await?;
+
+        let has_part = RelationshipTypeDefinition::create(
+            "user-a",
+            "has part",
+            Some("part_of"),
+            true,
+            "The source contains the target",
+            &db,
+        )
+        .await?;
+        assert_eq!(has_part.name, "HasPart");
+        assert_eq!(has_part.inverse_name.as_deref(), Some("PartOf"));
+
+        let undirected = RelationshipTypeDefinition::create(
+            "user-a",
+            "Sibling",
+            Some("Sibling"),
+            false,
+            "",
+            &db,
+        )
+        .await?;
+        assert_eq!(undirected.inverse_name, None);
+
+        for (name, inverse) in [("related", None), ("PartOf", None), ("Owns", Some("owns"))] {
+            assert!(
+                matches!(
+                    RelationshipTypeDefinition::create("user-a", name, inverse, true, "", &db)
+                        .await,
+                    Err(AppError::Validation(_))
+                ),
+                "{name:?} / {inverse:?} should be rejected"
+            );
+        }
+
+        let catalog = RelationshipTypeCatalog::for_user("user-a", &db).await?;
+        assert_eq!(catalog.resolve("is part of"), resolved("HasPart", true));
+        assert!(
+            RelationshipTypeCatalog::for_user("user-b", &db)
+                .await?
+                .resolve("HasPart")
+                .is_none()
+        );
+
+        let foreign = RelationshipTypeDefinition::delete(&has_part.id, "user-b", &db).await;
+        assert!(matches!(foreign, Err(AppError::NotFound(_))));
+        RelationshipTypeDefinition::delete(&has_part.id, "user-a", &db).await?;
+        assert_eq!(
+            RelationshipTypeDefinition::list_for_user("user-a", &db)
+                .await?
+                .len(),
+            1
+        );
+        Ok(())
+    }
+}
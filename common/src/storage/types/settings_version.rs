@@ -0,0 +1,267 @@
+use serde_json::{Map, Value};
+
+use crate::{
+    error::AppError,
+    storage::{
+        db::SurrealDbClient,
+        types::system_settings::{SystemSettings, SystemSettingsPatch},
+    },
+    stored_object,
+};
+
+/// Settings maintained by the application itself; never versioned or rolled back.
+const UNTRACKED_FIELDS: [&str; 5] = [
+    "id",
+    "embedding_backend",
+    "last_index_rebuild_at",
+    "index_rebuild_lease_owner",
+    "index_rebuild_lease_expires_at",
+];
+
+/// Changing these requires re-embedding, so rollbacks leave them as they are.
+const NOT_ROLLED_BACK: [&str; 2] = ["embedding_model", "embedding_dimensions"];
+
+/// Versions shown in the admin history.
+pub const SETTINGS_HISTORY_LIMIT: usize = 50;
+
+/// One changed setting, with its value before and after the change.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SettingChange {
+    pub field: String,
+    pub before: Value,
+    pub after: Value,
+}
+
+stored_object!(
+    /// A recorded edit of [`SystemSettings`].
+    ///
+    /// `previous` holds every tracked setting as it was before the edit, so rolling back
+    /// restores it as a whole, even when later versions changed other fields.
+    SettingsVersion, "settings_version", {
+    version: u64,
+    /// Email of the admin who made the change; `None` for changes made at startup.
+    author: Option<String>,
+    changes: Vec<SettingChange>,
+    previous: Value,
+    /// The version this change rolled back, if it was a rollback.
+    rollback_of: Option<u64>
+});
+
+#[derive(Deserialize)]
+struct LatestVersion {
+    version: u64,
+}
+
+impl SettingsVersion {
+    /// Records the change from `before` to `after`; `None` when no tracked setting changed.
+    pub async fn record(
+        db: &SurrealDbClient,
+        before: &SystemSettings,
+        after: &SystemSettings,
+        author: Option<&str>,
+        rollback_of: Option<u64>,
+    ) -> Result<Option<Self>, AppError> {
+        let previous = tracked_fields(before)?;
+        let changes = diff(&previous, &tracked_fields(after)?);
+        if changes.is_empty() {
+            return Ok(None);
+        }
+
+        let latest: Option<LatestVersion> = db
+            .client
+            .query("SELECT version FROM type::table($table) ORDER BY version DESC LIMIT 1")
+            .bind(("table", Self::table_name()))
+            .await?
+            .take(0)?;
+        let now = Utc::now();
+        let version = Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            created_at: now,
+            updated_at: now,
+            version: latest.map_or(1, |latest| latest.version.saturating_add(1)),
+            author: author.map(ToString::to_string),
+            changes,
+            previous: Value::Object(previous),
+            rollback_of,
+        };
+        db.store_item(version.clone()).await?;
+        Ok(Some(version))
+    }
+
+    /// Most recent versions first.
+    pub async fn list_recent(db: &SurrealDbClient, limit: usize) -> Result<Vec<Self>, AppError> {
+        let versions: Vec<Self> = db
+            .client
+            .query("SELECT * FROM type::table($table) ORDER BY version DESC LIMIT $limit")
+            .bind(("table", Self::table_name()))
+            .bind(("limit", limit))
+            .await?
+            .take(0)?;
+        Ok(versions)
+    }
+
+    pub async fn get_by_version(
+        db: &SurrealDbClient,
+        version: u64,
+    ) -> Result<Option<Self>, AppError> {
+        let found: Option<Self> = db
+            .client
+            .query("SELECT * FROM type::table($table) WHERE version = $version LIMIT 1")
+            .bind(("table", Self::table_name()))
+            .bind(("version", version))
+            .await?
+            .take(0)?;
+        Ok(found)
+    }
+
+    /// Restores the settings as they were before `version`, recording the rollback as a
+    /// new version. Embedding settings are kept, since changing them needs a re-embed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` for an unknown version and `AppError::Validation` when
+    /// the restored settings are no longer valid.
+    pub async fn rollback(
+        db: &SurrealDbClient,
+        version: u64,
+        author: Option<&str>,
+    ) -> Result<SystemSettings, AppError> {
+        let target = Self::get_by_version(db, version)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("settings version {version} not found")))?;
+
+        let current = SystemSettings::get_current(db).await?;
+        let mut restored = serde_json::to_value(&current).map_err(AppError::internal)?;
+        if let (Value::Object(restored), Value::Object(previous)) = (&mut restored, target.previous)
+        {
+            for (field, value) in previous {
+                if !NOT_ROLLED_BACK.contains(&field.as_str()) {
+                    restored.insert(field, value);
+                }
+            }
+        }
+        let restored: SystemSettings =
+            serde_json::from_value(restored).map_err(AppError::internal)?;
+
+        SystemSettings::update_recorded(db, restored, author, Some(version)).await
+    }
+}
+
+impl SystemSettingsPatch {
+    /// Like [`Self::apply`], recording `author` in the settings history.
+    pub async fn apply_as(
+        self,
+        db: &SurrealDbClient,
+        author: &str,
+    ) -> Result<SystemSettings, AppError> {
+        let mut current = SystemSettings::get_current(db).await?;
+        self.apply_to(&mut current);
+        SystemSettings::update_recorded(db, current, Some(author), None).await
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn tracked_fields(settings: &SystemSettings) -> Result<Map<String, Value>, AppError> {
+    let Value::Object(mut fields) = serde_json::to_value(settings).map_err(AppError::internal)?
+    else {
+        return Err(AppError::InternalError(
+            "system settings did not serialize to an object".into(),
+        ));
+    };
+    for field in UNTRACKED_FIELDS {
+        fields.remove(field);
+    }
+    Ok(fields)
+}
+
+fn diff(before: &Map<String, Value>, after: &Map<String, Value>) -> Vec<SettingChange> {
+    after
+        .iter()
+        .filter_map(|(field, value)| {
+            let previous = before.get(field).cloned().unwrap_or(Value::Null);
+            (previous != *value).then(|| SettingChange {
+                field: field.clone(),
+                before: previous,
+                after: value.clone(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used)]
+
+    use super::*;
+    use crate::test_utils::setup_test_db;
+
+    #[tokio::test]
+    async fn patches_are_versioned_and_rollback_restores_previous_prompt() {
+        let db = setup_test_db().await.expect("test db");
+        let original = SystemSettings::get_current(&db).await.expect("settings");
+        let baseline = SettingsVersion::list_recent(&db, SETTINGS_HISTORY_LIMIT)
+            .await
+            .expect("history")
+            .len();
+
+        SystemSettingsPatch {
+            ingestion_system_prompt: Some("Broken prompt".into()),
+            ..Default::default()
+        }
+        .apply_as(&db, "admin@example.com")
+        .await
+        .expect("edit prompt");
+        SystemSettingsPatch {
+            registrations_enabled: Some(!original.registrations_enabled),
+            ..Default::default()
+        }
+        .apply_as(&db, "other@example.com")
+        .await
+        .expect("toggle registrations");
+        SystemSettingsPatch {
+            registrations_enabled: Some(!original.registrations_enabled),
+            ..Default::default()
+        }
+        .apply_as(&db, "other@example.com")
+        .await
+        .expect("unchanged patch");
+
+        let history = SettingsVersion::list_recent(&db, SETTINGS_HISTORY_LIMIT)
+            .await
+            .expect("history");
+        assert_eq!(
+            history.len(),
+            baseline.saturating_add(2),
+            "no-op patches are not recorded"
+        );
+        let prompt_edit = history.get(1).expect("prompt edit");
+        assert_eq!(prompt_edit.author.as_deref(), Some("admin@example.com"));
+        assert_eq!(prompt_edit.changes.len(), 1);
+        let change = prompt_edit.changes.first().expect("change");
+        assert_eq!(change.field, "ingestion_system_prompt");
+        assert_eq!(change.after, Value::from("Broken prompt"));
+
+        let restored =
+            SettingsVersion::rollback(&db, prompt_edit.version, Some("admin@example.com"))
+                .await
+                .expect("rollback");
+        assert_eq!(
+            restored.ingestion_system_prompt,
+            original.ingestion_system_prompt
+        );
+        assert_eq!(
+            restored.registrations_enabled, original.registrations_enabled,
+            "rollback restores the whole state before the version"
+        );
+
+        let latest = SettingsVersion::list_recent(&db, 1).await.expect("history");
+        let rollback = latest.first().expect("rollback version");
+        assert_eq!(rollback.rollback_of, Some(prompt_edit.version));
+        assert_eq!(rollback.version, prompt_edit.version.saturating_add(2));
+
+        assert!(matches!(
+            SettingsVersion::rollback(&db, 9_999, None).await,
+            Err(AppError::NotFound(_))
+        ));
+    }
+}
@@ -30,7 +30,16 @@ The JSON should have the following structure:
 "key": "unique-key-1",
 "name": "Entity Name",
 "description": "A detailed description of the entity.",
-"entity_type": "TypeOfEntity"
+"entity_type": "TypeOfEntity",
+"tags": ["tag-name"],
+"attributes": [
+{
+"kind": "deadline",
+"label": "Budget due",
+"value": "2026-03-31",
+"unit": null
+}
+]
 },
 // More entities...
 ],
@@ -38,22 +47,35 @@ The JSON should have the following structure:
 {
 "type": "RelationshipType",
 "source": "unique-key-1 or UUID from existing database",
-"target": "unique-key-1 or UUID from existing database"
+"target": "unique-key-1 or UUID from existing database",
+"weight": 0.8
 },
 // More relationships...
+],
+"tags": ["tag-name"],
+"action_items": [
+{
+"description": "Send the budget draft to the board",
+"owner": "Person Name or null",
+"due_date": "2026-03-31 or null"
+}
 ]
 }
 
 Guidelines:
 1. Do NOT generate any IDs or UUIDs. Use a unique `key` for each knowledge entity.
 2. Each KnowledgeEntity should have a unique `key`, a meaningful `name`, and a descriptive `description`.
-3. Define the type of each KnowledgeEntity using the following categories: Idea, Project, Document, Page, TextSnippet.
-4. Establish relationships between entities using types like RelatedTo, RelevantTo, SimilarTo.
+3. Define the type of each KnowledgeEntity using one of the entity types listed with the content (Idea, Project, Document, Page, TextSnippet, plus any user-defined types). Prefer the most specific type that fits.
+4. Establish relationships between entities using only the relationship types listed with the content, preferring the most specific one. Set `weight` between 0 and 1 to say how confident you are in the relationship.
 5. Use the `source` key to indicate the originating entity and the `target` key to indicate the related entity.
 6. You will be presented with a few existing KnowledgeEntities that are similar to the current ones. They will have an existing UUID. When creating relationships to these entities, use their UUID.
 7. Only create relationships between existing KnowledgeEntities.
 8. Entities that exist already in the database should NOT be created again. If there is only a minor overlap, skip creating a new entity.
-9. A new relationship MUST include a newly created KnowledgeEntity."#;
+9. A new relationship MUST include a newly created KnowledgeEntity.
+10. Tag the content as a whole with a few short, lowercase topic tags. Strongly prefer tags from the user's existing tags listed with the content, and only propose a new tag when none of them fit.
+11. Tag each KnowledgeEntity only with tags from the existing tags or the content's own tags. Leave an entity's tags empty when none apply.
+12. Record concrete facts stated about an entity as `attributes`. Use the kind `date` or `deadline` for dates, `person` for people, `amount` for money (put the currency in `unit`), `number` for other quantities (put the unit in `unit`), and `url` for links. Write dates as YYYY-MM-DD and numbers without thousands separators. Leave `attributes` empty when the content states no such facts.
+13. List tasks the content asks someone to do as `action_items`, with the responsible person as `owner` and the due date as YYYY-MM-DD in `due_date` when stated. Leave `action_items` empty when there are none."#;
 
 pub const DEFAULT_IMAGE_PROCESSING_PROMPT: &str = r#"Analyze this image and respond based on its primary content:
 - If the image is mainly text (document, screenshot, sign), transcribe the text verbatim.
@@ -61,3 +83,12 @@ pub const DEFAULT_IMAGE_PROCESSING_PROMPT: &str = r#"Analyze this image and resp
 - For hybrid images (diagrams, ads), briefly describe the visual, then transcribe the text under a "Text:" heading.
 
 Respond directly with the analysis."#;
+
+pub const DEFAULT_CONTENT_SUMMARY_SYSTEM_PROMPT: &str = r#"You summarize documents saved to a personal knowledge base so they can be scanned quickly later. You will receive the content along with its category and the user's context.
+
+Return a JSON object with:
+1. `title`: a short, specific title for the document (at most 10 words). Do not invent a title unrelated to the content.
+2. `overview`: an abstract of two to four sentences describing what the document is about and why it matters.
+3. `key_points`: three to six bullet-point takeaways, each a single sentence. Use fewer for short content.
+
+Write in the language of the content. If the content is marked as truncated, summarize what is present without guessing at the rest."#;
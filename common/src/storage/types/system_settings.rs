@@ -1,13 +1,20 @@
+use std::{fmt, str::FromStr};
+
 use chrono::{DateTime, Utc};
 use tracing::warn;
 
-use crate::utils::config::EmbeddingBackend;
+use crate::utils::config::{EmbeddingBackend, LlmBackend};
+use crate::utils::prompt_template::validate_prompt;
 use crate::utils::serde_helpers::{
     deserialize_flexible_id, deserialize_option_datetime, serialize_option_datetime,
 };
 use serde::{Deserialize, Serialize};
 
-use crate::{error::AppError, storage::db::SurrealDbClient, storage::types::StoredObject};
+use crate::{
+    error::AppError,
+    storage::db::SurrealDbClient,
+    storage::types::{StoredObject, settings_version::SettingsVersion},
+};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SystemSettings {
@@ -27,6 +34,36 @@ pub struct SystemSettings {
     pub image_processing_model: String,
     pub image_processing_prompt: String,
     pub voice_processing_model: String,
+    /// Backend serving `query_model`.
+    #[serde(default)]
+    pub query_backend: LlmBackend,
+    /// Backend serving `processing_model`.
+    #[serde(default)]
+    pub processing_backend: LlmBackend,
+    /// Backend serving `image_processing_model`.
+    #[serde(default)]
+    pub image_processing_backend: LlmBackend,
+    /// Tried in order when the query backend and model fail.
+    #[serde(default)]
+    pub query_fallbacks: Vec<ModelRoute>,
+    /// Tried in order when the processing backend and model fail.
+    #[serde(default)]
+    pub processing_fallbacks: Vec<ModelRoute>,
+    /// Tried in order when the image processing backend and model fail.
+    #[serde(default)]
+    pub image_processing_fallbacks: Vec<ModelRoute>,
+    /// OpenAI-compatible endpoints tried in order when transcription fails.
+    #[serde(default)]
+    pub voice_processing_fallbacks: Vec<ModelRoute>,
+    /// Processing routes replacing the defaults for content in specific categories.
+    #[serde(default)]
+    pub processing_category_routes: Vec<CategoryModelRoutes>,
+    /// Ingestion prompts replacing `ingestion_system_prompt` for content in specific categories.
+    #[serde(default)]
+    pub ingestion_category_prompts: Vec<CategoryPrompt>,
+    /// Per-model token prices used to estimate LLM spend.
+    #[serde(default)]
+    pub model_prices: Vec<ModelPrice>,
     /// When the maintainer last completed a scheduled `REBUILD INDEX` pass.
     #[serde(
         default,
@@ -61,6 +98,235 @@ pub struct SystemSettingsPatch {
     pub image_processing_model: Option<String>,
     pub image_processing_prompt: Option<String>,
     pub voice_processing_model: Option<String>,
+    pub query_backend: Option<LlmBackend>,
+    pub processing_backend: Option<LlmBackend>,
+    pub image_processing_backend: Option<LlmBackend>,
+    pub query_fallbacks: Option<Vec<ModelRoute>>,
+    pub processing_fallbacks: Option<Vec<ModelRoute>>,
+    pub image_processing_fallbacks: Option<Vec<ModelRoute>>,
+    pub voice_processing_fallbacks: Option<Vec<ModelRoute>>,
+    pub processing_category_routes: Option<Vec<CategoryModelRoutes>>,
+    pub ingestion_category_prompts: Option<Vec<CategoryPrompt>>,
+    pub model_prices: Option<Vec<ModelPrice>>,
+}
+
+/// One (endpoint, model) pair that can serve a model role.
+///
+/// Written as `backend:model` or `backend:model@base_url` in the admin UI, e.g.
+/// `ollama:llama3.1:8b@http://gpu-box:11434`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(clippy::module_name_repetitions)]
+pub struct ModelRoute {
+    pub backend: LlmBackend,
+    pub model: String,
+    /// Replaces the backend's configured base URL, e.g. a second OpenAI-compatible host.
+    #[serde(default)]
+    pub base_url: Option<String>,
+}
+
+impl ModelRoute {
+    pub fn new(backend: LlmBackend, model: impl Into<String>) -> Self {
+        Self {
+            backend,
+            model: model.into(),
+            base_url: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Parses one route per non-empty line.
+    #[allow(clippy::result_large_err)]
+    pub fn parse_list(text: &str) -> Result<Vec<Self>, AppError> {
+        text.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::parse)
+            .collect()
+    }
+
+    /// Formats routes one per line, the inverse of [`Self::parse_list`].
+    pub fn format_list(routes: &[Self]) -> String {
+        routes
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl FromStr for ModelRoute {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            AppError::Validation(format!(
+                "invalid model route '{s}': expected backend:model or backend:model@base_url"
+            ))
+        };
+        let (backend, rest) = s.trim().split_once(':').ok_or_else(invalid)?;
+        let backend = backend
+            .parse::<LlmBackend>()
+            .map_err(|err| AppError::Validation(err.to_string()))?;
+        let (model, base_url) = match rest.split_once('@') {
+            Some((model, base_url)) => (model, Some(base_url.trim())),
+            None => (rest, None),
+        };
+        let model = model.trim();
+        if model.is_empty() {
+            return Err(invalid());
+        }
+        let route = Self::new(backend, model);
+        Ok(match base_url {
+            Some(base_url) => route.with_base_url(base_url),
+            None => route,
+        })
+    }
+}
+
+impl fmt::Display for ModelRoute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.backend.as_str(), self.model)?;
+        if let Some(base_url) = &self.base_url {
+            write!(f, "@{base_url}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Ordered processing routes for one ingestion category.
+///
+/// Written as `Category => route, route` in the admin UI. The routes replace the
+/// processing defaults entirely, so a category can be pinned to a local model.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CategoryModelRoutes {
+    pub category: String,
+    pub routes: Vec<ModelRoute>,
+}
+
+impl CategoryModelRoutes {
+    /// Parses one `Category => route, route` override per non-empty line.
+    #[allow(clippy::result_large_err)]
+    pub fn parse_lines(text: &str) -> Result<Vec<Self>, AppError> {
+        text.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let (category, routes) = line.split_once("=>").ok_or_else(|| {
+                    AppError::Validation(format!(
+                        "invalid category override '{line}': expected Category => backend:model, ..."
+                    ))
+                })?;
+                let routes = routes
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|route| !route.is_empty())
+                    .map(str::parse)
+                    .collect::<Result<Vec<ModelRoute>, _>>()?;
+                Ok(Self {
+                    category: category.trim().to_string(),
+                    routes,
+                })
+            })
+            .collect()
+    }
+
+    /// Formats overrides one per line, the inverse of [`Self::parse_lines`].
+    pub fn format_lines(overrides: &[Self]) -> String {
+        overrides
+            .iter()
+            .map(|entry| {
+                let routes = entry
+                    .routes
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("{} => {routes}", entry.category)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Ingestion system prompt template for one category.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CategoryPrompt {
+    pub category: String,
+    pub prompt: String,
+}
+
+/// Price of a model in USD per million prompt and completion tokens.
+///
+/// Written as `model = prompt, completion` in the admin UI, e.g. `gpt-4o-mini = 0.15, 0.60`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub model: String,
+    pub prompt_per_million: f64,
+    pub completion_per_million: f64,
+}
+
+impl ModelPrice {
+    /// Estimated cost in USD of the given token counts.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn cost(&self, prompt_tokens: u64, completion_tokens: u64) -> f64 {
+        (prompt_tokens as f64 * self.prompt_per_million
+            + completion_tokens as f64 * self.completion_per_million)
+            / 1_000_000.0
+    }
+
+    /// Parses one `model = prompt, completion` price per non-empty line.
+    #[allow(clippy::result_large_err)]
+    pub fn parse_lines(text: &str) -> Result<Vec<Self>, AppError> {
+        text.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let invalid = || {
+                    AppError::Validation(format!(
+                        "invalid model price '{line}': expected model = prompt_price, completion_price"
+                    ))
+                };
+                let (model, prices) = line.rsplit_once('=').ok_or_else(invalid)?;
+                let (prompt, completion) = prices.split_once(',').ok_or_else(invalid)?;
+                Ok(Self {
+                    model: model.trim().to_string(),
+                    prompt_per_million: prompt.trim().parse().map_err(|_| invalid())?,
+                    completion_per_million: completion.trim().parse().map_err(|_| invalid())?,
+                })
+            })
+            .collect()
+    }
+
+    /// Formats prices one per line, the inverse of [`Self::parse_lines`].
+    pub fn format_lines(prices: &[Self]) -> String {
+        prices
+            .iter()
+            .map(|price| {
+                format!(
+                    "{} = {}, {}",
+                    price.model, price.prompt_per_million, price.completion_per_million
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Chat-model roles, each configured with a backend and a model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelRole {
+    /// Answering chat questions.
+    Query,
+    /// Ingestion analysis and summaries.
+    Processing,
+    /// Describing images and transcribing rendered PDF pages.
+    ImageProcessing,
 }
 
 enum UpdateMode {
@@ -113,6 +379,36 @@ impl SystemSettingsPatch {
         if let Some(value) = self.voice_processing_model {
             settings.voice_processing_model = value;
         }
+        if let Some(value) = self.query_backend {
+            settings.query_backend = value;
+        }
+        if let Some(value) = self.processing_backend {
+            settings.processing_backend = value;
+        }
+        if let Some(value) = self.image_processing_backend {
+            settings.image_processing_backend = value;
+        }
+        if let Some(value) = self.query_fallbacks {
+            settings.query_fallbacks = value;
+        }
+        if let Some(value) = self.processing_fallbacks {
+            settings.processing_fallbacks = value;
+        }
+        if let Some(value) = self.image_processing_fallbacks {
+            settings.image_processing_fallbacks = value;
+        }
+        if let Some(value) = self.voice_processing_fallbacks {
+            settings.voice_processing_fallbacks = value;
+        }
+        if let Some(value) = self.processing_category_routes {
+            settings.processing_category_routes = value;
+        }
+        if let Some(value) = self.ingestion_category_prompts {
+            settings.ingestion_category_prompts = value;
+        }
+        if let Some(value) = self.model_prices {
+            settings.model_prices = value;
+        }
     }
 
     pub async fn apply(self, db: &SurrealDbClient) -> Result<SystemSettings, AppError> {
@@ -122,6 +418,28 @@ impl SystemSettingsPatch {
     }
 }
 
+#[allow(clippy::result_large_err)]
+fn validate_route(name: &str, route: &ModelRoute) -> Result<(), AppError> {
+    if route.model.trim().is_empty() {
+        return Err(AppError::Validation(format!(
+            "{name} contains a route without a model"
+        )));
+    }
+    if let Some(base_url) = &route.base_url {
+        let parsed = url::Url::parse(base_url).map_err(|err| {
+            AppError::Validation(format!(
+                "{name} has an invalid base URL '{base_url}': {err}"
+            ))
+        })?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(AppError::Validation(format!(
+                "{name} base URL '{base_url}' must use http or https"
+            )));
+        }
+    }
+    Ok(())
+}
+
 const INDEX_REBUILD_LEASE_TTL: &str = "6h";
 
 impl SystemSettings {
@@ -158,17 +476,214 @@ impl SystemSettings {
                 return Err(AppError::Validation(format!("{name} must not be empty")));
             }
         }
+        validate_prompt("query_system_prompt", &self.query_system_prompt)?;
+        validate_prompt("ingestion_system_prompt", &self.ingestion_system_prompt)?;
+
+        self.validate_category_prompts()?;
+        self.validate_routes()?;
+        self.validate_prices()
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn validate_routes(&self) -> Result<(), AppError> {
+        let route_lists = [
+            ("query_fallbacks", &self.query_fallbacks),
+            ("processing_fallbacks", &self.processing_fallbacks),
+            (
+                "image_processing_fallbacks",
+                &self.image_processing_fallbacks,
+            ),
+            (
+                "voice_processing_fallbacks",
+                &self.voice_processing_fallbacks,
+            ),
+        ];
+        let category_lists = self
+            .processing_category_routes
+            .iter()
+            .map(|entry| ("processing_category_routes", &entry.routes));
+        for (name, routes) in route_lists.into_iter().chain(category_lists) {
+            for route in routes {
+                validate_route(name, route)?;
+            }
+        }
+
+        if let Some(route) = self
+            .voice_processing_fallbacks
+            .iter()
+            .find(|route| route.backend != LlmBackend::OpenAI)
+        {
+            return Err(AppError::Validation(format!(
+                "voice_processing_fallbacks only support the openai backend, got '{route}'"
+            )));
+        }
+
+        let mut categories: Vec<&str> = Vec::new();
+        for entry in &self.processing_category_routes {
+            let category = entry.category.trim();
+            if category.is_empty() {
+                return Err(AppError::Validation(
+                    "processing_category_routes entries need a category".into(),
+                ));
+            }
+            if entry.routes.is_empty() {
+                return Err(AppError::Validation(format!(
+                    "processing_category_routes for '{category}' needs at least one route"
+                )));
+            }
+            if categories
+                .iter()
+                .any(|seen| seen.eq_ignore_ascii_case(category))
+            {
+                return Err(AppError::Validation(format!(
+                    "processing_category_routes lists '{category}' more than once"
+                )));
+            }
+            categories.push(category);
+        }
 
         Ok(())
     }
 
+    #[allow(clippy::result_large_err)]
+    fn validate_category_prompts(&self) -> Result<(), AppError> {
+        let mut categories: Vec<&str> = Vec::new();
+        for entry in &self.ingestion_category_prompts {
+            let category = entry.category.trim();
+            if category.is_empty() {
+                return Err(AppError::Validation(
+                    "ingestion_category_prompts entries need a category".into(),
+                ));
+            }
+            if entry.prompt.trim().is_empty() {
+                return Err(AppError::Validation(format!(
+                    "ingestion prompt for '{category}' must not be empty"
+                )));
+            }
+            if categories
+                .iter()
+                .any(|seen| seen.eq_ignore_ascii_case(category))
+            {
+                return Err(AppError::Validation(format!(
+                    "ingestion_category_prompts lists '{category}' more than once"
+                )));
+            }
+            validate_prompt(&format!("ingestion prompt for '{category}'"), &entry.prompt)?;
+            categories.push(category);
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn validate_prices(&self) -> Result<(), AppError> {
+        let mut models: Vec<&str> = Vec::new();
+        for price in &self.model_prices {
+            let model = price.model.trim();
+            if model.is_empty() {
+                return Err(AppError::Validation(
+                    "model_prices entries need a model".into(),
+                ));
+            }
+            let valid = |value: f64| value.is_finite() && value >= 0.0;
+            if !valid(price.prompt_per_million) || !valid(price.completion_per_million) {
+                return Err(AppError::Validation(format!(
+                    "model_prices for '{model}' must be non-negative numbers"
+                )));
+            }
+            if models.contains(&model) {
+                return Err(AppError::Validation(format!(
+                    "model_prices lists '{model}' more than once"
+                )));
+            }
+            models.push(model);
+        }
+        Ok(())
+    }
+
+    /// Configured price of `model`, if any.
+    pub fn price_for(&self, model: &str) -> Option<&ModelPrice> {
+        self.model_prices
+            .iter()
+            .find(|price| price.model.trim() == model)
+    }
+
+    /// Ingestion prompt template for content in `category`: its override, or the default.
+    pub fn ingestion_prompt_for(&self, category: &str) -> &str {
+        let category = category.trim();
+        self.ingestion_category_prompts
+            .iter()
+            .find(|entry| entry.category.trim().eq_ignore_ascii_case(category))
+            .map_or(self.ingestion_system_prompt.as_str(), |entry| {
+                entry.prompt.as_str()
+            })
+    }
+
+    /// Backend and model configured for `role`.
+    pub fn llm_for(&self, role: ModelRole) -> (LlmBackend, &str) {
+        match role {
+            ModelRole::Query => (self.query_backend, &self.query_model),
+            ModelRole::Processing => (self.processing_backend, &self.processing_model),
+            ModelRole::ImageProcessing => {
+                (self.image_processing_backend, &self.image_processing_model)
+            }
+        }
+    }
+
+    /// Ordered routes serving `role`: the configured backend and model, then its fallbacks.
+    ///
+    /// A processing override for `category` replaces the whole list.
+    pub fn routes_for(&self, role: ModelRole, category: Option<&str>) -> Vec<ModelRoute> {
+        if role == ModelRole::Processing
+            && let Some(category) = category.map(str::trim)
+            && let Some(entry) = self
+                .processing_category_routes
+                .iter()
+                .find(|entry| entry.category.trim().eq_ignore_ascii_case(category))
+        {
+            return entry.routes.clone();
+        }
+
+        let (backend, model) = self.llm_for(role);
+        let fallbacks = match role {
+            ModelRole::Query => &self.query_fallbacks,
+            ModelRole::Processing => &self.processing_fallbacks,
+            ModelRole::ImageProcessing => &self.image_processing_fallbacks,
+        };
+        std::iter::once(ModelRoute::new(backend, model))
+            .chain(fallbacks.iter().cloned())
+            .collect()
+    }
+
+    /// Ordered transcription routes: `voice_processing_model`, then its fallbacks.
+    pub fn voice_routes(&self) -> Vec<ModelRoute> {
+        std::iter::once(ModelRoute::new(
+            LlmBackend::OpenAI,
+            self.voice_processing_model.as_str(),
+        ))
+        .chain(self.voice_processing_fallbacks.iter().cloned())
+        .collect()
+    }
+
     pub async fn get_current(db: &SurrealDbClient) -> Result<Self, AppError> {
         let settings: Option<Self> = db.get_item(Self::RECORD_ID).await?;
         settings.ok_or(AppError::NotFound("system settings not found".into()))
     }
 
     pub async fn update(db: &SurrealDbClient, changes: Self) -> Result<Self, AppError> {
-        Self::update_with_mode(db, changes, UpdateMode::User).await
+        Self::update_recorded(db, changes, None, None).await
+    }
+
+    /// Updates the settings and records the change as a [`SettingsVersion`].
+    pub(crate) async fn update_recorded(
+        db: &SurrealDbClient,
+        changes: Self,
+        author: Option<&str>,
+        rollback_of: Option<u64>,
+    ) -> Result<Self, AppError> {
+        let current = Self::get_current(db).await?;
+        let updated = Self::update_with_mode(db, changes, UpdateMode::User).await?;
+        SettingsVersion::record(db, &current, &updated, author, rollback_of).await?;
+        Ok(updated)
     }
 
     async fn update_with_mode(
@@ -587,6 +1102,193 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_patch_selects_backend_per_model_role() -> anyhow::Result<()> {
+        let db = setup_test_db().await?;
+
+        let current = SystemSettings::get_current(&db)
+            .await
+            .with_context(|| "Failed to get system settings".to_string())?;
+        assert_eq!(current.query_backend, LlmBackend::OpenAI);
+
+        let patched = SystemSettingsPatch {
+            processing_backend: Some(LlmBackend::Ollama),
+            processing_model: Some("llama3.1".into()),
+            ..Default::default()
+        }
+        .apply(&db)
+        .await
+        .with_context(|| "Failed to patch processing backend".to_string())?;
+
+        assert_eq!(
+            patched.llm_for(ModelRole::Processing),
+            (LlmBackend::Ollama, "llama3.1")
+        );
+        assert_eq!(patched.llm_for(ModelRole::Query).0, LlmBackend::OpenAI);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_category_prompts_override_ingestion_prompt_and_are_validated()
+    -> anyhow::Result<()> {
+        let db = setup_test_db().await?;
+
+        let stored = SystemSettingsPatch {
+            ingestion_category_prompts: Some(vec![CategoryPrompt {
+                category: "Papers".into(),
+                prompt: "Extract methods from this {{ category }}.".into(),
+            }]),
+            ..Default::default()
+        }
+        .apply(&db)
+        .await
+        .with_context(|| "Failed to patch category prompts".to_string())?;
+        assert_eq!(
+            stored.ingestion_prompt_for(" papers "),
+            "Extract methods from this {{ category }}."
+        );
+        assert_eq!(
+            stored.ingestion_prompt_for("Meeting notes"),
+            stored.ingestion_system_prompt
+        );
+
+        let unknown_variable = SystemSettingsPatch {
+            ingestion_category_prompts: Some(vec![CategoryPrompt {
+                category: "Papers".into(),
+                prompt: "Use {{ categroy }}".into(),
+            }]),
+            ..Default::default()
+        }
+        .apply(&db)
+        .await;
+        assert!(matches!(unknown_variable, Err(AppError::Validation(_))));
+
+        let broken_query_prompt = SystemSettingsPatch {
+            query_system_prompt: Some("{% if now %}unterminated".into()),
+            ..Default::default()
+        }
+        .apply(&db)
+        .await;
+        assert!(matches!(broken_query_prompt, Err(AppError::Validation(_))));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_patch_persists_fallback_and_category_routes() -> anyhow::Result<()> {
+        let db = setup_test_db().await?;
+
+        let fallbacks = ModelRoute::parse_list(
+            "ollama:llama3.1:8b@http://gpu-box:11434\n\n anthropic:claude-3-5-haiku-latest ",
+        )?;
+        let overrides =
+            CategoryModelRoutes::parse_lines("Legal => ollama:llama3.1:8b, ollama:mistral")?;
+        SystemSettingsPatch {
+            processing_fallbacks: Some(fallbacks.clone()),
+            processing_category_routes: Some(overrides),
+            ..Default::default()
+        }
+        .apply(&db)
+        .await
+        .with_context(|| "Failed to patch routes".to_string())?;
+
+        let stored = SystemSettings::get_current(&db).await?;
+        assert_eq!(stored.processing_fallbacks, fallbacks);
+        assert_eq!(
+            ModelRoute::format_list(&stored.processing_fallbacks),
+            "ollama:llama3.1:8b@http://gpu-box:11434\nanthropic:claude-3-5-haiku-latest"
+        );
+
+        let default_routes = stored.routes_for(ModelRole::Processing, Some("Notes"));
+        assert_eq!(default_routes.len(), 3);
+        assert_eq!(
+            default_routes.first(),
+            Some(&ModelRoute::new(
+                stored.processing_backend,
+                stored.processing_model.as_str()
+            ))
+        );
+
+        let legal_routes = stored.routes_for(ModelRole::Processing, Some(" legal "));
+        assert_eq!(
+            legal_routes,
+            vec![
+                ModelRoute::new(LlmBackend::Ollama, "llama3.1:8b"),
+                ModelRoute::new(LlmBackend::Ollama, "mistral"),
+            ]
+        );
+        assert_eq!(
+            stored.routes_for(ModelRole::Query, Some("Legal")).len(),
+            1,
+            "category overrides only apply to processing"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_update_rejects_invalid_routes() -> anyhow::Result<()> {
+        let db = setup_test_db().await?;
+        let current = SystemSettings::get_current(&db).await?;
+
+        let non_openai_voice = SystemSettings {
+            voice_processing_fallbacks: vec![ModelRoute::new(LlmBackend::Ollama, "whisper")],
+            ..current.clone()
+        };
+        let bad_url = SystemSettings {
+            query_fallbacks: vec![
+                ModelRoute::new(LlmBackend::OpenAI, "gpt-4o").with_base_url("ftp://example.com"),
+            ],
+            ..current.clone()
+        };
+        let duplicate_category = SystemSettings {
+            processing_category_routes: CategoryModelRoutes::parse_lines(
+                "Legal => ollama:mistral\nLEGAL => ollama:llama3.1",
+            )?,
+            ..current
+        };
+        for invalid in [non_openai_voice, bad_url, duplicate_category] {
+            let result = SystemSettings::update(&db, invalid).await;
+            assert!(matches!(result, Err(AppError::Validation(_))));
+        }
+
+        assert!(ModelRoute::parse_list("gpt-4o").is_err());
+        assert!(ModelRoute::parse_list("bedrock:titan").is_err());
+        assert!(CategoryModelRoutes::parse_lines("Legal ollama:mistral").is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_patch_persists_model_prices() -> anyhow::Result<()> {
+        let db = setup_test_db().await?;
+
+        let prices = ModelPrice::parse_lines("gpt-4o-mini = 0.15, 0.6\n\nllama3.1:8b = 0, 0")?;
+        SystemSettingsPatch {
+            model_prices: Some(prices.clone()),
+            ..Default::default()
+        }
+        .apply(&db)
+        .await
+        .with_context(|| "Failed to patch model prices".to_string())?;
+
+        let stored = SystemSettings::get_current(&db).await?;
+        assert_eq!(stored.model_prices, prices);
+        assert_eq!(
+            ModelPrice::format_lines(&stored.model_prices),
+            "gpt-4o-mini = 0.15, 0.6\nllama3.1:8b = 0, 0"
+        );
+        let price = stored.price_for("gpt-4o-mini").expect("configured price");
+        assert!((price.cost(2_000_000, 1_000_000) - 0.9).abs() < 1e-9);
+        assert!(stored.price_for("gpt-4o").is_none());
+
+        let negative = SystemSettings {
+            model_prices: ModelPrice::parse_lines("gpt-4o = -1, 10")?,
+            ..stored
+        };
+        let result = SystemSettings::update(&db, negative).await;
+        assert!(matches!(result, Err(AppError::Validation(_))));
+        assert!(ModelPrice::parse_lines("gpt-4o = 2.5").is_err());
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_update_rejects_empty_model_name() -> anyhow::Result<()> {
         let db = setup_test_db().await?;
@@ -0,0 +1,442 @@
+use std::collections::HashMap;
+
+use sha2::{Digest, Sha256};
+
+use crate::{error::AppError, storage::db::SurrealDbClient, stored_object};
+
+use super::{knowledge_entity::KnowledgeEntity, text_content::TextContent};
+
+/// Longest accepted tag name, after normalization.
+pub const MAX_TAG_NAME_CHARS: usize = 40;
+
+stored_object!(
+    /// One entry in a user's tag vocabulary.
+    ///
+    /// Content and entities carry tags by name in their `tags` arrays; the vocabulary
+    /// lists the names in use and is what enrichment is steered towards. Ids are derived
+    /// from the user and name, so recording a tag twice is harmless.
+    Tag, "tag", {
+    user_id: String,
+    name: String
+});
+
+/// How often a tag is used, for the tag management view.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Clone, Serialize)]
+pub struct TagUsage {
+    pub tag: Tag,
+    pub content_count: usize,
+    pub entity_count: usize,
+}
+
+/// Lowercases `raw`, drops a leading `#`, and joins words with `-`, so "Machine
+/// Learning" and "#machine-learning" are the same tag. Returns `None` when nothing
+/// usable is left or the result is longer than [`MAX_TAG_NAME_CHARS`].
+#[must_use]
+#[allow(clippy::module_name_repetitions)]
+pub fn normalize_tag(raw: &str) -> Option<String> {
+    let words: Vec<String> = raw
+        .trim()
+        .trim_start_matches('#')
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    let name = words.join("-");
+
+    if name.is_empty() || name.chars().count() > MAX_TAG_NAME_CHARS {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+/// Normalizes each tag, dropping unusable ones and duplicates while keeping order.
+#[must_use]
+pub fn normalize_tags<I, S>(raw: I) -> Vec<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut tags: Vec<String> = Vec::new();
+    for tag in raw
+        .into_iter()
+        .filter_map(|tag| normalize_tag(tag.as_ref()))
+    {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+/// Splits a comma-separated form field into normalized tags.
+#[must_use]
+pub fn parse_tag_list(raw: &str) -> Vec<String> {
+    normalize_tags(raw.split(','))
+}
+
+impl Tag {
+    /// The record id of `name` in the vocabulary of `user_id`.
+    #[must_use]
+    pub fn id_for(user_id: &str, name: &str) -> String {
+        let digest = Sha256::digest(format!("{user_id}\n{name}").as_bytes());
+        format!("{digest:x}")
+    }
+
+    /// The user's vocabulary, sorted by name.
+    pub async fn list_for_user(user_id: &str, db: &SurrealDbClient) -> Result<Vec<Self>, AppError> {
+        let tags: Vec<Self> = db
+            .client
+            .query("SELECT * FROM type::table($table) WHERE user_id = $user_id ORDER BY name ASC")
+            .bind(("table", Self::table_name()))
+            .bind(("user_id", user_id.to_string()))
+            .await?
+            .take(0)?;
+
+        Ok(tags)
+    }
+
+    /// Names in the user's vocabulary, sorted.
+    pub async fn names_for_user(
+        user_id: &str,
+        db: &SurrealDbClient,
+    ) -> Result<Vec<String>, AppError> {
+        Ok(Self::list_for_user(user_id, db)
+            .await?
+            .into_iter()
+            .map(|tag| tag.name)
+            .collect())
+    }
+
+    /// Adds a tag to the vocabulary.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::Validation` for a name that normalizes to nothing or is
+    /// already in the vocabulary.
+    pub async fn create(
+        user_id: &str,
+        raw_name: &str,
+        db: &SurrealDbClient,
+    ) -> Result<(), AppError> {
+        let name = validated_name(raw_name)?;
+        if Self::names_for_user(user_id, db).await?.contains(&name) {
+            return Err(AppError::Validation(format!("tag '{name}' already exists")));
+        }
+        Self::ensure(user_id, &[name], db).await
+    }
+
+    /// Records already-normalized `names` in the vocabulary, keeping existing entries.
+    pub async fn ensure(
+        user_id: &str,
+        names: &[String],
+        db: &SurrealDbClient,
+    ) -> Result<(), AppError> {
+        if names.is_empty() {
+            return Ok(());
+        }
+
+        let rows: Vec<serde_json::Value> = names
+            .iter()
+            .map(|name| {
+                serde_json::json!({
+                    "id": Self::id_for(user_id, name),
+                    "name": name,
+                })
+            })
+            .collect();
+
+        db.client
+            .query(
+                "FOR $row IN $rows {
+                    UPSERT type::thing($table, $row.id) SET
+                        user_id = $user_id,
+                        name = $row.name,
+                        created_at = created_at ?? time::now(),
+                        updated_at = updated_at ?? time::now();
+                };",
+            )
+            .bind(("table", Self::table_name()))
+            .bind(("rows", rows))
+            .bind(("user_id", user_id.to_string()))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// Replaces the tags of one content item or entity owned by `user_id`, recording any
+    /// new names in the vocabulary. Returns the normalized tags that were stored.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the item does not exist for `user_id`.
+    pub async fn set_item_tags<T: StoredObject>(
+        item_id: &str,
+        user_id: &str,
+        raw_tags: &[String],
+        db: &SurrealDbClient,
+    ) -> Result<Vec<String>, AppError> {
+        let tags = normalize_tags(raw_tags);
+
+        let updated: Option<surrealdb::sql::Thing> = db
+            .client
+            .query(
+                "UPDATE type::thing($table, $id) SET tags = $tags, updated_at = time::now()
+                 WHERE user_id = $user_id RETURN VALUE id",
+            )
+            .bind(("table", T::table_name()))
+            .bind(("id", item_id.to_string()))
+            .bind(("tags", tags.clone()))
+            .bind(("user_id", user_id.to_string()))
+            .await?
+            .take(0)?;
+        if updated.is_none() {
+            return Err(AppError::NotFound(format!("{} not found", T::table_name())));
+        }
+
+        Self::ensure(user_id, &tags, db).await?;
+        Ok(tags)
+    }
+
+    /// Renames a tag everywhere it is used. Renaming onto an existing tag merges the two.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the tag does not exist for `user_id` and
+    /// `AppError::Validation` for an unusable new name.
+    pub async fn rename(
+        id: &str,
+        user_id: &str,
+        raw_name: &str,
+        db: &SurrealDbClient,
+    ) -> Result<(), AppError> {
+        let tag = Self::get_owned(id, user_id, db).await?;
+        let name = validated_name(raw_name)?;
+        if name == tag.name {
+            return Ok(());
+        }
+
+        db.client
+            .query(
+                "BEGIN TRANSACTION;
+                 UPDATE type::table($content_table)
+                     SET tags = array::distinct(array::append(array::complement(tags, [$old]), $new))
+                     WHERE user_id = $user_id AND tags CONTAINS $old;
+                 UPDATE type::table($entity_table)
+                     SET tags = array::distinct(array::append(array::complement(tags, [$old]), $new))
+                     WHERE user_id = $user_id AND tags CONTAINS $old;
+                 DELETE type::thing($table, $old_id);
+                 UPSERT type::thing($table, $new_id) SET
+                     user_id = $user_id,
+                     name = $new,
+                     created_at = created_at ?? time::now(),
+                     updated_at = time::now();
+                 COMMIT TRANSACTION;",
+            )
+            .bind(("table", Self::table_name()))
+            .bind(("content_table", TextContent::table_name()))
+            .bind(("entity_table", KnowledgeEntity::table_name()))
+            .bind(("user_id", user_id.to_string()))
+            .bind(("old", tag.name))
+            .bind(("old_id", tag.id))
+            .bind(("new_id", Self::id_for(user_id, &name)))
+            .bind(("new", name))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// Removes a tag from the vocabulary and from every item that carries it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AppError::NotFound` if the tag does not exist for `user_id`.
+    pub async fn delete(id: &str, user_id: &str, db: &SurrealDbClient) -> Result<(), AppError> {
+        let tag = Self::get_owned(id, user_id, db).await?;
+
+        db.client
+            .query(
+                "BEGIN TRANSACTION;
+                 UPDATE type::table($content_table) SET tags = array::complement(tags, [$name])
+                     WHERE user_id = $user_id AND tags CONTAINS $name;
+                 UPDATE type::table($entity_table) SET tags = array::complement(tags, [$name])
+                     WHERE user_id = $user_id AND tags CONTAINS $name;
+                 DELETE type::thing($table, $id);
+                 COMMIT TRANSACTION;",
+            )
+            .bind(("table", Self::table_name()))
+            .bind(("content_table", TextContent::table_name()))
+            .bind(("entity_table", KnowledgeEntity::table_name()))
+            .bind(("user_id", user_id.to_string()))
+            .bind(("name", tag.name))
+            .bind(("id", tag.id))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// The vocabulary with the number of content items and entities using each tag.
+    pub async fn usage_for_user(
+        user_id: &str,
+        db: &SurrealDbClient,
+    ) -> Result<Vec<TagUsage>, AppError> {
+        let mut response = db
+            .client
+            .query("SELECT VALUE tags FROM type::table($content_table) WHERE user_id = $user_id")
+            .query("SELECT VALUE tags FROM type::table($entity_table) WHERE user_id = $user_id")
+            .bind(("content_table", TextContent::table_name()))
+            .bind(("entity_table", KnowledgeEntity::table_name()))
+            .bind(("user_id", user_id.to_string()))
+            .await?;
+        let content_tags: Vec<Option<Vec<String>>> = response.take(0)?;
+        let entity_tags: Vec<Option<Vec<String>>> = response.take(1)?;
+
+        let content_counts = count_tags(content_tags);
+        let entity_counts = count_tags(entity_tags);
+
+        Ok(Self::list_for_user(user_id, db)
+            .await?
+            .into_iter()
+            .map(|tag| TagUsage {
+                content_count: content_counts.get(&tag.name).copied().unwrap_or(0),
+                entity_count: entity_counts.get(&tag.name).copied().unwrap_or(0),
+                tag,
+            })
+            .collect())
+    }
+
+    async fn get_owned(id: &str, user_id: &str, db: &SurrealDbClient) -> Result<Self, AppError> {
+        db.get_item::<Self>(id)
+            .await?
+            .filter(|tag| tag.user_id == user_id)
+            .ok_or_else(|| AppError::NotFound("tag not found".to_string()))
+    }
+}
+
+fn validated_name(raw: &str) -> Result<String, AppError> {
+    normalize_tag(raw).ok_or_else(|| {
+        AppError::Validation(format!(
+            "tags need at least one letter or digit and at most {MAX_TAG_NAME_CHARS} characters"
+        ))
+    })
+}
+
+fn count_tags(rows: Vec<Option<Vec<String>>>) -> HashMap<String, usize> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for tag in rows.into_iter().flatten().flatten() {
+        let count = counts.entry(tag).or_insert(0);
+        *count = count.saturating_add(1);
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::must_use_candidate)]
+    use super::*;
+    use crate::{storage::types::knowledge_entity::KnowledgeEntityType, test_utils::setup_test_db};
+
+    #[test]
+    fn normalize_tag_folds_case_spacing_and_hashes() {
+        assert_eq!(
+            normalize_tag("  Machine Learning "),
+            Some("machine-learning".to_string())
+        );
+        assert_eq!(
+            normalize_tag("#machine_learning"),
+            Some("machine-learning".to_string())
+        );
+        assert_eq!(
+            normalize_tag("Årsredovisning"),
+            Some("årsredovisning".to_string())
+        );
+        assert_eq!(normalize_tag(" # "), None);
+        assert_eq!(normalize_tag(&"x".repeat(MAX_TAG_NAME_CHARS + 1)), None);
+    }
+
+    #[test]
+    fn parse_tag_list_dedupes_in_order() {
+        assert_eq!(
+            parse_tag_list("Rust, rust , ,Databases,#rust"),
+            vec!["rust".to_string(), "databases".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn set_item_tags_records_vocabulary_and_checks_owner() -> anyhow::Result<()> {
+        let db = setup_test_db().await?;
+        let content = TextContent::new(
+            "notes".to_string(),
+            None,
+            "notes".to_string(),
+            None,
+            None,
+            "user-a".to_string(),
+        );
+        db.store_item(content.clone()).await?;
+
+        let stored = Tag::set_item_tags::<TextContent>(
+            &content.id,
+            "user-a",
+            &["Rust".to_string(), "Async IO".to_string()],
+            &db,
+        )
+        .await?;
+        assert_eq!(stored, vec!["rust".to_string(), "async-io".to_string()]);
+
+        let reloaded: TextContent = db.get_item(&content.id).await?.expect("content");
+        assert_eq!(reloaded.tags, stored);
+        assert_eq!(
+            Tag::names_for_user("user-a", &db).await?,
+            vec!["async-io".to_string(), "rust".to_string()]
+        );
+
+        let foreign =
+            Tag::set_item_tags::<TextContent>(&content.id, "user-b", &["x".to_string()], &db).await;
+        assert!(matches!(foreign, Err(AppError::NotFound(_))));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn rename_merges_and_delete_untags_items() -> anyhow::Result<()> {
+        let db = setup_test_db().await?;
+        let entity = KnowledgeEntity::new(
+            "source".to_string(),
+            "Tokio".to_string(),
+            "Async runtime".to_string(),
+            KnowledgeEntityType::Project,
+            None,
+            "user-a".to_string(),
+        );
+        db.store_item(entity.clone()).await?;
+        Tag::set_item_tags::<KnowledgeEntity>(
+            &entity.id,
+            "user-a",
+            &["rustlang".to_string(), "rust".to_string()],
+            &db,
+        )
+        .await?;
+
+        Tag::rename(&Tag::id_for("user-a", "rustlang"), "user-a", "Rust", &db).await?;
+        let renamed: KnowledgeEntity = db.get_item(&entity.id).await?.expect("entity");
+        assert_eq!(renamed.tags, vec!["rust".to_string()]);
+        assert_eq!(Tag::names_for_user("user-a", &db).await?, vec!["rust"]);
+
+        let usage = Tag::usage_for_user("user-a", &db).await?;
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage.first().map(|u| u.entity_count), Some(1));
+
+        let foreign = Tag::delete(&Tag::id_for("user-a", "rust"), "user-b", &db).await;
+        assert!(matches!(foreign, Err(AppError::NotFound(_))));
+
+        Tag::delete(&Tag::id_for("user-a", "rust"), "user-a", &db).await?;
+        let untagged: KnowledgeEntity = db.get_item(&entity.id).await?.expect("entity");
+        assert!(untagged.tags.is_empty());
+        assert!(Tag::list_for_user("user-a", &db).await?.is_empty());
+        Ok(())
+    }
+}
@@ -89,6 +89,29 @@ impl TextChunk {
             })
     }
 
+    /// Chunks of `source_ids` most similar to `query_embedding`, across all of those sources.
+    pub async fn vector_search_in_sources(
+        take: usize,
+        query_embedding: &[f32],
+        source_ids: &[String],
+        db: &SurrealDbClient,
+        user_id: &str,
+    ) -> Result<Vec<TextChunkSearchResult>, AppError> {
+        db.vector_search_in_sources::<Self, TextChunkEmbedding>(
+            take,
+            query_embedding,
+            source_ids,
+            user_id,
+        )
+        .await
+        .map(|results| {
+            results
+                .into_iter()
+                .map(|(chunk, score)| TextChunkSearchResult { chunk, score })
+                .collect()
+        })
+    }
+
     /// Full-text search over text chunks using the BM25 FTS index.
     pub async fn fts_search(
         take: usize,
@@ -1,5 +1,4 @@
 use std::collections::{HashMap, HashSet};
-use std::str::FromStr;
 
 use surrealdb::RecordId;
 use surrealdb::opt::PatchOp;
@@ -63,13 +62,30 @@ pub struct UrlInfo {
     pub image_id: String,
 }
 
+/// LLM-written digest of a document, generated at ingestion.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ContentSummary {
+    /// Suggested title for the document.
+    pub title: String,
+    /// Short abstract of the document.
+    pub overview: String,
+    /// Bullet-point key takeaways.
+    #[serde(default)]
+    pub key_points: Vec<String>,
+}
+
 stored_object!(TextContent, "text_content", {
     text: String,
     file_info: Option<FileInfo>,
     url_info: Option<UrlInfo>,
     context: Option<String>,
     category: String,
-    user_id: String
+    user_id: String,
+    #[serde(default)]
+    summary: Option<ContentSummary>,
+    /// Tag names from the user's vocabulary (see [`super::tag::Tag`]).
+    #[serde(default)]
+    tags: Vec<String>
 });
 
 impl TextContent {
@@ -93,22 +109,40 @@ impl TextContent {
             context,
             category,
             user_id,
+            summary: None,
+            tags: Vec::new(),
         }
     }
 
     /// SurrealQL deletes for ingested child rows keyed by `source_id` (no transaction wrapper).
     ///
     /// Used inside larger transactions (e.g. ingestion `persist_artifacts`) and mirrored by
-    /// [`Self::clear_ingested_children`].
+    /// [`Self::clear_ingested_children`]. Entities this source created but other sources
+    /// still mention are handed to the oldest remaining mention instead of being deleted.
     pub const CLEAR_INGESTED_CHILD_ROWS_SURQL: &'static str = r"
+LET $owned = (SELECT id, (SELECT source_id, created_at FROM knowledge_entity_mention
+        WHERE entity_id = record::id($parent.id) AND source_id != $source_id
+        ORDER BY created_at ASC LIMIT 1)[0].source_id AS next_source
+    FROM knowledge_entity WHERE source_id = $source_id AND user_id = $user_id);
+FOR $entity IN $owned {
+    IF $entity.next_source != NONE {
+        UPDATE $entity.id SET source_id = $entity.next_source;
+        UPDATE knowledge_entity_embedding SET source_id = $entity.next_source WHERE entity_id = $entity.id;
+    } ELSE {
+        DELETE knowledge_entity_embedding WHERE entity_id = $entity.id;
+        DELETE $entity.id;
+    };
+};
+DELETE knowledge_entity_mention WHERE source_id = $source_id AND user_id = $user_id;
 DELETE relates_to WHERE metadata.source_id = $source_id AND metadata.user_id = $user_id;
 DELETE text_chunk_embedding WHERE source_id = $source_id;
 DELETE text_chunk WHERE source_id = $source_id;
-DELETE knowledge_entity_embedding WHERE source_id = $source_id;
-DELETE knowledge_entity WHERE source_id = $source_id;
+DELETE action_item WHERE source_id = $source_id AND user_id = $user_id;
+DELETE knowledge_suggestion WHERE source_id = $source_id AND user_id = $user_id;
 ";
 
-    /// Removes chunks, embeddings, entities, and relationships for one ingested document snapshot.
+    /// Removes chunks, embeddings, entities, relationships, action items, and review
+    /// suggestions for one ingested document snapshot.
     pub async fn clear_ingested_children(
         source_id: &str,
         user_id: &str,
@@ -232,6 +266,19 @@ DELETE knowledge_entity WHERE source_id = $source_id;
             .map_err(AppError::from)
     }
 
+    /// Human-readable label for this content (URL title, file name, context, or text preview).
+    #[must_use]
+    pub fn source_label(&self) -> String {
+        build_source_label(&SourceLabelParts {
+            id: &self.id,
+            url_info: self.url_info.as_ref(),
+            file_info: self.file_info.as_ref(),
+            context: self.context.as_deref(),
+            category: &self.category,
+            text: &self.text,
+        })
+    }
+
     /// Builds a fallback display label for a source id when no matching content row exists.
     #[must_use]
     pub fn fallback_source_label(source_id: &str) -> String {
@@ -253,16 +300,7 @@ DELETE knowledge_entity WHERE source_id = $source_id;
             return Ok(HashMap::new());
         }
 
-        let record_ids: Vec<RecordId> = source_ids
-            .iter()
-            .filter_map(|id| {
-                if id.contains(':') {
-                    RecordId::from_str(id).ok()
-                } else {
-                    Some(RecordId::from_table_key(Self::table_name(), id))
-                }
-            })
-            .collect();
+        let record_ids = Self::source_record_ids(&source_ids);
 
         let mut response = db
             .client
@@ -285,13 +323,101 @@ DELETE knowledge_entity WHERE source_id = $source_id;
 
         let mut labels = HashMap::new();
         for content in contents {
-            let label = build_source_label(&content);
+            let label = build_source_label(&SourceLabelParts {
+                id: &content.id,
+                url_info: content.url_info.as_ref(),
+                file_info: content.file_info.as_ref(),
+                context: content.context.as_deref(),
+                category: &content.category,
+                text: &content.text,
+            });
             labels.insert(content.id.clone(), label.clone());
             labels.insert(format!("{}:{}", Self::table_name(), content.id), label);
         }
 
         Ok(labels)
     }
+
+    /// Loads the ingestion summaries of the given source ids owned by `user_id`, keyed
+    /// by bare id. Sources without a summary are left out.
+    pub async fn resolve_source_summaries(
+        db: &SurrealDbClient,
+        user_id: &str,
+        source_ids: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<HashMap<String, ContentSummary>, AppError> {
+        let source_ids: HashSet<String> = source_ids
+            .into_iter()
+            .map(|id| id.as_ref().to_string())
+            .collect();
+
+        if source_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let mut response = db
+            .client
+            .query(
+                "SELECT id, summary FROM type::table($table_name) WHERE user_id = $user_id AND id INSIDE $record_ids AND summary != NONE",
+            )
+            .bind(("table_name", Self::table_name()))
+            .bind(("user_id", user_id.to_owned()))
+            .bind(("record_ids", Self::source_record_ids(&source_ids)))
+            .await
+            .map_err(AppError::from)?;
+
+        let rows: Vec<SourceSummaryRow> = response.take(0).map_err(AppError::from)?;
+
+        Ok(rows.into_iter().map(|row| (row.id, row.summary)).collect())
+    }
+
+    /// Narrows `source_ids` to the sources owned by `user_id` that carry `tag`, returned
+    /// as bare ids.
+    pub async fn source_ids_with_tag(
+        db: &SurrealDbClient,
+        user_id: &str,
+        source_ids: impl IntoIterator<Item = impl AsRef<str>>,
+        tag: &str,
+    ) -> Result<HashSet<String>, AppError> {
+        let source_ids: HashSet<String> = source_ids
+            .into_iter()
+            .map(|id| id.as_ref().to_string())
+            .collect();
+
+        if source_ids.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        let mut response = db
+            .client
+            .query(
+                "SELECT id FROM type::table($table_name) WHERE user_id = $user_id AND id INSIDE $record_ids AND $tag INSIDE tags",
+            )
+            .bind(("table_name", Self::table_name()))
+            .bind(("user_id", user_id.to_owned()))
+            .bind(("record_ids", Self::source_record_ids(&source_ids)))
+            .bind(("tag", tag.to_owned()))
+            .await
+            .map_err(AppError::from)?;
+
+        let rows: Vec<SourceIdRow> = response.take(0).map_err(AppError::from)?;
+
+        Ok(rows.into_iter().map(|row| row.id).collect())
+    }
+
+    fn source_record_ids(source_ids: &HashSet<String>) -> Vec<RecordId> {
+        source_ids
+            .iter()
+            .map(|id| {
+                let key = id
+                    .strip_prefix(Self::table_name())
+                    .and_then(|rest| rest.strip_prefix(':'))
+                    .map_or(id.as_str(), |key| {
+                        key.trim_start_matches('⟨').trim_end_matches('⟩')
+                    });
+                RecordId::from_table_key(Self::table_name(), key)
+            })
+            .collect()
+    }
 }
 
 const SOURCE_LABEL_MAX_CHARS: usize = 80;
@@ -312,6 +438,29 @@ struct SourceLabelRow {
     text: String,
 }
 
+#[derive(Deserialize)]
+struct SourceIdRow {
+    #[serde(deserialize_with = "deserialize_flexible_id")]
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct SourceSummaryRow {
+    #[serde(deserialize_with = "deserialize_flexible_id")]
+    id: String,
+    summary: ContentSummary,
+}
+
+/// Borrowed view of the fields that feed [`build_source_label`].
+struct SourceLabelParts<'a> {
+    id: &'a str,
+    url_info: Option<&'a UrlInfo>,
+    file_info: Option<&'a FileInfo>,
+    context: Option<&'a str>,
+    category: &'a str,
+    text: &'a str,
+}
+
 fn source_id_suffix(source_id: &str) -> String {
     let start = source_id.len().saturating_sub(8);
     source_id[start..].to_string()
@@ -354,8 +503,8 @@ fn first_non_empty_line(text: &str, max_chars: usize) -> Option<String> {
     })
 }
 
-fn build_source_label(row: &SourceLabelRow) -> String {
-    if let Some(url_info) = row.url_info.as_ref() {
+fn build_source_label(row: &SourceLabelParts<'_>) -> String {
+    if let Some(url_info) = row.url_info {
         let title = url_info.title.trim();
         if !title.is_empty() {
             return title.to_string();
@@ -367,21 +516,21 @@ fn build_source_label(row: &SourceLabelRow) -> String {
         }
     }
 
-    if let Some(file_info) = row.file_info.as_ref() {
+    if let Some(file_info) = row.file_info {
         let name = file_info.file_name.trim();
         if !name.is_empty() {
             return name.to_string();
         }
     }
 
-    if let Some(context) = row.context.as_ref() {
+    if let Some(context) = row.context {
         let trimmed = context.trim();
         if !trimmed.is_empty() {
             return truncate_with_ellipsis(trimmed, SOURCE_LABEL_MAX_CHARS);
         }
     }
 
-    if let Some(text_label) = first_non_empty_line(&row.text, SOURCE_LABEL_MAX_CHARS) {
+    if let Some(text_label) = first_non_empty_line(row.text, SOURCE_LABEL_MAX_CHARS) {
         return text_label;
     }
 
@@ -390,7 +539,7 @@ fn build_source_label(row: &SourceLabelRow) -> String {
         return truncate_with_ellipsis(category, SOURCE_LABEL_MAX_CHARS);
     }
 
-    TextContent::fallback_source_label(&row.id)
+    TextContent::fallback_source_label(row.id)
 }
 
 #[cfg(test)]
@@ -402,6 +551,8 @@ mod tests {
     use crate::{
         storage::types::{
             knowledge_entity::{KnowledgeEntity, KnowledgeEntityType},
+            knowledge_entity_embedding::KnowledgeEntityEmbedding,
+            knowledge_entity_mention::KnowledgeEntityMention,
             knowledge_relationship::KnowledgeRelationship,
             text_chunk::TextChunk,
         },
@@ -681,6 +832,102 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_resolve_source_summaries_skips_unsummarized_and_foreign() -> anyhow::Result<()> {
+        let db = setup_test_db().await?;
+        let user_id = "summary_user";
+
+        let mut summarized = TextContent::new(
+            "A long essay about tidal energy.".to_string(),
+            None,
+            "notes".to_string(),
+            None,
+            None,
+            user_id.to_string(),
+        );
+        summarized.summary = Some(ContentSummary {
+            title: "Tidal energy".to_string(),
+            overview: "An essay on tidal power.".to_string(),
+            key_points: vec!["Tides are predictable".to_string()],
+        });
+        let plain = TextContent::new(
+            "No summary here".to_string(),
+            None,
+            "notes".to_string(),
+            None,
+            None,
+            user_id.to_string(),
+        );
+        let mut foreign = summarized.clone();
+        foreign.id = Uuid::new_v4().to_string();
+        foreign.user_id = "other_user".to_string();
+
+        db.store_item(summarized.clone()).await?;
+        db.store_item(plain.clone()).await?;
+        db.store_item(foreign.clone()).await?;
+
+        let summaries = TextContent::resolve_source_summaries(
+            &db,
+            user_id,
+            [
+                summarized.id.clone(),
+                format!("text_content:{}", plain.id),
+                foreign.id.clone(),
+            ],
+        )
+        .await?;
+
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries.get(&summarized.id), summarized.summary.as_ref());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_source_ids_with_tag_filters_by_tag_and_owner() -> anyhow::Result<()> {
+        let db = setup_test_db().await?;
+        let user_id = "tag_user";
+
+        let mut tagged = TextContent::new(
+            "Notes on sourdough".to_string(),
+            None,
+            "notes".to_string(),
+            None,
+            None,
+            user_id.to_string(),
+        );
+        tagged.tags = vec!["baking".to_string()];
+        let untagged = TextContent::new(
+            "Notes on sailing".to_string(),
+            None,
+            "notes".to_string(),
+            None,
+            None,
+            user_id.to_string(),
+        );
+        let mut foreign = tagged.clone();
+        foreign.id = Uuid::new_v4().to_string();
+        foreign.user_id = "other_user".to_string();
+
+        db.store_item(tagged.clone()).await?;
+        db.store_item(untagged.clone()).await?;
+        db.store_item(foreign.clone()).await?;
+
+        let ids = TextContent::source_ids_with_tag(
+            &db,
+            user_id,
+            [
+                format!("text_content:{}", tagged.id),
+                untagged.id.clone(),
+                foreign.id.clone(),
+            ],
+            "baking",
+        )
+        .await?;
+
+        assert_eq!(ids, HashSet::from([tagged.id.clone()]));
+        Ok(())
+    }
+
     #[tokio::test]
     async fn clear_ingested_children_removes_chunks_entities_and_relationships()
     -> anyhow::Result<()> {
@@ -757,4 +1004,85 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn clear_ingested_children_keeps_entities_mentioned_elsewhere() -> anyhow::Result<()> {
+        let db = setup_test_db().await?;
+        let user_id = "clear-shared-user";
+        let source_a = Uuid::new_v4().to_string();
+        let source_b = Uuid::new_v4().to_string();
+
+        let shared = KnowledgeEntity::new(
+            source_a.clone(),
+            "Rust".to_string(),
+            "A language".to_string(),
+            KnowledgeEntityType::Idea,
+            None,
+            user_id.to_string(),
+        );
+        let only_a = KnowledgeEntity::new(
+            source_a.clone(),
+            "Only A".to_string(),
+            "Mentioned once".to_string(),
+            KnowledgeEntityType::Idea,
+            None,
+            user_id.to_string(),
+        );
+        KnowledgeEntity::store_with_embedding(shared.clone(), vec![0.1; 3], 3, &db).await?;
+        KnowledgeEntity::store_with_embedding(only_a.clone(), vec![0.2; 3], 3, &db).await?;
+        for (entity_id, source_id, name) in [
+            (&shared.id, &source_a, "Rust"),
+            (&only_a.id, &source_a, "Only A"),
+            (&shared.id, &source_b, "rust-lang"),
+        ] {
+            db.store_item(KnowledgeEntityMention::new(
+                entity_id.clone(),
+                source_id.clone(),
+                user_id.to_string(),
+                name.to_string(),
+            ))
+            .await?;
+        }
+
+        TextContent::clear_ingested_children(&source_a, user_id, &db).await?;
+
+        let survivor: Option<KnowledgeEntity> = db.get_item(&shared.id).await?;
+        assert_eq!(
+            survivor.map(|entity| entity.source_id),
+            Some(source_b.clone())
+        );
+        let removed: Option<KnowledgeEntity> = db.get_item(&only_a.id).await?;
+        assert!(removed.is_none());
+
+        let embedding: Option<KnowledgeEntityEmbedding> = db.get_item(&shared.id).await?;
+        assert_eq!(embedding.map(|row| row.source_id), Some(source_b));
+
+        let mentions = KnowledgeEntityMention::for_entity(&shared.id, user_id, &db).await?;
+        assert_eq!(mentions.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_source_label_prefers_url_title_then_context() {
+        let mut content = TextContent::new(
+            "First line\nSecond line".to_string(),
+            None,
+            "notes".to_string(),
+            None,
+            Some(UrlInfo {
+                url: "https://example.com".to_string(),
+                title: "Example Title".to_string(),
+                image_id: String::new(),
+            }),
+            "user123".to_string(),
+        );
+        assert_eq!(content.source_label(), "Example Title");
+
+        content.url_info = None;
+        content.context = Some("Meeting notes".to_string());
+        assert_eq!(content.source_label(), "Meeting notes");
+
+        content.context = None;
+        assert_eq!(content.source_label(), "First line");
+    }
 }
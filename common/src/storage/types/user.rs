@@ -1,4 +1,7 @@
-use crate::{error::AppError, storage::db::SurrealDbClient, stored_object};
+use crate::{
+    error::AppError, storage::db::SurrealDbClient, stored_object,
+    utils::prompt_template::validate_prompt,
+};
 use anyhow::anyhow;
 use async_trait::async_trait;
 use axum_session_auth::Authentication;
@@ -25,7 +28,7 @@ pub struct CategoryResponse {
     category: String,
 }
 
-use std::str::FromStr;
+use std::{collections::HashMap, str::FromStr};
 
 /// Supported UI themes.
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -88,7 +91,17 @@ stored_object!(
     #[serde(default)]
     timezone: String,
     #[serde(default, deserialize_with = "deserialize_theme_or_default")]
-    theme: Theme
+    theme: Theme,
+    /// Stage AI-suggested entities and relationships for review instead of adding them
+    /// to the graph straight away.
+    #[serde(default)]
+    review_all_suggestions: bool,
+    /// Categories whose AI suggestions are always staged for review.
+    #[serde(default)]
+    review_categories: Vec<String>,
+    /// Template replacing the admin query prompt in this user's chats.
+    #[serde(default)]
+    query_system_prompt: Option<String>
 });
 
 fn deserialize_theme_or_default<'de, D>(deserializer: D) -> Result<Theme, D::Error>
@@ -329,6 +342,31 @@ impl User {
         Ok(user)
     }
 
+    /// Email addresses of the users in `ids`, keyed by user id; unknown ids are left out.
+    pub async fn emails_by_id(
+        ids: &[String],
+        db: &SurrealDbClient,
+    ) -> Result<HashMap<String, String>, AppError> {
+        #[derive(Deserialize)]
+        struct Row {
+            id: String,
+            email: String,
+        }
+
+        let records: Vec<surrealdb::RecordId> = ids
+            .iter()
+            .map(|id| surrealdb::RecordId::from_table_key(Self::table_name(), id.as_str()))
+            .collect();
+        let rows: Vec<Row> = db
+            .client
+            .query("SELECT record::id(id) AS id, email FROM $records")
+            .bind(("records", records))
+            .await?
+            .take(0)?;
+
+        Ok(rows.into_iter().map(|row| (row.id, row.email)).collect())
+    }
+
     pub async fn find_by_api_key(
         api_key: &str,
         db: &SurrealDbClient,
@@ -441,10 +479,7 @@ impl User {
         // Extract the entity types from the response
         let entity_types: Vec<String> = response
             .into_iter()
-            .map(|item| {
-                let normalized = KnowledgeEntityType::from(item.entity_type);
-                format!("{normalized:?}")
-            })
+            .map(|item| KnowledgeEntityType::from(item.entity_type).to_string())
             .collect();
 
         Ok(entity_types)
@@ -553,6 +588,71 @@ impl User {
         Ok(())
     }
 
+    /// Whether AI-suggested entities and relationships from content in `category` need
+    /// the user's approval before they enter the graph.
+    #[must_use]
+    pub fn requires_suggestion_review(&self, category: &str) -> bool {
+        self.review_all_suggestions
+            || self
+                .review_categories
+                .iter()
+                .any(|reviewed| reviewed.eq_ignore_ascii_case(category.trim()))
+    }
+
+    pub async fn update_suggestion_review(
+        user_id: &str,
+        review_all: bool,
+        categories: &[String],
+        db: &SurrealDbClient,
+    ) -> Result<(), AppError> {
+        let mut review_categories: Vec<String> = Vec::new();
+        for category in categories.iter().map(|category| category.trim()) {
+            if !category.is_empty()
+                && !review_categories
+                    .iter()
+                    .any(|existing| existing.eq_ignore_ascii_case(category))
+            {
+                review_categories.push(category.to_string());
+            }
+        }
+
+        db.query(
+            "UPDATE type::thing('user', $user_id)
+             SET review_all_suggestions = $review_all, review_categories = $review_categories",
+        )
+        .bind(("user_id", user_id.to_string()))
+        .bind(("review_all", review_all))
+        .bind(("review_categories", review_categories))
+        .await?;
+        Ok(())
+    }
+
+    /// Query prompt template for this user's chats: their override, or the admin default.
+    #[must_use]
+    pub fn query_prompt_template<'a>(&'a self, settings: &'a SystemSettings) -> &'a str {
+        self.query_system_prompt
+            .as_deref()
+            .unwrap_or(&settings.query_system_prompt)
+    }
+
+    /// Sets the user's query prompt override; a blank prompt clears it.
+    pub async fn update_query_prompt(
+        user_id: &str,
+        prompt: &str,
+        db: &SurrealDbClient,
+    ) -> Result<Option<String>, AppError> {
+        let prompt = (!prompt.trim().is_empty()).then(|| prompt.to_string());
+        if let Some(prompt) = &prompt {
+            validate_prompt("query prompt", prompt)?;
+        }
+
+        db.query("UPDATE type::thing('user', $user_id) SET query_system_prompt = $prompt")
+            .bind(("user_id", user_id.to_string()))
+            .bind(("prompt", prompt.clone()))
+            .await?;
+        Ok(prompt)
+    }
+
     pub async fn get_user_categories(
         user_id: &str,
         db: &SurrealDbClient,
@@ -964,6 +1064,28 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_emails_by_id_skips_unknown_users() -> anyhow::Result<()> {
+        let db = setup_test_db().await?;
+
+        let user = User::create_new(
+            "emails@example.com".to_string(),
+            "password".to_string(),
+            &db,
+            "UTC".to_string(),
+            "system".to_string(),
+        )
+        .await?;
+
+        let emails = User::emails_by_id(&[user.id.clone(), "missing".to_string()], &db).await?;
+        assert_eq!(emails.len(), 1);
+        assert_eq!(
+            emails.get(&user.id).map(String::as_str),
+            Some("emails@example.com")
+        );
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_api_key_management() -> anyhow::Result<()> {
         // Setup test database
@@ -1300,4 +1422,85 @@ mod tests {
         assert_eq!(updated2.theme, Theme::System);
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_suggestion_review_settings() -> anyhow::Result<()> {
+        let db = setup_test_db().await?;
+        let user = User::create_new(
+            "review_test@example.com".to_string(),
+            "password".to_string(),
+            &db,
+            "UTC".to_string(),
+            "system".to_string(),
+        )
+        .await
+        .with_context(|| "Failed to create user".to_string())?;
+        assert!(!user.requires_suggestion_review("Legal"));
+
+        let categories = vec![
+            " Legal ".to_string(),
+            "legal".to_string(),
+            "HR".to_string(),
+            " ".to_string(),
+        ];
+        User::update_suggestion_review(&user.id, false, &categories, &db).await?;
+        let updated = db
+            .get_item::<User>(&user.id)
+            .await?
+            .with_context(|| "expected user".to_string())?;
+        assert_eq!(
+            updated.review_categories,
+            vec!["Legal".to_string(), "HR".to_string()]
+        );
+        assert!(updated.requires_suggestion_review("legal"));
+        assert!(!updated.requires_suggestion_review("Notes"));
+
+        User::update_suggestion_review(&user.id, true, &[], &db).await?;
+        let updated = db
+            .get_item::<User>(&user.id)
+            .await?
+            .with_context(|| "expected user".to_string())?;
+        assert!(updated.requires_suggestion_review("Notes"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_query_prompt_override() -> anyhow::Result<()> {
+        let db = setup_test_db().await?;
+        let user = User::create_new(
+            "prompt_test@example.com".to_string(),
+            "password".to_string(),
+            &db,
+            "UTC".to_string(),
+            "system".to_string(),
+        )
+        .await
+        .with_context(|| "Failed to create user".to_string())?;
+        let settings = SystemSettings::get_current(&db).await?;
+        assert_eq!(
+            user.query_prompt_template(&settings),
+            settings.query_system_prompt
+        );
+
+        let invalid = User::update_query_prompt(&user.id, "Hi {{ usr.timezone }}", &db).await;
+        assert!(matches!(invalid, Err(AppError::Validation(_))));
+
+        User::update_query_prompt(&user.id, "Answer in {{ user.timezone }}.", &db).await?;
+        let updated = db
+            .get_item::<User>(&user.id)
+            .await?
+            .with_context(|| "expected user".to_string())?;
+        assert_eq!(
+            updated.query_prompt_template(&settings),
+            "Answer in {{ user.timezone }}."
+        );
+
+        User::update_query_prompt(&user.id, "  ", &db).await?;
+        let cleared = db
+            .get_item::<User>(&user.id)
+            .await?
+            .with_context(|| "expected user".to_string())?;
+        assert_eq!(cleared.query_system_prompt, None);
+        Ok(())
+    }
 }
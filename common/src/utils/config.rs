@@ -50,6 +50,126 @@ impl FromStr for EmbeddingBackend {
     }
 }
 
+/// Error returned when parsing an LLM backend name.
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("unknown llm backend '{input}': expected 'openai', 'ollama', or 'anthropic'")]
+pub struct ParseLlmBackendError {
+    /// The unrecognized input string.
+    pub input: String,
+}
+
+/// Selects the chat-completion API a model role talks to.
+#[derive(Clone, Copy, Deserialize, Serialize, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum LlmBackend {
+    /// OpenAI or any OpenAI-compatible server at `openai_base_url` (default).
+    #[default]
+    OpenAI,
+    /// Native Ollama chat API at `ollama_base_url`.
+    Ollama,
+    /// Anthropic Messages API at `anthropic_base_url`.
+    Anthropic,
+}
+
+impl LlmBackend {
+    pub const ALL: [Self; 3] = [Self::OpenAI, Self::Ollama, Self::Anthropic];
+
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::OpenAI => "openai",
+            Self::Ollama => "ollama",
+            Self::Anthropic => "anthropic",
+        }
+    }
+}
+
+impl FromStr for LlmBackend {
+    type Err = ParseLlmBackendError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "openai" | "openai-compatible" => Ok(Self::OpenAI),
+            "ollama" => Ok(Self::Ollama),
+            "anthropic" | "claude" => Ok(Self::Anthropic),
+            other => Err(ParseLlmBackendError {
+                input: other.to_string(),
+            }),
+        }
+    }
+}
+
+/// How the OpenAI-compatible backend requests schema-constrained output.
+#[derive(Clone, Copy, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StructuredOutputMode {
+    /// `response_format: json_schema` (default).
+    #[default]
+    JsonSchema,
+    /// One forced tool call whose arguments carry the JSON, for servers without `json_schema`.
+    ToolCall,
+}
+
+/// Where deterministic LLM completions are cached.
+#[derive(Clone, Copy, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LlmCacheMode {
+    /// Every call goes to the provider (default).
+    #[default]
+    Off,
+    /// Entries live in the `llm_cache_entry` table.
+    Database,
+    /// Entries are JSON files under `llm_cache_dir`.
+    Disk,
+}
+
+/// Error returned when parsing a duplicate ingestion policy name.
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("unknown duplicate policy '{input}': expected 'skip', 'link', or 'force'")]
+pub struct ParseDuplicatePolicyError {
+    /// The unrecognized input string.
+    pub input: String,
+}
+
+/// How ingestion treats text and URL payloads that were already submitted.
+#[derive(Clone, Copy, Deserialize, Serialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicatePolicy {
+    /// Do not queue the payload; report the existing task instead.
+    Skip,
+    /// Do not queue the payload; hand back the existing task so callers can follow it (default).
+    #[default]
+    Link,
+    /// Always queue a new task, even for identical content.
+    Force,
+}
+
+impl DuplicatePolicy {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Skip => "skip",
+            Self::Link => "link",
+            Self::Force => "force",
+        }
+    }
+}
+
+impl FromStr for DuplicatePolicy {
+    type Err = ParseDuplicatePolicyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "skip" => Ok(Self::Skip),
+            "link" => Ok(Self::Link),
+            "force" => Ok(Self::Force),
+            other => Err(ParseDuplicatePolicyError {
+                input: other.to_string(),
+            }),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Deserialize, Debug, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum StorageKind {
@@ -86,6 +206,8 @@ fn default_pdf_ingest_mode() -> PdfIngestMode {
 #[allow(clippy::module_name_repetitions)]
 #[derive(Clone, Deserialize, Debug)]
 pub struct AppConfig {
+    /// Required unless `llm_mock` is set.
+    #[serde(default)]
     pub openai_api_key: String,
     pub surrealdb_address: String,
     pub surrealdb_username: String,
@@ -97,6 +219,33 @@ pub struct AppConfig {
     pub http_port: u16,
     #[serde(default = "default_base_url")]
     pub openai_base_url: String,
+    /// Structured-output strategy of the OpenAI-compatible backend.
+    #[serde(default)]
+    pub openai_structured_output: StructuredOutputMode,
+    #[serde(default = "default_ollama_base_url")]
+    pub ollama_base_url: String,
+    #[serde(default)]
+    pub anthropic_api_key: String,
+    #[serde(default = "default_anthropic_base_url")]
+    pub anthropic_base_url: String,
+    /// Output token cap sent with every Anthropic request (the API requires one).
+    #[serde(default = "default_anthropic_max_tokens")]
+    pub anthropic_max_tokens: u32,
+    /// Seconds one chat request may take before the next fallback route is tried (`0` disables).
+    #[serde(default = "default_llm_request_timeout_secs")]
+    pub llm_request_timeout_secs: u64,
+    /// Cache for ingestion analysis and vision completions of identical requests.
+    #[serde(default)]
+    pub llm_cache: LlmCacheMode,
+    /// Directory of the disk cache; defaults to `llm_cache` under `data_dir`.
+    #[serde(default)]
+    pub llm_cache_dir: Option<String>,
+    /// Seconds a cached completion stays valid (`0` keeps entries until purged).
+    #[serde(default = "default_llm_cache_ttl_secs")]
+    pub llm_cache_ttl_secs: u64,
+    /// Answers every LLM call with the offline mock provider instead of the configured backends.
+    #[serde(default)]
+    pub llm_mock: bool,
     #[serde(default = "default_storage_kind")]
     pub storage: StorageKind,
     #[serde(default)]
@@ -111,6 +260,10 @@ pub struct AppConfig {
     pub reranking_enabled: bool,
     #[serde(default)]
     pub reranking_pool_size: Option<usize>,
+    /// Follow knowledge graph relationships from the best entities during chat and search
+    /// retrieval, pulling in neighbor entities and their chunks.
+    #[serde(default)]
+    pub retrieval_graph_expansion: bool,
     #[serde(default)]
     pub fastembed_cache_dir: Option<String>,
     #[serde(default)]
@@ -135,6 +288,18 @@ pub struct AppConfig {
     pub ingest_max_context_bytes: usize,
     #[serde(default = "default_ingest_max_category_bytes")]
     pub ingest_max_category_bytes: usize,
+    /// Handling of repeated text/URL submissions; callers may override per request.
+    #[serde(default)]
+    pub ingest_duplicate_policy: DuplicatePolicy,
+    /// Ingestion tasks a single worker process runs at the same time.
+    #[serde(default = "default_ingest_worker_concurrency")]
+    pub ingest_worker_concurrency: usize,
+    /// Tasks allowed inside the LLM enrichment call at once, across a worker's tasks.
+    #[serde(default = "default_ingest_llm_concurrency")]
+    pub ingest_llm_concurrency: usize,
+    /// Tasks allowed inside CPU-heavy stages (content extraction, embedding) at once.
+    #[serde(default = "default_ingest_cpu_concurrency")]
+    pub ingest_cpu_concurrency: usize,
     /// Seconds between scheduled `REBUILD INDEX` maintainer runs (`0` disables).
     #[serde(default = "default_index_rebuild_interval_secs")]
     pub index_rebuild_interval_secs: u64,
@@ -150,6 +315,29 @@ fn default_base_url() -> String {
     "https://api.openai.com/v1".to_string()
 }
 
+/// Default address of a local Ollama server.
+fn default_ollama_base_url() -> String {
+    "http://localhost:11434".to_string()
+}
+
+fn default_anthropic_base_url() -> String {
+    "https://api.anthropic.com".to_string()
+}
+
+fn default_anthropic_max_tokens() -> u32 {
+    8192
+}
+
+/// Per-route LLM request budget; `0` disables the timeout.
+fn default_llm_request_timeout_secs() -> u64 {
+    300
+}
+
+/// Cached completions are reused for 30 days.
+fn default_llm_cache_ttl_secs() -> u64 {
+    2_592_000
+}
+
 /// Whether reranking is enabled by default.
 fn default_reranking_enabled() -> bool {
     false
@@ -175,6 +363,19 @@ fn default_ingest_max_category_bytes() -> usize {
     128
 }
 
+fn default_ingest_worker_concurrency() -> usize {
+    1
+}
+
+fn default_ingest_llm_concurrency() -> usize {
+    4
+}
+
+/// One CPU-bound stage per available core.
+fn default_ingest_cpu_concurrency() -> usize {
+    std::thread::available_parallelism().map_or(2, std::num::NonZeroUsize::get)
+}
+
 fn default_index_rebuild_interval_secs() -> u64 {
     86_400
 }
@@ -233,6 +434,16 @@ impl Default for AppConfig {
             data_dir: default_data_dir(),
             http_port: 0,
             openai_base_url: default_base_url(),
+            openai_structured_output: StructuredOutputMode::default(),
+            ollama_base_url: default_ollama_base_url(),
+            anthropic_api_key: String::new(),
+            anthropic_base_url: default_anthropic_base_url(),
+            anthropic_max_tokens: default_anthropic_max_tokens(),
+            llm_request_timeout_secs: default_llm_request_timeout_secs(),
+            llm_cache: LlmCacheMode::default(),
+            llm_cache_dir: None,
+            llm_cache_ttl_secs: default_llm_cache_ttl_secs(),
+            llm_mock: false,
             storage: default_storage_kind(),
             s3_bucket: None,
             s3_endpoint: None,
@@ -240,6 +451,7 @@ impl Default for AppConfig {
             pdf_ingest_mode: default_pdf_ingest_mode(),
             reranking_enabled: default_reranking_enabled(),
             reranking_pool_size: None,
+            retrieval_graph_expansion: false,
             fastembed_cache_dir: None,
             fastembed_show_download_progress: None,
             fastembed_max_length: None,
@@ -251,6 +463,10 @@ impl Default for AppConfig {
             ingest_max_content_bytes: default_ingest_max_content_bytes(),
             ingest_max_context_bytes: default_ingest_max_context_bytes(),
             ingest_max_category_bytes: default_ingest_max_category_bytes(),
+            ingest_duplicate_policy: DuplicatePolicy::default(),
+            ingest_worker_concurrency: default_ingest_worker_concurrency(),
+            ingest_llm_concurrency: default_ingest_llm_concurrency(),
+            ingest_cpu_concurrency: default_ingest_cpu_concurrency(),
             index_rebuild_interval_secs: default_index_rebuild_interval_secs(),
         }
     }
@@ -273,7 +489,7 @@ pub fn get_config() -> Result<AppConfig, ConfigError> {
 mod tests {
     #![allow(clippy::expect_used)]
 
-    use super::EmbeddingBackend;
+    use super::{DuplicatePolicy, EmbeddingBackend, LlmBackend};
 
     #[test]
     fn embedding_backend_defaults_to_fastembed() {
@@ -291,4 +507,26 @@ mod tests {
             EmbeddingBackend::FastEmbed
         );
     }
+
+    #[test]
+    fn duplicate_policy_defaults_to_link_and_parses() {
+        assert_eq!(DuplicatePolicy::default(), DuplicatePolicy::Link);
+        assert_eq!(
+            "Force".parse::<DuplicatePolicy>().expect("force"),
+            DuplicatePolicy::Force
+        );
+        assert!("ignore".parse::<DuplicatePolicy>().is_err());
+    }
+
+    #[test]
+    fn llm_backend_defaults_to_openai_and_round_trips() {
+        assert_eq!(LlmBackend::default(), LlmBackend::OpenAI);
+        for backend in LlmBackend::ALL {
+            assert_eq!(
+                backend.as_str().parse::<LlmBackend>().expect("parse"),
+                backend
+            );
+        }
+        assert!("bedrock".parse::<LlmBackend>().is_err());
+    }
 }
@@ -0,0 +1,368 @@
+//! Anthropic Messages API. Structured output is requested as a single forced tool call,
+//! since the API has no `json_schema` response format.
+
+use async_trait::async_trait;
+use futures::{StreamExt, future};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use super::{
+    ChatMessage, ChatRole, Completion, CompletionRequest, LlmProvider, ProviderStream, StreamChunk,
+    TokenUsage, ensure_success, response_lines, system_prompt,
+};
+use crate::{error::LlmError, utils::config::LlmBackend};
+
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Chat completions against the Anthropic Messages API.
+#[allow(clippy::module_name_repetitions)]
+pub struct AnthropicProvider {
+    http: reqwest::Client,
+    messages_url: String,
+    api_key: String,
+    max_tokens: u32,
+}
+
+#[derive(Serialize)]
+struct MessagesRequest {
+    model: String,
+    max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<String>,
+    messages: Vec<AnthropicMessage>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<Value>,
+    stream: bool,
+}
+
+#[derive(Serialize)]
+struct AnthropicMessage {
+    role: &'static str,
+    content: Vec<Value>,
+}
+
+#[derive(Deserialize)]
+struct MessagesResponse {
+    #[serde(default)]
+    content: Vec<ContentBlock>,
+    #[serde(default)]
+    usage: Option<AnthropicUsage>,
+}
+
+#[derive(Deserialize, Default)]
+struct AnthropicUsage {
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+}
+
+#[derive(Deserialize)]
+struct StreamMessage {
+    #[serde(default)]
+    usage: AnthropicUsage,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ContentBlock {
+    Text {
+        text: String,
+    },
+    ToolUse {
+        input: Value,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamEvent {
+    MessageStart {
+        message: StreamMessage,
+    },
+    MessageDelta {
+        #[serde(default)]
+        usage: AnthropicUsage,
+    },
+    ContentBlockDelta {
+        delta: StreamDelta,
+    },
+    Error {
+        error: Value,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamDelta {
+    TextDelta {
+        text: String,
+    },
+    InputJsonDelta {
+        partial_json: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+impl AnthropicProvider {
+    pub fn new(http: reqwest::Client, base_url: &str, api_key: &str, max_tokens: u32) -> Self {
+        Self {
+            http,
+            messages_url: format!("{}/v1/messages", base_url.trim_end_matches('/')),
+            api_key: api_key.to_string(),
+            max_tokens,
+        }
+    }
+
+    async fn send(
+        &self,
+        request: CompletionRequest,
+        stream: bool,
+    ) -> Result<reqwest::Response, LlmError> {
+        if self.api_key.is_empty() {
+            return Err(LlmError::Unsupported(
+                "anthropic_api_key is not configured".into(),
+            ));
+        }
+        let response = self
+            .http
+            .post(&self.messages_url)
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(&messages_body(request, self.max_tokens, stream))
+            .send()
+            .await?;
+        ensure_success(LlmBackend::Anthropic, response).await
+    }
+}
+
+#[async_trait]
+impl LlmProvider for AnthropicProvider {
+    fn backend(&self) -> LlmBackend {
+        LlmBackend::Anthropic
+    }
+
+    async fn complete(&self, request: CompletionRequest) -> Result<Completion, LlmError> {
+        let response: MessagesResponse = self.send(request, false).await?.json().await?;
+        let usage = response.usage.as_ref().map(|usage| TokenUsage {
+            prompt_tokens: usage.input_tokens,
+            completion_tokens: usage.output_tokens,
+        });
+        Ok(Completion {
+            content: response_text(response),
+            usage,
+        })
+    }
+
+    async fn stream(&self, request: CompletionRequest) -> Result<ProviderStream, LlmError> {
+        let response = self.send(request, true).await?;
+
+        // Input tokens arrive with `message_start`, output tokens with `message_delta`.
+        Ok(response_lines(response)
+            .scan(0_u64, |input_tokens, line| {
+                let item = match line {
+                    Ok(line) => line
+                        .strip_prefix("data:")
+                        .and_then(|data| stream_event(data.trim(), input_tokens).transpose()),
+                    Err(err) => Some(Err(err)),
+                };
+                future::ready(Some(item))
+            })
+            .filter_map(future::ready)
+            .boxed())
+    }
+}
+
+fn messages_body(request: CompletionRequest, max_tokens: u32, stream: bool) -> MessagesRequest {
+    let system = system_prompt(&request.messages);
+    let messages = request
+        .messages
+        .into_iter()
+        .filter(|message| message.role != ChatRole::System)
+        .map(to_anthropic_message)
+        .collect();
+
+    let (tools, tool_choice) = match request.structured_output {
+        Some(output) => (
+            vec![json!({
+                "name": output.name,
+                "description": output.description,
+                "input_schema": output.schema,
+            })],
+            Some(json!({ "type": "tool", "name": output.name })),
+        ),
+        None => (Vec::new(), None),
+    };
+
+    MessagesRequest {
+        model: request.model,
+        max_tokens,
+        system,
+        messages,
+        tools,
+        tool_choice,
+        stream,
+    }
+}
+
+fn to_anthropic_message(message: ChatMessage) -> AnthropicMessage {
+    let mut content: Vec<Value> = message
+        .images
+        .into_iter()
+        .map(|image| {
+            json!({
+                "type": "image",
+                "source": { "type": "base64", "media_type": "image/png", "data": image },
+            })
+        })
+        .collect();
+    content.push(json!({ "type": "text", "text": message.content }));
+
+    AnthropicMessage {
+        role: if message.role == ChatRole::Assistant {
+            "assistant"
+        } else {
+            "user"
+        },
+        content,
+    }
+}
+
+/// The forced tool's input when present, otherwise the concatenated text blocks.
+fn response_text(response: MessagesResponse) -> String {
+    let mut text = String::new();
+    for block in response.content {
+        match block {
+            ContentBlock::ToolUse { input } => return input.to_string(),
+            ContentBlock::Text { text: part } => text.push_str(&part),
+            ContentBlock::Other => {}
+        }
+    }
+    text
+}
+
+fn stream_event(data: &str, input_tokens: &mut u64) -> Result<Option<StreamChunk>, LlmError> {
+    let event: StreamEvent = serde_json::from_str(data)
+        .map_err(|e| LlmError::Response(format!("invalid anthropic stream event: {e}")))?;
+    match event {
+        StreamEvent::MessageStart { message } => {
+            *input_tokens = message.usage.input_tokens;
+            Ok(None)
+        }
+        StreamEvent::MessageDelta { usage } => Ok(Some(StreamChunk::Usage(TokenUsage {
+            prompt_tokens: *input_tokens,
+            completion_tokens: usage.output_tokens,
+        }))),
+        StreamEvent::ContentBlockDelta {
+            delta: StreamDelta::TextDelta { text },
+        } => Ok(Some(StreamChunk::Text(text))),
+        StreamEvent::ContentBlockDelta {
+            delta: StreamDelta::InputJsonDelta { partial_json },
+        } => Ok(Some(StreamChunk::Text(partial_json))),
+        StreamEvent::Error { error } => Err(LlmError::Response(error.to_string())),
+        StreamEvent::ContentBlockDelta {
+            delta: StreamDelta::Other,
+        }
+        | StreamEvent::Other => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used)]
+
+    use super::*;
+    use crate::utils::llm::StructuredOutput;
+
+    #[test]
+    fn structured_requests_force_the_schema_tool_and_lift_the_system_prompt() {
+        let request = CompletionRequest {
+            model: "claude-sonnet".into(),
+            messages: vec![ChatMessage::system("sys"), ChatMessage::user("hi")],
+            structured_output: Some(StructuredOutput::new(
+                "answer",
+                "An answer",
+                json!({"type": "object"}),
+            )),
+        };
+
+        let body = serde_json::to_value(messages_body(request, 1024, false)).expect("serialize");
+
+        assert_eq!(body.pointer("/system"), Some(&json!("sys")));
+        assert_eq!(
+            body.pointer("/messages")
+                .and_then(Value::as_array)
+                .map(Vec::len),
+            Some(1)
+        );
+        assert_eq!(body.pointer("/tool_choice/name"), Some(&json!("answer")));
+        assert_eq!(
+            body.pointer("/tools/0/input_schema"),
+            Some(&json!({"type": "object"}))
+        );
+    }
+
+    #[test]
+    fn tool_input_and_stream_deltas_become_text() {
+        let response: MessagesResponse = serde_json::from_value(json!({
+            "content": [
+                {"type": "text", "text": "ignored"},
+                {"type": "tool_use", "id": "t1", "name": "answer", "input": {"answer": "42"}},
+            ]
+        }))
+        .expect("response");
+        assert_eq!(response_text(response), r#"{"answer":"42"}"#);
+
+        let mut input_tokens = 0;
+        let delta = r#"{"type":"content_block_delta","index":0,"delta":{"type":"input_json_delta","partial_json":"{\"ans"}}"#;
+        assert_eq!(
+            stream_event(delta, &mut input_tokens).expect("event"),
+            Some(StreamChunk::Text(r#"{"ans"#.into()))
+        );
+        assert_eq!(
+            stream_event(r#"{"type":"message_stop"}"#, &mut input_tokens).expect("event"),
+            None
+        );
+        assert!(
+            stream_event(
+                r#"{"type":"error","error":{"type":"overloaded_error"}}"#,
+                &mut input_tokens
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn usage_is_read_from_responses_and_stream_events() {
+        let response: MessagesResponse = serde_json::from_value(json!({
+            "content": [{"type": "text", "text": "hi"}],
+            "usage": {"input_tokens": 25, "output_tokens": 4},
+        }))
+        .expect("response");
+        assert_eq!(
+            response
+                .usage
+                .map(|usage| (usage.input_tokens, usage.output_tokens)),
+            Some((25, 4))
+        );
+
+        let mut input_tokens = 0;
+        let start = r#"{"type":"message_start","message":{"id":"m1","usage":{"input_tokens":25,"output_tokens":1}}}"#;
+        assert_eq!(stream_event(start, &mut input_tokens).expect("event"), None);
+        let delta = r#"{"type":"message_delta","delta":{"stop_reason":"end_turn"},"usage":{"output_tokens":15}}"#;
+        assert_eq!(
+            stream_event(delta, &mut input_tokens).expect("event"),
+            Some(StreamChunk::Usage(TokenUsage {
+                prompt_tokens: 25,
+                completion_tokens: 15,
+            }))
+        );
+    }
+}
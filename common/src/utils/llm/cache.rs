@@ -0,0 +1,299 @@
+//! Content-addressed cache of deterministic completions.
+
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+use uuid::Uuid;
+
+use super::{ChatMessage, StructuredOutput};
+use crate::{
+    error::AppError,
+    storage::{
+        db::SurrealDbClient,
+        types::{llm_cache_entry::LlmCacheEntry, system_settings::ModelRoute},
+    },
+    utils::config::{AppConfig, LlmBackend, LlmCacheMode},
+};
+
+/// Completions keyed by a digest of the route, messages and schema that produced them.
+///
+/// Store errors are logged and treated as misses, so the cache never fails a request.
+pub struct ResponseCache {
+    store: CacheStore,
+    ttl: Option<TimeDelta>,
+}
+
+enum CacheStore {
+    Database(Arc<SurrealDbClient>),
+    Disk(PathBuf),
+}
+
+/// Everything that determines a completion; its JSON digest is the cache key.
+#[derive(Serialize)]
+struct CacheKey<'a> {
+    backend: LlmBackend,
+    base_url: Option<&'a str>,
+    model: &'a str,
+    messages: &'a [ChatMessage],
+    structured_output: Option<&'a StructuredOutput>,
+}
+
+/// A cached completion on disk.
+#[derive(Serialize, Deserialize)]
+struct DiskEntry {
+    model: String,
+    content: String,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+impl ResponseCache {
+    /// The cache selected by `llm_cache`, or `None` when caching is off.
+    pub fn from_config(config: &AppConfig, db: Arc<SurrealDbClient>) -> Option<Self> {
+        let ttl = (config.llm_cache_ttl_secs > 0)
+            .then(|| i64::try_from(config.llm_cache_ttl_secs).ok())
+            .flatten()
+            .and_then(TimeDelta::try_seconds);
+        match config.llm_cache {
+            LlmCacheMode::Off => None,
+            LlmCacheMode::Database => Some(Self::in_database(db, ttl)),
+            LlmCacheMode::Disk => {
+                let dir = config.llm_cache_dir.as_ref().map_or_else(
+                    || Path::new(&config.data_dir).join("llm_cache"),
+                    PathBuf::from,
+                );
+                Some(Self::on_disk(dir, ttl))
+            }
+        }
+    }
+
+    /// Keeps entries in the `llm_cache_entry` table; `ttl` of `None` never expires them.
+    #[must_use]
+    pub fn in_database(db: Arc<SurrealDbClient>, ttl: Option<TimeDelta>) -> Self {
+        Self {
+            store: CacheStore::Database(db),
+            ttl,
+        }
+    }
+
+    /// Keeps entries as JSON files below `dir`; `ttl` of `None` never expires them.
+    #[must_use]
+    pub fn on_disk(dir: impl Into<PathBuf>, ttl: Option<TimeDelta>) -> Self {
+        Self {
+            store: CacheStore::Disk(dir.into()),
+            ttl,
+        }
+    }
+
+    /// Cached content of the request, if `route` answered it before and it has not expired.
+    pub async fn get(
+        &self,
+        route: &ModelRoute,
+        messages: &[ChatMessage],
+        structured_output: Option<&StructuredOutput>,
+    ) -> Option<String> {
+        let key = cache_key(route, messages, structured_output)?;
+        let found = match &self.store {
+            CacheStore::Database(db) => LlmCacheEntry::get_fresh(&key, db)
+                .await
+                .map(|entry| entry.map(|entry| entry.content)),
+            CacheStore::Disk(dir) => read_entry(&entry_path(dir, &key)).await,
+        };
+        found
+            .inspect_err(|err| warn!(error = %err, %route, "LLM cache lookup failed"))
+            .ok()
+            .flatten()
+    }
+
+    /// Stores `content` as the answer of `route` to the request.
+    pub async fn put(
+        &self,
+        route: &ModelRoute,
+        messages: &[ChatMessage],
+        structured_output: Option<&StructuredOutput>,
+        content: &str,
+    ) {
+        let Some(key) = cache_key(route, messages, structured_output) else {
+            return;
+        };
+        let expires_at = self.ttl.and_then(|ttl| Utc::now().checked_add_signed(ttl));
+        let stored = match &self.store {
+            CacheStore::Database(db) => db
+                .upsert_item(LlmCacheEntry::new(&key, &route.model, content, expires_at))
+                .await
+                .map(|_| ())
+                .map_err(AppError::from),
+            CacheStore::Disk(dir) => {
+                let entry = DiskEntry {
+                    model: route.model.clone(),
+                    content: content.to_string(),
+                    expires_at,
+                };
+                write_entry(&entry_path(dir, &key), &entry).await
+            }
+        };
+        if let Err(err) = stored {
+            warn!(error = %err, %route, "failed to cache LLM response");
+        }
+    }
+
+    /// Removes every entry, returning how many there were.
+    pub async fn purge(&self) -> Result<u64, AppError> {
+        match &self.store {
+            CacheStore::Database(db) => LlmCacheEntry::purge_all(db).await,
+            CacheStore::Disk(dir) => purge_dir(dir).await,
+        }
+    }
+}
+
+fn cache_key(
+    route: &ModelRoute,
+    messages: &[ChatMessage],
+    structured_output: Option<&StructuredOutput>,
+) -> Option<String> {
+    let request = CacheKey {
+        backend: route.backend,
+        base_url: route.base_url.as_deref(),
+        model: &route.model,
+        messages,
+        structured_output,
+    };
+    let encoded = serde_json::to_vec(&request).ok()?;
+    Some(format!("{:x}", Sha256::digest(&encoded)))
+}
+
+/// Entries are sharded by the first two hex digits of their key.
+fn entry_path(dir: &Path, key: &str) -> PathBuf {
+    dir.join(key.get(..2).unwrap_or(key))
+        .join(format!("{key}.json"))
+}
+
+async fn read_entry(path: &Path) -> Result<Option<String>, AppError> {
+    let bytes = match tokio::fs::read(path).await {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let entry: DiskEntry = serde_json::from_slice(&bytes).map_err(AppError::internal)?;
+    let expired = entry
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= Utc::now());
+    Ok((!expired).then_some(entry.content))
+}
+
+/// Writes through a temporary file so readers never see a partial entry.
+async fn write_entry(path: &Path, entry: &DiskEntry) -> Result<(), AppError> {
+    let Some(parent) = path.parent() else {
+        return Err(AppError::InternalError(
+            "cache entry without a directory".into(),
+        ));
+    };
+    tokio::fs::create_dir_all(parent).await?;
+    let temporary = parent.join(format!(".{}.tmp", Uuid::new_v4()));
+    let encoded = serde_json::to_vec(entry).map_err(AppError::internal)?;
+    tokio::fs::write(&temporary, encoded).await?;
+    tokio::fs::rename(&temporary, path).await?;
+    Ok(())
+}
+
+async fn purge_dir(dir: &Path) -> Result<u64, AppError> {
+    let mut shards = match tokio::fs::read_dir(dir).await {
+        Ok(shards) => shards,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(0),
+        Err(err) => return Err(err.into()),
+    };
+    let mut removed: u64 = 0;
+    while let Some(shard) = shards.next_entry().await? {
+        if !shard.file_type().await?.is_dir() {
+            continue;
+        }
+        let mut entries = tokio::fs::read_dir(shard.path()).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "json")
+            {
+                tokio::fs::remove_file(&path).await?;
+                removed = removed.saturating_add(1);
+            }
+        }
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used)]
+
+    use serde_json::json;
+
+    use super::*;
+
+    fn request() -> (Vec<ChatMessage>, StructuredOutput) {
+        (
+            vec![
+                ChatMessage::system("Extract entities."),
+                ChatMessage::user("Minne keeps notes."),
+            ],
+            StructuredOutput::new("analysis", "Entities", json!({"type": "object"})),
+        )
+    }
+
+    async fn assert_round_trip(cache: &ResponseCache) {
+        let route = ModelRoute::new(LlmBackend::OpenAI, "gpt-4o-mini");
+        let other = ModelRoute::new(LlmBackend::Ollama, "gpt-4o-mini");
+        let (messages, schema) = request();
+
+        assert_eq!(cache.get(&route, &messages, Some(&schema)).await, None);
+        cache
+            .put(&route, &messages, Some(&schema), r#"{"entities":[]}"#)
+            .await;
+        assert_eq!(
+            cache.get(&route, &messages, Some(&schema)).await.as_deref(),
+            Some(r#"{"entities":[]}"#)
+        );
+        assert_eq!(
+            cache.get(&route, &messages, None).await,
+            None,
+            "schema is keyed"
+        );
+        assert_eq!(
+            cache.get(&other, &messages, Some(&schema)).await,
+            None,
+            "backend is keyed"
+        );
+
+        assert_eq!(cache.purge().await.expect("purge"), 1);
+        assert_eq!(cache.get(&route, &messages, Some(&schema)).await, None);
+    }
+
+    #[tokio::test]
+    async fn database_cache_hits_identical_requests_until_purged() {
+        let db = Arc::new(crate::test_utils::setup_test_db().await.expect("test db"));
+        assert_round_trip(&ResponseCache::in_database(db, TimeDelta::try_hours(1))).await;
+    }
+
+    #[tokio::test]
+    async fn disk_cache_hits_identical_requests_until_purged() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        assert_round_trip(&ResponseCache::on_disk(dir.path(), None)).await;
+    }
+
+    #[tokio::test]
+    async fn expired_entries_are_missed() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let cache = ResponseCache::on_disk(dir.path(), TimeDelta::try_seconds(-1));
+        let route = ModelRoute::new(LlmBackend::OpenAI, "gpt-4o-mini");
+        let (messages, _) = request();
+
+        cache.put(&route, &messages, None, "stale").await;
+        assert_eq!(cache.get(&route, &messages, None).await, None);
+    }
+}
@@ -0,0 +1,484 @@
+//! Offline provider giving deterministic, schema-valid answers without any API.
+//!
+//! Together with the hashed embedding backend it lets the server and worker run end to
+//! end without network access or API keys, in development and in integration tests.
+
+use std::path::Path;
+
+use async_trait::async_trait;
+use futures::{StreamExt, stream};
+use serde_json::{Map, Value, json};
+
+use super::{
+    ChatMessage, ChatRole, Completion, CompletionRequest, LlmProvider, ProviderStream, StreamChunk,
+    TokenUsage,
+};
+use crate::{error::LlmError, utils::config::LlmBackend};
+
+/// Longest excerpt of the prompt quoted in generated text.
+const EXCERPT_CHARS: usize = 120;
+/// Entities extracted from one document.
+const MAX_ENTITIES: usize = 3;
+/// Context entries an answer references.
+const MAX_REFERENCES: usize = 3;
+/// Characters per streamed chunk.
+const STREAM_CHUNK_CHARS: usize = 24;
+
+/// Answers every model role from the request alone.
+///
+/// Ingestion analysis names entities after capitalized words of the content and links
+/// them in order; chat answers quote the first context entry and reference the ids of
+/// the supplied context; any other schema is filled with an excerpt of the prompt.
+/// Images are described by their size and audio files by their name.
+#[allow(clippy::module_name_repetitions)]
+pub struct MockProvider;
+
+#[async_trait]
+impl LlmProvider for MockProvider {
+    /// Reported as OpenAI-compatible, the one backend that serves every role.
+    fn backend(&self) -> LlmBackend {
+        LlmBackend::OpenAI
+    }
+
+    async fn complete(&self, request: CompletionRequest) -> Result<Completion, LlmError> {
+        let content = respond(&request);
+        let prompt_chars = request
+            .messages
+            .iter()
+            .map(|message| message.content.chars().count())
+            .sum();
+        Ok(Completion {
+            usage: Some(TokenUsage {
+                prompt_tokens: estimate_tokens(prompt_chars),
+                completion_tokens: estimate_tokens(content.chars().count()),
+            }),
+            content,
+        })
+    }
+
+    async fn stream(&self, request: CompletionRequest) -> Result<ProviderStream, LlmError> {
+        let completion = self.complete(request).await?;
+        let characters: Vec<char> = completion.content.chars().collect();
+        let mut chunks: Vec<Result<StreamChunk, LlmError>> = characters
+            .chunks(STREAM_CHUNK_CHARS)
+            .map(|chunk| Ok(StreamChunk::Text(chunk.iter().collect())))
+            .collect();
+        if let Some(usage) = completion.usage {
+            chunks.push(Ok(StreamChunk::Usage(usage)));
+        }
+        Ok(stream::iter(chunks).boxed())
+    }
+
+    async fn transcribe(&self, _model: &str, file_path: &str) -> Result<String, LlmError> {
+        let metadata = tokio::fs::metadata(file_path)
+            .await
+            .map_err(|err| LlmError::Response(format!("cannot read audio file: {err}")))?;
+        let name = Path::new(file_path)
+            .file_name()
+            .map_or_else(|| file_path.into(), |name| name.to_string_lossy());
+        Ok(format!(
+            "Offline transcription of {name} ({} bytes).",
+            metadata.len()
+        ))
+    }
+}
+
+fn respond(request: &CompletionRequest) -> String {
+    let prompt = request
+        .messages
+        .iter()
+        .rev()
+        .find(|message| message.role == ChatRole::User)
+        .map_or("", |message| message.content.as_str());
+
+    if let Some(output) = &request.structured_output {
+        let properties = output.schema.get("properties");
+        let has = |name: &str| properties.and_then(|props| props.get(name)).is_some();
+        let value = if has("knowledge_entities") {
+            analysis(prompt, &output.schema)
+        } else if has("answer") && has("references") {
+            chat_answer(prompt)
+        } else {
+            sample_value(&output.schema, &excerpt(content_section(prompt)))
+        };
+        return value.to_string();
+    }
+
+    let images: Vec<&String> = request
+        .messages
+        .iter()
+        .flat_map(|message: &ChatMessage| &message.images)
+        .collect();
+    if images.is_empty() {
+        return format!("Offline response to: {}", excerpt(prompt));
+    }
+    images
+        .iter()
+        .enumerate()
+        .map(|(index, image)| {
+            format!(
+                "Offline description of image {}: a picture of {} encoded bytes.",
+                index.saturating_add(1),
+                image.len()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Entities named after the content's capitalized words, each related to the next.
+fn analysis(prompt: &str, schema: &Value) -> Value {
+    let text = content_section(prompt);
+    let entity_type = first_enum_value(
+        schema,
+        "/properties/knowledge_entities/items/properties/entity_type/enum",
+    )
+    .unwrap_or_else(|| "Document".into());
+    let relationship_type = first_enum_value(
+        schema,
+        "/properties/relationships/items/properties/type/enum",
+    );
+
+    let names = entity_names(text);
+    let entities: Vec<Value> = names
+        .iter()
+        .enumerate()
+        .map(|(index, name)| {
+            json!({
+                "key": format!("entity-{index}"),
+                "name": name,
+                "description": format!("{name}, as mentioned in: {}", excerpt(text)),
+                "entity_type": entity_type,
+                "tags": [],
+                "attributes": [],
+            })
+        })
+        .collect();
+    let relationships: Vec<Value> = relationship_type
+        .map(|kind| {
+            (1..names.len())
+                .map(|index| {
+                    json!({
+                        "type": kind,
+                        "source": format!("entity-{}", index.saturating_sub(1)),
+                        "target": format!("entity-{index}"),
+                        "weight": 0.5,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    json!({
+        "knowledge_entities": entities,
+        "relationships": relationships,
+        "tags": [],
+        "action_items": [],
+    })
+}
+
+/// An answer quoting the first context entry and referencing the supplied context ids.
+fn chat_answer(prompt: &str) -> Value {
+    let question = section_after(prompt, "User Question:").unwrap_or(prompt);
+    let context = section_after(prompt, "Context Information:")
+        .and_then(|section| section.find('[').and_then(|start| section.get(start..)))
+        .and_then(|json| {
+            serde_json::Deserializer::from_str(json)
+                .into_iter::<Vec<Value>>()
+                .next()
+                .and_then(Result::ok)
+        })
+        .unwrap_or_default();
+
+    let references: Vec<Value> = context
+        .iter()
+        .filter_map(|entry| entry.get("id").and_then(Value::as_str))
+        .take(MAX_REFERENCES)
+        .map(|id| json!({ "reference": id }))
+        .collect();
+    let answer = match context
+        .first()
+        .and_then(|entry| entry.get("content"))
+        .and_then(Value::as_str)
+    {
+        Some(first_entry) => format!(
+            "Offline answer to \"{}\", based on {} context entries: {}",
+            excerpt(question),
+            context.len(),
+            excerpt(first_entry)
+        ),
+        None => format!(
+            "Offline answer to \"{}\": nothing relevant was found in your knowledge base.",
+            excerpt(question)
+        ),
+    };
+
+    json!({ "answer": answer, "references": references })
+}
+
+/// A value of the shape `schema` describes, with every string set to `text`.
+fn sample_value(schema: &Value, text: &str) -> Value {
+    if let Some(first) = schema
+        .get("enum")
+        .and_then(Value::as_array)
+        .and_then(|values| values.first())
+    {
+        return first.clone();
+    }
+
+    let kind = match schema.get("type") {
+        Some(Value::String(kind)) => kind.as_str(),
+        Some(Value::Array(kinds)) => kinds
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|kind| *kind != "null")
+            .unwrap_or("null"),
+        _ if schema.get("properties").is_some() => "object",
+        _ => "string",
+    };
+    match kind {
+        "object" => Value::Object(
+            schema
+                .get("properties")
+                .and_then(Value::as_object)
+                .map(|properties| {
+                    properties
+                        .iter()
+                        .map(|(name, property)| (name.clone(), sample_value(property, text)))
+                        .collect::<Map<_, _>>()
+                })
+                .unwrap_or_default(),
+        ),
+        "array" => Value::Array(
+            schema
+                .get("items")
+                .map(|items| vec![sample_value(items, text)])
+                .unwrap_or_default(),
+        ),
+        "integer" | "number" => schema.get("minimum").cloned().unwrap_or_else(|| json!(0)),
+        "boolean" => Value::Bool(false),
+        "null" => Value::Null,
+        _ => Value::String(text.to_string()),
+    }
+}
+
+fn first_enum_value(schema: &Value, pointer: &str) -> Option<String> {
+    schema
+        .pointer(pointer)
+        .and_then(Value::as_array)
+        .and_then(|values| values.first())
+        .and_then(Value::as_str)
+        .map(ToString::to_string)
+}
+
+/// Distinct capitalized words, falling back to the first word of the text.
+fn entity_names(text: &str) -> Vec<String> {
+    let words: Vec<&str> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect();
+    let mut names: Vec<String> = Vec::new();
+    for word in &words {
+        let capitalized = word.chars().next().is_some_and(char::is_uppercase);
+        if capitalized
+            && word.chars().count() >= 3
+            && !names.iter().any(|name| name.eq_ignore_ascii_case(word))
+        {
+            names.push((*word).to_string());
+            if names.len() == MAX_ENTITIES {
+                break;
+            }
+        }
+    }
+    if names.is_empty() {
+        names.push(words.first().map_or("Untitled", |word| word).to_string());
+    }
+    names
+}
+
+/// The submitted content of an ingestion prompt, or the whole prompt.
+fn content_section(prompt: &str) -> &str {
+    let Some(content) = section_after(prompt, "Content:") else {
+        return prompt;
+    };
+    content
+        .find("\nEntity types:")
+        .and_then(|end| content.get(..end))
+        .unwrap_or(content)
+}
+
+/// Text following the line starting with `marker`, without its `====` underline.
+fn section_after<'a>(prompt: &'a str, marker: &str) -> Option<&'a str> {
+    let start = prompt.find(marker)?.saturating_add(marker.len());
+    let rest = prompt.get(start..)?;
+    Some(rest.trim_start_matches(|c: char| c.is_whitespace() || c == '='))
+}
+
+/// The text with whitespace collapsed, cut to [`EXCERPT_CHARS`].
+fn excerpt(text: &str) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.chars().count() <= EXCERPT_CHARS {
+        return collapsed;
+    }
+    let cut: String = collapsed.chars().take(EXCERPT_CHARS).collect();
+    format!("{}...", cut.trim_end())
+}
+
+/// Roughly four characters per token.
+fn estimate_tokens(chars: usize) -> u64 {
+    u64::try_from(chars.div_ceil(4)).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used, clippy::indexing_slicing)]
+
+    use super::*;
+    use crate::utils::llm::StructuredOutput;
+
+    fn request(prompt: &str, schema: Option<Value>) -> CompletionRequest {
+        CompletionRequest {
+            model: "mock".into(),
+            messages: vec![ChatMessage::system("sys"), ChatMessage::user(prompt)],
+            structured_output: schema
+                .map(|schema| StructuredOutput::new("output", "Structured output", schema)),
+        }
+    }
+
+    #[tokio::test]
+    async fn analysis_names_entities_after_the_content_and_links_them() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "knowledge_entities": {"type": "array", "items": {"type": "object", "properties": {
+                    "entity_type": {"type": "string", "enum": ["Idea", "Person"]}
+                }}},
+                "relationships": {"type": "array", "items": {"type": "object", "properties": {
+                    "type": {"type": "string", "enum": ["RelatedTo"]}
+                }}}
+            }
+        });
+        let prompt = "Category:\nnotes\ncontext:\nNone\nContent:\nAda met Grace to discuss Rust and ada.\nEntity types:\nIdea";
+
+        let completion = MockProvider
+            .complete(request(prompt, Some(schema.clone())))
+            .await
+            .expect("completion");
+        let analysis: Value = serde_json::from_str(&completion.content).expect("json");
+
+        let names: Vec<&str> = analysis["knowledge_entities"]
+            .as_array()
+            .expect("entities")
+            .iter()
+            .filter_map(|entity| entity["name"].as_str())
+            .collect();
+        assert_eq!(names, ["Ada", "Grace", "Rust"]);
+        assert_eq!(analysis["knowledge_entities"][0]["entity_type"], "Idea");
+        assert_eq!(analysis["relationships"].as_array().map(Vec::len), Some(2));
+        assert_eq!(analysis["relationships"][1]["source"], "entity-1");
+        assert_eq!(analysis["relationships"][1]["target"], "entity-2");
+
+        let again = MockProvider
+            .complete(request(prompt, Some(schema)))
+            .await
+            .expect("completion");
+        assert_eq!(
+            again.content, completion.content,
+            "answers are deterministic"
+        );
+        assert!(
+            completion
+                .usage
+                .is_some_and(|usage| usage.prompt_tokens > 0)
+        );
+    }
+
+    #[tokio::test]
+    async fn chat_answers_reference_the_supplied_context() {
+        let schema = json!({
+            "type": "object",
+            "properties": {"answer": {"type": "string"}, "references": {"type": "array"}}
+        });
+        let prompt = "Chat history:\n====\n\nContext Information:\n==================\n[{\"id\":\"chunk-1\",\"content\":\"Foo ships in May\"},{\"id\":\"chunk-2\",\"content\":\"other\"}]\n\nUser Question:\n==================\nWhen does Foo ship?";
+
+        let streamed: Vec<StreamChunk> = MockProvider
+            .stream(request(prompt, Some(schema)))
+            .await
+            .expect("stream")
+            .map(|chunk| chunk.expect("chunk"))
+            .collect()
+            .await;
+        let text: String = streamed
+            .iter()
+            .filter_map(|chunk| match chunk {
+                StreamChunk::Text(text) => Some(text.as_str()),
+                StreamChunk::Usage(_) => None,
+            })
+            .collect();
+        assert!(matches!(streamed.last(), Some(StreamChunk::Usage(_))));
+
+        let answer: Value = serde_json::from_str(&text).expect("json");
+        assert_eq!(
+            answer["references"],
+            json!([{"reference": "chunk-1"}, {"reference": "chunk-2"}])
+        );
+        let answer = answer["answer"].as_str().expect("answer");
+        assert!(answer.contains("When does Foo ship?"), "{answer}");
+        assert!(answer.contains("Foo ships in May"), "{answer}");
+    }
+
+    #[test]
+    fn other_schemas_are_filled_with_matching_types() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "title": {"type": "string"},
+                "key_points": {"type": "array", "items": {"type": "string"}},
+                "owner": {"type": ["string", "null"]},
+                "score": {"type": "number", "minimum": 1},
+                "kind": {"type": "string", "enum": ["date", "url"]},
+                "done": {"type": "boolean"}
+            }
+        });
+        assert_eq!(
+            sample_value(&schema, "text"),
+            json!({
+                "title": "text",
+                "key_points": ["text"],
+                "owner": "text",
+                "score": 1,
+                "kind": "date",
+                "done": false
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn images_and_audio_are_described_without_a_model() {
+        let message = ChatMessage::user("Describe").with_images(vec!["aGVsbG8=".into()]);
+        let completion = MockProvider
+            .complete(CompletionRequest {
+                model: "mock".into(),
+                messages: vec![message],
+                structured_output: None,
+            })
+            .await
+            .expect("completion");
+        assert!(completion.content.contains("8 encoded bytes"));
+
+        let file = tempfile::NamedTempFile::new().expect("temp file");
+        std::fs::write(file.path(), b"audio").expect("write audio");
+        let path = file.path().to_string_lossy().to_string();
+        let transcript = MockProvider
+            .transcribe("whisper-1", &path)
+            .await
+            .expect("transcription");
+        assert!(transcript.contains("(5 bytes)"), "{transcript}");
+        assert!(
+            MockProvider
+                .transcribe("whisper-1", "/missing.mp3")
+                .await
+                .is_err()
+        );
+    }
+}
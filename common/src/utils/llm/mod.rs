@@ -0,0 +1,904 @@
+//! Chat-completion providers behind one trait, selected per model role from `SystemSettings`.
+
+mod anthropic;
+mod cache;
+mod mock;
+mod ollama;
+mod openai;
+
+pub use anthropic::AnthropicProvider;
+pub use cache::ResponseCache;
+pub use mock::MockProvider;
+pub use ollama::OllamaProvider;
+pub use openai::OpenAiCompatibleProvider;
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+use async_openai::{Client, config::OpenAIConfig};
+use async_trait::async_trait;
+use futures::{Stream, StreamExt, future, stream};
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{debug, info, warn};
+
+use crate::{
+    error::{AppError, LlmError},
+    storage::{
+        db::SurrealDbClient,
+        types::{
+            llm_usage::{LlmUsage, UsageRole},
+            system_settings::{ModelRole, ModelRoute, SystemSettings},
+        },
+    },
+    utils::config::{AppConfig, LlmBackend, StructuredOutputMode},
+};
+
+/// Speaker of a chat message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatRole {
+    System,
+    User,
+    Assistant,
+}
+
+/// One message of a completion request. `images` hold base64-encoded PNGs.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChatMessage {
+    pub role: ChatRole,
+    pub content: String,
+    pub images: Vec<String>,
+}
+
+impl ChatMessage {
+    pub fn system(content: impl Into<String>) -> Self {
+        Self {
+            role: ChatRole::System,
+            content: content.into(),
+            images: Vec::new(),
+        }
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self {
+            role: ChatRole::User,
+            content: content.into(),
+            images: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_images(mut self, images: Vec<String>) -> Self {
+        self.images = images;
+        self
+    }
+}
+
+/// JSON schema a structured response must follow.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StructuredOutput {
+    pub name: String,
+    pub description: String,
+    pub schema: Value,
+}
+
+impl StructuredOutput {
+    pub fn new(name: impl Into<String>, description: impl Into<String>, schema: Value) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            schema,
+        }
+    }
+}
+
+/// Provider-neutral chat-completion request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompletionRequest {
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+    pub structured_output: Option<StructuredOutput>,
+}
+
+/// Tokens a backend reported for one request.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+}
+
+/// Final text of a completion; the JSON document when structured output was requested.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completion {
+    pub content: String,
+    /// Tokens spent, when the backend reports them.
+    pub usage: Option<TokenUsage>,
+}
+
+impl Completion {
+    pub fn text(content: impl Into<String>) -> Self {
+        Self {
+            content: content.into(),
+            usage: None,
+        }
+    }
+}
+
+/// Incremental text of a streamed completion.
+pub type CompletionStream = Pin<Box<dyn Stream<Item = Result<String, LlmError>> + Send>>;
+
+/// One item of a provider stream: more text, or the request's token usage.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamChunk {
+    Text(String),
+    Usage(TokenUsage),
+}
+
+/// Stream returned by [`LlmProvider::stream`]; [`LlmProviders`] strips usage from it.
+pub type ProviderStream = Pin<Box<dyn Stream<Item = Result<StreamChunk, LlmError>> + Send>>;
+
+/// A chat-completion backend.
+#[allow(clippy::module_name_repetitions)]
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    fn backend(&self) -> LlmBackend;
+
+    async fn complete(&self, request: CompletionRequest) -> Result<Completion, LlmError>;
+
+    async fn stream(&self, request: CompletionRequest) -> Result<ProviderStream, LlmError>;
+
+    /// Transcribes an audio file; only the OpenAI-compatible backend offers speech-to-text.
+    async fn transcribe(&self, model: &str, file_path: &str) -> Result<String, LlmError> {
+        let _ = (model, file_path);
+        Err(LlmError::Unsupported(format!(
+            "{} does not offer audio transcription",
+            self.backend().as_str()
+        )))
+    }
+}
+
+/// One provider per backend; requests walk the ordered routes configured for a role.
+///
+/// Each route gets `llm_request_timeout_secs`; on an error, a timeout or a context-length
+/// rejection the next route is tried. Routes with their own base URL get a provider of
+/// their own, built on first use and kept for the process lifetime.
+///
+/// With a usage log attached, every call made through a [`Self::scoped`] handle is
+/// recorded as an [`LlmUsage`] row for that user. With a response cache attached,
+/// completions made through a [`Self::cached`] handle are answered from it when possible.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone)]
+pub struct LlmProviders {
+    openai: Arc<dyn LlmProvider>,
+    ollama: Arc<dyn LlmProvider>,
+    anthropic: Arc<dyn LlmProvider>,
+    endpoints: Option<Arc<EndpointFactory>>,
+    custom: Arc<Mutex<EndpointCache>>,
+    request_timeout: Option<Duration>,
+    usage_log: Option<Arc<SurrealDbClient>>,
+    scope: Option<UsageScope>,
+    response_cache: Option<Arc<ResponseCache>>,
+    use_cache: bool,
+}
+
+/// Who LLM calls are made for, and the ingestion source or conversation behind them.
+#[derive(Debug, Clone)]
+struct UsageScope {
+    user_id: String,
+    reference_id: Option<String>,
+}
+
+/// Writes the usage of one served call.
+struct UsageRecorder {
+    db: Arc<SurrealDbClient>,
+    scope: UsageScope,
+    role: UsageRole,
+    route: ModelRoute,
+}
+
+impl UsageRecorder {
+    /// Stores the call; failures are logged so accounting never fails a request.
+    async fn record(self, usage: Option<TokenUsage>) {
+        let usage = usage.unwrap_or_default();
+        let record = LlmUsage::new(
+            &self.scope.user_id,
+            self.scope.reference_id.as_deref(),
+            self.role,
+            &self.route,
+            usage.prompt_tokens,
+            usage.completion_tokens,
+        );
+        if let Err(err) = self.db.store_item(record).await {
+            warn!(error = %err, route = %self.route, "failed to record LLM usage");
+        }
+    }
+}
+
+/// Providers built for base URL overrides, keyed by backend and URL.
+type EndpointCache = HashMap<(LlmBackend, String), Arc<dyn LlmProvider>>;
+
+/// Builds providers for routes that override a backend's base URL.
+struct EndpointFactory {
+    http: reqwest::Client,
+    openai_api_key: String,
+    structured_output: StructuredOutputMode,
+    anthropic_api_key: String,
+    anthropic_max_tokens: u32,
+}
+
+impl EndpointFactory {
+    fn build(&self, backend: LlmBackend, base_url: &str) -> Arc<dyn LlmProvider> {
+        match backend {
+            LlmBackend::OpenAI => Arc::new(OpenAiCompatibleProvider::new(
+                Arc::new(Client::with_config(
+                    OpenAIConfig::new()
+                        .with_api_key(&self.openai_api_key)
+                        .with_api_base(base_url),
+                )),
+                self.structured_output,
+            )),
+            LlmBackend::Ollama => Arc::new(OllamaProvider::new(self.http.clone(), base_url)),
+            LlmBackend::Anthropic => Arc::new(AnthropicProvider::new(
+                self.http.clone(),
+                base_url,
+                &self.anthropic_api_key,
+                self.anthropic_max_tokens,
+            )),
+        }
+    }
+}
+
+impl LlmProviders {
+    /// Providers for the configured backends, or only [`MockProvider`] when `llm_mock` is set.
+    pub fn from_config(config: &AppConfig, openai_client: Arc<Client<OpenAIConfig>>) -> Self {
+        if config.llm_mock {
+            return Self::uniform(Arc::new(MockProvider));
+        }
+        let http = reqwest::Client::new();
+        Self {
+            openai: Arc::new(OpenAiCompatibleProvider::new(
+                openai_client,
+                config.openai_structured_output,
+            )),
+            ollama: Arc::new(OllamaProvider::new(http.clone(), &config.ollama_base_url)),
+            anthropic: Arc::new(AnthropicProvider::new(
+                http.clone(),
+                &config.anthropic_base_url,
+                &config.anthropic_api_key,
+                config.anthropic_max_tokens,
+            )),
+            endpoints: Some(Arc::new(EndpointFactory {
+                http,
+                openai_api_key: config.openai_api_key.clone(),
+                structured_output: config.openai_structured_output,
+                anthropic_api_key: config.anthropic_api_key.clone(),
+                anthropic_max_tokens: config.anthropic_max_tokens,
+            })),
+            custom: Arc::default(),
+            request_timeout: (config.llm_request_timeout_secs > 0)
+                .then(|| Duration::from_secs(config.llm_request_timeout_secs)),
+            usage_log: None,
+            scope: None,
+            response_cache: None,
+            use_cache: false,
+        }
+    }
+
+    /// Routes every backend and endpoint to `provider`, e.g. a stub in tests.
+    pub fn uniform(provider: Arc<dyn LlmProvider>) -> Self {
+        Self {
+            openai: Arc::clone(&provider),
+            ollama: Arc::clone(&provider),
+            anthropic: provider,
+            endpoints: None,
+            custom: Arc::default(),
+            request_timeout: None,
+            usage_log: None,
+            scope: None,
+            response_cache: None,
+            use_cache: false,
+        }
+    }
+
+    /// Replaces the per-route time budget (`None` waits indefinitely).
+    #[must_use]
+    pub fn with_request_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Records token usage of scoped calls in `db`.
+    #[must_use]
+    pub fn with_usage_log(mut self, db: Arc<SurrealDbClient>) -> Self {
+        self.usage_log = Some(db);
+        self
+    }
+
+    /// Handle whose calls are accounted to `user_id`, and to the ingestion source or
+    /// conversation `reference_id`. Calls made without a scope are not recorded.
+    #[must_use]
+    pub fn scoped(&self, user_id: &str, reference_id: Option<&str>) -> Self {
+        Self {
+            scope: Some(UsageScope {
+                user_id: user_id.to_string(),
+                reference_id: reference_id.map(ToString::to_string),
+            }),
+            ..self.clone()
+        }
+    }
+
+    /// Serves completions of [`Self::cached`] handles from `cache`.
+    #[must_use]
+    pub fn with_response_cache(mut self, cache: Arc<ResponseCache>) -> Self {
+        self.response_cache = Some(cache);
+        self
+    }
+
+    /// Handle whose completions are looked up in, and stored to, the response cache.
+    ///
+    /// Only for deterministic work such as ingestion analysis, where an identical request
+    /// should get the same answer; cache hits cost nothing and record no usage.
+    #[must_use]
+    pub fn cached(&self) -> Self {
+        Self {
+            use_cache: true,
+            ..self.clone()
+        }
+    }
+
+    /// Empties the response cache; `None` when no cache is configured.
+    pub async fn purge_response_cache(&self) -> Result<Option<u64>, AppError> {
+        match &self.response_cache {
+            Some(cache) => cache.purge().await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn cache(&self) -> Option<&ResponseCache> {
+        self.response_cache.as_deref().filter(|_| self.use_cache)
+    }
+
+    fn recorder(&self, role: UsageRole, route: &ModelRoute) -> Option<UsageRecorder> {
+        Some(UsageRecorder {
+            db: Arc::clone(self.usage_log.as_ref()?),
+            scope: self.scope.clone()?,
+            role,
+            route: route.clone(),
+        })
+    }
+
+    pub fn get(&self, backend: LlmBackend) -> &Arc<dyn LlmProvider> {
+        match backend {
+            LlmBackend::OpenAI => &self.openai,
+            LlmBackend::Ollama => &self.ollama,
+            LlmBackend::Anthropic => &self.anthropic,
+        }
+    }
+
+    /// Provider serving `route`, honouring its base URL override.
+    fn endpoint(&self, route: &ModelRoute) -> Arc<dyn LlmProvider> {
+        let (Some(base_url), Some(endpoints)) = (&route.base_url, &self.endpoints) else {
+            return Arc::clone(self.get(route.backend));
+        };
+        let mut custom = self.custom.lock().unwrap_or_else(PoisonError::into_inner);
+        Arc::clone(
+            custom
+                .entry((route.backend, base_url.clone()))
+                .or_insert_with(|| endpoints.build(route.backend, base_url)),
+        )
+    }
+
+    /// Completes `messages` with the routes configured for `role`.
+    pub async fn complete(
+        &self,
+        settings: &SystemSettings,
+        role: ModelRole,
+        messages: Vec<ChatMessage>,
+        structured_output: Option<StructuredOutput>,
+    ) -> Result<Completion, AppError> {
+        self.complete_routed(
+            role,
+            &settings.routes_for(role, None),
+            messages,
+            structured_output,
+        )
+        .await
+    }
+
+    /// Completes ingestion work for content in `category`, honouring category overrides.
+    pub async fn complete_for_category(
+        &self,
+        settings: &SystemSettings,
+        role: ModelRole,
+        category: &str,
+        messages: Vec<ChatMessage>,
+        structured_output: Option<StructuredOutput>,
+    ) -> Result<Completion, AppError> {
+        let routes = settings.routes_for(role, Some(category));
+        self.complete_routed(role, &routes, messages, structured_output)
+            .await
+    }
+
+    /// Completes `messages` for `role` with the first of `routes` that answers.
+    pub async fn complete_routed(
+        &self,
+        role: ModelRole,
+        routes: &[ModelRoute],
+        messages: Vec<ChatMessage>,
+        structured_output: Option<StructuredOutput>,
+    ) -> Result<Completion, AppError> {
+        let cache = self.cache();
+        if let Some(cache) = cache {
+            for route in routes {
+                if let Some(content) = cache
+                    .get(route, &messages, structured_output.as_ref())
+                    .await
+                {
+                    debug!(%route, "LLM response served from cache");
+                    return Ok(Completion::text(content));
+                }
+            }
+        }
+
+        let (completion, route) = self
+            .with_fallback(routes, |provider, route| {
+                let request = CompletionRequest {
+                    model: route.model.clone(),
+                    messages: messages.clone(),
+                    structured_output: structured_output.clone(),
+                };
+                async move { provider.complete(request).await }
+            })
+            .await?;
+        if let Some(cache) = cache {
+            cache
+                .put(
+                    route,
+                    &messages,
+                    structured_output.as_ref(),
+                    &completion.content,
+                )
+                .await;
+        }
+        if let Some(recorder) = self.recorder(role.into(), route) {
+            recorder.record(completion.usage).await;
+        }
+        Ok(completion)
+    }
+
+    /// Streams a completion from the first route for `role` that opens a stream.
+    ///
+    /// Fallback only covers opening the stream; an error mid-stream ends it.
+    pub async fn stream(
+        &self,
+        settings: &SystemSettings,
+        role: ModelRole,
+        messages: Vec<ChatMessage>,
+        structured_output: Option<StructuredOutput>,
+    ) -> Result<CompletionStream, AppError> {
+        let routes = settings.routes_for(role, None);
+        let (chunks, route) = self
+            .with_fallback(&routes, |provider, route| {
+                let request = CompletionRequest {
+                    model: route.model.clone(),
+                    messages: messages.clone(),
+                    structured_output: structured_output.clone(),
+                };
+                async move { provider.stream(request).await }
+            })
+            .await?;
+
+        // Usage arrives as a late chunk; it is recorded once the stream is drained.
+        let usage = Arc::new(Mutex::new(None));
+        let seen = Arc::clone(&usage);
+        let text = chunks.filter_map(move |chunk| {
+            let item = match chunk {
+                Ok(StreamChunk::Text(text)) => Some(Ok(text)),
+                Ok(StreamChunk::Usage(reported)) => {
+                    *seen.lock().unwrap_or_else(PoisonError::into_inner) = Some(reported);
+                    None
+                }
+                Err(err) => Some(Err(err)),
+            };
+            future::ready(item)
+        });
+        let recorder = self.recorder(role.into(), route);
+        let finish = stream::once(async move {
+            if let Some(recorder) = recorder {
+                let reported = *usage.lock().unwrap_or_else(PoisonError::into_inner);
+                recorder.record(reported).await;
+            }
+        })
+        .filter_map(|()| future::ready(None));
+
+        Ok(text.chain(finish).boxed())
+    }
+
+    /// Transcribes audio with `voice_processing_model`, then its OpenAI-compatible fallbacks.
+    pub async fn transcribe(
+        &self,
+        settings: &SystemSettings,
+        file_path: &str,
+    ) -> Result<String, AppError> {
+        let routes = settings.voice_routes();
+        let (text, route) = self
+            .with_fallback(&routes, |provider, route| {
+                let model = route.model.clone();
+                async move { provider.transcribe(&model, file_path).await }
+            })
+            .await?;
+        if let Some(recorder) = self.recorder(UsageRole::Transcription, route) {
+            recorder.record(None).await;
+        }
+        Ok(text)
+    }
+
+    /// Calls each route in turn until one succeeds, returning its result and route.
+    async fn with_fallback<'r, T, F, Fut>(
+        &self,
+        routes: &'r [ModelRoute],
+        mut call: F,
+    ) -> Result<(T, &'r ModelRoute), AppError>
+    where
+        F: FnMut(Arc<dyn LlmProvider>, &ModelRoute) -> Fut,
+        Fut: Future<Output = Result<T, LlmError>>,
+    {
+        let mut last_error = None;
+        for (attempt, route) in routes.iter().enumerate() {
+            let pending = call(self.endpoint(route), route);
+            let result = match self.request_timeout {
+                Some(limit) => tokio::time::timeout(limit, pending)
+                    .await
+                    .unwrap_or(Err(LlmError::Timeout(limit.as_secs()))),
+                None => pending.await,
+            };
+            match result {
+                Ok(value) => {
+                    if attempt > 0 {
+                        info!(%route, attempt, "LLM request served by fallback route");
+                    }
+                    return Ok((value, route));
+                }
+                Err(err) => {
+                    warn!(
+                        %route,
+                        attempt,
+                        reason = fallback_reason(&err),
+                        error = %err,
+                        remaining = routes.len().saturating_sub(attempt.saturating_add(1)),
+                        "LLM route failed"
+                    );
+                    last_error = Some(err);
+                }
+            }
+        }
+        Err(last_error
+            .unwrap_or_else(|| LlmError::Unsupported("no model routes configured".into()))
+            .into())
+    }
+}
+
+fn fallback_reason(err: &LlmError) -> &'static str {
+    if matches!(err, LlmError::Timeout(_)) {
+        "timeout"
+    } else if err.is_context_length() {
+        "context_length"
+    } else {
+        "error"
+    }
+}
+
+/// Fails with the response body when a provider answers with a non-success status.
+async fn ensure_success(
+    backend: LlmBackend,
+    response: reqwest::Response,
+) -> Result<reqwest::Response, LlmError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let message = response.text().await.unwrap_or_default();
+    Err(LlmError::Status {
+        backend: backend.as_str(),
+        status: status.as_u16(),
+        message,
+    })
+}
+
+struct LineReader {
+    response: reqwest::Response,
+    buffer: Vec<u8>,
+    finished: bool,
+}
+
+/// Splits a streaming HTTP body into lines (NDJSON and server-sent events are line based).
+fn response_lines(
+    response: reqwest::Response,
+) -> impl Stream<Item = Result<String, LlmError>> + Send {
+    let reader = LineReader {
+        response,
+        buffer: Vec::new(),
+        finished: false,
+    };
+    stream::unfold(reader, |mut reader| async move {
+        loop {
+            if let Some(end) = reader.buffer.iter().position(|byte| *byte == b'\n') {
+                let line: Vec<u8> = reader.buffer.drain(..=end).collect();
+                let text = String::from_utf8_lossy(&line)
+                    .trim_end_matches(['\r', '\n'])
+                    .to_string();
+                return Some((Ok(text), reader));
+            }
+            if reader.finished {
+                if reader.buffer.is_empty() {
+                    return None;
+                }
+                let rest = std::mem::take(&mut reader.buffer);
+                return Some((Ok(String::from_utf8_lossy(&rest).into_owned()), reader));
+            }
+            match reader.response.chunk().await {
+                Ok(Some(bytes)) => reader.buffer.extend_from_slice(&bytes),
+                Ok(None) => reader.finished = true,
+                Err(err) => {
+                    reader.finished = true;
+                    reader.buffer.clear();
+                    return Some((Err(err.into()), reader));
+                }
+            }
+        }
+    })
+}
+
+/// Joins the system messages of a request, for APIs that take the system prompt separately.
+fn system_prompt(messages: &[ChatMessage]) -> Option<String> {
+    let prompt = messages
+        .iter()
+        .filter(|message| message.role == ChatRole::System)
+        .map(|message| message.content.as_str())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    (!prompt.is_empty()).then_some(prompt)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used)]
+
+    use super::*;
+
+    struct EchoProvider;
+
+    #[async_trait]
+    impl LlmProvider for EchoProvider {
+        fn backend(&self) -> LlmBackend {
+            LlmBackend::Ollama
+        }
+
+        async fn complete(&self, request: CompletionRequest) -> Result<Completion, LlmError> {
+            Ok(Completion {
+                content: request.model,
+                usage: Some(TokenUsage {
+                    prompt_tokens: 12,
+                    completion_tokens: 3,
+                }),
+            })
+        }
+
+        async fn stream(&self, request: CompletionRequest) -> Result<ProviderStream, LlmError> {
+            Ok(stream::iter([
+                Ok(StreamChunk::Text(request.model)),
+                Ok(StreamChunk::Usage(TokenUsage {
+                    prompt_tokens: 7,
+                    completion_tokens: 1,
+                })),
+            ])
+            .boxed())
+        }
+    }
+
+    #[tokio::test]
+    async fn providers_route_by_role_model_and_reject_transcription_elsewhere() {
+        let providers = LlmProviders::uniform(Arc::new(EchoProvider));
+        let db = crate::test_utils::setup_test_db().await.expect("test db");
+        let mut settings = SystemSettings::get_current(&db).await.expect("settings");
+        settings.query_model = "query-model".into();
+        settings.processing_model = "processing-model".into();
+
+        let query = providers
+            .complete(&settings, ModelRole::Query, Vec::new(), None)
+            .await
+            .expect("complete");
+        assert_eq!(query.content, "query-model");
+
+        let streamed: Vec<_> = providers
+            .stream(&settings, ModelRole::Processing, Vec::new(), None)
+            .await
+            .expect("stream")
+            .collect()
+            .await;
+        assert_eq!(streamed.len(), 1);
+
+        let transcription = providers.transcribe(&settings, "/tmp/audio.mp3").await;
+        assert!(matches!(
+            transcription,
+            Err(AppError::Llm(LlmError::Unsupported(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn scoped_calls_record_usage_per_user_role_and_reference() {
+        let db = Arc::new(crate::test_utils::setup_test_db().await.expect("test db"));
+        let settings = SystemSettings::get_current(&db).await.expect("settings");
+        let providers =
+            LlmProviders::uniform(Arc::new(EchoProvider)).with_usage_log(Arc::clone(&db));
+
+        providers
+            .complete(&settings, ModelRole::Query, Vec::new(), None)
+            .await
+            .expect("unscoped completion");
+        let scoped = providers.scoped("user-1", Some("conversation-1"));
+        scoped
+            .complete(&settings, ModelRole::Processing, Vec::new(), None)
+            .await
+            .expect("scoped completion");
+        let streamed: Vec<_> = scoped
+            .stream(&settings, ModelRole::Query, Vec::new(), None)
+            .await
+            .expect("stream")
+            .collect()
+            .await;
+        assert_eq!(streamed.len(), 1, "usage chunks are not forwarded");
+
+        let mut records: Vec<LlmUsage> = db.get_all_stored_items().await.expect("usage rows");
+        records.sort_by_key(|record| record.prompt_tokens);
+        assert_eq!(records.len(), 2, "unscoped calls are not recorded");
+        let chat = records.first().expect("chat usage");
+        let ingestion = records.get(1).expect("ingestion usage");
+        assert_eq!(chat.role, UsageRole::Chat);
+        assert_eq!((chat.prompt_tokens, chat.completion_tokens), (7, 1));
+        assert_eq!(ingestion.role, UsageRole::Ingestion);
+        assert_eq!(ingestion.model, settings.processing_model);
+        assert_eq!(ingestion.user_id, "user-1");
+        assert_eq!(ingestion.reference_id.as_deref(), Some("conversation-1"));
+    }
+
+    #[tokio::test]
+    async fn cached_handles_reuse_completions_without_recording_usage() {
+        let db = Arc::new(crate::test_utils::setup_test_db().await.expect("test db"));
+        let settings = SystemSettings::get_current(&db).await.expect("settings");
+        let cache = ResponseCache::in_database(Arc::clone(&db), None);
+        let providers = LlmProviders::uniform(Arc::new(EchoProvider))
+            .with_usage_log(Arc::clone(&db))
+            .with_response_cache(Arc::new(cache));
+        let messages = vec![ChatMessage::user("same content")];
+
+        let uncached = providers.scoped("user-1", None);
+        let cached = uncached.cached();
+        for handle in [&uncached, &cached, &cached] {
+            let completion = handle
+                .complete(&settings, ModelRole::Processing, messages.clone(), None)
+                .await
+                .expect("completion");
+            assert_eq!(completion.content, settings.processing_model);
+        }
+
+        let records: Vec<LlmUsage> = db.get_all_stored_items().await.expect("usage rows");
+        assert_eq!(records.len(), 2, "the cache hit made no call");
+        assert_eq!(
+            providers.purge_response_cache().await.expect("purge"),
+            Some(1)
+        );
+        assert_eq!(
+            LlmProviders::uniform(Arc::new(EchoProvider))
+                .purge_response_cache()
+                .await
+                .expect("purge"),
+            None
+        );
+    }
+
+    /// Fails for models named `down*`, rejects `long*` as too long, stalls on `slow*`.
+    struct FlakyProvider;
+
+    #[async_trait]
+    impl LlmProvider for FlakyProvider {
+        fn backend(&self) -> LlmBackend {
+            LlmBackend::OpenAI
+        }
+
+        async fn complete(&self, request: CompletionRequest) -> Result<Completion, LlmError> {
+            if request.model.starts_with("down") {
+                return Err(LlmError::Status {
+                    backend: "openai",
+                    status: 503,
+                    message: "unavailable".into(),
+                });
+            }
+            if request.model.starts_with("long") {
+                return Err(LlmError::Status {
+                    backend: "openai",
+                    status: 400,
+                    message: "This model's maximum context length is 8192 tokens".into(),
+                });
+            }
+            if request.model.starts_with("slow") {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+            Ok(Completion::text(request.model))
+        }
+
+        async fn stream(&self, request: CompletionRequest) -> Result<ProviderStream, LlmError> {
+            let completion = self.complete(request).await?;
+            Ok(stream::iter([Ok(StreamChunk::Text(completion.content))]).boxed())
+        }
+    }
+
+    #[tokio::test]
+    async fn routed_completion_falls_back_past_errors_timeouts_and_context_limits() {
+        let providers = LlmProviders::uniform(Arc::new(FlakyProvider))
+            .with_request_timeout(Some(Duration::from_millis(50)));
+        let routes = [
+            ModelRoute::new(LlmBackend::OpenAI, "down-primary"),
+            ModelRoute::new(LlmBackend::Ollama, "slow-local").with_base_url("http://gpu:11434"),
+            ModelRoute::new(LlmBackend::Anthropic, "long-context"),
+            ModelRoute::new(LlmBackend::OpenAI, "backup"),
+        ];
+
+        let completion = providers
+            .complete_routed(ModelRole::Processing, &routes, Vec::new(), None)
+            .await
+            .expect("fallback route answers");
+        assert_eq!(completion.content, "backup");
+
+        let exhausted = providers
+            .complete_routed(
+                ModelRole::Processing,
+                routes.get(..2).expect("two routes"),
+                Vec::new(),
+                None,
+            )
+            .await;
+        assert!(matches!(
+            exhausted,
+            Err(AppError::Llm(LlmError::Timeout(_)))
+        ));
+
+        let empty = providers
+            .complete_routed(ModelRole::Processing, &[], Vec::new(), None)
+            .await;
+        assert!(matches!(
+            empty,
+            Err(AppError::Llm(LlmError::Unsupported(_)))
+        ));
+    }
+
+    #[test]
+    fn context_length_errors_are_recognised() {
+        let rejected = LlmError::Status {
+            backend: "anthropic",
+            status: 400,
+            message: "prompt is too long: 210000 tokens > 200000 maximum".into(),
+        };
+        assert!(rejected.is_context_length());
+        assert_eq!(fallback_reason(&rejected), "context_length");
+        assert_eq!(fallback_reason(&LlmError::Timeout(30)), "timeout");
+        assert!(!LlmError::Response("empty".into()).is_context_length());
+    }
+
+    #[test]
+    fn system_prompt_joins_only_system_messages() {
+        let messages = vec![
+            ChatMessage::system("first"),
+            ChatMessage::user("question"),
+            ChatMessage::system("second"),
+        ];
+        assert_eq!(system_prompt(&messages).as_deref(), Some("first\n\nsecond"));
+        assert_eq!(system_prompt(&[ChatMessage::user("only")]), None);
+    }
+}
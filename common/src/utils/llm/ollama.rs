@@ -0,0 +1,231 @@
+//! Native Ollama chat API (`/api/chat`), with JSON schemas passed through `format`.
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{
+    ChatMessage, ChatRole, Completion, CompletionRequest, LlmProvider, ProviderStream, StreamChunk,
+    TokenUsage, ensure_success, response_lines,
+};
+use crate::{error::LlmError, utils::config::LlmBackend};
+
+/// Chat completions against a local or remote Ollama server.
+#[allow(clippy::module_name_repetitions)]
+pub struct OllamaProvider {
+    http: reqwest::Client,
+    chat_url: String,
+}
+
+#[derive(Serialize)]
+struct OllamaChatRequest {
+    model: String,
+    messages: Vec<OllamaMessage>,
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<Value>,
+}
+
+#[derive(Serialize)]
+struct OllamaMessage {
+    role: &'static str,
+    content: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    images: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct OllamaChatChunk {
+    #[serde(default)]
+    message: Option<OllamaChunkMessage>,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    done: bool,
+    #[serde(default)]
+    prompt_eval_count: Option<u64>,
+    #[serde(default)]
+    eval_count: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct OllamaChunkMessage {
+    #[serde(default)]
+    content: String,
+}
+
+impl OllamaProvider {
+    pub fn new(http: reqwest::Client, base_url: &str) -> Self {
+        Self {
+            http,
+            chat_url: format!("{}/api/chat", base_url.trim_end_matches('/')),
+        }
+    }
+
+    async fn send(
+        &self,
+        request: CompletionRequest,
+        stream: bool,
+    ) -> Result<reqwest::Response, LlmError> {
+        let response = self
+            .http
+            .post(&self.chat_url)
+            .json(&chat_body(request, stream))
+            .send()
+            .await?;
+        ensure_success(LlmBackend::Ollama, response).await
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OllamaProvider {
+    fn backend(&self) -> LlmBackend {
+        LlmBackend::Ollama
+    }
+
+    async fn complete(&self, request: CompletionRequest) -> Result<Completion, LlmError> {
+        let chunk: OllamaChatChunk = self.send(request, false).await?.json().await?;
+        let usage = chunk_usage(&chunk);
+        Ok(Completion {
+            content: chunk_text(chunk)?,
+            usage,
+        })
+    }
+
+    async fn stream(&self, request: CompletionRequest) -> Result<ProviderStream, LlmError> {
+        let response = self.send(request, true).await?;
+
+        Ok(response_lines(response)
+            .filter_map(|line| async move {
+                match line {
+                    Ok(line) if line.trim().is_empty() => None,
+                    Ok(line) => Some(
+                        serde_json::from_str::<OllamaChatChunk>(&line)
+                            .map_err(|e| LlmError::Response(format!("invalid ollama chunk: {e}")))
+                            .and_then(stream_chunk),
+                    ),
+                    Err(err) => Some(Err(err)),
+                }
+            })
+            .boxed())
+    }
+}
+
+fn chat_body(request: CompletionRequest, stream: bool) -> OllamaChatRequest {
+    OllamaChatRequest {
+        model: request.model,
+        messages: request
+            .messages
+            .into_iter()
+            .map(to_ollama_message)
+            .collect(),
+        stream,
+        format: request.structured_output.map(|output| output.schema),
+    }
+}
+
+fn to_ollama_message(message: ChatMessage) -> OllamaMessage {
+    OllamaMessage {
+        role: match message.role {
+            ChatRole::System => "system",
+            ChatRole::User => "user",
+            ChatRole::Assistant => "assistant",
+        },
+        content: message.content,
+        images: message.images,
+    }
+}
+
+fn chunk_text(chunk: OllamaChatChunk) -> Result<String, LlmError> {
+    if let Some(error) = chunk.error {
+        return Err(LlmError::Response(error));
+    }
+    Ok(chunk
+        .message
+        .map(|message| message.content)
+        .unwrap_or_default())
+}
+
+/// Counts reported on the final (`done`) chunk.
+fn chunk_usage(chunk: &OllamaChatChunk) -> Option<TokenUsage> {
+    if !chunk.done || (chunk.prompt_eval_count.is_none() && chunk.eval_count.is_none()) {
+        return None;
+    }
+    Some(TokenUsage {
+        prompt_tokens: chunk.prompt_eval_count.unwrap_or_default(),
+        completion_tokens: chunk.eval_count.unwrap_or_default(),
+    })
+}
+
+/// The final chunk carries usage instead of text; Ollama sends it with empty content.
+fn stream_chunk(chunk: OllamaChatChunk) -> Result<StreamChunk, LlmError> {
+    match chunk_usage(&chunk) {
+        Some(usage) => Ok(StreamChunk::Usage(usage)),
+        None => chunk_text(chunk).map(StreamChunk::Text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used)]
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::utils::llm::StructuredOutput;
+
+    #[test]
+    fn chat_body_passes_schema_as_format_and_images_inline() {
+        let request = CompletionRequest {
+            model: "llama3.1".into(),
+            messages: vec![
+                ChatMessage::system("sys"),
+                ChatMessage::user("describe").with_images(vec!["aGVsbG8=".into()]),
+            ],
+            structured_output: Some(StructuredOutput::new(
+                "answer",
+                "An answer",
+                json!({"type": "object"}),
+            )),
+        };
+
+        let body = serde_json::to_value(chat_body(request, false)).expect("serialize");
+
+        assert_eq!(body.pointer("/format"), Some(&json!({"type": "object"})));
+        assert_eq!(body.pointer("/stream"), Some(&json!(false)));
+        assert_eq!(body.pointer("/messages/0/role"), Some(&json!("system")));
+        assert!(body.pointer("/messages/0/images").is_none());
+        assert_eq!(
+            body.pointer("/messages/1/images"),
+            Some(&json!(["aGVsbG8="]))
+        );
+    }
+
+    #[test]
+    fn chunk_text_surfaces_server_errors() {
+        let chunk: OllamaChatChunk =
+            serde_json::from_str(r#"{"error":"model 'x' not found"}"#).expect("chunk");
+        assert!(matches!(chunk_text(chunk), Err(LlmError::Response(_))));
+
+        let chunk: OllamaChatChunk =
+            serde_json::from_str(r#"{"message":{"role":"assistant","content":"hi"},"done":false}"#)
+                .expect("chunk");
+        assert_eq!(chunk_text(chunk).expect("text"), "hi");
+    }
+
+    #[test]
+    fn final_chunk_reports_token_usage() {
+        let chunk: OllamaChatChunk = serde_json::from_str(
+            r#"{"message":{"role":"assistant","content":""},"done":true,"prompt_eval_count":26,"eval_count":290}"#,
+        )
+        .expect("chunk");
+        assert_eq!(
+            stream_chunk(chunk).expect("usage"),
+            StreamChunk::Usage(TokenUsage {
+                prompt_tokens: 26,
+                completion_tokens: 290,
+            })
+        );
+    }
+}
@@ -0,0 +1,290 @@
+//! OpenAI and OpenAI-compatible chat completions via `async-openai`.
+
+use std::sync::Arc;
+
+use async_openai::{
+    Client,
+    config::OpenAIConfig,
+    types::{
+        audio::{AudioResponseFormat, CreateTranscriptionRequestArgs},
+        chat::{
+            ChatCompletionMessageToolCalls, ChatCompletionNamedToolChoice,
+            ChatCompletionRequestAssistantMessage, ChatCompletionRequestMessage,
+            ChatCompletionRequestMessageContentPartImageArgs,
+            ChatCompletionRequestMessageContentPartTextArgs, ChatCompletionRequestSystemMessage,
+            ChatCompletionRequestUserMessage, ChatCompletionRequestUserMessageArgs,
+            ChatCompletionResponseMessage, ChatCompletionStreamOptions,
+            ChatCompletionStreamResponseDelta, ChatCompletionTool, ChatCompletionToolChoiceOption,
+            ChatCompletionTools, CompletionUsage, CreateChatCompletionRequest, FunctionName,
+            FunctionObject, ImageDetail, ImageUrlArgs, ResponseFormat, ResponseFormatJsonSchema,
+        },
+    },
+};
+use async_trait::async_trait;
+use futures::StreamExt;
+
+use super::{
+    ChatMessage, ChatRole, Completion, CompletionRequest, LlmProvider, ProviderStream, StreamChunk,
+    TokenUsage,
+};
+use crate::{
+    error::LlmError,
+    utils::config::{LlmBackend, StructuredOutputMode},
+};
+
+/// Chat completions against OpenAI or any server speaking its API.
+#[allow(clippy::module_name_repetitions)]
+pub struct OpenAiCompatibleProvider {
+    client: Arc<Client<OpenAIConfig>>,
+    structured_output: StructuredOutputMode,
+}
+
+impl OpenAiCompatibleProvider {
+    pub fn new(client: Arc<Client<OpenAIConfig>>, structured_output: StructuredOutputMode) -> Self {
+        Self {
+            client,
+            structured_output,
+        }
+    }
+
+    fn build_request(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<CreateChatCompletionRequest, LlmError> {
+        let messages = request
+            .messages
+            .into_iter()
+            .map(to_openai_message)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut built = CreateChatCompletionRequest {
+            model: request.model,
+            messages,
+            ..Default::default()
+        };
+
+        if let Some(output) = request.structured_output {
+            match self.structured_output {
+                StructuredOutputMode::JsonSchema => {
+                    built.response_format = Some(ResponseFormat::JsonSchema {
+                        json_schema: ResponseFormatJsonSchema {
+                            description: Some(output.description),
+                            name: output.name,
+                            schema: output.schema,
+                            strict: Some(true),
+                        },
+                    });
+                }
+                StructuredOutputMode::ToolCall => {
+                    built.tool_choice = Some(ChatCompletionToolChoiceOption::Function(
+                        ChatCompletionNamedToolChoice {
+                            function: FunctionName {
+                                name: output.name.clone(),
+                            },
+                        },
+                    ));
+                    built.tools = Some(vec![ChatCompletionTools::Function(ChatCompletionTool {
+                        function: FunctionObject {
+                            name: output.name,
+                            description: Some(output.description),
+                            parameters: Some(output.schema),
+                            strict: None,
+                        },
+                    })]);
+                }
+            }
+        }
+
+        Ok(built)
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiCompatibleProvider {
+    fn backend(&self) -> LlmBackend {
+        LlmBackend::OpenAI
+    }
+
+    async fn complete(&self, request: CompletionRequest) -> Result<Completion, LlmError> {
+        let request = self.build_request(request)?;
+        let response = self.client.chat().create(request).await?;
+
+        let content = response
+            .choices
+            .first()
+            .map(|choice| response_text(&choice.message))
+            .unwrap_or_default();
+        Ok(Completion {
+            content,
+            usage: response.usage.as_ref().map(token_usage),
+        })
+    }
+
+    async fn stream(&self, request: CompletionRequest) -> Result<ProviderStream, LlmError> {
+        let mut request = self.build_request(request)?;
+        request.stream_options = Some(ChatCompletionStreamOptions {
+            include_usage: Some(true),
+            include_obfuscation: None,
+        });
+        let stream = self.client.chat().create_stream(request).await?;
+
+        Ok(stream
+            .map(|chunk| -> Result<StreamChunk, LlmError> {
+                let chunk = chunk?;
+                if let Some(usage) = chunk.usage.as_ref().filter(|_| chunk.choices.is_empty()) {
+                    return Ok(StreamChunk::Usage(token_usage(usage)));
+                }
+                Ok(StreamChunk::Text(
+                    chunk
+                        .choices
+                        .first()
+                        .map(|choice| delta_text(&choice.delta))
+                        .unwrap_or_default(),
+                ))
+            })
+            .boxed())
+    }
+
+    async fn transcribe(&self, model: &str, file_path: &str) -> Result<String, LlmError> {
+        let request = CreateTranscriptionRequestArgs::default()
+            .file(file_path)
+            .model(model)
+            .response_format(AudioResponseFormat::Json)
+            .build()?;
+
+        let response = self.client.audio().transcription().create(request).await?;
+        Ok(response.text)
+    }
+}
+
+fn to_openai_message(message: ChatMessage) -> Result<ChatCompletionRequestMessage, LlmError> {
+    Ok(match message.role {
+        ChatRole::System => ChatCompletionRequestSystemMessage::from(message.content).into(),
+        ChatRole::Assistant => ChatCompletionRequestAssistantMessage::from(message.content).into(),
+        ChatRole::User if message.images.is_empty() => {
+            ChatCompletionRequestUserMessage::from(message.content).into()
+        }
+        ChatRole::User => {
+            let mut parts = Vec::with_capacity(message.images.len().saturating_add(1));
+            parts.push(
+                ChatCompletionRequestMessageContentPartTextArgs::default()
+                    .text(message.content)
+                    .build()?
+                    .into(),
+            );
+            for image in message.images {
+                parts.push(
+                    ChatCompletionRequestMessageContentPartImageArgs::default()
+                        .image_url(
+                            ImageUrlArgs::default()
+                                .url(format!("data:image/png;base64,{image}"))
+                                .detail(ImageDetail::High)
+                                .build()?,
+                        )
+                        .build()?
+                        .into(),
+                );
+            }
+            ChatCompletionRequestUserMessageArgs::default()
+                .content(parts)
+                .build()?
+                .into()
+        }
+    })
+}
+
+/// Text of a response message, preferring the arguments of a structured-output tool call.
+fn response_text(message: &ChatCompletionResponseMessage) -> String {
+    message
+        .tool_calls
+        .as_ref()
+        .and_then(|calls| {
+            calls.iter().find_map(|call| match call {
+                ChatCompletionMessageToolCalls::Function(call) => {
+                    Some(call.function.arguments.clone())
+                }
+                ChatCompletionMessageToolCalls::Custom(_) => None,
+            })
+        })
+        .or_else(|| message.content.clone())
+        .unwrap_or_default()
+}
+
+fn token_usage(usage: &CompletionUsage) -> TokenUsage {
+    TokenUsage {
+        prompt_tokens: u64::from(usage.prompt_tokens),
+        completion_tokens: u64::from(usage.completion_tokens),
+    }
+}
+
+fn delta_text(delta: &ChatCompletionStreamResponseDelta) -> String {
+    let mut text = delta.content.clone().unwrap_or_default();
+    for call in delta.tool_calls.iter().flatten() {
+        if let Some(arguments) = call
+            .function
+            .as_ref()
+            .and_then(|function| function.arguments.as_deref())
+        {
+            text.push_str(arguments);
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used)]
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::utils::llm::StructuredOutput;
+
+    fn structured_request() -> CompletionRequest {
+        CompletionRequest {
+            model: "local-model".into(),
+            messages: vec![ChatMessage::system("sys"), ChatMessage::user("hi")],
+            structured_output: Some(StructuredOutput::new(
+                "answer",
+                "An answer",
+                json!({"type": "object"}),
+            )),
+        }
+    }
+
+    #[test]
+    fn tool_call_mode_forces_the_schema_tool() {
+        let provider = OpenAiCompatibleProvider::new(
+            Arc::new(Client::with_config(OpenAIConfig::default())),
+            StructuredOutputMode::ToolCall,
+        );
+        let request = provider
+            .build_request(structured_request())
+            .expect("request");
+
+        assert!(request.response_format.is_none());
+        assert!(matches!(
+            request.tool_choice,
+            Some(ChatCompletionToolChoiceOption::Function(ref choice)) if choice.function.name == "answer"
+        ));
+        assert_eq!(request.tools.map(|tools| tools.len()), Some(1));
+    }
+
+    #[test]
+    fn json_schema_mode_sets_response_format() {
+        let provider = OpenAiCompatibleProvider::new(
+            Arc::new(Client::with_config(OpenAIConfig::default())),
+            StructuredOutputMode::JsonSchema,
+        );
+        let request = provider
+            .build_request(structured_request())
+            .expect("request");
+
+        assert!(matches!(
+            request.response_format,
+            Some(ResponseFormat::JsonSchema { .. })
+        ));
+        assert!(request.tools.is_none());
+        assert_eq!(request.messages.len(), 2);
+    }
+}
@@ -1,5 +1,7 @@
 pub mod config;
 pub mod embedding;
 pub mod ingest_limits;
+pub mod llm;
+pub mod prompt_template;
 pub mod serde_helpers;
 pub mod template_engine;
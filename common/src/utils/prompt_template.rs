@@ -0,0 +1,140 @@
+//! System prompts stored as minijinja templates and rendered per request.
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use minijinja::{Environment, UndefinedBehavior};
+use serde::Serialize;
+
+use crate::error::AppError;
+
+/// Variables available to system prompt templates.
+///
+/// Every variable is always defined, so templates can reference any of them; unknown
+/// names fail validation instead of rendering as empty text.
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptVariables {
+    /// Category of the ingested content; empty for chat.
+    pub category: String,
+    pub user: PromptUser,
+    /// Current time in the user's timezone, e.g. `2026-03-31 14:05 (Europe/Oslo)`.
+    pub now: String,
+    /// Comma-separated entity type names known for the user.
+    pub entity_types: String,
+}
+
+/// The part of the user exposed to prompt templates.
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptUser {
+    pub timezone: String,
+}
+
+impl PromptVariables {
+    #[must_use]
+    pub fn new(timezone: &str, now: DateTime<Utc>) -> Self {
+        let tz = timezone.parse::<Tz>().unwrap_or(Tz::UTC);
+        Self {
+            category: String::new(),
+            user: PromptUser {
+                timezone: tz.name().to_string(),
+            },
+            now: format!(
+                "{} ({})",
+                now.with_timezone(&tz).format("%Y-%m-%d %H:%M"),
+                tz
+            ),
+            entity_types: String::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_category(mut self, category: impl Into<String>) -> Self {
+        self.category = category.into();
+        self
+    }
+
+    #[must_use]
+    pub fn with_entity_types<'a>(mut self, names: impl IntoIterator<Item = &'a str>) -> Self {
+        self.entity_types = names.into_iter().collect::<Vec<_>>().join(", ");
+        self
+    }
+
+    /// Placeholder values used to validate and preview templates in the admin editors.
+    #[must_use]
+    pub fn sample() -> Self {
+        Self::new("UTC", Utc::now())
+            .with_category("Meeting notes")
+            .with_entity_types(["Person", "Organization", "Project"])
+    }
+}
+
+/// Renders `template` with `variables`.
+#[allow(clippy::result_large_err)]
+pub fn render_prompt(template: &str, variables: &PromptVariables) -> Result<String, AppError> {
+    let mut env = Environment::new();
+    env.set_undefined_behavior(UndefinedBehavior::Strict);
+    env.render_str(template, variables)
+        .map_err(|err| AppError::Validation(format!("invalid prompt template: {err}")))
+}
+
+/// Checks that `template` parses and only uses known variables; `name` labels the error.
+#[allow(clippy::result_large_err)]
+pub fn validate_prompt(name: &str, template: &str) -> Result<(), AppError> {
+    render_prompt(template, &PromptVariables::sample())
+        .map(|_| ())
+        .map_err(|err| match err {
+            AppError::Validation(message) => AppError::Validation(format!("{name}: {message}")),
+            other => other,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used)]
+
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn renders_variables_in_the_users_timezone() {
+        let now = Utc
+            .with_ymd_and_hms(2026, 3, 31, 12, 5, 0)
+            .single()
+            .expect("valid time");
+        let variables = PromptVariables::new("Europe/Oslo", now)
+            .with_category("Papers")
+            .with_entity_types(["Person", "Method"]);
+
+        let rendered = render_prompt(
+            "{{ category }} | {{ user.timezone }} | {{ now }} | {{ entity_types }}{% if category == 'Papers' %} | cite{% endif %}",
+            &variables,
+        )
+        .expect("render");
+        assert_eq!(
+            rendered,
+            "Papers | Europe/Oslo | 2026-03-31 14:05 (Europe/Oslo) | Person, Method | cite"
+        );
+    }
+
+    #[test]
+    fn plain_prompts_and_json_braces_render_unchanged() {
+        let prompt = "Return {\n\"key\": \"value\"\n} only.";
+        assert_eq!(
+            render_prompt(prompt, &PromptVariables::sample()).expect("render"),
+            prompt
+        );
+    }
+
+    #[test]
+    fn validation_rejects_unknown_variables_and_syntax_errors() {
+        assert!(validate_prompt("prompt", "Hello {{ categroy }}").is_err());
+        assert!(validate_prompt("prompt", "{% if category %}unterminated").is_err());
+        assert!(validate_prompt("prompt", "Now: {{ now }}").is_ok());
+    }
+
+    #[test]
+    fn unknown_timezones_fall_back_to_utc() {
+        let variables = PromptVariables::new("Not/AZone", Utc::now());
+        assert_eq!(variables.user.timezone, "UTC");
+    }
+}
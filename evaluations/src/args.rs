@@ -94,6 +94,10 @@ pub struct RetrievalSettings {
     #[arg(long)]
     pub chunk_rrf_use_fts: Option<bool>,
 
+    /// Enable the graph expansion stage
+    #[arg(long = "graph-expansion", action = clap::ArgAction::SetTrue, default_value_t = false)]
+    pub graph_expansion: bool,
+
     /// Require verified chunks (disable with --llm-mode)
     #[arg(skip = true)]
     pub require_verified_chunks: bool,
@@ -114,6 +118,7 @@ impl Default for RetrievalSettings {
             chunk_rrf_fts_weight: None,
             chunk_rrf_use_vector: None,
             chunk_rrf_use_fts: None,
+            graph_expansion: false,
             require_verified_chunks: true,
         }
     }
@@ -156,6 +161,10 @@ pub struct IngestConfig {
     /// Delete cached paragraph shards before rebuilding the ingestion corpus
     #[arg(long)]
     pub slice_reset_ingestion: bool,
+
+    /// Reuse LLM analysis responses cached in this directory across ingestion runs
+    #[arg(long)]
+    pub llm_cache_dir: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Args)]
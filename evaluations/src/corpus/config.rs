@@ -9,6 +9,7 @@ pub struct CorpusCacheConfig {
     pub refresh_embeddings_only: bool,
     pub ingestion_batch_size: usize,
     pub ingestion_max_retries: usize,
+    pub llm_cache_dir: Option<PathBuf>,
 }
 
 impl From<&Config> for CorpusCacheConfig {
@@ -19,6 +20,7 @@ impl From<&Config> for CorpusCacheConfig {
             refresh_embeddings_only: config.ingest.refresh_embeddings_only,
             ingestion_batch_size: config.ingest.ingestion_batch_size,
             ingestion_max_retries: config.ingest.ingestion_max_retries,
+            llm_cache_dir: config.ingest.llm_cache_dir.clone(),
         }
     }
 }
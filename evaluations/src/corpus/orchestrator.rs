@@ -15,7 +15,10 @@ use common::{
         store::{DynStorage, StorageManager},
         types::{StoredObject, ingestion_payload::IngestionPayload, ingestion_task::IngestionTask},
     },
-    utils::config::{AppConfig, StorageKind},
+    utils::{
+        config::{AppConfig, StorageKind},
+        llm::{LlmProviders, ResponseCache},
+    },
 };
 use futures::future::try_join_all;
 use ingestion_pipeline::{IngestionConfig, IngestionPipeline};
@@ -254,11 +257,12 @@ pub async fn ensure_corpus(
             embedding_dimension,
             cache.ingestion_batch_size,
             cache.ingestion_max_retries,
+            cache.llm_cache_dir.as_deref(),
             ingestion_config.clone(),
         )
         .await
         .context("ingesting missing slice paragraphs")?;
-        for (request, shard) in ingest_requests.into_iter().zip(new_shards.into_iter()) {
+        for (request, shard) in ingest_requests.into_iter().zip(new_shards) {
             store.persist(&shard)?;
             records[request.slot] = Some(ParagraphShardRecord {
                 shard,
@@ -436,6 +440,7 @@ async fn ingest_paragraph_batch(
     embedding_dimension: usize,
     batch_size: usize,
     max_retries: usize,
+    llm_cache_dir: Option<&Path>,
     ingestion_config: IngestionConfig,
 ) -> Result<Vec<ParagraphShard>> {
     if targets.is_empty() {
@@ -455,9 +460,15 @@ async fn ingest_paragraph_batch(
     let storage = StorageManager::with_backend(backend, StorageKind::Memory);
 
     let pipeline_config = ingestion_config.clone();
+    let mut llm_providers = LlmProviders::from_config(&app_config, Arc::clone(&openai));
+    if let Some(dir) = llm_cache_dir {
+        llm_providers =
+            llm_providers.with_response_cache(Arc::new(ResponseCache::on_disk(dir, None)));
+    }
+    let llm_providers = Arc::new(llm_providers);
     let pipeline = IngestionPipeline::new_with_config(
         db,
-        Arc::clone(&openai),
+        llm_providers,
         app_config,
         None::<Arc<retrieval_pipeline::reranking::RerankerPool>>,
         storage,
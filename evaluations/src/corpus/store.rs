@@ -490,6 +490,9 @@ pub async fn seed_manifest_into_db(db: &SurrealDbClient, manifest: &CorpusManife
                 entities: paragraph.entities.clone(),
                 relationships: paragraph.relationships.clone(),
                 chunks: paragraph.chunks.clone(),
+                mentions: Vec::new(),
+                action_items: Vec::new(),
+                suggestions: Vec::new(),
             };
 
             persist_artifacts(db, &tuning, embedding_dimensions, artifacts)
@@ -508,6 +511,7 @@ pub async fn seed_manifest_into_db(db: &SurrealDbClient, manifest: &CorpusManife
                 "BEGIN TRANSACTION;
                  DELETE text_chunk_embedding;
                  DELETE knowledge_entity_embedding;
+                 DELETE knowledge_entity_mention;
                  DELETE relates_to;
                  DELETE text_chunk;
                  DELETE knowledge_entity;
@@ -547,6 +551,8 @@ mod tests {
             context: None,
             category: "test".to_string(),
             user_id: user_id.clone(),
+            summary: None,
+            tags: Vec::new(),
         };
 
         let entity = KnowledgeEntity {
@@ -559,6 +565,7 @@ mod tests {
             entity_type: KnowledgeEntityType::Document,
             metadata: None,
             user_id: user_id.clone(),
+            tags: Vec::new(),
         };
         let relationship = KnowledgeRelationship::new(
             format!("knowledge_entity:{}", entity.id),
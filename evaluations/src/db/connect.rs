@@ -222,6 +222,9 @@ pub(crate) async fn ensure_eval_user(db: &SurrealDbClient) -> Result<User> {
         admin: false,
         timezone: "UTC".to_string(),
         theme: Theme::System,
+        review_all_suggestions: false,
+        review_categories: Vec::new(),
+        query_system_prompt: None,
     };
 
     if let Some(existing) = db.get_item::<User>(user.id()).await? {
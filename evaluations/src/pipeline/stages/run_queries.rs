@@ -75,6 +75,7 @@ pub(crate) async fn run_queries(ctx: &mut EvaluationContext<'_>) -> anyhow::Resu
     if let Some(value) = config.retrieval.max_chunks_per_entity {
         retrieval_config.tuning.max_chunks_per_entity = value;
     }
+    retrieval_config.expand_graph = config.retrieval.graph_expansion;
 
     let active_tuning = retrieval_config.tuning.clone();
     let effective_chunk_vector = config
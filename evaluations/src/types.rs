@@ -201,7 +201,7 @@ pub struct EvaluationCandidate {
 
 impl EvaluationCandidate {
     fn from_entity(entity: &RetrievedEntity) -> Self {
-        let entity_category = Some(format!("{:?}", entity.entity.entity_type));
+        let entity_category = Some(entity.entity.entity_type.to_string());
         Self {
             entity_id: entity.entity.id().to_string(),
             source_id: entity.entity.source_id.clone(),
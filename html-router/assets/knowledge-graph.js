@@ -94,8 +94,13 @@
 	}
 
 	function buildColorMaps(nodes, links) {
+		const typeColor = buildMap(nodes.map((n) => n.entity_type));
+		// User-defined entity types carry their own color.
+		nodes.forEach((n) => {
+			if (n.type_color) typeColor.set(n.entity_type, n.type_color);
+		});
 		return {
-			typeColor: buildMap(nodes.map((n) => n.entity_type)),
+			typeColor,
 			relColor: linkColorMap(links.map((l) => l.relationship_type)),
 		};
 	}
@@ -107,9 +112,11 @@
 	async function loadGraphData(container) {
 		const et = container.dataset.entityType || "";
 		const cc = container.dataset.contentCategory || "";
+		const tag = container.dataset.tag || "";
 		const qs = new URLSearchParams();
 		if (et) qs.set("entity_type", et);
 		if (cc) qs.set("content_category", cc);
+		if (tag) qs.set("tag", tag);
 
 		const url =
 			"/knowledge/graph.json" + (qs.toString() ? "?" + qs.toString() : "");
@@ -192,15 +199,23 @@
 			.data(links)
 			.join("path")
 			.attr("stroke", (d) => relColor.get(d.relationship_type) || "#CBD5E1")
-			.attr("stroke-width", 1.5)
+			.attr("stroke-width", linkWidth)
 			.attr("marker-end", (d) =>
-				markerFor(
-					d.relationship_type || "rel",
-					relColor.get(d.relationship_type) || "#CBD5E1",
-				),
+				d.directed === false
+					? null
+					: markerFor(
+							d.relationship_type || "rel",
+							relColor.get(d.relationship_type) || "#CBD5E1",
+						),
 			);
 	}
 
+	// Heavier relationships draw thicker; weights range from 0 to 1.
+	function linkWidth(d) {
+		const w = typeof d.weight === "number" ? d.weight : 1;
+		return 0.75 + 1.5 * Math.max(0, Math.min(1, w));
+	}
+
 	function drawLinkLabels(g, links) {
 		return g
 			.append("g")
@@ -306,6 +321,7 @@
 						: 0.05;
 				})
 				.attr("marker-end", (d) => {
+					if (d.directed === false) return null;
 					const c = relColor.get(d.relationship_type) || "#CBD5E1";
 					return markerFor(d.relationship_type || "rel", c);
 				});
@@ -1,6 +1,7 @@
 use common::storage::types::conversation::SidebarConversation;
 use common::storage::{db::SurrealDbClient, store::StorageManager};
 use common::utils::embedding::EmbeddingProvider;
+use common::utils::llm::LlmProviders;
 use common::utils::template_engine::{ProvidesTemplateEngine, TemplateEngine};
 use common::{create_template_engine, storage::db::ProvidesDb, utils::config::AppConfig};
 use retrieval_pipeline::reranking::RerankerPool;
@@ -20,6 +21,7 @@ use crate::{OpenAIClientType, SessionStoreType};
 pub struct HtmlState {
     pub db: Arc<SurrealDbClient>,
     pub openai_client: Arc<OpenAIClientType>,
+    pub llm_providers: Arc<LlmProviders>,
     pub templates: Arc<TemplateEngine>,
     pub session_store: Arc<SessionStoreType>,
     pub config: AppConfig,
@@ -43,6 +45,7 @@ const CONVERSATION_ARCHIVE_CACHE_CLEANUP_WRITE_INTERVAL: usize = 64;
 pub struct StateResources {
     pub db: Arc<SurrealDbClient>,
     pub openai_client: Arc<OpenAIClientType>,
+    pub llm_providers: Arc<LlmProviders>,
     pub session_store: Arc<SessionStoreType>,
     pub storage: StorageManager,
     pub config: AppConfig,
@@ -61,6 +64,7 @@ impl HtmlState {
         Self {
             db: resources.db,
             openai_client: resources.openai_client,
+            llm_providers: resources.llm_providers,
             templates,
             session_store: resources.session_store,
             config: resources.config,
@@ -214,9 +218,14 @@ mod tests {
             EmbeddingProvider::new_hashed(8).expect("Failed to create embedding provider"),
         );
 
+        let openai_client = Arc::new(async_openai::Client::new());
         HtmlState::new_with_resources(StateResources {
             db,
-            openai_client: Arc::new(async_openai::Client::new()),
+            llm_providers: Arc::new(LlmProviders::from_config(
+                &config,
+                Arc::clone(&openai_client),
+            )),
+            openai_client,
             session_store,
             storage,
             config,
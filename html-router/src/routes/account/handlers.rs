@@ -9,7 +9,10 @@ use crate::{
         response_middleware::{TemplateResponse, TemplateResult},
     },
 };
-use common::storage::types::user::{Theme, User};
+use common::storage::types::{
+    llm_usage::{LlmUsage, USAGE_REPORT_DAYS, UsageReport},
+    user::{Theme, User},
+};
 
 use crate::html_state::HtmlState;
 
@@ -23,12 +26,31 @@ pub struct AccountPageData {
     selected_timezone: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     selected_theme: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    suggestion_review: Option<SuggestionReviewData>,
+    /// The user's query prompt override; empty when they use the admin default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    query_prompt: Option<String>,
+    /// The user's own LLM usage over the last `usage_days` days.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    usage: Option<UsageReport>,
+    usage_days: i64,
+}
+
+#[derive(Serialize)]
+pub struct SuggestionReviewData {
+    review_all: bool,
+    review_categories: Vec<String>,
+    /// The user's existing categories, as a hint.
+    categories: Vec<String>,
 }
 
 pub async fn show_account_page(
     RequireUser(user): RequireUser,
-    State(_state): State<HtmlState>,
+    State(state): State<HtmlState>,
 ) -> TemplateResult {
+    let categories = User::get_user_categories(&user.id, &state.db).await?;
+    let usage = LlmUsage::recent_report(&state.db, Some(&user.id)).await?;
     let timezones = TZ_VARIANTS
         .iter()
         .map(std::string::ToString::to_string)
@@ -49,6 +71,14 @@ pub async fn show_account_page(
             api_key: user.api_key,
             selected_timezone: None,
             selected_theme: None,
+            suggestion_review: Some(SuggestionReviewData {
+                review_all: user.review_all_suggestions,
+                review_categories: user.review_categories,
+                categories,
+            }),
+            query_prompt: Some(user.query_system_prompt.unwrap_or_default()),
+            usage: Some(usage),
+            usage_days: USAGE_REPORT_DAYS,
         },
     ))
 }
@@ -74,6 +104,10 @@ pub async fn set_api_key(
             api_key: Some(api_key),
             selected_timezone: None,
             selected_theme: None,
+            suggestion_review: None,
+            query_prompt: None,
+            usage: None,
+            usage_days: USAGE_REPORT_DAYS,
         },
     ))
 }
@@ -123,6 +157,10 @@ pub async fn update_timezone(
             api_key: None,
             selected_timezone: Some(form.timezone),
             selected_theme: None,
+            suggestion_review: None,
+            query_prompt: None,
+            usage: None,
+            usage_days: USAGE_REPORT_DAYS,
         },
     ))
 }
@@ -160,6 +198,97 @@ pub async fn update_theme(
             api_key: None,
             selected_timezone: None,
             selected_theme: Some(form.theme),
+            suggestion_review: None,
+            query_prompt: None,
+            usage: None,
+            usage_days: USAGE_REPORT_DAYS,
+        },
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct UpdateSuggestionReviewForm {
+    /// Checkbox value; present when every suggestion should be reviewed.
+    review_all: Option<String>,
+    /// Comma-separated category names.
+    #[serde(default)]
+    review_categories: String,
+}
+
+pub async fn update_suggestion_review(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+    auth: AuthSessionType,
+    Form(form): Form<UpdateSuggestionReviewForm>,
+) -> TemplateResult {
+    let review_all = form.review_all.is_some();
+    let categories: Vec<String> = form
+        .review_categories
+        .split(',')
+        .map(ToString::to_string)
+        .collect();
+    User::update_suggestion_review(&user.id, review_all, &categories, &state.db).await?;
+
+    // Clear the cache
+    auth.cache_clear_user(user.id.clone());
+
+    let updated = state
+        .db
+        .get_item::<User>(&user.id)
+        .await?
+        .map_or_else(Vec::new, |updated| updated.review_categories);
+
+    Ok(TemplateResponse::new_partial(
+        "auth/account_settings.html",
+        "suggestion_review_section",
+        AccountPageData {
+            timezones: vec![],
+            theme_options: vec![],
+            api_key: None,
+            selected_timezone: None,
+            selected_theme: None,
+            suggestion_review: Some(SuggestionReviewData {
+                review_all,
+                review_categories: updated,
+                categories: User::get_user_categories(&user.id, &state.db).await?,
+            }),
+            query_prompt: None,
+            usage: None,
+            usage_days: USAGE_REPORT_DAYS,
+        },
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct UpdateQueryPromptForm {
+    #[serde(default)]
+    query_system_prompt: String,
+}
+
+pub async fn update_query_prompt(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+    auth: AuthSessionType,
+    Form(form): Form<UpdateQueryPromptForm>,
+) -> TemplateResult {
+    let prompt = User::update_query_prompt(&user.id, &form.query_system_prompt, &state.db).await?;
+
+    // Clear the cache
+    auth.cache_clear_user(user.id.clone());
+
+    Ok(TemplateResponse::new_partial(
+        "auth/account_settings.html",
+        "query_prompt_section",
+        AccountPageData {
+            timezones: vec![],
+            theme_options: vec![],
+            api_key: None,
+            selected_timezone: None,
+            selected_theme: None,
+            suggestion_review: None,
+            query_prompt: Some(prompt.unwrap_or_default()),
+            usage: None,
+            usage_days: USAGE_REPORT_DAYS,
         },
     ))
 }
@@ -17,6 +17,11 @@ where
         .route("/set-api-key", post(handlers::set_api_key))
         .route("/update-timezone", patch(handlers::update_timezone))
         .route("/update-theme", patch(handlers::update_theme))
+        .route(
+            "/update-suggestion-review",
+            patch(handlers::update_suggestion_review),
+        )
+        .route("/update-chat-prompt", patch(handlers::update_query_prompt))
         .route(
             "/change-password",
             get(handlers::show_change_password).patch(handlers::change_password),
@@ -1,33 +1,45 @@
 use async_openai::types::models::ListModelResponse;
 use axum::{
     Form,
-    extract::{Query, State},
+    extract::{Path, Query, State},
 };
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use common::{
     error::AppError,
     storage::types::{
         analytics::Analytics,
+        ingestion_task::IngestionTask,
+        llm_usage::{LlmUsage, USAGE_REPORT_DAYS, UsageReport},
+        settings_version::{SETTINGS_HISTORY_LIMIT, SettingsVersion},
         system_prompts::{
             DEFAULT_IMAGE_PROCESSING_PROMPT, DEFAULT_INGRESS_ANALYSIS_SYSTEM_PROMPT,
             DEFAULT_QUERY_SYSTEM_PROMPT,
         },
-        system_settings::{SystemSettings, SystemSettingsPatch},
+        system_settings::{
+            CategoryModelRoutes, CategoryPrompt, ModelPrice, ModelRoute, SystemSettings,
+            SystemSettingsPatch,
+        },
+        user::User,
     },
     utils::{
-        config::AppConfig,
+        config::{AppConfig, LlmBackend},
         embedding::{
             EmbeddingBackend, FastEmbedModelOption, fastembed_model_dimension,
             is_valid_fastembed_model_code, list_fastembed_embedding_models,
         },
+        prompt_template::{PromptVariables, render_prompt},
     },
 };
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::{
     html_state::HtmlState,
-    middlewares::response_middleware::{TemplateResponse, TemplateResult},
+    middlewares::{
+        auth_middleware::RequireUser,
+        response_middleware::{TemplateResponse, TemplateResult},
+    },
 };
 
 #[derive(Serialize)]
@@ -38,18 +50,48 @@ pub struct AdminPanelData {
     default_query_prompt: String,
     default_image_prompt: String,
     available_models: Option<ListModelResponse>,
+    llm_backends: Vec<&'static str>,
+    model_routes: ModelRoutesText,
     fastembed_models: Option<Vec<FastEmbedModelOption>>,
     fastembed_model_locked_by_config: bool,
     effective_embedding_backend: String,
+    usage: Option<UsageReport>,
+    usage_days: i64,
+    model_prices: String,
+    history: Option<Vec<SettingsVersionView>>,
     current_section: AdminSection,
 }
 
+/// Fallback and category route lists as edited in the model settings textareas.
+#[derive(Serialize)]
+pub struct ModelRoutesText {
+    query: String,
+    processing: String,
+    image_processing: String,
+    voice_processing: String,
+    categories: String,
+}
+
+impl ModelRoutesText {
+    fn from_settings(settings: &SystemSettings) -> Self {
+        Self {
+            query: ModelRoute::format_list(&settings.query_fallbacks),
+            processing: ModelRoute::format_list(&settings.processing_fallbacks),
+            image_processing: ModelRoute::format_list(&settings.image_processing_fallbacks),
+            voice_processing: ModelRoute::format_list(&settings.voice_processing_fallbacks),
+            categories: CategoryModelRoutes::format_lines(&settings.processing_category_routes),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum AdminSection {
     #[default]
     Overview,
     Models,
+    Usage,
+    History,
 }
 
 #[derive(Deserialize)]
@@ -63,6 +105,8 @@ pub async fn show_admin_panel(
 ) -> TemplateResult {
     let section = match query.section.as_deref() {
         Some("models") => AdminSection::Models,
+        Some("usage") => AdminSection::Usage,
+        Some("history") => AdminSection::History,
         _ => AdminSection::Overview,
     };
 
@@ -80,14 +124,7 @@ pub async fn show_admin_panel(
 
     let (available_models, fastembed_models, fastembed_model_locked_by_config) =
         if section == AdminSection::Models {
-            let available_models = Some(
-                state
-                    .openai_client
-                    .models()
-                    .list()
-                    .await
-                    .map_err(|e| AppError::InternalError(e.to_string()))?,
-            );
+            let available_models = list_openai_models(&state).await;
             let fastembed_models = is_fastembed_admin_context(&settings, &state.config)
                 .then(list_fastembed_embedding_models);
             let fastembed_model_locked_by_config = state.config.fastembed_model.is_some();
@@ -100,6 +137,18 @@ pub async fn show_admin_panel(
             (None, None, false)
         };
 
+    let usage = if section == AdminSection::Usage {
+        Some(usage_report_by_email(&state).await?)
+    } else {
+        None
+    };
+
+    let history = if section == AdminSection::History {
+        Some(settings_history(&state).await?)
+    } else {
+        None
+    };
+
     let effective_backend = effective_embedding_backend(&settings, &state.config)
         .as_str()
         .to_string();
@@ -107,9 +156,15 @@ pub async fn show_admin_panel(
     Ok(TemplateResponse::new_template(
         "admin/base.html",
         AdminPanelData {
+            model_routes: ModelRoutesText::from_settings(&settings),
+            model_prices: ModelPrice::format_lines(&settings.model_prices),
+            usage,
+            usage_days: USAGE_REPORT_DAYS,
+            history,
             settings,
             analytics,
             available_models,
+            llm_backends: llm_backend_options(),
             fastembed_models,
             fastembed_model_locked_by_config,
             effective_embedding_backend: effective_backend,
@@ -121,6 +176,120 @@ pub async fn show_admin_panel(
     ))
 }
 
+/// Usage of all users over the report window, with users labelled by email.
+async fn usage_report_by_email(state: &HtmlState) -> Result<UsageReport, AppError> {
+    let mut report = LlmUsage::recent_report(&state.db, None).await?;
+
+    let user_ids: Vec<String> = report.per_user.iter().map(|row| row.key.clone()).collect();
+    let emails = User::emails_by_id(&user_ids, &state.db).await?;
+    for row in &mut report.per_user {
+        if let Some(email) = emails.get(&row.key) {
+            row.key.clone_from(email);
+        }
+    }
+    Ok(report)
+}
+
+/// A settings version as shown in the admin history.
+#[derive(Serialize)]
+pub struct SettingsVersionView {
+    version: u64,
+    created_at: DateTime<Utc>,
+    author: Option<String>,
+    rollback_of: Option<u64>,
+    changes: Vec<SettingChangeView>,
+}
+
+/// A changed setting with both values rendered as text.
+#[derive(Serialize)]
+pub struct SettingChangeView {
+    field: String,
+    before: String,
+    after: String,
+}
+
+impl From<SettingsVersion> for SettingsVersionView {
+    fn from(version: SettingsVersion) -> Self {
+        Self {
+            version: version.version,
+            created_at: version.created_at,
+            author: version.author,
+            rollback_of: version.rollback_of,
+            changes: version
+                .changes
+                .into_iter()
+                .map(|change| SettingChangeView {
+                    field: change.field,
+                    before: setting_value_text(&change.before),
+                    after: setting_value_text(&change.after),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Strings as they are, so prompt edits read as text; everything else as JSON.
+fn setting_value_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Null => String::new(),
+        other => serde_json::to_string_pretty(other).unwrap_or_else(|_| other.to_string()),
+    }
+}
+
+async fn settings_history(state: &HtmlState) -> Result<Vec<SettingsVersionView>, AppError> {
+    Ok(
+        SettingsVersion::list_recent(&state.db, SETTINGS_HISTORY_LIMIT)
+            .await?
+            .into_iter()
+            .map(SettingsVersionView::from)
+            .collect(),
+    )
+}
+
+#[derive(Serialize)]
+pub struct SettingsHistoryData {
+    history: Vec<SettingsVersionView>,
+    rolled_back: Option<u64>,
+}
+
+/// Restores the settings as they were before `version` and re-renders the history.
+pub async fn rollback_settings(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+    Path(version): Path<u64>,
+) -> TemplateResult {
+    SettingsVersion::rollback(&state.db, version, Some(&user.email)).await?;
+    info!(version, admin = %user.email, "Admin rolled back system settings");
+
+    Ok(TemplateResponse::new_partial(
+        "admin/sections/history.html",
+        "settings_history",
+        SettingsHistoryData {
+            history: settings_history(&state).await?,
+            rolled_back: Some(version),
+        },
+    ))
+}
+
+/// Models offered by the OpenAI-compatible endpoint; other backends' models are typed in.
+async fn list_openai_models(state: &HtmlState) -> Option<ListModelResponse> {
+    match state.openai_client.models().list().await {
+        Ok(models) => Some(models),
+        Err(err) => {
+            warn!(error = %err, "Failed to list models from the OpenAI-compatible endpoint");
+            None
+        }
+    }
+}
+
+fn llm_backend_options() -> Vec<&'static str> {
+    LlmBackend::ALL
+        .iter()
+        .map(|backend| backend.as_str())
+        .collect()
+}
+
 fn checkbox_to_bool<'de, D>(deserializer: D) -> Result<bool, D::Error>
 where
     D: serde::Deserializer<'de>,
@@ -142,13 +311,14 @@ pub struct RegistrationToggleData {
 
 pub async fn toggle_registration_status(
     State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
     Form(input): Form<RegistrationToggleInput>,
 ) -> TemplateResult {
     let new_settings = SystemSettingsPatch {
         registrations_enabled: Some(input.registration_open),
         ..Default::default()
     }
-    .apply(&state.db)
+    .apply_as(&state.db, &user.email)
     .await?;
 
     Ok(TemplateResponse::new_partial(
@@ -160,12 +330,106 @@ pub async fn toggle_registration_status(
     ))
 }
 
+#[derive(Serialize)]
+pub struct ReingestAllData {
+    queued: usize,
+    already_queued: usize,
+}
+
+pub async fn reingest_all_content(State(state): State<HtmlState>) -> TemplateResult {
+    let outcomes = IngestionTask::create_reingest_for_all(&state.db).await?;
+    let queued = outcomes
+        .iter()
+        .filter(|outcome| outcome.is_created())
+        .count();
+
+    info!(
+        queued,
+        total = outcomes.len(),
+        "Admin queued bulk re-ingest"
+    );
+
+    Ok(TemplateResponse::new_partial(
+        "admin/sections/overview.html",
+        "reingest_status",
+        ReingestAllData {
+            queued,
+            already_queued: outcomes.len().saturating_sub(queued),
+        },
+    ))
+}
+
+#[derive(Serialize)]
+pub struct PurgeCacheData {
+    purged: Option<u64>,
+}
+
+pub async fn purge_llm_cache(State(state): State<HtmlState>) -> TemplateResult {
+    let purged = state.llm_providers.purge_response_cache().await?;
+    if let Some(purged) = purged {
+        info!(purged, "Admin purged LLM response cache");
+    }
+
+    Ok(TemplateResponse::new_partial(
+        "admin/sections/overview.html",
+        "llm_cache_status",
+        PurgeCacheData { purged },
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct ModelPricesInput {
+    model_prices: String,
+}
+
+#[derive(Serialize)]
+pub struct ModelPricesData {
+    model_prices: String,
+}
+
+pub async fn update_model_prices(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+    Form(input): Form<ModelPricesInput>,
+) -> TemplateResult {
+    let new_settings = SystemSettingsPatch {
+        model_prices: Some(ModelPrice::parse_lines(&input.model_prices)?),
+        ..Default::default()
+    }
+    .apply_as(&state.db, &user.email)
+    .await?;
+
+    Ok(TemplateResponse::new_partial(
+        "admin/sections/usage.html",
+        "model_prices_form",
+        ModelPricesData {
+            model_prices: ModelPrice::format_lines(&new_settings.model_prices),
+        },
+    ))
+}
+
 #[derive(Deserialize)]
 pub struct ModelSettingsInput {
     query_model: String,
     processing_model: String,
     image_processing_model: String,
     voice_processing_model: String,
+    #[serde(default)]
+    query_backend: Option<LlmBackend>,
+    #[serde(default)]
+    processing_backend: Option<LlmBackend>,
+    #[serde(default)]
+    image_processing_backend: Option<LlmBackend>,
+    #[serde(default)]
+    query_fallbacks: Option<String>,
+    #[serde(default)]
+    processing_fallbacks: Option<String>,
+    #[serde(default)]
+    image_processing_fallbacks: Option<String>,
+    #[serde(default)]
+    voice_processing_fallbacks: Option<String>,
+    #[serde(default)]
+    processing_category_routes: Option<String>,
     embedding_model: Option<String>,
     embedding_dimensions: Option<u32>,
 }
@@ -173,7 +437,9 @@ pub struct ModelSettingsInput {
 #[derive(Serialize)]
 pub struct ModelSettingsData {
     settings: SystemSettings,
-    available_models: ListModelResponse,
+    available_models: Option<ListModelResponse>,
+    llm_backends: Vec<&'static str>,
+    model_routes: ModelRoutesText,
     fastembed_models: Option<Vec<FastEmbedModelOption>>,
     fastembed_model_locked_by_config: bool,
     effective_embedding_backend: String,
@@ -272,21 +538,35 @@ fn plan_embedding_settings_update(
 
 pub async fn update_model_settings(
     State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
     Form(input): Form<ModelSettingsInput>,
 ) -> TemplateResult {
     let current_settings = SystemSettings::get_current(&state.db).await?;
     let embedding_plan = plan_embedding_settings_update(&current_settings, &input, &state.config)?;
 
+    let parse_routes = |text: Option<&str>| text.map(ModelRoute::parse_list).transpose();
     let new_settings = SystemSettingsPatch {
+        query_fallbacks: parse_routes(input.query_fallbacks.as_deref())?,
+        processing_fallbacks: parse_routes(input.processing_fallbacks.as_deref())?,
+        image_processing_fallbacks: parse_routes(input.image_processing_fallbacks.as_deref())?,
+        voice_processing_fallbacks: parse_routes(input.voice_processing_fallbacks.as_deref())?,
+        processing_category_routes: input
+            .processing_category_routes
+            .as_deref()
+            .map(CategoryModelRoutes::parse_lines)
+            .transpose()?,
         query_model: Some(input.query_model),
         processing_model: Some(input.processing_model),
         image_processing_model: Some(input.image_processing_model),
         voice_processing_model: Some(input.voice_processing_model),
+        query_backend: input.query_backend,
+        processing_backend: input.processing_backend,
+        image_processing_backend: input.image_processing_backend,
         embedding_model: Some(embedding_plan.embedding_model),
         embedding_dimensions: Some(embedding_plan.embedding_dimensions),
         ..Default::default()
     }
-    .apply(&state.db)
+    .apply_as(&state.db, &user.email)
     .await?;
 
     if embedding_plan.reembedding_needed {
@@ -302,12 +582,7 @@ pub async fn update_model_settings(
         );
     }
 
-    let available_models = state
-        .openai_client
-        .models()
-        .list()
-        .await
-        .map_err(|_e| AppError::InternalError("Failed to get models".to_string()))?;
+    let available_models = list_openai_models(&state).await;
 
     let effective_backend = effective_embedding_backend(&new_settings, &state.config)
         .as_str()
@@ -319,8 +594,10 @@ pub async fn update_model_settings(
         "admin/sections/models.html",
         "model_settings_form",
         ModelSettingsData {
+            model_routes: ModelRoutesText::from_settings(&new_settings),
             settings: new_settings,
             available_models,
+            llm_backends: llm_backend_options(),
             fastembed_models: show_fastembed_models,
             fastembed_model_locked_by_config: state.config.fastembed_model.is_some(),
             effective_embedding_backend: effective_backend,
@@ -350,6 +627,16 @@ mod tests {
             image_processing_model: "gpt-4o-mini".into(),
             image_processing_prompt: "p".into(),
             voice_processing_model: "whisper-1".into(),
+            query_backend: LlmBackend::OpenAI,
+            processing_backend: LlmBackend::OpenAI,
+            image_processing_backend: LlmBackend::OpenAI,
+            query_fallbacks: Vec::new(),
+            processing_fallbacks: Vec::new(),
+            image_processing_fallbacks: Vec::new(),
+            voice_processing_fallbacks: Vec::new(),
+            processing_category_routes: Vec::new(),
+            ingestion_category_prompts: Vec::new(),
+            model_prices: Vec::new(),
             last_index_rebuild_at: None,
             index_rebuild_lease_owner: None,
             index_rebuild_lease_expires_at: None,
@@ -369,6 +656,14 @@ mod tests {
             processing_model: current.processing_model.clone(),
             image_processing_model: current.image_processing_model.clone(),
             voice_processing_model: current.voice_processing_model.clone(),
+            query_backend: None,
+            processing_backend: None,
+            image_processing_backend: None,
+            query_fallbacks: None,
+            processing_fallbacks: None,
+            image_processing_fallbacks: None,
+            voice_processing_fallbacks: None,
+            processing_category_routes: None,
             embedding_model: Some("Xenova/bge-base-en-v1.5".into()),
             embedding_dimensions: None,
         };
@@ -391,6 +686,14 @@ mod tests {
             processing_model: current.processing_model.clone(),
             image_processing_model: current.image_processing_model.clone(),
             voice_processing_model: current.voice_processing_model.clone(),
+            query_backend: None,
+            processing_backend: None,
+            image_processing_backend: None,
+            query_fallbacks: None,
+            processing_fallbacks: None,
+            image_processing_fallbacks: None,
+            voice_processing_fallbacks: None,
+            processing_category_routes: None,
             embedding_model: Some("Xenova/bge-large-en-v1.5".into()),
             embedding_dimensions: None,
         };
@@ -435,13 +738,14 @@ pub struct SystemPromptSectionData {
 
 pub async fn patch_query_prompt(
     State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
     Form(input): Form<SystemPromptUpdateInput>,
 ) -> TemplateResult {
     let new_settings = SystemSettingsPatch {
         query_system_prompt: Some(input.query_system_prompt),
         ..Default::default()
     }
-    .apply(&state.db)
+    .apply_as(&state.db, &user.email)
     .await?;
 
     Ok(TemplateResponse::new_partial(
@@ -478,13 +782,14 @@ pub struct IngestionPromptUpdateInput {
 
 pub async fn patch_ingestion_prompt(
     State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
     Form(input): Form<IngestionPromptUpdateInput>,
 ) -> TemplateResult {
     let new_settings = SystemSettingsPatch {
         ingestion_system_prompt: Some(input.ingestion_system_prompt),
         ..Default::default()
     }
-    .apply(&state.db)
+    .apply_as(&state.db, &user.email)
     .await?;
 
     Ok(TemplateResponse::new_partial(
@@ -521,13 +826,94 @@ pub struct ImagePromptUpdateInput {
 
 pub async fn patch_image_prompt(
     State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
     Form(input): Form<ImagePromptUpdateInput>,
 ) -> TemplateResult {
     let new_settings = SystemSettingsPatch {
         image_processing_prompt: Some(input.image_processing_prompt),
         ..Default::default()
     }
-    .apply(&state.db)
+    .apply_as(&state.db, &user.email)
+    .await?;
+
+    Ok(TemplateResponse::new_partial(
+        "admin/sections/overview.html",
+        "system_prompt_section",
+        SystemPromptSectionData {
+            settings: new_settings,
+        },
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct CategoryPromptQuery {
+    #[serde(default)]
+    category: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct CategoryPromptEditData {
+    settings: SystemSettings,
+    category: String,
+    prompt: String,
+}
+
+pub async fn show_edit_category_prompt(
+    State(state): State<HtmlState>,
+    Query(query): Query<CategoryPromptQuery>,
+) -> TemplateResult {
+    let settings = SystemSettings::get_current(&state.db).await?;
+    let category = query.category.unwrap_or_default();
+    let prompt = if category.trim().is_empty() {
+        settings.ingestion_system_prompt.clone()
+    } else {
+        settings.ingestion_prompt_for(&category).to_string()
+    };
+
+    Ok(TemplateResponse::new_template(
+        "admin/edit_category_prompt_modal.html",
+        CategoryPromptEditData {
+            settings,
+            category,
+            prompt,
+        },
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct CategoryPromptUpdateInput {
+    category: String,
+    prompt: String,
+}
+
+pub async fn patch_category_prompt(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+    Form(input): Form<CategoryPromptUpdateInput>,
+) -> TemplateResult {
+    let category = input.category.trim();
+    if category.is_empty() {
+        return Err(AppError::Validation("category must not be empty".into()).into());
+    }
+
+    let current = SystemSettings::get_current(&state.db).await?;
+    let mut prompts: Vec<CategoryPrompt> = current
+        .ingestion_category_prompts
+        .into_iter()
+        .filter(|entry| !entry.category.trim().eq_ignore_ascii_case(category))
+        .collect();
+    if !input.prompt.trim().is_empty() {
+        prompts.push(CategoryPrompt {
+            category: category.to_string(),
+            prompt: input.prompt,
+        });
+    }
+
+    let new_settings = SystemSettingsPatch {
+        ingestion_category_prompts: Some(prompts),
+        ..Default::default()
+    }
+    .apply_as(&state.db, &user.email)
     .await?;
 
     Ok(TemplateResponse::new_partial(
@@ -538,3 +924,44 @@ pub async fn patch_image_prompt(
         },
     ))
 }
+
+#[derive(Deserialize)]
+pub struct PromptPreviewInput {
+    #[serde(alias = "query_system_prompt", alias = "ingestion_system_prompt")]
+    prompt: String,
+    #[serde(default)]
+    category: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct PromptPreviewData {
+    rendered: Option<String>,
+    error: Option<String>,
+    category: Option<String>,
+}
+
+/// Renders a prompt template with sample values, showing template errors inline.
+pub async fn preview_prompt(Form(input): Form<PromptPreviewInput>) -> TemplateResult {
+    let category = input
+        .category
+        .map(|category| category.trim().to_string())
+        .filter(|category| !category.is_empty());
+    let mut variables = PromptVariables::sample();
+    if let Some(category) = &category {
+        variables = variables.with_category(category.as_str());
+    }
+
+    let (rendered, error) = match render_prompt(&input.prompt, &variables) {
+        Ok(rendered) => (Some(rendered), None),
+        Err(err) => (None, Some(err.to_string())),
+    };
+
+    Ok(TemplateResponse::new_template(
+        "admin/prompt_preview.html",
+        PromptPreviewData {
+            rendered,
+            error,
+            category,
+        },
+    ))
+}
@@ -3,12 +3,14 @@ use axum::{
     Router,
     extract::FromRef,
     middleware::from_fn,
-    routing::{get, patch},
+    routing::{get, patch, post},
 };
 use handlers::{
-    patch_image_prompt, patch_ingestion_prompt, patch_query_prompt, show_admin_panel,
-    show_edit_image_prompt, show_edit_ingestion_prompt, show_edit_system_prompt,
-    toggle_registration_status, update_model_settings,
+    patch_category_prompt, patch_image_prompt, patch_ingestion_prompt, patch_query_prompt,
+    preview_prompt, purge_llm_cache, reingest_all_content, rollback_settings, show_admin_panel,
+    show_edit_category_prompt, show_edit_image_prompt, show_edit_ingestion_prompt,
+    show_edit_system_prompt, toggle_registration_status, update_model_prices,
+    update_model_settings,
 };
 
 use crate::{html_state::HtmlState, middlewares::auth_middleware::require_admin};
@@ -22,11 +24,18 @@ where
         .route("/admin", get(show_admin_panel))
         .route("/toggle-registrations", patch(toggle_registration_status))
         .route("/update-model-settings", patch(update_model_settings))
+        .route("/update-model-prices", patch(update_model_prices))
         .route("/edit-query-prompt", get(show_edit_system_prompt))
         .route("/update-query-prompt", patch(patch_query_prompt))
         .route("/edit-ingestion-prompt", get(show_edit_ingestion_prompt))
         .route("/update-ingestion-prompt", patch(patch_ingestion_prompt))
         .route("/edit-image-prompt", get(show_edit_image_prompt))
         .route("/update-image-prompt", patch(patch_image_prompt))
+        .route("/edit-category-prompt", get(show_edit_category_prompt))
+        .route("/update-category-prompt", patch(patch_category_prompt))
+        .route("/preview-prompt", post(preview_prompt))
+        .route("/reingest-all-content", post(reingest_all_content))
+        .route("/purge-llm-cache", post(purge_llm_cache))
+        .route("/rollback-settings/{version}", post(rollback_settings))
         .route_layer(from_fn(require_admin))
 }
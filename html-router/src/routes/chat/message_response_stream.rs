@@ -1,6 +1,6 @@
 #![allow(clippy::missing_docs_in_private_items)]
 
-use std::{pin::Pin, sync::Arc, time::Duration};
+use std::{collections::HashMap, pin::Pin, sync::Arc, time::Duration};
 
 use async_stream::stream;
 use axum::{
@@ -10,14 +10,15 @@ use axum::{
         sse::{Event, KeepAlive, KeepAliveStream},
     },
 };
+use chrono::Utc;
 use futures::{
-    Stream, StreamExt, TryStreamExt,
+    Stream, StreamExt,
     stream::{self, once},
 };
 use json_stream_parser::JsonStreamParser;
 use minijinja::Value;
 use retrieval_pipeline::answer_retrieval::{
-    LLMResponseFormat, chunks_to_chat_context, create_chat_request,
+    LLMResponseFormat, chat_response_format, chunks_to_chat_context, create_chat_messages,
     create_user_message_with_history,
 };
 use serde::{Deserialize, Serialize};
@@ -26,13 +27,21 @@ use tokio::sync::Mutex;
 use tokio::sync::mpsc::channel;
 use tracing::{debug, error, info};
 
-use common::storage::{
-    db::SurrealDbClient,
-    types::{
-        conversation::Conversation,
-        message::{Message, MessageRole},
-        system_settings::SystemSettings,
-        user::User,
+use common::{
+    storage::{
+        db::SurrealDbClient,
+        types::{
+            conversation::Conversation,
+            entity_fact::SourceExtractions,
+            message::{Message, MessageRole},
+            system_settings::{ModelRole, SystemSettings},
+            text_content::TextContent,
+            user::User,
+        },
+    },
+    utils::{
+        llm::{ChatMessage, CompletionStream},
+        prompt_template::{PromptVariables, render_prompt},
     },
 };
 
@@ -192,7 +201,7 @@ pub async fn get_response_stream(
     RequireUser(user): RequireUser,
     Query(params): Query<QueryParams>,
 ) -> SseResponse {
-    let (user_message, user, _conversation, history, existing_ai_response) =
+    let (user_message, user, conversation, history, existing_ai_response) =
         match get_message_and_user(&state.db, user, &params.message_id).await {
             Ok((user_message, user, conversation, history, existing_ai_response)) => (
                 user_message,
@@ -208,22 +217,33 @@ pub async fn get_response_stream(
         return create_replayed_response_stream(&state, existing_ai_message);
     }
 
-    let (request, allowed_reference_ids) =
+    let (settings, messages, allowed_reference_ids) =
         match prepare_chat_request(&state, &user_message, &user, &history).await {
             Ok(result) => result,
             Err(sse) => return sse,
         };
 
-    let openai_stream = match state.openai_client.chat().create_stream(request).await {
+    let completion_stream = match state
+        .llm_providers
+        .scoped(&user.id, Some(&conversation.id))
+        .stream(
+            &settings,
+            ModelRole::Query,
+            messages,
+            Some(chat_response_format()),
+        )
+        .await
+    {
         Ok(stream) => stream,
-        Err(_e) => {
-            return sse_with_keep_alive(create_error_stream("Failed to create OpenAI stream"));
+        Err(e) => {
+            error!("Failed to create LLM stream: {e}");
+            return sse_with_keep_alive(create_error_stream("Failed to create LLM stream"));
         }
     };
 
     build_chat_event_stream(
         state,
-        openai_stream,
+        completion_stream,
         &user_message,
         user.id.clone(),
         allowed_reference_ids,
@@ -232,13 +252,7 @@ pub async fn get_response_stream(
 
 fn build_chat_event_stream(
     state: HtmlState,
-    openai_stream: impl Stream<
-        Item = Result<
-            async_openai::types::chat::CreateChatCompletionStreamResponse,
-            async_openai::error::OpenAIError,
-        >,
-    > + Send
-    + 'static,
+    completion_stream: CompletionStream,
     user_message: &Message,
     user_id: String,
     allowed_reference_ids: Vec<String>,
@@ -257,21 +271,14 @@ fn build_chat_event_stream(
 
     let json_state = Arc::new(Mutex::new(StreamParserState::new()));
 
-    let event_stream = openai_stream
-        .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
+    let event_stream = completion_stream
         .map(move |result| {
             let tx_storage = tx.clone();
             let json_state = Arc::clone(&json_state);
 
             stream! {
                 match result {
-                    Ok(response) => {
-                        let content = response
-                            .choices
-                            .first()
-                            .and_then(|choice| choice.delta.content.clone())
-                            .unwrap_or_default();
-
+                    Ok(content) => {
                         if !content.is_empty() {
                             let _ = tx_storage.send(content.clone()).await;
 
@@ -340,19 +347,14 @@ async fn prepare_chat_request(
     user_message: &Message,
     user: &User,
     history: &[Message],
-) -> Result<
-    (
-        async_openai::types::chat::CreateChatCompletionRequest,
-        Vec<String>,
-    ),
-    SseResponse,
-> {
+) -> Result<(SystemSettings, Vec<ChatMessage>, Vec<String>), SseResponse> {
     let rerank_lease = match state.reranker_pool.as_ref() {
         Some(pool) => pool.checkout().await,
         None => None,
     };
 
-    let config = retrieval_pipeline::RetrievalConfig::default();
+    let mut config = retrieval_pipeline::RetrievalConfig::default();
+    config.expand_graph = state.config.retrieval_graph_expansion;
 
     let retrieval_result = match retrieval_pipeline::retrieve(
         &state.db,
@@ -374,12 +376,31 @@ async fn prepare_chat_request(
 
     let allowed_reference_ids = collect_reference_ids_from_retrieval(&retrieval_result);
 
-    let context_json = match retrieval_result {
-        retrieval_pipeline::RetrievalOutput::Chunks(chunks) => chunks_to_chat_context(&chunks),
-        retrieval_pipeline::RetrievalOutput::WithEntities { chunks, .. } => {
-            chunks_to_chat_context(&chunks)
-        }
+    let chunks = match retrieval_result {
+        retrieval_pipeline::RetrievalOutput::Chunks(chunks)
+        | retrieval_pipeline::RetrievalOutput::WithEntities { chunks, .. } => chunks,
     };
+    let source_summaries = TextContent::resolve_source_summaries(
+        &state.db,
+        &user.id,
+        chunks.iter().map(|chunk| chunk.chunk.source_id.as_str()),
+    )
+    .await
+    .unwrap_or_else(|err| {
+        error!("Failed to load source summaries for chat context: {err}");
+        HashMap::new()
+    });
+    let source_extractions = SourceExtractions::load(
+        &state.db,
+        &user.id,
+        chunks.iter().map(|chunk| chunk.chunk.source_id.as_str()),
+    )
+    .await
+    .unwrap_or_else(|err| {
+        error!("Failed to load extracted facts for chat context: {err}");
+        HashMap::new()
+    });
+    let context_json = chunks_to_chat_context(&chunks, &source_summaries, &source_extractions);
     let formatted_user_message =
         create_user_message_with_history(&context_json, history, &user_message.content);
     let Ok(settings) = SystemSettings::get_current(&state.db).await else {
@@ -387,13 +408,19 @@ async fn prepare_chat_request(
             "Failed to retrieve system settings",
         )));
     };
-    let Ok(request) = create_chat_request(formatted_user_message, &settings) else {
-        return Err(sse_with_keep_alive(create_error_stream(
-            "Failed to create chat request",
-        )));
+    let variables = PromptVariables::new(&user.timezone, Utc::now());
+    let system_prompt = match render_prompt(user.query_prompt_template(&settings), &variables) {
+        Ok(prompt) => prompt,
+        Err(err) => {
+            error!("Failed to render query prompt: {err}");
+            return Err(sse_with_keep_alive(create_error_stream(
+                "Failed to render the system prompt",
+            )));
+        }
     };
+    let messages = create_chat_messages(formatted_user_message, system_prompt);
 
-    Ok((request, allowed_reference_ids))
+    Ok((settings, messages, allowed_reference_ids))
 }
 
 fn spawn_storage_task(
@@ -556,6 +583,9 @@ mod tests {
             admin: false,
             timezone: "UTC".to_string(),
             theme: Theme::System,
+            review_all_suggestions: false,
+            review_categories: Vec::new(),
+            query_system_prompt: None,
         }
     }
 
@@ -1,17 +1,26 @@
 use axum::{
     Form,
     extract::{Path, Query, State},
+    http::{HeaderValue, StatusCode},
+    response::IntoResponse,
 };
-use axum_htmx::{HxBoosted, HxRequest, HxTarget};
+use axum_htmx::{HX_TRIGGER, HxBoosted, HxRequest, HxTarget};
 use serde::{Deserialize, Serialize};
 
-use common::storage::types::{file_info::FileInfo, text_content::TextContent, user::User};
+use common::storage::types::{
+    action_item::ActionItem,
+    file_info::FileInfo,
+    ingestion_task::{IngestionTask, TaskPriority},
+    tag::{Tag, parse_tag_list},
+    text_content::TextContent,
+    user::User,
+};
 
 use crate::{
     html_state::HtmlState,
     middlewares::{
         auth_middleware::RequireUser,
-        response_middleware::{TemplateResponse, TemplateResult},
+        response_middleware::{ResponseResult, TemplateResponse, TemplateResult},
     },
     utils::pagination::{Pagination, paginate_items},
     utils::text_content_preview::truncate_text_contents,
@@ -25,6 +34,8 @@ pub struct ContentPageData {
     text_contents: Vec<TextContent>,
     categories: Vec<String>,
     selected_category: Option<String>,
+    tags: Vec<String>,
+    selected_tag: Option<String>,
     pagination: Pagination,
     page_query: String,
 }
@@ -37,6 +48,7 @@ pub struct RecentTextContentData {
 #[derive(Deserialize)]
 pub struct FilterParams {
     category: Option<String>,
+    tag: Option<String>,
     page: Option<usize>,
 }
 
@@ -53,31 +65,50 @@ pub async fn show_content_page(
         .as_ref()
         .map(|c| c.trim())
         .filter(|c| !c.is_empty());
+    let tag_filter = params
+        .tag
+        .as_ref()
+        .map(|t| t.trim())
+        .filter(|t| !t.is_empty());
 
-    // load categories and filtered/all contents
+    // load categories, tags and filtered/all contents
     let categories = User::get_user_categories(&user.id, &state.db).await?;
-    let full_contents = match category_filter {
+    let tags = Tag::names_for_user(&user.id, &state.db).await?;
+    let mut full_contents = match category_filter {
         Some(category) => {
             User::get_text_contents_by_category(&user.id, category, &state.db).await?
         }
         None => User::get_text_contents(&user.id, &state.db).await?,
     };
+    if let Some(tag) = tag_filter {
+        full_contents.retain(|content| content.tags.iter().any(|t| t == tag));
+    }
 
     let (page_contents, pagination) = paginate_items(full_contents, params.page, CONTENTS_PER_PAGE);
     let text_contents = truncate_text_contents(page_contents);
 
-    let page_query = category_filter
-        .map(|category| {
-            let mut serializer = form_urlencoded::Serializer::new(String::new());
+    let page_query = {
+        let mut serializer = form_urlencoded::Serializer::new(String::new());
+        if let Some(category) = category_filter {
             serializer.append_pair("category", category);
-            format!("&{}", serializer.finish())
-        })
-        .unwrap_or_default();
+        }
+        if let Some(tag) = tag_filter {
+            serializer.append_pair("tag", tag);
+        }
+        let encoded = serializer.finish();
+        if encoded.is_empty() {
+            String::new()
+        } else {
+            format!("&{encoded}")
+        }
+    };
 
     let data = ContentPageData {
         text_contents,
         categories,
         selected_category: params.category.clone(),
+        tags,
+        selected_tag: tag_filter.map(ToString::to_string),
         pagination,
         page_query,
     };
@@ -116,6 +147,9 @@ pub struct PatchTextContentParams {
     context: String,
     category: String,
     text: String,
+    /// Comma-separated tag names.
+    #[serde(default)]
+    tags: String,
 }
 pub async fn patch_text_content(
     State(state): State<HtmlState>,
@@ -127,6 +161,8 @@ pub async fn patch_text_content(
     User::get_and_validate_text_content(&id, &user.id, &state.db).await?;
 
     TextContent::patch(&id, &form.context, &form.category, &form.text, &state.db).await?;
+    Tag::set_item_tags::<TextContent>(&id, &user.id, &parse_tag_list(&form.tags), &state.db)
+        .await?;
 
     if target.as_deref() == Some("latest_content_section") {
         let text_contents =
@@ -153,6 +189,8 @@ pub async fn patch_text_content(
             text_contents,
             categories,
             selected_category: None,
+            tags: Tag::names_for_user(&user.id, &state.db).await?,
+            selected_tag: None,
             pagination,
             page_query: String::new(),
         },
@@ -197,12 +235,51 @@ pub async fn delete_text_content(
             text_contents,
             categories,
             selected_category: None,
+            tags: Tag::names_for_user(&user.id, &state.db).await?,
+            selected_tag: None,
             pagination,
             page_query: String::new(),
         },
     ))
 }
 
+pub async fn reingest_text_content(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+    Path(id): Path<String>,
+) -> ResponseResult {
+    let text_content = User::get_and_validate_text_content(&id, &user.id, &state.db).await?;
+
+    let outcome =
+        IngestionTask::create_reingest(&text_content, TaskPriority::Normal, &state.db).await?;
+
+    let trigger_payload = if outcome.is_created() {
+        serde_json::json!({
+            "toast": {
+                "title": "Re-ingest queued",
+                "description": "This content will be re-processed with the current prompt and models.",
+                "type": "success"
+            }
+        })
+    } else {
+        serde_json::json!({
+            "toast": {
+                "title": "Re-ingest in progress",
+                "description": "This content is already queued for re-processing.",
+                "type": "info"
+            }
+        })
+    };
+    let trigger_value = serde_json::to_string(&trigger_payload).unwrap_or_default();
+
+    let mut response = StatusCode::NO_CONTENT.into_response();
+    if let Ok(header_value) = HeaderValue::from_str(&trigger_value) {
+        response.headers_mut().insert(HX_TRIGGER, header_value);
+    }
+
+    Ok(response)
+}
+
 pub async fn show_content_read_modal(
     State(state): State<HtmlState>,
     RequireUser(user): RequireUser,
@@ -211,14 +288,46 @@ pub async fn show_content_read_modal(
     #[derive(Serialize)]
     pub struct TextContentReadModalData {
         pub text_content: TextContent,
+        pub action_items: Vec<ActionItem>,
     }
 
     // Get and validate the text content
     let text_content = User::get_and_validate_text_content(&id, &user.id, &state.db).await?;
+    let action_items = ActionItem::list_for_source(&text_content.id, &user.id, &state.db).await?;
 
     Ok(TemplateResponse::new_template(
         "content/read_content_modal.html",
-        TextContentReadModalData { text_content },
+        TextContentReadModalData {
+            text_content,
+            action_items,
+        },
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct ActionItemDoneParams {
+    #[serde(default)]
+    pub done: bool,
+}
+
+/// Toggles an action item and re-renders the action item list of its document.
+pub async fn set_action_item_done(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+    Path(id): Path<String>,
+    Form(form): Form<ActionItemDoneParams>,
+) -> TemplateResult {
+    #[derive(Serialize)]
+    pub struct ActionItemsData {
+        pub action_items: Vec<ActionItem>,
+    }
+
+    let item = ActionItem::set_done(&id, &user.id, form.done, &state.db).await?;
+    let action_items = ActionItem::list_for_source(&item.source_id, &user.id, &state.db).await?;
+
+    Ok(TemplateResponse::new_template(
+        "content/action_items.html",
+        ActionItemsData { action_items },
     ))
 }
 
@@ -1,9 +1,13 @@
 mod handlers;
 
-use axum::{Router, extract::FromRef, routing::get};
+use axum::{
+    Router,
+    extract::FromRef,
+    routing::{get, post},
+};
 use handlers::{
-    delete_text_content, patch_text_content, show_content_page, show_content_read_modal,
-    show_recent_content, show_text_content_edit_form,
+    delete_text_content, patch_text_content, reingest_text_content, set_action_item_done,
+    show_content_page, show_content_read_modal, show_recent_content, show_text_content_edit_form,
 };
 
 use crate::html_state::HtmlState;
@@ -17,6 +21,11 @@ where
         .route("/content", get(show_content_page))
         .route("/content/recent", get(show_recent_content))
         .route("/content/{id}/read", get(show_content_read_modal))
+        .route("/content/{id}/reingest", post(reingest_text_content))
+        .route(
+            "/content/action-items/{id}/done",
+            post(set_action_item_done),
+        )
         .route(
             "/content/{id}",
             get(show_text_content_edit_form)
@@ -23,7 +23,10 @@ use common::storage::types::user::DashboardStats;
 use common::{
     error::AppError,
     storage::types::{
-        file_info::FileInfo, ingestion_task::IngestionTask, text_content::TextContent, user::User,
+        file_info::FileInfo,
+        ingestion_task::{IngestionTask, TaskPriority},
+        text_content::TextContent,
+        user::User,
     },
 };
 
@@ -163,6 +166,23 @@ pub async fn delete_job(
     ))
 }
 
+/// Moves a queued task ahead of regular and bulk work.
+pub async fn prioritize_job(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+    Path(id): Path<String>,
+) -> TemplateResult {
+    IngestionTask::set_priority(&id, &user.id, TaskPriority::High, &state.db).await?;
+
+    let active_jobs = User::get_unfinished_ingestion_tasks(&user.id, &state.db).await?;
+
+    Ok(TemplateResponse::new_partial(
+        "dashboard/active_jobs.html",
+        "active_jobs_section",
+        ActiveJobsData { active_jobs },
+    ))
+}
+
 pub async fn show_active_jobs(
     State(state): State<HtmlState>,
     RequireUser(user): RequireUser,
@@ -224,6 +244,9 @@ fn summarize_task_content(task: &IngestionTask) -> (String, String) {
         common::storage::types::ingestion_payload::IngestionPayload::File { file_info, .. } => {
             ("File".to_string(), file_info.file_name.clone())
         }
+        common::storage::types::ingestion_payload::IngestionPayload::Reingest { label, .. } => {
+            ("Re-ingest".to_string(), label.clone())
+        }
     }
 }
 
@@ -3,10 +3,11 @@ pub mod handlers;
 use axum::{
     Router,
     extract::FromRef,
-    routing::{delete, get},
+    routing::{delete, get, post},
 };
 use handlers::{
-    delete_job, delete_text_content, index_handler, serve_file, show_active_jobs, show_task_archive,
+    delete_job, delete_text_content, index_handler, prioritize_job, serve_file, show_active_jobs,
+    show_task_archive,
 };
 
 use crate::html_state::HtmlState;
@@ -19,6 +20,7 @@ where
     Router::new()
         .route("/", get(index_handler))
         .route("/jobs/{job_id}", delete(delete_job))
+        .route("/jobs/{job_id}/prioritize", post(prioritize_job))
         .route("/jobs/archive", get(show_task_archive))
         .route("/active-jobs", get(show_active_jobs))
         .route("/text-content/{id}", delete(delete_text_content))
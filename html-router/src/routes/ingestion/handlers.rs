@@ -2,12 +2,13 @@ use std::{pin::Pin, sync::Arc, time::Duration};
 
 use axum::{
     extract::{Query, State},
-    http::StatusCode,
+    http::{HeaderValue, StatusCode},
     response::{
         Sse,
         sse::{Event, KeepAlive, KeepAliveStream},
     },
 };
+use axum_htmx::HX_TRIGGER;
 use axum_typed_multipart::{FieldData, TryFromMultipart, TypedMultipart};
 use futures::{Stream, StreamExt, TryFutureExt, future::try_join_all, stream};
 use minijinja::context;
@@ -21,7 +22,7 @@ use common::{
     storage::types::{
         file_info::FileInfo,
         ingestion_payload::IngestionPayload,
-        ingestion_task::{IngestionTask, TaskState},
+        ingestion_task::{EnqueueOutcome, IngestionTask, TaskPriority, TaskState},
         user::User,
     },
     utils::ingest_limits::{IngestValidationError, validate_ingest_input},
@@ -31,7 +32,10 @@ use crate::{
     html_state::HtmlState,
     middlewares::{
         auth_middleware::RequireUser,
-        response_middleware::{TemplateResponse, TemplateResult},
+        response_middleware::{
+            ResponseResult, TemplateResponse, TemplateResult, template_as_response,
+            template_with_headers,
+        },
     },
 };
 
@@ -81,20 +85,30 @@ pub struct IngestionParams {
     pub content: Option<String>,
     pub context: String,
     pub category: String,
+    pub priority: Option<String>,
     #[form_data(limit = "20000000")]
     #[form_data(default)]
     pub files: Vec<FieldData<NamedTempFile>>,
 }
 
+fn validation_error_template(err: IngestValidationError) -> TemplateResponse {
+    match err {
+        IngestValidationError::PayloadTooLarge(message) => {
+            TemplateResponse::error(StatusCode::PAYLOAD_TOO_LARGE, "Payload Too Large", &message)
+        }
+        IngestValidationError::BadRequest(message) => TemplateResponse::bad_request(&message),
+    }
+}
+
 pub async fn process_ingest_form(
     State(state): State<HtmlState>,
     RequireUser(user): RequireUser,
     TypedMultipart(input): TypedMultipart<IngestionParams>,
-) -> TemplateResult {
+) -> ResponseResult {
     if input.content.as_ref().is_none_or(|c| c.len() < 2) && input.files.is_empty() {
-        return Ok(TemplateResponse::bad_request(
+        return Ok(template_as_response(TemplateResponse::bad_request(
             "You need to either add files or content",
-        ));
+        )));
     }
 
     let content_bytes = input.content.as_ref().map_or(0, String::len);
@@ -103,26 +117,30 @@ pub async fn process_ingest_form(
     let category_bytes = input.category.len();
     let file_count = input.files.len();
 
-    match validate_ingest_input(
+    if let Err(err) = validate_ingest_input(
         &state.config,
         input.content.as_deref(),
         &input.context,
         &input.category,
         file_count,
     ) {
-        Ok(()) => {}
-        Err(IngestValidationError::PayloadTooLarge(message)) => {
-            return Ok(TemplateResponse::error(
-                StatusCode::PAYLOAD_TOO_LARGE,
-                "Payload Too Large",
-                &message,
-            ));
-        }
-        Err(IngestValidationError::BadRequest(message)) => {
-            return Ok(TemplateResponse::bad_request(&message));
-        }
+        return Ok(template_as_response(validation_error_template(err)));
     }
 
+    let priority = match input
+        .priority
+        .as_deref()
+        .map(str::parse::<TaskPriority>)
+        .transpose()
+    {
+        Ok(priority) => priority.unwrap_or_default(),
+        Err(err) => {
+            return Ok(template_as_response(TemplateResponse::bad_request(
+                &err.to_string(),
+            )));
+        }
+    };
+
     info!(
         user_id = %user.id,
         has_content,
@@ -130,6 +148,7 @@ pub async fn process_ingest_form(
         ctx_len,
         category_bytes,
         file_count,
+        priority = priority.as_str(),
         "Received ingest form submission"
     );
 
@@ -147,12 +166,47 @@ pub async fn process_ingest_form(
         user.id.clone(),
     )?;
 
-    let tasks = IngestionTask::create_all_and_add_to_db(payloads, &user.id, &state.db).await?;
+    let outcomes = IngestionTask::create_all_with_policy(
+        payloads,
+        &user.id,
+        state.config.ingest_duplicate_policy,
+        priority,
+        &state.db,
+    )
+    .await?;
 
-    Ok(TemplateResponse::new_template(
-        "dashboard/current_task.html",
-        NewTasksData { tasks },
-    ))
+    let duplicates = outcomes
+        .iter()
+        .filter(|outcome| !outcome.is_created())
+        .count();
+    let tasks: Vec<IngestionTask> = outcomes
+        .into_iter()
+        .filter(EnqueueOutcome::is_created)
+        .map(EnqueueOutcome::into_task)
+        .collect();
+
+    let template =
+        TemplateResponse::new_template("dashboard/current_task.html", NewTasksData { tasks });
+
+    if duplicates == 0 {
+        return Ok(template_as_response(template));
+    }
+
+    // Linked tasks are already listed in the active jobs section, so only notify.
+    let trigger_payload = serde_json::json!({
+        "toast": {
+            "title": "Already added",
+            "description": "This content matches something you already added, so it was not queued again.",
+            "type": "info"
+        }
+    });
+    let trigger_value = serde_json::to_string(&trigger_payload).unwrap_or_default();
+
+    Ok(template_with_headers(template, |headers| {
+        if let Ok(header_value) = HeaderValue::from_str(&trigger_value) {
+            headers.insert(HX_TRIGGER, header_value);
+        }
+    }))
 }
 
 #[derive(Deserialize)]
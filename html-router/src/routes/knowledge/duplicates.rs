@@ -0,0 +1,169 @@
+use std::sync::Arc;
+
+use axum::{
+    Form,
+    extract::{Path, State},
+};
+use axum_htmx::{HxBoosted, HxRequest};
+use serde::{Deserialize, Serialize};
+
+use common::{
+    error::AppError,
+    storage::{
+        db::SurrealDbClient,
+        types::{
+            duplicate_report::{DuplicateGroup, DuplicateGroupStatus, DuplicateReport},
+            entity_operation::EntityOperation,
+        },
+    },
+};
+
+use super::handlers::graph_refresh_response;
+use crate::{
+    html_state::HtmlState,
+    middlewares::{
+        auth_middleware::RequireUser,
+        response_middleware::{ResponseResult, TemplateResponse, TemplateResult},
+    },
+};
+
+const DUPLICATES_TEMPLATE: &str = "knowledge/duplicates.html";
+
+#[derive(Serialize)]
+pub struct DuplicateReportData {
+    report: Option<DuplicateReport>,
+    open_groups: Vec<DuplicateGroup>,
+    resolved_count: usize,
+    running: bool,
+}
+
+async fn load_report_data(
+    user_id: &str,
+    db: &SurrealDbClient,
+) -> Result<DuplicateReportData, AppError> {
+    let report = DuplicateReport::get_for_user(user_id, db).await?;
+    let (open_groups, resolved): (Vec<DuplicateGroup>, Vec<DuplicateGroup>) = report
+        .as_ref()
+        .map(|report| report.groups.clone())
+        .unwrap_or_default()
+        .into_iter()
+        .partition(|group| group.status == DuplicateGroupStatus::Open);
+
+    Ok(DuplicateReportData {
+        running: report.as_ref().is_some_and(DuplicateReport::is_running),
+        report,
+        open_groups,
+        resolved_count: resolved.len(),
+    })
+}
+
+fn report_partial(data: DuplicateReportData) -> TemplateResponse {
+    TemplateResponse::new_partial(DUPLICATES_TEMPLATE, "duplicate_report", data)
+}
+
+pub async fn show_duplicates_page(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+    HxRequest(is_htmx): HxRequest,
+    HxBoosted(is_boosted): HxBoosted,
+) -> TemplateResult {
+    let data = load_report_data(&user.id, &state.db).await?;
+
+    if is_htmx && !is_boosted {
+        Ok(TemplateResponse::new_partial(
+            DUPLICATES_TEMPLATE,
+            "main",
+            data,
+        ))
+    } else {
+        Ok(TemplateResponse::new_template(DUPLICATES_TEMPLATE, data))
+    }
+}
+
+/// Report section only; polled while a scan is running.
+pub async fn show_duplicate_report(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+) -> TemplateResult {
+    let data = load_report_data(&user.id, &state.db).await?;
+    Ok(report_partial(data))
+}
+
+pub async fn start_duplicate_scan(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+) -> TemplateResult {
+    if let Some(report) = DuplicateReport::begin_scan(&user.id, &state.db).await? {
+        let db = Arc::clone(&state.db);
+        tokio::spawn(async move {
+            report.run_scan(&db).await;
+        });
+    }
+
+    let data = load_report_data(&user.id, &state.db).await?;
+    Ok(report_partial(data))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MergeDuplicateGroupParams {
+    pub survivor_id: String,
+}
+
+pub async fn merge_duplicate_group(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+    Path(group_id): Path<String>,
+    Form(form): Form<MergeDuplicateGroupParams>,
+) -> ResponseResult {
+    let report = DuplicateReport::get_for_user(&user.id, &state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("duplicate report not found".into()))?;
+    let group = report
+        .open_group(&group_id)
+        .ok_or_else(|| AppError::NotFound("duplicate group not found".into()))?;
+
+    if !group
+        .members
+        .iter()
+        .any(|member| member.entity_id == form.survivor_id)
+    {
+        return Err(AppError::Validation("pick an entity from this group to keep".into()).into());
+    }
+    let merged_ids: Vec<String> = group
+        .members
+        .iter()
+        .filter(|member| member.entity_id != form.survivor_id)
+        .map(|member| member.entity_id.clone())
+        .collect();
+
+    EntityOperation::merge(
+        &form.survivor_id,
+        &merged_ids,
+        &user.id,
+        &state.db,
+        &state.embedding_provider,
+    )
+    .await?;
+    DuplicateReport::resolve_group(&user.id, &group_id, DuplicateGroupStatus::Merged, &state.db)
+        .await?;
+
+    let data = load_report_data(&user.id, &state.db).await?;
+    Ok(graph_refresh_response(report_partial(data)))
+}
+
+pub async fn dismiss_duplicate_group(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+    Path(group_id): Path<String>,
+) -> TemplateResult {
+    DuplicateReport::resolve_group(
+        &user.id,
+        &group_id,
+        DuplicateGroupStatus::Dismissed,
+        &state.db,
+    )
+    .await?;
+
+    let data = load_report_data(&user.id, &state.db).await?;
+    Ok(report_partial(data))
+}
@@ -19,8 +19,15 @@ use common::{
     storage::{
         db::SurrealDbClient,
         types::{
+            entity_operation::{EntityOperation, SplitRequest},
+            entity_type_definition::EntityTypeDefinition,
             knowledge_entity::{KnowledgeEntity, KnowledgeEntityType},
-            knowledge_relationship::KnowledgeRelationship,
+            knowledge_entity_mention::KnowledgeEntityMention,
+            knowledge_relationship::{DEFAULT_RELATIONSHIP_WEIGHT, KnowledgeRelationship},
+            relationship_type_definition::{
+                DEFAULT_RELATIONSHIP_TYPE, RelationshipTypeCatalog, ResolvedRelationshipType,
+            },
+            tag::{Tag, parse_tag_list},
             user::User,
         },
     },
@@ -45,88 +52,38 @@ use crate::{
 use url::form_urlencoded;
 
 const KNOWLEDGE_ENTITIES_PER_PAGE: usize = 12;
-const RELATIONSHIP_TYPE_OPTIONS: &[&str] = &["RelatedTo", "RelevantTo", "SimilarTo", "References"];
-const DEFAULT_RELATIONSHIP_TYPE: &str = "RelatedTo";
 const MAX_RELATIONSHIP_SUGGESTIONS: usize = 10;
 
 const GRAPH_REFRESH_TRIGGER: &str = r#"{"knowledge-graph-refresh":true}"#;
-const RELATIONSHIP_TYPE_ALIASES: &[(&str, &str)] = &[("relatesto", "RelatedTo")];
 
-fn relationship_type_or_default(value: Option<&str>) -> String {
-    match value {
-        Some(raw) => canonicalize_relationship_type(raw),
-        None => DEFAULT_RELATIONSHIP_TYPE.to_string(),
+/// Resolves a relationship type submitted in a form; blank means the default type.
+fn resolve_form_relationship_type(
+    catalog: &RelationshipTypeCatalog,
+    value: Option<&str>,
+) -> Result<ResolvedRelationshipType, AppError> {
+    match value.map(str::trim).filter(|value| !value.is_empty()) {
+        None => Ok(ResolvedRelationshipType {
+            name: DEFAULT_RELATIONSHIP_TYPE.to_string(),
+            reversed: false,
+        }),
+        Some(raw) => catalog.resolve(raw).ok_or_else(|| {
+            AppError::Validation(format!(
+                "unknown relationship type '{raw}'; add it under Relationship Types first"
+            ))
+        }),
     }
 }
 
-fn canonicalize_relationship_type(value: &str) -> String {
-    let trimmed = value.trim();
-    if trimmed.is_empty() {
-        return DEFAULT_RELATIONSHIP_TYPE.to_string();
-    }
-
-    let key: String = trimmed
-        .chars()
-        .filter(char::is_ascii_alphanumeric)
-        .flat_map(char::to_lowercase)
-        .collect();
-
-    for option in RELATIONSHIP_TYPE_OPTIONS {
-        let option_key: String = option
-            .chars()
-            .filter(char::is_ascii_alphanumeric)
-            .flat_map(char::to_lowercase)
-            .collect();
-        if option_key == key {
-            return (*option).to_string();
-        }
-    }
-
-    for (alias, target) in RELATIONSHIP_TYPE_ALIASES {
-        if *alias == key {
-            return (*target).to_string();
-        }
-    }
-
-    let mut result = String::new();
-    for segment in trimmed
-        .split(|c: char| !c.is_ascii_alphanumeric())
-        .filter(|segment| !segment.is_empty())
-    {
-        let mut chars = segment.chars();
-        if let Some(first) = chars.next() {
-            result.extend(first.to_uppercase());
-            for ch in chars {
-                result.extend(ch.to_lowercase());
-            }
-        }
-    }
-
-    if result.is_empty() {
-        trimmed.to_string()
-    } else {
-        result
-    }
+/// The managed type a stored relationship uses, or its raw type if it matches none.
+/// Relationships named by an inverse keep their raw type so the direction reads right.
+fn relationship_type_label(catalog: &RelationshipTypeCatalog, raw: &str) -> String {
+    catalog
+        .resolve(raw)
+        .filter(|resolved| !resolved.reversed)
+        .map_or_else(|| raw.to_string(), |resolved| resolved.name)
 }
 
-fn collect_relationship_type_options(relationships: &[KnowledgeRelationship]) -> Vec<String> {
-    let mut options: HashSet<String> = RELATIONSHIP_TYPE_OPTIONS
-        .iter()
-        .map(|value| (*value).to_string())
-        .collect();
-
-    for relationship in relationships {
-        options.insert(canonicalize_relationship_type(
-            &relationship.metadata.relationship_type,
-        ));
-    }
-
-    let mut options: Vec<String> = options.into_iter().collect();
-    options.sort();
-    options
-}
-
-fn graph_refresh_response(template: TemplateResponse) -> Response {
+pub(super) fn graph_refresh_response(template: TemplateResponse) -> Response {
     template_with_headers(template, |headers| {
         if let Ok(value) = HeaderValue::from_str(GRAPH_REFRESH_TRIGGER) {
             headers.insert(HX_TRIGGER, value);
@@ -138,6 +95,7 @@ fn graph_refresh_response(template: TemplateResponse) -> Response {
 pub struct FilterParams {
     entity_type: Option<String>,
     content_category: Option<String>,
+    tag: Option<String>,
     page: Option<usize>,
 }
 
@@ -145,14 +103,12 @@ pub async fn show_new_knowledge_entity_form(
     State(state): State<HtmlState>,
     RequireUser(user): RequireUser,
 ) -> TemplateResult {
-    let entity_types: Vec<String> = KnowledgeEntityType::variants()
-        .iter()
-        .map(ToString::to_string)
-        .collect();
+    let entity_types = EntityTypeDefinition::type_names_for_user(&user.id, &state.db).await?;
 
     let existing_entities = User::get_knowledge_entities(&user.id, &state.db).await?;
-    let relationships = User::get_knowledge_relationships(&user.id, &state.db).await?;
-    let relationship_type_options = collect_relationship_type_options(&relationships);
+    let relationship_type_options = RelationshipTypeCatalog::for_user(&user.id, &state.db)
+        .await?
+        .names();
     let empty_selected: HashSet<String> = HashSet::new();
     let empty_scores: HashMap<String, f32> = HashMap::new();
     let relationship_options =
@@ -164,7 +120,7 @@ pub async fn show_new_knowledge_entity_form(
             entity_types,
             relationship_list: RelationshipListData {
                 relationship_options,
-                relationship_type: relationship_type_or_default(None),
+                relationship_type: DEFAULT_RELATIONSHIP_TYPE.to_string(),
                 suggestion_count: 0,
             },
             relationship_type_options,
@@ -184,8 +140,11 @@ pub async fn create_knowledge_entity(
 
     let description = form.description.trim().to_string();
     let entity_type = KnowledgeEntityType::from(form.entity_type.trim().to_string());
+    let catalog = RelationshipTypeCatalog::for_user(&user.id, &state.db).await?;
+    let relationship_type =
+        resolve_form_relationship_type(&catalog, form.relationship_type.as_deref())?;
 
-    let embedding_input = KnowledgeEntity::embedding_input_text(&name, &description, entity_type);
+    let embedding_input = KnowledgeEntity::embedding_input_text(&name, &description, &entity_type);
     let embedding = state
         .embedding_provider
         .embed(&embedding_input)
@@ -211,7 +170,6 @@ pub async fn create_knowledge_entity(
     )
     .await?;
 
-    let relationship_type = relationship_type_or_default(form.relationship_type.as_deref());
     let user_id = user.id.clone();
 
     debug!("form: {:?}", form);
@@ -234,12 +192,17 @@ pub async fn create_knowledge_entity(
                 continue;
             }
 
+            let (in_, out) = if relationship_type.reversed {
+                (target_id, new_entity_id.clone())
+            } else {
+                (new_entity_id.clone(), target_id)
+            };
             let relationship = KnowledgeRelationship::new(
-                new_entity_id.clone(),
-                target_id,
+                in_,
+                out,
                 user_id.clone(),
                 format!("manual::{new_entity_id}"),
-                relationship_type.clone(),
+                relationship_type.name.clone(),
             );
             relationship.store_relationship(&state.db).await?;
         }
@@ -322,7 +285,13 @@ pub async fn suggest_knowledge_relationships(
         }
     }
 
-    let relationship_type = relationship_type_or_default(form.relationship_type.as_deref());
+    let relationship_type = form
+        .relationship_type
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .unwrap_or(DEFAULT_RELATIONSHIP_TYPE)
+        .to_string();
 
     let entities: Vec<KnowledgeEntity> = entity_lookup.into_values().collect();
     let relationship_options =
@@ -347,6 +316,8 @@ pub struct KnowledgeBaseData {
     content_categories: Vec<String>,
     selected_entity_type: Option<String>,
     selected_content_category: Option<String>,
+    tags: Vec<String>,
+    selected_tag: Option<String>,
     pagination: Pagination,
     page_query: String,
     relationship_type_options: Vec<String>,
@@ -382,7 +353,7 @@ async fn suggest_related_entities(
     entity_lookup: &HashMap<String, KnowledgeEntity>,
 ) -> Result<HashMap<String, f32>, AppError> {
     let embedding_input =
-        KnowledgeEntity::embedding_input_text(draft.name, draft.description, draft.entity_type);
+        KnowledgeEntity::embedding_input_text(draft.name, draft.description, &draft.entity_type);
     let embedding = embedding_provider.embed(&embedding_input).await?;
 
     let take = MAX_RELATIONSHIP_SUGGESTIONS * 2;
@@ -489,18 +460,21 @@ fn build_relationship_options(
 
 fn build_relationship_rows(
     relationships: Vec<KnowledgeRelationship>,
+    catalog: &RelationshipTypeCatalog,
 ) -> (Vec<RelationshipTableRow>, Vec<String>, String) {
-    let relationship_type_options = collect_relationship_type_options(&relationships);
+    let relationship_type_options = catalog.names();
     let mut frequency: HashMap<String, usize> = HashMap::new();
     let relationships = relationships
         .into_iter()
         .map(|relationship| {
             let relationship_type_label =
-                canonicalize_relationship_type(&relationship.metadata.relationship_type);
-            let count = frequency
-                .entry(relationship_type_label.clone())
-                .or_insert(0);
-            *count = count.saturating_add(1);
+                relationship_type_label(catalog, &relationship.metadata.relationship_type);
+            if catalog.get(&relationship_type_label).is_some() {
+                let count = frequency
+                    .entry(relationship_type_label.clone())
+                    .or_insert(0);
+                *count = count.saturating_add(1);
+            }
             RelationshipTableRow {
                 relationship,
                 relationship_type_label,
@@ -522,9 +496,10 @@ fn build_relationship_rows(
 fn build_relationship_table_data(
     entities: Vec<KnowledgeEntity>,
     relationships: Vec<KnowledgeRelationship>,
+    catalog: &RelationshipTypeCatalog,
 ) -> RelationshipTableData {
     let (relationships, relationship_type_options, default_relationship_type) =
-        build_relationship_rows(relationships);
+        build_relationship_rows(relationships, catalog);
 
     RelationshipTableData {
         entities,
@@ -534,6 +509,28 @@ fn build_relationship_table_data(
     }
 }
 
+/// Loads the entities matching the category or type filter, narrowed to a tag if one
+/// is selected.
+async fn load_filtered_entities(
+    user_id: &str,
+    params: &FilterParams,
+    db: &SurrealDbClient,
+) -> Result<Vec<KnowledgeEntity>, AppError> {
+    let mut entities = match &params.content_category {
+        Some(cat) => User::get_knowledge_entities_by_content_category(user_id, cat, db).await?,
+        None => match &params.entity_type {
+            Some(etype) => User::get_knowledge_entities_by_type(user_id, etype, db).await?,
+            None => User::get_knowledge_entities(user_id, db).await?,
+        },
+    };
+
+    if let Some(tag) = &params.tag {
+        entities.retain(|entity| entity.tags.contains(tag));
+    }
+
+    Ok(entities)
+}
+
 async fn build_knowledge_base_data(
     state: &HtmlState,
     user: &User,
@@ -541,16 +538,9 @@ async fn build_knowledge_base_data(
 ) -> Result<KnowledgeBaseData, AppError> {
     let entity_types = User::get_entity_types(&user.id, &state.db).await?;
     let content_categories = User::get_user_categories(&user.id, &state.db).await?;
+    let tags = Tag::names_for_user(&user.id, &state.db).await?;
 
-    let entities = match &params.content_category {
-        Some(cat) => {
-            User::get_knowledge_entities_by_content_category(&user.id, cat, &state.db).await?
-        }
-        None => match &params.entity_type {
-            Some(etype) => User::get_knowledge_entities_by_type(&user.id, etype, &state.db).await?,
-            None => User::get_knowledge_entities(&user.id, &state.db).await?,
-        },
-    };
+    let entities = load_filtered_entities(&user.id, params, &state.db).await?;
 
     let (visible_entities, pagination) =
         paginate_slice(&entities, params.page, KNOWLEDGE_ENTITIES_PER_PAGE);
@@ -563,6 +553,9 @@ async fn build_knowledge_base_data(
         if let Some(content_category) = params.content_category.as_deref() {
             serializer.append_pair("content_category", content_category);
         }
+        if let Some(tag) = params.tag.as_deref() {
+            serializer.append_pair("tag", tag);
+        }
         let encoded = serializer.finish();
         if encoded.is_empty() {
             String::new()
@@ -579,8 +572,9 @@ async fn build_knowledge_base_data(
             entity_id_set.contains(rel.in_.as_str()) && entity_id_set.contains(rel.out.as_str())
         })
         .collect();
+    let catalog = RelationshipTypeCatalog::for_user(&user.id, &state.db).await?;
     let (relationships, relationship_type_options, default_relationship_type) =
-        build_relationship_rows(filtered_relationships);
+        build_relationship_rows(filtered_relationships, &catalog);
 
     Ok(KnowledgeBaseData {
         entities,
@@ -590,6 +584,8 @@ async fn build_knowledge_base_data(
         content_categories,
         selected_entity_type: params.entity_type.clone(),
         selected_content_category: params.content_category.clone(),
+        tags,
+        selected_tag: params.tag.clone(),
         pagination,
         page_query,
         relationship_type_options,
@@ -840,6 +836,7 @@ pub async fn show_knowledge_page(
     // Normalize filters: treat empty or "none" as no filter
     params.entity_type = normalize_filter(params.entity_type.take());
     params.content_category = normalize_filter(params.content_category.take());
+    params.tag = normalize_filter(params.tag.take());
 
     let kb_data = build_knowledge_base_data(&state, &user, &params).await?;
 
@@ -865,6 +862,8 @@ pub struct GraphNode {
     pub id: String,
     pub name: String,
     pub entity_type: String,
+    /// Color of a user-defined type; built-in types use the graph palette.
+    pub type_color: Option<String>,
     pub degree: usize,
 }
 
@@ -873,6 +872,9 @@ pub struct GraphLink {
     pub source: String,
     pub target: String,
     pub relationship_type: String,
+    /// Undirected types are drawn without an arrow.
+    pub directed: bool,
+    pub weight: f32,
 }
 
 #[derive(Serialize)]
@@ -889,23 +891,17 @@ pub async fn get_knowledge_graph_json(
     // Normalize filters: treat empty or "none" as no filter
     params.entity_type = normalize_filter(params.entity_type.take());
     params.content_category = normalize_filter(params.content_category.take());
+    params.tag = normalize_filter(params.tag.take());
 
     // Load entities based on filters
-    let entities: Vec<KnowledgeEntity> = match &params.content_category {
-        Some(cat) => {
-            User::get_knowledge_entities_by_content_category(&user.id, cat, &state.db).await?
-        }
-        None => match &params.entity_type {
-            Some(etype) => User::get_knowledge_entities_by_type(&user.id, etype, &state.db).await?,
-            None => User::get_knowledge_entities(&user.id, &state.db).await?,
-        },
-    };
+    let entities = load_filtered_entities(&user.id, &params, &state.db).await?;
 
     // All relationships for user, then filter to those whose endpoints are in the set
     let relationships: Vec<KnowledgeRelationship> =
         User::get_knowledge_relationships(&user.id, &state.db).await?;
 
     let entity_ids: HashSet<String> = entities.iter().map(|e| e.id.clone()).collect();
+    let catalog = RelationshipTypeCatalog::for_user(&user.id, &state.db).await?;
 
     let mut degree_count: HashMap<String, usize> = HashMap::new();
     let mut links: Vec<GraphLink> = Vec::new();
@@ -916,21 +912,38 @@ pub async fn get_knowledge_graph_json(
             *count = count.saturating_add(1);
             let count = degree_count.entry(rel.out.clone()).or_insert(0);
             *count = count.saturating_add(1);
+            let relationship_type =
+                relationship_type_label(&catalog, &rel.metadata.relationship_type);
             links.push(GraphLink {
                 source: rel.out.clone(),
                 target: rel.in_.clone(),
-                relationship_type: canonicalize_relationship_type(&rel.metadata.relationship_type),
+                directed: catalog
+                    .get(&relationship_type)
+                    .is_none_or(|kind| kind.directed),
+                relationship_type,
+                weight: rel.metadata.weight,
             });
         }
     }
 
+    let type_colors: HashMap<String, String> =
+        EntityTypeDefinition::list_for_user(&user.id, &state.db)
+            .await?
+            .into_iter()
+            .map(|definition| (definition.name, definition.color))
+            .collect();
+
     let nodes: Vec<GraphNode> = entities
         .into_iter()
-        .map(|e| GraphNode {
-            id: e.id.clone(),
-            name: e.name.clone(),
-            entity_type: format!("{:?}", e.entity_type),
-            degree: *degree_count.get(&e.id).unwrap_or(&0),
+        .map(|e| {
+            let entity_type = e.entity_type.to_string();
+            GraphNode {
+                type_color: type_colors.get(&entity_type).cloned(),
+                degree: *degree_count.get(&e.id).unwrap_or(&0),
+                id: e.id,
+                name: e.name,
+                entity_type,
+            }
         })
         .collect();
 
@@ -968,22 +981,28 @@ pub async fn show_edit_knowledge_entity_form(
     pub struct EntityData {
         entity: KnowledgeEntity,
         entity_types: Vec<String>,
+        aliases: Vec<String>,
     }
 
-    // Get entity types
-    let entity_types: Vec<String> = KnowledgeEntityType::variants()
-        .iter()
-        .map(ToString::to_string)
-        .collect();
-
     // Get the entity and validate ownership
     let entity = User::get_and_validate_knowledge_entity(&id, &user.id, &state.db).await?;
 
+    // Get entity types, keeping the current one selectable even if its definition was deleted
+    let mut entity_types = EntityTypeDefinition::type_names_for_user(&user.id, &state.db).await?;
+    let current_type = entity.entity_type.to_string();
+    if !entity_types.contains(&current_type) {
+        entity_types.push(current_type);
+    }
+
+    let aliases =
+        KnowledgeEntityMention::aliases(&entity.id, &entity.name, &user.id, &state.db).await?;
+
     Ok(TemplateResponse::new_template(
         "knowledge/edit_knowledge_entity_modal.html",
         EntityData {
             entity,
             entity_types,
+            aliases,
         },
     ))
 }
@@ -994,6 +1013,9 @@ pub struct PatchKnowledgeEntityParams {
     pub name: String,
     pub entity_type: String,
     pub description: String,
+    /// Comma-separated tag names.
+    #[serde(default)]
+    pub tags: String,
 }
 
 #[derive(Serialize)]
@@ -1005,6 +1027,14 @@ pub struct EntityListData {
     selected_entity_type: Option<String>,
     selected_content_category: Option<String>,
     page_query: String,
+    last_operation: Option<EntityOperationSummary>,
+}
+
+/// The part of an [`EntityOperation`] the undo banner needs.
+#[derive(Serialize)]
+pub struct EntityOperationSummary {
+    id: String,
+    summary: String,
 }
 
 pub async fn patch_knowledge_entity(
@@ -1027,6 +1057,13 @@ pub async fn patch_knowledge_entity(
         &state.embedding_provider,
     )
     .await?;
+    Tag::set_item_tags::<KnowledgeEntity>(
+        &form.id,
+        &user.id,
+        &parse_tag_list(&form.tags),
+        &state.db,
+    )
+    .await?;
 
     // Get updated list of entities
     let (visible_entities, pagination) = paginate_items(
@@ -1052,6 +1089,7 @@ pub async fn patch_knowledge_entity(
             selected_entity_type: None,
             selected_content_category: None,
             page_query: String::new(),
+            last_operation: None,
         },
     )))
 }
@@ -1066,6 +1104,7 @@ pub async fn delete_knowledge_entity(
 
     // Delete the entity
     state.db.delete_item::<KnowledgeEntity>(&id).await?;
+    KnowledgeEntityMention::delete_for_entity(&id, &user.id, &state.db).await?;
 
     // Get updated list of entities
     let (visible_entities, pagination) = paginate_items(
@@ -1090,6 +1129,7 @@ pub async fn delete_knowledge_entity(
             selected_entity_type: None,
             selected_content_category: None,
             page_query: String::new(),
+            last_operation: None,
         },
     )))
 }
@@ -1112,7 +1152,8 @@ pub async fn delete_knowledge_relationship(
     let entities = User::get_knowledge_entities(&user.id, &state.db).await?;
 
     let relationships = User::get_knowledge_relationships(&user.id, &state.db).await?;
-    let table_data = build_relationship_table_data(entities, relationships);
+    let catalog = RelationshipTypeCatalog::for_user(&user.id, &state.db).await?;
+    let table_data = build_relationship_table_data(entities, relationships, &catalog);
 
     // Render updated list
     Ok(graph_refresh_response(TemplateResponse::new_template(
@@ -1126,6 +1167,19 @@ pub struct SaveKnowledgeRelationshipInput {
     pub in_: String,
     pub out: String,
     pub relationship_type: String,
+    pub weight: Option<String>,
+}
+
+/// Parses a relationship weight from a form; blank means the default weight.
+pub(super) fn parse_form_weight(raw: Option<&str>) -> Result<Option<f32>, AppError> {
+    raw.map(str::trim)
+        .filter(|raw| !raw.is_empty())
+        .map(|raw| {
+            raw.parse::<f32>().map_err(|_| {
+                AppError::Validation(format!("'{raw}' is not a weight between 0 and 1"))
+            })
+        })
+        .transpose()
 }
 
 pub async fn save_knowledge_relationship(
@@ -1134,21 +1188,32 @@ pub async fn save_knowledge_relationship(
     Form(form): Form<SaveKnowledgeRelationshipInput>,
 ) -> ResponseResult {
     // Construct relationship
-    let relationship_type = canonicalize_relationship_type(&form.relationship_type);
-    let relationship = KnowledgeRelationship::new(
-        form.in_,
-        form.out,
+    let catalog = RelationshipTypeCatalog::for_user(&user.id, &state.db).await?;
+    let relationship_type =
+        resolve_form_relationship_type(&catalog, Some(&form.relationship_type))?;
+    let (in_, out) = if relationship_type.reversed {
+        (form.out, form.in_)
+    } else {
+        (form.in_, form.out)
+    };
+    let mut relationship = KnowledgeRelationship::new(
+        in_,
+        out,
         user.id.clone(),
         "manual".into(),
-        relationship_type,
+        relationship_type.name,
     );
+    if let Some(weight) = parse_form_weight(form.weight.as_deref())? {
+        relationship = relationship.with_weight(weight);
+    }
 
     relationship.store_relationship(&state.db).await?;
 
     let entities = User::get_knowledge_entities(&user.id, &state.db).await?;
 
     let relationships = User::get_knowledge_relationships(&user.id, &state.db).await?;
-    let table_data = build_relationship_table_data(entities, relationships);
+    let catalog = RelationshipTypeCatalog::for_user(&user.id, &state.db).await?;
+    let table_data = build_relationship_table_data(entities, relationships, &catalog);
 
     // Render updated list
     Ok(graph_refresh_response(TemplateResponse::new_template(
@@ -1156,3 +1221,284 @@ pub async fn save_knowledge_relationship(
         table_data,
     )))
 }
+
+#[derive(Deserialize)]
+pub struct RelationshipWeightInput {
+    pub weight: Option<String>,
+}
+
+pub async fn update_knowledge_relationship_weight(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+    Path(id): Path<String>,
+    Form(form): Form<RelationshipWeightInput>,
+) -> ResponseResult {
+    let weight = parse_form_weight(form.weight.as_deref())?.unwrap_or(DEFAULT_RELATIONSHIP_WEIGHT);
+    KnowledgeRelationship::set_weight(&id, &user.id, weight, &state.db).await?;
+
+    let entities = User::get_knowledge_entities(&user.id, &state.db).await?;
+    let relationships = User::get_knowledge_relationships(&user.id, &state.db).await?;
+    let catalog = RelationshipTypeCatalog::for_user(&user.id, &state.db).await?;
+    let table_data = build_relationship_table_data(entities, relationships, &catalog);
+
+    Ok(graph_refresh_response(TemplateResponse::new_template(
+        "knowledge/relationship_table.html",
+        table_data,
+    )))
+}
+
+#[derive(Serialize)]
+pub struct EntityTypesData {
+    builtin_types: Vec<String>,
+    definitions: Vec<EntityTypeDefinition>,
+}
+
+async fn load_entity_types_data(
+    user_id: &str,
+    db: &SurrealDbClient,
+) -> Result<EntityTypesData, AppError> {
+    Ok(EntityTypesData {
+        builtin_types: KnowledgeEntityType::variants()
+            .iter()
+            .map(ToString::to_string)
+            .collect(),
+        definitions: EntityTypeDefinition::list_for_user(user_id, db).await?,
+    })
+}
+
+pub async fn show_entity_types_modal(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+) -> TemplateResult {
+    let data = load_entity_types_data(&user.id, &state.db).await?;
+
+    Ok(TemplateResponse::new_template(
+        "knowledge/entity_types_modal.html",
+        data,
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateEntityTypeParams {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub color: Option<String>,
+}
+
+pub async fn create_entity_type(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+    Form(form): Form<CreateEntityTypeParams>,
+) -> ResponseResult {
+    EntityTypeDefinition::create(
+        &user.id,
+        &form.name,
+        &form.description,
+        form.color.as_deref(),
+        &state.db,
+    )
+    .await?;
+
+    let data = load_entity_types_data(&user.id, &state.db).await?;
+    Ok(graph_refresh_response(TemplateResponse::new_partial(
+        "knowledge/entity_types_modal.html",
+        "entity_type_list",
+        data,
+    )))
+}
+
+pub async fn delete_entity_type(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+    Path(id): Path<String>,
+) -> ResponseResult {
+    EntityTypeDefinition::delete(&id, &user.id, &state.db).await?;
+
+    let data = load_entity_types_data(&user.id, &state.db).await?;
+    Ok(graph_refresh_response(TemplateResponse::new_partial(
+        "knowledge/entity_types_modal.html",
+        "entity_type_list",
+        data,
+    )))
+}
+
+/// Renders the first page of the entity list with an undo banner for the latest
+/// merge or split, if one is still in effect.
+async fn entity_list_with_undo(user_id: &str, db: &SurrealDbClient) -> Result<Response, AppError> {
+    let (visible_entities, pagination) = paginate_items(
+        User::get_knowledge_entities(user_id, db).await?,
+        Some(1),
+        KNOWLEDGE_ENTITIES_PER_PAGE,
+    );
+    let entity_types = User::get_entity_types(user_id, db).await?;
+    let content_categories = User::get_user_categories(user_id, db).await?;
+    let last_operation = EntityOperation::latest_undoable(user_id, db)
+        .await?
+        .map(|operation| EntityOperationSummary {
+            id: operation.id,
+            summary: operation.summary,
+        });
+
+    Ok(graph_refresh_response(TemplateResponse::new_template(
+        "knowledge/entity_list.html",
+        EntityListData {
+            visible_entities,
+            pagination,
+            entity_types,
+            content_categories,
+            selected_entity_type: None,
+            selected_content_category: None,
+            page_query: String::new(),
+            last_operation,
+        },
+    )))
+}
+
+/// Non-empty, trimmed values submitted under `key`; checkbox groups repeat the key.
+fn form_values<'a>(pairs: &'a [(String, String)], key: &'a str) -> impl Iterator<Item = &'a str> {
+    pairs
+        .iter()
+        .filter(move |(name, _)| name == key || name.strip_suffix("[]") == Some(key))
+        .map(|(_, value)| value.trim())
+        .filter(|value| !value.is_empty())
+}
+
+pub async fn show_merge_entity_form(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+    Path(id): Path<String>,
+) -> TemplateResult {
+    #[derive(Serialize)]
+    pub struct MergeEntityData {
+        entity: KnowledgeEntity,
+        candidates: Vec<KnowledgeEntity>,
+    }
+
+    let entity = User::get_and_validate_knowledge_entity(&id, &user.id, &state.db).await?;
+
+    let mut candidates: Vec<KnowledgeEntity> = User::get_knowledge_entities(&user.id, &state.db)
+        .await?
+        .into_iter()
+        .filter(|candidate| candidate.id != entity.id)
+        .collect();
+    candidates.sort_by_key(|candidate| candidate.name.to_lowercase());
+
+    Ok(TemplateResponse::new_template(
+        "knowledge/merge_entity_modal.html",
+        MergeEntityData { entity, candidates },
+    ))
+}
+
+pub async fn merge_knowledge_entity(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+    Path(id): Path<String>,
+    Form(form): Form<Vec<(String, String)>>,
+) -> ResponseResult {
+    let merge_ids: Vec<String> = form_values(&form, "merge_ids")
+        .map(ToString::to_string)
+        .collect();
+
+    EntityOperation::merge(
+        &id,
+        &merge_ids,
+        &user.id,
+        &state.db,
+        &state.embedding_provider,
+    )
+    .await?;
+
+    Ok(entity_list_with_undo(&user.id, &state.db).await?)
+}
+
+pub async fn show_split_entity_form(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+    Path(id): Path<String>,
+) -> TemplateResult {
+    #[derive(Serialize)]
+    pub struct SplitRelationshipOption {
+        id: String,
+        label: String,
+    }
+
+    #[derive(Serialize)]
+    pub struct SplitEntityData {
+        entity: KnowledgeEntity,
+        entity_types: Vec<String>,
+        relationships: Vec<SplitRelationshipOption>,
+    }
+
+    let entity = User::get_and_validate_knowledge_entity(&id, &user.id, &state.db).await?;
+    let entity_types = EntityTypeDefinition::type_names_for_user(&user.id, &state.db).await?;
+    let catalog = RelationshipTypeCatalog::for_user(&user.id, &state.db).await?;
+
+    let names: HashMap<String, String> = User::get_knowledge_entities(&user.id, &state.db)
+        .await?
+        .into_iter()
+        .map(|entity| (entity.id, entity.name))
+        .collect();
+    let name_of = |id: &str| names.get(id).cloned().unwrap_or_else(|| id.to_string());
+
+    let relationships = User::get_knowledge_relationships(&user.id, &state.db)
+        .await?
+        .into_iter()
+        .filter(|relationship| relationship.in_ == entity.id || relationship.out == entity.id)
+        .map(|relationship| SplitRelationshipOption {
+            label: format!(
+                "{} \u{2192} {} ({})",
+                name_of(&relationship.in_),
+                name_of(&relationship.out),
+                relationship_type_label(&catalog, &relationship.metadata.relationship_type)
+            ),
+            id: relationship.id,
+        })
+        .collect();
+
+    Ok(TemplateResponse::new_template(
+        "knowledge/split_entity_modal.html",
+        SplitEntityData {
+            entity,
+            entity_types,
+            relationships,
+        },
+    ))
+}
+
+pub async fn split_knowledge_entity(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+    Path(id): Path<String>,
+    Form(form): Form<Vec<(String, String)>>,
+) -> ResponseResult {
+    let field = |key: &str| {
+        form_values(&form, key)
+            .next()
+            .unwrap_or_default()
+            .to_string()
+    };
+
+    let request = SplitRequest {
+        name: field("name"),
+        description: field("description"),
+        entity_type: KnowledgeEntityType::from(field("entity_type")),
+        relationship_ids: form_values(&form, "relationship_ids")
+            .map(ToString::to_string)
+            .collect(),
+    };
+
+    EntityOperation::split(&id, request, &user.id, &state.db, &state.embedding_provider).await?;
+
+    Ok(entity_list_with_undo(&user.id, &state.db).await?)
+}
+
+pub async fn undo_entity_operation(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+    Path(id): Path<String>,
+) -> ResponseResult {
+    EntityOperation::undo(&id, &user.id, &state.db).await?;
+
+    Ok(entity_list_with_undo(&user.id, &state.db).await?)
+}
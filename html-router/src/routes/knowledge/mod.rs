@@ -1,16 +1,32 @@
+mod duplicates;
 mod handlers;
+mod relationship_types;
+mod suggestions;
+mod tags;
 
 use axum::{
     Router,
     extract::FromRef,
-    routing::{delete, get, post},
+    routing::{delete, get, patch, post},
+};
+use duplicates::{
+    dismiss_duplicate_group, merge_duplicate_group, show_duplicate_report, show_duplicates_page,
+    start_duplicate_scan,
 };
 use handlers::{
-    create_knowledge_entity, delete_knowledge_entity, delete_knowledge_relationship,
-    get_knowledge_graph_json, patch_knowledge_entity, save_knowledge_relationship,
-    show_edit_knowledge_entity_form, show_knowledge_page, show_new_knowledge_entity_form,
-    suggest_knowledge_relationships,
+    create_entity_type, create_knowledge_entity, delete_entity_type, delete_knowledge_entity,
+    delete_knowledge_relationship, get_knowledge_graph_json, merge_knowledge_entity,
+    patch_knowledge_entity, save_knowledge_relationship, show_edit_knowledge_entity_form,
+    show_entity_types_modal, show_knowledge_page, show_merge_entity_form,
+    show_new_knowledge_entity_form, show_split_entity_form, split_knowledge_entity,
+    suggest_knowledge_relationships, undo_entity_operation, update_knowledge_relationship_weight,
+};
+use relationship_types::{
+    clean_up_relationship_types, create_relationship_type, delete_relationship_type,
+    show_relationship_types_modal,
 };
+use suggestions::{accept_suggestion, reject_suggestion, show_suggestions_page};
+use tags::{create_tag, delete_tag, rename_tag, show_tags_modal};
 
 use crate::html_state::HtmlState;
 
@@ -22,6 +38,49 @@ where
     Router::new()
         .route("/knowledge", get(show_knowledge_page))
         .route("/knowledge/graph.json", get(get_knowledge_graph_json))
+        .route(
+            "/knowledge/entity-types",
+            get(show_entity_types_modal).post(create_entity_type),
+        )
+        .route("/knowledge/entity-types/{id}", delete(delete_entity_type))
+        .route(
+            "/knowledge/relationship-types",
+            get(show_relationship_types_modal).post(create_relationship_type),
+        )
+        .route(
+            "/knowledge/relationship-types/clean-up",
+            post(clean_up_relationship_types),
+        )
+        .route(
+            "/knowledge/relationship-types/{id}",
+            delete(delete_relationship_type),
+        )
+        .route("/knowledge/tags", get(show_tags_modal).post(create_tag))
+        .route("/knowledge/tags/{id}", patch(rename_tag).delete(delete_tag))
+        .route("/knowledge/suggestions", get(show_suggestions_page))
+        .route(
+            "/knowledge/suggestions/{id}/accept",
+            post(accept_suggestion),
+        )
+        .route(
+            "/knowledge/suggestions/{id}/reject",
+            post(reject_suggestion),
+        )
+        .route("/knowledge/duplicates", get(show_duplicates_page))
+        .route("/knowledge/duplicates/report", get(show_duplicate_report))
+        .route("/knowledge/duplicates/scan", post(start_duplicate_scan))
+        .route(
+            "/knowledge/duplicates/{group_id}/merge",
+            post(merge_duplicate_group),
+        )
+        .route(
+            "/knowledge/duplicates/{group_id}/dismiss",
+            post(dismiss_duplicate_group),
+        )
+        .route(
+            "/knowledge/operations/{id}/undo",
+            post(undo_entity_operation),
+        )
         .route("/knowledge-entity/new", get(show_new_knowledge_entity_form))
         .route("/knowledge-entity", post(create_knowledge_entity))
         .route(
@@ -30,6 +89,14 @@ where
                 .delete(delete_knowledge_entity)
                 .patch(patch_knowledge_entity),
         )
+        .route(
+            "/knowledge-entity/{id}/merge",
+            get(show_merge_entity_form).post(merge_knowledge_entity),
+        )
+        .route(
+            "/knowledge-entity/{id}/split",
+            get(show_split_entity_form).post(split_knowledge_entity),
+        )
         .route(
             "/knowledge-entity/suggestions",
             post(suggest_knowledge_relationships),
@@ -37,6 +104,6 @@ where
         .route("/knowledge-relationship", post(save_knowledge_relationship))
         .route(
             "/knowledge-relationship/{id}",
-            delete(delete_knowledge_relationship),
+            delete(delete_knowledge_relationship).patch(update_knowledge_relationship_weight),
         )
 }
@@ -0,0 +1,124 @@
+use axum::{
+    Form,
+    extract::{Path, State},
+};
+use serde::{Deserialize, Serialize};
+
+use common::{
+    error::AppError,
+    storage::{
+        db::SurrealDbClient,
+        types::{
+            knowledge_relationship::{KnowledgeRelationship, RelationshipTypeCleanup},
+            relationship_type_definition::{
+                RelationshipType, RelationshipTypeCatalog, RelationshipTypeDefinition,
+            },
+        },
+    },
+};
+
+use super::handlers::graph_refresh_response;
+use crate::{
+    html_state::HtmlState,
+    middlewares::{
+        auth_middleware::RequireUser,
+        response_middleware::{ResponseResult, TemplateResponse, TemplateResult},
+    },
+};
+
+const RELATIONSHIP_TYPES_TEMPLATE: &str = "knowledge/relationship_types_modal.html";
+
+#[derive(Serialize)]
+pub struct RelationshipTypesData {
+    types: Vec<RelationshipType>,
+    /// Result of the last clean-up, shown right after running it.
+    cleanup: Option<RelationshipTypeCleanup>,
+}
+
+async fn load_relationship_types_data(
+    user_id: &str,
+    cleanup: Option<RelationshipTypeCleanup>,
+    db: &SurrealDbClient,
+) -> Result<RelationshipTypesData, AppError> {
+    Ok(RelationshipTypesData {
+        types: RelationshipTypeCatalog::for_user(user_id, db)
+            .await?
+            .types()
+            .to_vec(),
+        cleanup,
+    })
+}
+
+async fn relationship_type_list_response(
+    user_id: &str,
+    cleanup: Option<RelationshipTypeCleanup>,
+    db: &SurrealDbClient,
+) -> ResponseResult {
+    let data = load_relationship_types_data(user_id, cleanup, db).await?;
+    Ok(graph_refresh_response(TemplateResponse::new_partial(
+        RELATIONSHIP_TYPES_TEMPLATE,
+        "relationship_type_list",
+        data,
+    )))
+}
+
+pub async fn show_relationship_types_modal(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+) -> TemplateResult {
+    let data = load_relationship_types_data(&user.id, None, &state.db).await?;
+
+    Ok(TemplateResponse::new_template(
+        RELATIONSHIP_TYPES_TEMPLATE,
+        data,
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateRelationshipTypeParams {
+    pub name: String,
+    pub inverse_name: Option<String>,
+    /// Checkbox value; present when the type is directed.
+    pub directed: Option<String>,
+    #[serde(default)]
+    pub description: String,
+}
+
+pub async fn create_relationship_type(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+    Form(form): Form<CreateRelationshipTypeParams>,
+) -> ResponseResult {
+    RelationshipTypeDefinition::create(
+        &user.id,
+        &form.name,
+        form.inverse_name.as_deref(),
+        form.directed.is_some(),
+        &form.description,
+        &state.db,
+    )
+    .await?;
+
+    relationship_type_list_response(&user.id, None, &state.db).await
+}
+
+pub async fn delete_relationship_type(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+    Path(id): Path<String>,
+) -> ResponseResult {
+    RelationshipTypeDefinition::delete(&id, &user.id, &state.db).await?;
+
+    relationship_type_list_response(&user.id, None, &state.db).await
+}
+
+/// Rewrites existing relationships onto the managed types and reports what is left.
+pub async fn clean_up_relationship_types(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+) -> ResponseResult {
+    let catalog = RelationshipTypeCatalog::for_user(&user.id, &state.db).await?;
+    let cleanup = KnowledgeRelationship::canonicalize_types(&user.id, &catalog, &state.db).await?;
+
+    relationship_type_list_response(&user.id, Some(cleanup), &state.db).await
+}
@@ -0,0 +1,141 @@
+use axum::{
+    Form,
+    extract::{Path, State},
+};
+use axum_htmx::{HxBoosted, HxRequest};
+use serde::{Deserialize, Serialize};
+
+use common::{
+    error::AppError,
+    storage::{
+        db::SurrealDbClient,
+        types::{
+            entity_type_definition::EntityTypeDefinition,
+            knowledge_suggestion::{KnowledgeSuggestion, SuggestionEdits, SuggestionStatus},
+            relationship_type_definition::RelationshipTypeCatalog,
+        },
+    },
+};
+
+use super::handlers::{graph_refresh_response, parse_form_weight};
+use crate::{
+    html_state::HtmlState,
+    middlewares::{
+        auth_middleware::RequireUser,
+        response_middleware::{ResponseResult, TemplateResponse, TemplateResult},
+    },
+};
+
+const SUGGESTIONS_TEMPLATE: &str = "knowledge/suggestions.html";
+
+#[derive(Serialize)]
+pub struct SuggestionRow {
+    suggestion: KnowledgeSuggestion,
+    in_name: Option<String>,
+    out_name: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct SuggestionReviewData {
+    rows: Vec<SuggestionRow>,
+    entity_type_options: Vec<String>,
+    relationship_type_options: Vec<String>,
+}
+
+async fn load_review_data(
+    user_id: &str,
+    db: &SurrealDbClient,
+) -> Result<SuggestionReviewData, AppError> {
+    let suggestions =
+        KnowledgeSuggestion::list_for_user(user_id, SuggestionStatus::Pending, db).await?;
+    let names = KnowledgeSuggestion::endpoint_names(&suggestions, user_id, db).await?;
+    let rows = suggestions
+        .into_iter()
+        .map(|mut suggestion| {
+            // Embeddings are only needed to store accepted entities.
+            suggestion.embedding = None;
+            let (in_name, out_name) = suggestion
+                .relationship
+                .as_ref()
+                .map_or((None, None), |rel| {
+                    (names.get(&rel.in_).cloned(), names.get(&rel.out).cloned())
+                });
+            SuggestionRow {
+                suggestion,
+                in_name,
+                out_name,
+            }
+        })
+        .collect();
+
+    Ok(SuggestionReviewData {
+        rows,
+        entity_type_options: EntityTypeDefinition::type_names_for_user(user_id, db).await?,
+        relationship_type_options: RelationshipTypeCatalog::for_user(user_id, db)
+            .await?
+            .names(),
+    })
+}
+
+fn review_partial(data: SuggestionReviewData) -> TemplateResponse {
+    TemplateResponse::new_partial(SUGGESTIONS_TEMPLATE, "suggestion_list", data)
+}
+
+pub async fn show_suggestions_page(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+    HxRequest(is_htmx): HxRequest,
+    HxBoosted(is_boosted): HxBoosted,
+) -> TemplateResult {
+    let data = load_review_data(&user.id, &state.db).await?;
+
+    if is_htmx && !is_boosted {
+        Ok(TemplateResponse::new_partial(
+            SUGGESTIONS_TEMPLATE,
+            "main",
+            data,
+        ))
+    } else {
+        Ok(TemplateResponse::new_template(SUGGESTIONS_TEMPLATE, data))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AcceptSuggestionParams {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub entity_type: Option<String>,
+    pub relationship_type: Option<String>,
+    pub weight: Option<String>,
+}
+
+pub async fn accept_suggestion(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+    Path(id): Path<String>,
+    Form(form): Form<AcceptSuggestionParams>,
+) -> ResponseResult {
+    let edits = SuggestionEdits {
+        weight: parse_form_weight(form.weight.as_deref())?,
+        name: form.name,
+        description: form.description,
+        entity_type: form.entity_type,
+        relationship_type: form.relationship_type,
+    };
+    KnowledgeSuggestion::accept(&id, &user.id, &edits, &state.db, &state.embedding_provider)
+        .await?;
+
+    let data = load_review_data(&user.id, &state.db).await?;
+    Ok(graph_refresh_response(review_partial(data)))
+}
+
+pub async fn reject_suggestion(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+    Path(id): Path<String>,
+) -> TemplateResult {
+    KnowledgeSuggestion::reject(&id, &user.id, &state.db).await?;
+
+    let data = load_review_data(&user.id, &state.db).await?;
+    Ok(review_partial(data))
+}
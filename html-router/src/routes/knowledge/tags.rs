@@ -0,0 +1,89 @@
+use axum::{
+    Form,
+    extract::{Path, State},
+};
+use serde::{Deserialize, Serialize};
+
+use common::{
+    error::AppError,
+    storage::{
+        db::SurrealDbClient,
+        types::tag::{Tag, TagUsage},
+    },
+};
+
+use super::handlers::graph_refresh_response;
+use crate::{
+    html_state::HtmlState,
+    middlewares::{
+        auth_middleware::RequireUser,
+        response_middleware::{ResponseResult, TemplateResponse, TemplateResult},
+    },
+};
+
+const TAGS_TEMPLATE: &str = "knowledge/tags_modal.html";
+
+#[derive(Serialize)]
+pub struct TagsData {
+    tags: Vec<TagUsage>,
+}
+
+async fn load_tags_data(user_id: &str, db: &SurrealDbClient) -> Result<TagsData, AppError> {
+    Ok(TagsData {
+        tags: Tag::usage_for_user(user_id, db).await?,
+    })
+}
+
+async fn tag_list_response(user_id: &str, db: &SurrealDbClient) -> ResponseResult {
+    let data = load_tags_data(user_id, db).await?;
+    Ok(graph_refresh_response(TemplateResponse::new_partial(
+        TAGS_TEMPLATE,
+        "tag_list",
+        data,
+    )))
+}
+
+pub async fn show_tags_modal(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+) -> TemplateResult {
+    let data = load_tags_data(&user.id, &state.db).await?;
+
+    Ok(TemplateResponse::new_template(TAGS_TEMPLATE, data))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TagNameParams {
+    pub name: String,
+}
+
+pub async fn create_tag(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+    Form(form): Form<TagNameParams>,
+) -> ResponseResult {
+    Tag::create(&user.id, &form.name, &state.db).await?;
+
+    tag_list_response(&user.id, &state.db).await
+}
+
+pub async fn rename_tag(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+    Path(id): Path<String>,
+    Form(form): Form<TagNameParams>,
+) -> ResponseResult {
+    Tag::rename(&id, &user.id, &form.name, &state.db).await?;
+
+    tag_list_response(&user.id, &state.db).await
+}
+
+pub async fn delete_tag(
+    State(state): State<HtmlState>,
+    RequireUser(user): RequireUser,
+    Path(id): Path<String>,
+) -> ResponseResult {
+    Tag::delete(&id, &user.id, &state.db).await?;
+
+    tag_list_response(&user.id, &state.db).await
+}
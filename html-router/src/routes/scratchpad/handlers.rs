@@ -16,7 +16,9 @@ use crate::middlewares::{
     },
 };
 use common::storage::types::{
-    ingestion_payload::IngestionPayload, ingestion_task::IngestionTask, scratchpad::Scratchpad,
+    ingestion_payload::IngestionPayload,
+    ingestion_task::{IngestionTask, TaskPriority},
+    scratchpad::Scratchpad,
 };
 
 #[derive(Serialize)]
@@ -323,8 +325,15 @@ pub async fn ingest_scratchpad(
         user_id: user.id.clone(),
     };
 
-    let task = IngestionTask::new(payload, user.id.clone());
-    state.db.store_item(task).await?;
+    let outcome = IngestionTask::create_with_policy(
+        payload,
+        &user.id,
+        state.config.ingest_duplicate_policy,
+        // Archiving a scratchpad is an interactive capture; queue it ahead of bulk work.
+        TaskPriority::High,
+        &state.db,
+    )
+    .await?;
 
     // Archive the scratchpad once queued for ingestion
     Scratchpad::archive(&scratchpad_id, &user.id, &state.db, true).await?;
@@ -339,13 +348,23 @@ pub async fn ingest_scratchpad(
         .map(ScratchpadArchiveItem::from)
         .collect();
 
-    let trigger_payload = serde_json::json!({
-        "toast": {
-            "title": "Ingestion queued",
-            "description": format!("\"{}\" archived and added to the ingestion queue.", scratchpad.title),
-            "type": "success"
-        }
-    });
+    let trigger_payload = if outcome.is_created() {
+        serde_json::json!({
+            "toast": {
+                "title": "Ingestion queued",
+                "description": format!("\"{}\" archived and added to the ingestion queue.", scratchpad.title),
+                "type": "success"
+            }
+        })
+    } else {
+        serde_json::json!({
+            "toast": {
+                "title": "Already added",
+                "description": format!("\"{}\" archived; its content was already added, so it was not queued again.", scratchpad.title),
+                "type": "info"
+            }
+        })
+    };
     let trigger_value = serde_json::to_string(&trigger_payload).unwrap_or_else(|_| {
         r#"{"toast":{"title":"Ingestion queued","description":"Scratchpad archived and added to the ingestion queue.","type":"success"}}"#.to_string()
     });
@@ -2,7 +2,7 @@ use std::collections::HashSet;
 
 use axum::extract::{Query, State};
 use axum_htmx::{HxBoosted, HxRequest};
-use common::storage::types::{text_content::TextContent, user::User};
+use common::storage::types::{tag::Tag, text_content::TextContent, user::User};
 use retrieval_pipeline::{
     RetrievalConfig, RetrievalOutput, RetrievedChunk, RetrievedEntity, retrieve,
 };
@@ -70,6 +70,9 @@ pub struct SearchParams {
     query: Option<String>,
     #[serde(default)]
     view: SearchView,
+    /// Only return results from content, or entities, carrying this tag.
+    #[serde(default, deserialize_with = "empty_string_as_none")]
+    tag: Option<String>,
 }
 
 /// Chunk result for template rendering
@@ -109,6 +112,8 @@ pub struct AnswerData {
     search_result: Vec<SearchResultForTemplate>,
     query_param: String,
     view_param: String,
+    tags: Vec<String>,
+    tag_param: String,
 }
 
 pub async fn search_result_handler(
@@ -119,9 +124,10 @@ pub async fn search_result_handler(
     HxBoosted(is_boosted): HxBoosted,
 ) -> TemplateResult {
     let view = params.view;
+    let tag = params.tag;
     let (search_results_for_template, final_query_param_for_template) =
         if let Some(actual_query) = params.query {
-            perform_search(&state, &user, actual_query, view).await?
+            perform_search(&state, &user, actual_query, view, tag.as_deref()).await?
         } else {
             (Vec::<SearchResultForTemplate>::new(), String::new())
         };
@@ -130,6 +136,8 @@ pub async fn search_result_handler(
         search_result: search_results_for_template,
         query_param: final_query_param_for_template,
         view_param: view.as_str().to_string(),
+        tags: Tag::names_for_user(&user.id, &state.db).await?,
+        tag_param: tag.unwrap_or_default(),
     };
 
     if is_htmx && !is_boosted {
@@ -148,6 +156,7 @@ async fn perform_search(
     user: &User,
     query: String,
     view: SearchView,
+    tag: Option<&str>,
 ) -> Result<(Vec<SearchResultForTemplate>, String), HtmlError> {
     const TOTAL_LIMIT: usize = 10;
 
@@ -156,10 +165,11 @@ async fn perform_search(
         return Ok((Vec::new(), String::new()));
     }
 
-    let config = match view {
+    let mut config = match view {
         SearchView::Chunks => RetrievalConfig::default(),
         SearchView::All | SearchView::Entities => RetrievalConfig::with_entities(),
     };
+    config.expand_graph = state.config.retrieval_graph_expansion;
 
     let reranker_lease = match &state.reranker_pool {
         Some(pool) => pool.checkout().await,
@@ -175,6 +185,10 @@ async fn perform_search(
         reranker_lease,
     )
     .await?;
+    let result = match tag {
+        Some(tag) => filter_by_tag(state, user, result, tag).await?,
+        None => result,
+    };
 
     let mut results = match view {
         SearchView::Chunks => {
@@ -213,6 +227,46 @@ async fn perform_search(
     Ok((results, trimmed_query.to_string()))
 }
 
+/// Keeps chunks whose source content carries `tag`, and entities that carry it
+/// themselves or come from such content.
+async fn filter_by_tag(
+    state: &HtmlState,
+    user: &User,
+    result: RetrievalOutput,
+    tag: &str,
+) -> Result<RetrievalOutput, HtmlError> {
+    let (mut chunks, entities) = match result {
+        RetrievalOutput::Chunks(chunks) => (chunks, None),
+        RetrievalOutput::WithEntities { chunks, entities } => (chunks, Some(entities)),
+    };
+
+    let source_ids: HashSet<&str> = chunks
+        .iter()
+        .map(|chunk_result| chunk_result.chunk.source_id.as_str())
+        .chain(
+            entities
+                .iter()
+                .flatten()
+                .map(|entity_result| entity_result.entity.source_id.as_str()),
+        )
+        .collect();
+    let tagged_sources =
+        TextContent::source_ids_with_tag(&state.db, &user.id, source_ids, tag).await?;
+
+    chunks.retain(|chunk_result| tagged_sources.contains(&chunk_result.chunk.source_id));
+
+    Ok(match entities {
+        Some(mut entities) => {
+            entities.retain(|entity_result| {
+                entity_result.entity.tags.iter().any(|t| t == tag)
+                    || tagged_sources.contains(&entity_result.entity.source_id)
+            });
+            RetrievalOutput::WithEntities { chunks, entities }
+        }
+        None => RetrievalOutput::Chunks(chunks),
+    })
+}
+
 fn chunk_results_for_template(
     chunks: &[RetrievedChunk],
     source_label_map: &std::collections::HashMap<String, String>,
@@ -263,7 +317,7 @@ fn entity_results_for_template(
                     id: entity_result.entity.id.clone(),
                     name: entity_result.entity.name.clone(),
                     description: entity_result.entity.description.clone(),
-                    entity_type: format!("{:?}", entity_result.entity.entity_type),
+                    entity_type: entity_result.entity.entity_type.to_string(),
                     source_id: entity_result.entity.source_id.clone(),
                     source_label,
                     score: entity_result.score,
@@ -9,10 +9,20 @@ use axum::{
     response::Response,
 };
 use common::{
-    storage::{db::SurrealDbClient, store::StorageManager, types::user::User},
+    storage::{
+        db::SurrealDbClient,
+        store::StorageManager,
+        types::{
+            llm_usage::{LlmUsage, UsageRole},
+            settings_version::SettingsVersion,
+            system_settings::{ModelRoute, SystemSettings},
+            user::User,
+        },
+    },
     utils::{
-        config::{AppConfig, StorageKind},
+        config::{AppConfig, LlmBackend, StorageKind},
         embedding::EmbeddingProvider,
+        llm::LlmProviders,
     },
 };
 use html_router::{
@@ -45,9 +55,14 @@ async fn build_test_app() -> (Router, Arc<SurrealDbClient>) {
     let embedding_provider =
         Arc::new(EmbeddingProvider::new_hashed(8).expect("embedding provider"));
 
+    let openai_client = Arc::new(async_openai::Client::new());
     let state = HtmlState::new_with_resources(StateResources {
         db: Arc::clone(&db),
-        openai_client: Arc::new(async_openai::Client::new()),
+        llm_providers: Arc::new(LlmProviders::from_config(
+            &config,
+            Arc::clone(&openai_client),
+        )),
+        openai_client,
         session_store,
         storage,
         config,
@@ -551,3 +566,181 @@ async fn admin_route_redirects_non_admin_user() {
 
     assert_eq!(admin_response.status(), StatusCode::OK);
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn llm_usage_is_shown_to_admins_per_user_and_on_the_account_page() {
+    let (app, db) = build_test_app().await;
+
+    let admin = User::create_new(
+        "usage_admin@example.com".to_string(),
+        "admin_password".to_string(),
+        &db,
+        "UTC".to_string(),
+        "system".to_string(),
+    )
+    .await
+    .expect("admin user");
+    let member = User::create_new(
+        "usage_member@example.com".to_string(),
+        "member_password".to_string(),
+        &db,
+        "UTC".to_string(),
+        "system".to_string(),
+    )
+    .await
+    .expect("member user");
+
+    let route = ModelRoute::new(LlmBackend::OpenAI, "gpt-4o-mini");
+    db.store_item(LlmUsage::new(
+        &member.id,
+        Some("conversation-1"),
+        UsageRole::Chat,
+        &route,
+        1234,
+        56,
+    ))
+    .await
+    .expect("member usage");
+    db.store_item(LlmUsage::new(
+        &admin.id,
+        None,
+        UsageRole::Ingestion,
+        &route,
+        777,
+        1,
+    ))
+    .await
+    .expect("admin usage");
+
+    let admin_cookie = sign_in(&app, "usage_admin@example.com", "admin_password").await;
+    let admin_page = get_html(&app, "/admin?section=usage", Some(&admin_cookie)).await;
+    assert!(admin_page.contains("usage_member@example.com"));
+    assert!(admin_page.contains("usage_admin@example.com"));
+    assert!(
+        admin_page.contains("gpt-4o-mini"),
+        "unpriced model is flagged"
+    );
+
+    let member_cookie = sign_in(&app, "usage_member@example.com", "member_password").await;
+    let account_page = get_html(&app, "/account", Some(&member_cookie)).await;
+    assert!(
+        account_page.contains("1 call and 1290 tokens"),
+        "account page only sums the user's own usage"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn admin_prompt_preview_renders_templates_and_reports_errors() {
+    let (app, db) = build_test_app().await;
+    User::create_new(
+        "prompt_admin@example.com".to_string(),
+        "admin_password".to_string(),
+        &db,
+        "UTC".to_string(),
+        "system".to_string(),
+    )
+    .await
+    .expect("admin user");
+    let cookie = sign_in(&app, "prompt_admin@example.com", "admin_password").await;
+
+    let preview = |body: &'static str| {
+        let app = app.clone();
+        let cookie = cookie.clone();
+        async move {
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .method("POST")
+                        .uri("/preview-prompt")
+                        .header(header::COOKIE, cookie)
+                        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                        .body(Body::from(body))
+                        .expect("preview request"),
+                )
+                .await
+                .expect("preview response");
+            response_body(response).await
+        }
+    };
+
+    let rendered = preview("prompt=Extract+from+%7B%7B+category+%7D%7D&category=Papers").await;
+    assert!(rendered.contains("Extract from Papers"), "{rendered}");
+
+    let rendered = preview("ingestion_system_prompt=Types%3A+%7B%7B+entity_types+%7D%7D").await;
+    assert!(rendered.contains("Types: Person, Organization, Project"));
+
+    let failed = preview("query_system_prompt=%7B%7B+categroy+%7D%7D").await;
+    assert!(failed.contains("text-error"), "{failed}");
+}
+
+#[tokio::test]
+async fn admin_prompt_edits_are_listed_in_history_and_can_be_rolled_back() {
+    let (app, db) = build_test_app().await;
+    User::create_new(
+        "history_admin@example.com".to_string(),
+        "admin_password".to_string(),
+        &db,
+        "UTC".to_string(),
+        "system".to_string(),
+    )
+    .await
+    .expect("admin user");
+    let cookie = sign_in(&app, "history_admin@example.com", "admin_password").await;
+    let original = SystemSettings::get_current(&db)
+        .await
+        .expect("settings")
+        .ingestion_system_prompt;
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("PATCH")
+                .uri("/update-ingestion-prompt")
+                .header(header::COOKIE, cookie.clone())
+                .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(Body::from("ingestion_system_prompt=Broken+prompt"))
+                .expect("patch request"),
+        )
+        .await
+        .expect("patch response");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/admin?section=history")
+                .header(header::COOKIE, cookie.clone())
+                .body(Body::empty())
+                .expect("history request"),
+        )
+        .await
+        .expect("history response");
+    let page = response_body(response).await;
+    assert!(page.contains("history_admin@example.com"), "{page}");
+    assert!(page.contains("Broken prompt"));
+
+    let edit = SettingsVersion::list_recent(&db, 1)
+        .await
+        .expect("history")
+        .into_iter()
+        .next()
+        .expect("prompt edit");
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/rollback-settings/{}", edit.version))
+                .header(header::COOKIE, cookie)
+                .body(Body::empty())
+                .expect("rollback request"),
+        )
+        .await
+        .expect("rollback response");
+    let rendered = response_body(response).await;
+    assert!(rendered.contains("rollback of v"), "{rendered}");
+
+    let restored = SystemSettings::get_current(&db).await.expect("settings");
+    assert_eq!(restored.ingestion_system_prompt, original);
+}
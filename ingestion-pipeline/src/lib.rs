@@ -4,17 +4,24 @@ pub mod pipeline;
 pub mod utils;
 
 use chrono::Utc;
-use common::storage::{
-    db::SurrealDbClient,
-    indexes::maybe_run_scheduled_index_rebuild,
-    types::ingestion_task::{DEFAULT_LEASE_SECS, IngestionTask},
+use common::{
+    storage::{
+        db::SurrealDbClient,
+        indexes::maybe_run_scheduled_index_rebuild,
+        types::ingestion_task::{DEFAULT_LEASE_SECS, IngestionTask},
+    },
+    utils::config::AppConfig,
 };
 pub use pipeline::{
     EmbeddedKnowledgeEntity, EmbeddedTextChunk, IngestionConfig, IngestionPipeline,
     IngestionTuning, PipelineArtifacts, persist_artifacts,
 };
 use std::sync::Arc;
-use tokio::time::{Duration, sleep};
+use tokio::{
+    sync::Semaphore,
+    task::JoinSet,
+    time::{Duration, sleep},
+};
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
@@ -23,17 +30,61 @@ const WORKER_IDLE_BACKOFF_MS: u64 = 500;
 /// How long the worker sleeps after a transient claim error before retrying.
 const WORKER_CLAIM_ERROR_BACKOFF_MS: u64 = 1_000;
 
+/// Runtime knobs for [`run_worker_loop`].
+#[derive(Debug, Clone, Copy)]
+pub struct WorkerOptions {
+    /// Maximum number of tasks processed at the same time by this worker.
+    pub concurrency: usize,
+    /// Interval for the scheduled index rebuild; `0` disables it.
+    pub index_rebuild_interval_secs: u64,
+}
+
+impl WorkerOptions {
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            concurrency: config.ingest_worker_concurrency,
+            index_rebuild_interval_secs: config.index_rebuild_interval_secs,
+        }
+    }
+}
+
+impl Default for WorkerOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: 1,
+            index_rebuild_interval_secs: 0,
+        }
+    }
+}
+
 pub async fn run_worker_loop(
     db: Arc<SurrealDbClient>,
     ingestion_pipeline: Arc<IngestionPipeline>,
-    index_rebuild_interval_secs: u64,
+    options: WorkerOptions,
 ) -> anyhow::Result<()> {
     let worker_id = format!("ingestion-worker-{}", Uuid::new_v4());
     let lease_duration = Duration::from_secs(DEFAULT_LEASE_SECS as u64);
     let idle_backoff = Duration::from_millis(WORKER_IDLE_BACKOFF_MS);
     let claim_error_backoff = Duration::from_millis(WORKER_CLAIM_ERROR_BACKOFF_MS);
+    let concurrency = options.concurrency.max(1);
+
+    info!(%worker_id, concurrency, "starting ingestion worker");
+
+    let slots = Arc::new(Semaphore::new(concurrency));
+    let mut running = JoinSet::new();
+
+    let result = loop {
+        while let Some(joined) = running.try_join_next() {
+            if let Err(err) = joined {
+                error!(%worker_id, error = %err, "ingestion task panicked");
+            }
+        }
+
+        let permit = match Arc::clone(&slots).acquire_owned().await {
+            Ok(permit) => permit,
+            Err(err) => break Err(anyhow::Error::from(err)),
+        };
 
-    loop {
         match IngestionTask::claim_next_ready(&db, &worker_id, Utc::now(), lease_duration).await {
             Ok(Some(task)) => {
                 let task_id = task.id.clone();
@@ -43,20 +94,30 @@ pub async fn run_worker_loop(
                     attempt = task.attempts,
                     "claimed ingestion task"
                 );
-                if let Err(err) = ingestion_pipeline.process_task(task).await {
-                    error!(%worker_id, %task_id, error = %err, "ingestion task failed");
-                }
+                let pipeline = Arc::clone(&ingestion_pipeline);
+                let worker_id = worker_id.clone();
+                running.spawn(async move {
+                    if let Err(err) = pipeline.process_task(task).await {
+                        error!(%worker_id, %task_id, error = %err, "ingestion task failed");
+                    }
+                    drop(permit);
+                });
             }
             Ok(None) => {
-                maybe_run_scheduled_index_rebuild(
-                    db.as_ref(),
-                    &worker_id,
-                    index_rebuild_interval_secs,
-                )
-                .await;
+                drop(permit);
+                // Rebuilds are heavy; only run them when this worker has nothing in flight.
+                if running.is_empty() {
+                    maybe_run_scheduled_index_rebuild(
+                        db.as_ref(),
+                        &worker_id,
+                        options.index_rebuild_interval_secs,
+                    )
+                    .await;
+                }
                 sleep(idle_backoff).await;
             }
             Err(err) => {
+                drop(permit);
                 error!(%worker_id, error = %err, "failed to claim ingestion task");
                 warn!(
                     backoff_ms = WORKER_CLAIM_ERROR_BACKOFF_MS,
@@ -65,5 +126,8 @@ pub async fn run_worker_loop(
                 sleep(claim_error_backoff).await;
             }
         }
-    }
+    };
+
+    running.shutdown().await;
+    result
 }
@@ -12,6 +12,21 @@ pub struct IngestionTuning {
     /// Maximum characters of content body used to build the similarity-search query
     /// during retrieval. Longer bodies are truncated to keep embedding inputs bounded.
     pub embedding_query_char_limit: usize,
+    /// Minimum cosine similarity for folding a new entity into an existing one of the
+    /// same type during entity resolution. Exact name matches do not need it.
+    pub entity_resolution_min_similarity: f32,
+    /// Nearest existing entities considered per new entity during resolution.
+    pub entity_resolution_candidates: usize,
+    /// Documents longer than this many characters are enriched section by section and
+    /// the per-section results merged, instead of in one request.
+    pub long_document_chars: usize,
+    /// Target size in characters of each section in long-document enrichment.
+    pub enrichment_section_chars: usize,
+    /// Maximum characters of content body sent to the summary stage. Longer bodies are
+    /// truncated and the model is told so.
+    pub summary_input_chars: usize,
+    /// Tags outside the user's vocabulary that enrichment may add to one document.
+    pub max_new_tags_per_document: usize,
 }
 
 impl Default for IngestionTuning {
@@ -27,6 +42,12 @@ impl Default for IngestionTuning {
             chunk_max_tokens: 512,
             chunk_overlap_tokens: 50,
             embedding_query_char_limit: 12_000,
+            entity_resolution_min_similarity: 0.92,
+            entity_resolution_candidates: 5,
+            long_document_chars: 60_000,
+            enrichment_section_chars: 24_000,
+            summary_input_chars: 24_000,
+            max_new_tags_per_document: 3,
         }
     }
 }
@@ -5,8 +5,10 @@ use common::{
     storage::{
         db::SurrealDbClient,
         types::{
-            ingestion_task::IngestionTask, knowledge_entity::KnowledgeEntity,
-            knowledge_relationship::KnowledgeRelationship, text_chunk::TextChunk,
+            action_item::ActionItem, ingestion_task::IngestionTask,
+            knowledge_entity::KnowledgeEntity, knowledge_entity_mention::KnowledgeEntityMention,
+            knowledge_relationship::KnowledgeRelationship,
+            knowledge_suggestion::KnowledgeSuggestion, text_chunk::TextChunk,
             text_content::TextContent,
         },
     },
@@ -17,7 +19,7 @@ use tracing::error;
 
 use super::enrichment_result::LLMEnrichmentResult;
 
-use super::{config::IngestionConfig, services::PipelineServices};
+use super::{config::IngestionConfig, resolution::resolve_entities, services::PipelineServices};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EmbeddedKnowledgeEntity {
@@ -49,6 +51,33 @@ pub struct PipelineArtifacts {
     pub entities: Vec<EmbeddedKnowledgeEntity>,
     pub relationships: Vec<KnowledgeRelationship>,
     pub chunks: Vec<EmbeddedTextChunk>,
+    /// Mentions of existing entities this document resolved to. Entities in `entities`
+    /// get their own mention when persisted.
+    pub mentions: Vec<KnowledgeEntityMention>,
+    pub action_items: Vec<ActionItem>,
+    /// Entities and relationships held back for the user's review.
+    pub suggestions: Vec<KnowledgeSuggestion>,
+}
+
+impl PipelineArtifacts {
+    /// Moves the proposed entities and relationships into pending suggestions, so only
+    /// chunks, action items and mentions of existing entities reach the graph.
+    #[must_use]
+    pub fn into_review(mut self) -> Self {
+        let entities = std::mem::take(&mut self.entities);
+        let relationships = std::mem::take(&mut self.relationships);
+        self.suggestions.extend(
+            entities
+                .into_iter()
+                .map(|item| KnowledgeSuggestion::for_entity(item.entity, item.embedding))
+                .chain(
+                    relationships
+                        .into_iter()
+                        .map(KnowledgeSuggestion::for_relationship),
+                ),
+        );
+        self
+    }
 }
 
 impl<'a> PipelineContext<'a> {
@@ -110,6 +139,16 @@ impl<'a> PipelineContext<'a> {
         let analysis = self.take_analysis()?;
 
         let (entities, relationships) = self.services.convert_analysis(&content, &analysis).await?;
+        let action_items = analysis.to_action_items(&content.id, &content.user_id);
+        let resolved = resolve_entities(
+            self.db,
+            &self.pipeline_config.tuning,
+            &content.id,
+            &content.user_id,
+            entities,
+            relationships,
+        )
+        .await?;
 
         let chunk_range = self.chunk_token_range();
         let chunk_overlap = self.chunk_overlap_tokens();
@@ -121,9 +160,12 @@ impl<'a> PipelineContext<'a> {
 
         Ok(PipelineArtifacts {
             text_content: content,
-            entities,
-            relationships,
+            entities: resolved.entities,
+            relationships: resolved.relationships,
             chunks,
+            mentions: resolved.mentions,
+            action_items,
+            suggestions: Vec::new(),
         })
     }
 
@@ -142,6 +184,9 @@ impl<'a> PipelineContext<'a> {
             entities: Vec::new(),
             relationships: Vec::new(),
             chunks,
+            mentions: Vec::new(),
+            action_items: Vec::new(),
+            suggestions: Vec::new(),
         })
     }
 
@@ -1,24 +1,72 @@
+use std::collections::{HashMap, HashSet};
+
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 
 use common::{
     error::AppError,
     storage::types::{
+        action_item::ActionItem,
+        entity_fact::{EntityAttribute, EntityAttributeKind},
         knowledge_entity::{KnowledgeEntity, KnowledgeEntityType},
-        knowledge_relationship::KnowledgeRelationship,
+        knowledge_relationship::{
+            DEFAULT_RELATIONSHIP_WEIGHT, KnowledgeRelationship, normalize_weight,
+        },
+        relationship_type_definition::RelationshipTypeCatalog,
+        tag::normalize_tags,
     },
     utils::embedding::EmbeddingProvider,
 };
 
-use crate::pipeline::context::EmbeddedKnowledgeEntity;
+use crate::pipeline::{context::EmbeddedKnowledgeEntity, resolution::normalize_entity_name};
 use crate::utils::graph_mapper::GraphMapper;
 
+/// Merged descriptions stop growing once they reach this many characters.
+const MAX_MERGED_DESCRIPTION_CHARS: usize = 1_200;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct LLMKnowledgeEntity {
     pub key: String,
     pub name: String,
     pub description: String,
     pub entity_type: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Typed facts about the entity (dates, deadlines, URLs, numbers, amounts, people).
+    #[serde(default)]
+    pub attributes: Vec<LLMEntityAttribute>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LLMEntityAttribute {
+    pub kind: String,
+    pub label: String,
+    pub value: String,
+    #[serde(default)]
+    pub unit: Option<String>,
+}
+
+impl LLMEntityAttribute {
+    /// The stored form of this fact, or `None` for an unknown kind or a value that
+    /// does not match it.
+    pub fn to_attribute(&self) -> Option<EntityAttribute> {
+        EntityAttribute {
+            kind: EntityAttributeKind::parse(&self.kind)?,
+            label: self.label.clone(),
+            value: self.value.clone(),
+            unit: self.unit.clone(),
+        }
+        .normalized()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LLMActionItem {
+    pub description: String,
+    #[serde(default)]
+    pub owner: Option<String>,
+    #[serde(default)]
+    pub due_date: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -27,15 +75,155 @@ pub struct LLMRelationship {
     pub type_: String,
     pub source: String,
     pub target: String,
+    /// Confidence in the relationship, from `0.0` to `1.0`.
+    #[serde(default)]
+    pub weight: Option<f32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct LLMEnrichmentResult {
     pub knowledge_entities: Vec<LLMKnowledgeEntity>,
     pub relationships: Vec<LLMRelationship>,
+    /// Tags for the document as a whole.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub action_items: Vec<LLMActionItem>,
 }
 
 impl LLMEnrichmentResult {
+    /// Reduces the per-section results of a long document into one result.
+    ///
+    /// Section-local keys are namespaced so they cannot collide, entities sharing a
+    /// normalized name are folded into the first one (appending new descriptions and
+    /// facts), relationships are re-pointed, deduplicated, and stripped of self-loops, and
+    /// repeated action items are dropped.
+    pub fn merge_sections(sections: Vec<Self>) -> Self {
+        let mut knowledge_entities: Vec<LLMKnowledgeEntity> = Vec::new();
+        let mut by_name: HashMap<String, usize> = HashMap::new();
+        let mut relationships = Vec::new();
+        let mut seen_relationships = HashSet::new();
+        let mut tags: Vec<String> = Vec::new();
+        let mut action_items: Vec<LLMActionItem> = Vec::new();
+        let mut seen_action_items = HashSet::new();
+
+        for (index, section) in sections.into_iter().enumerate() {
+            let mut keys: HashMap<String, String> = HashMap::new();
+            extend_unique(&mut tags, section.tags);
+            for item in section.action_items {
+                if seen_action_items.insert(item.description.trim().to_lowercase()) {
+                    action_items.push(item);
+                }
+            }
+
+            for entity in section.knowledge_entities {
+                let name = normalize_entity_name(&entity.name);
+                if !name.is_empty()
+                    && let Some(existing) = by_name
+                        .get(&name)
+                        .and_then(|&position| knowledge_entities.get_mut(position))
+                {
+                    keys.insert(entity.key, existing.key.clone());
+                    extend_unique(&mut existing.tags, entity.tags);
+                    extend_unique(&mut existing.attributes, entity.attributes);
+                    let description = entity.description.trim();
+                    if !description.is_empty()
+                        && !existing.description.contains(description)
+                        && existing.description.chars().count() < MAX_MERGED_DESCRIPTION_CHARS
+                    {
+                        if !existing.description.is_empty() {
+                            existing.description.push(' ');
+                        }
+                        existing.description.push_str(description);
+                    }
+                    continue;
+                }
+
+                let key = format!("s{index}:{}", entity.key);
+                keys.insert(entity.key.clone(), key.clone());
+                if !name.is_empty() {
+                    by_name.insert(name, knowledge_entities.len());
+                }
+                knowledge_entities.push(LLMKnowledgeEntity { key, ..entity });
+            }
+
+            for relationship in section.relationships {
+                let source = keys
+                    .get(&relationship.source)
+                    .cloned()
+                    .unwrap_or(relationship.source);
+                let target = keys
+                    .get(&relationship.target)
+                    .cloned()
+                    .unwrap_or(relationship.target);
+                if source == target {
+                    continue;
+                }
+                if seen_relationships.insert((
+                    source.clone(),
+                    target.clone(),
+                    relationship.type_.clone(),
+                )) {
+                    relationships.push(LLMRelationship {
+                        source,
+                        target,
+                        ..relationship
+                    });
+                }
+            }
+        }
+
+        Self {
+            knowledge_entities,
+            relationships,
+            tags,
+            action_items,
+        }
+    }
+
+    /// Normalizes the proposed tags and holds them to the user's vocabulary.
+    ///
+    /// Document tags already in `vocabulary` are always kept; at most `max_new_tags`
+    /// others are accepted. Entity tags may only use the vocabulary or the document's
+    /// accepted tags, so entities never introduce tags of their own.
+    pub fn constrain_tags(&mut self, vocabulary: &[String], max_new_tags: usize) {
+        let mut new_tags = 0usize;
+        self.tags = normalize_tags(&self.tags)
+            .into_iter()
+            .filter(|tag| {
+                if vocabulary.contains(tag) {
+                    return true;
+                }
+                if new_tags < max_new_tags {
+                    new_tags = new_tags.saturating_add(1);
+                    return true;
+                }
+                false
+            })
+            .collect();
+
+        for entity in &mut self.knowledge_entities {
+            entity.tags = normalize_tags(&entity.tags)
+                .into_iter()
+                .filter(|tag| vocabulary.contains(tag) || self.tags.contains(tag))
+                .collect();
+        }
+    }
+
+    /// Maps every relationship onto a type in `catalog`, flipping those named by an
+    /// inverse and falling back to the default type for unknown names. Weights are
+    /// clamped to `0.0..=1.0`.
+    pub fn constrain_relationship_types(&mut self, catalog: &RelationshipTypeCatalog) {
+        for relationship in &mut self.relationships {
+            let resolved = catalog.canonicalize(&relationship.type_);
+            if resolved.reversed {
+                std::mem::swap(&mut relationship.source, &mut relationship.target);
+            }
+            relationship.type_ = resolved.name;
+            relationship.weight = relationship.weight.map(normalize_weight);
+        }
+    }
+
     pub async fn to_database_entities(
         &self,
         source_id: &str,
@@ -53,6 +241,22 @@ impl LLMEnrichmentResult {
         Ok((entities, relationships))
     }
 
+    /// The action items to store for `source_id`; blank ones are dropped.
+    pub fn to_action_items(&self, source_id: &str, user_id: &str) -> Vec<ActionItem> {
+        self.action_items
+            .iter()
+            .filter_map(|item| {
+                ActionItem::new(
+                    source_id.to_string(),
+                    user_id.to_string(),
+                    &item.description,
+                    item.owner.as_deref(),
+                    item.due_date.as_deref(),
+                )
+            })
+            .collect()
+    }
+
     fn create_mapper(&self) -> GraphMapper {
         let mut mapper = GraphMapper::new();
 
@@ -84,7 +288,7 @@ impl LLMEnrichmentResult {
             embedding_inputs.push(KnowledgeEntity::embedding_input_text(
                 &llm_entity.name,
                 &llm_entity.description,
-                entity_type,
+                &entity_type,
             ));
             prepared.push((llm_entity, assigned_id, entity_type));
         }
@@ -117,8 +321,15 @@ impl LLMEnrichmentResult {
                     description: llm_entity.description.clone(),
                     entity_type,
                     source_id: source_id.to_string(),
-                    metadata: None,
+                    metadata: EntityAttribute::to_metadata(
+                        &llm_entity
+                            .attributes
+                            .iter()
+                            .filter_map(LLMEntityAttribute::to_attribute)
+                            .collect::<Vec<_>>(),
+                    ),
                     user_id: user_id.to_string(),
+                    tags: llm_entity.tags.clone(),
                 },
                 embedding,
             });
@@ -145,12 +356,21 @@ impl LLMEnrichmentResult {
                     user_id.to_string(),
                     source_id.to_string(),
                     rel.type_.clone(),
-                ))
+                )
+                .with_weight(rel.weight.unwrap_or(DEFAULT_RELATIONSHIP_WEIGHT)))
             })
             .collect()
     }
 }
 
+fn extend_unique<T: PartialEq>(target: &mut Vec<T>, items: Vec<T>) {
+    for item in items {
+        if !target.contains(&item) {
+            target.push(item);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::expect_used)]
@@ -164,6 +384,8 @@ mod tests {
             name: format!("name-{key}"),
             description: format!("desc-{key}"),
             entity_type: "Idea".to_string(),
+            tags: Vec::new(),
+            attributes: Vec::new(),
         }
     }
 
@@ -172,6 +394,7 @@ mod tests {
             type_: type_.to_string(),
             source: source.to_string(),
             target: target.to_string(),
+            weight: None,
         }
     }
 
@@ -180,6 +403,8 @@ mod tests {
         let result = LLMEnrichmentResult {
             knowledge_entities: vec![entity("k1"), entity("k2")],
             relationships: Vec::new(),
+            tags: Vec::new(),
+            action_items: Vec::new(),
         };
 
         let mapper = result.create_mapper();
@@ -197,6 +422,8 @@ mod tests {
         let result = LLMEnrichmentResult {
             knowledge_entities: vec![entity("k1"), entity("k2")],
             relationships: vec![relationship("relates_to", "k1", "k2")],
+            tags: Vec::new(),
+            action_items: Vec::new(),
         };
         let mapper = result.create_mapper();
 
@@ -219,6 +446,8 @@ mod tests {
         let result = LLMEnrichmentResult {
             knowledge_entities: vec![entity("k1")],
             relationships: vec![relationship("relates_to", "k1", &raw.to_string())],
+            tags: Vec::new(),
+            action_items: Vec::new(),
         };
         let mapper = result.create_mapper();
 
@@ -237,6 +466,8 @@ mod tests {
         let result = LLMEnrichmentResult {
             knowledge_entities: vec![entity("k1"), entity("k2"), entity("k3")],
             relationships: Vec::new(),
+            tags: Vec::new(),
+            action_items: Vec::new(),
         };
         let mapper = result.create_mapper();
         let provider = EmbeddingProvider::new_hashed(8)?;
@@ -258,11 +489,220 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn merge_sections_folds_entities_by_name_and_repoints_relationships() {
+        let existing = Uuid::new_v4().to_string();
+        let first = LLMEnrichmentResult {
+            knowledge_entities: vec![entity("k1"), entity("k2")],
+            relationships: vec![relationship("relates_to", "k1", "k2")],
+            tags: Vec::new(),
+            action_items: Vec::new(),
+        };
+        let mut renamed = entity("k1");
+        renamed.name = "Name K2!".to_string();
+        renamed.description = "more about k2".to_string();
+        let second = LLMEnrichmentResult {
+            // k1 here is a different entity than in the first section.
+            knowledge_entities: vec![renamed, entity("k3")],
+            relationships: vec![
+                relationship("relates_to", "k3", "k1"),
+                relationship("relates_to", "k1", &existing),
+            ],
+            tags: Vec::new(),
+            action_items: Vec::new(),
+        };
+        let third = LLMEnrichmentResult {
+            knowledge_entities: vec![entity("k2")],
+            relationships: vec![relationship("relates_to", "k2", "k2")],
+            tags: Vec::new(),
+            action_items: Vec::new(),
+        };
+
+        let merged = LLMEnrichmentResult::merge_sections(vec![first, second, third]);
+
+        let keys: Vec<&str> = merged
+            .knowledge_entities
+            .iter()
+            .map(|entity| entity.key.as_str())
+            .collect();
+        assert_eq!(keys, vec!["s0:k1", "s0:k2", "s1:k3"]);
+        let k2 = merged.knowledge_entities.get(1).expect("k2");
+        assert_eq!(k2.description, "desc-k2 more about k2");
+
+        let edges: Vec<(&str, &str)> = merged
+            .relationships
+            .iter()
+            .map(|rel| (rel.source.as_str(), rel.target.as_str()))
+            .collect();
+        assert_eq!(
+            edges,
+            vec![
+                ("s0:k1", "s0:k2"),
+                ("s1:k3", "s0:k2"),
+                ("s0:k2", existing.as_str()),
+            ]
+        );
+
+        let mapper = merged.create_mapper();
+        assert!(
+            merged
+                .process_relationships("source-1", "user-1", &mapper)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn merge_sections_unions_document_and_entity_tags() {
+        let mut first_entity = entity("k1");
+        first_entity.tags = vec!["rust".to_string()];
+        let first = LLMEnrichmentResult {
+            knowledge_entities: vec![first_entity],
+            relationships: Vec::new(),
+            tags: vec!["rust".to_string(), "databases".to_string()],
+            action_items: Vec::new(),
+        };
+        let mut repeated = entity("k9");
+        repeated.name = "name-k1".to_string();
+        repeated.tags = vec!["rust".to_string(), "async".to_string()];
+        let second = LLMEnrichmentResult {
+            knowledge_entities: vec![repeated],
+            relationships: Vec::new(),
+            tags: vec!["databases".to_string(), "async".to_string()],
+            action_items: Vec::new(),
+        };
+
+        let merged = LLMEnrichmentResult::merge_sections(vec![first, second]);
+
+        assert_eq!(merged.tags, vec!["rust", "databases", "async"]);
+        let folded = merged.knowledge_entities.first().expect("folded entity");
+        assert_eq!(folded.tags, vec!["rust", "async"]);
+    }
+
+    #[test]
+    fn constrain_tags_prefers_vocabulary_and_caps_new_tags() {
+        let mut tagged = entity("k1");
+        tagged.tags = vec![
+            "Reading".to_string(),
+            "Sailing".to_string(),
+            "#new one".to_string(),
+        ];
+        let mut result = LLMEnrichmentResult {
+            knowledge_entities: vec![tagged],
+            relationships: Vec::new(),
+            tags: vec![
+                "New One".to_string(),
+                "reading".to_string(),
+                "second new".to_string(),
+                "Cooking".to_string(),
+            ],
+            action_items: Vec::new(),
+        };
+        let vocabulary = vec![
+            "reading".to_string(),
+            "cooking".to_string(),
+            "sailing".to_string(),
+        ];
+
+        result.constrain_tags(&vocabulary, 1);
+
+        assert_eq!(result.tags, vec!["new-one", "reading", "cooking"]);
+        let entity = result.knowledge_entities.first().expect("entity");
+        assert_eq!(entity.tags, vec!["reading", "sailing", "new-one"]);
+    }
+
+    #[tokio::test]
+    async fn process_entities_stores_valid_attributes_in_metadata() -> anyhow::Result<()> {
+        let mut launch = entity("k1");
+        launch.attributes = vec![
+            LLMEntityAttribute {
+                kind: "Deadline".to_string(),
+                label: "launch".to_string(),
+                value: "2026-05-01".to_string(),
+                unit: None,
+            },
+            LLMEntityAttribute {
+                kind: "deadline".to_string(),
+                label: "vague".to_string(),
+                value: "soon".to_string(),
+                unit: None,
+            },
+            LLMEntityAttribute {
+                kind: "mood".to_string(),
+                label: "team".to_string(),
+                value: "happy".to_string(),
+                unit: None,
+            },
+        ];
+        let result = LLMEnrichmentResult {
+            knowledge_entities: vec![launch, entity("k2")],
+            relationships: Vec::new(),
+            tags: Vec::new(),
+            action_items: Vec::new(),
+        };
+        let mapper = result.create_mapper();
+        let provider = EmbeddingProvider::new_hashed(8)?;
+
+        let entities = result
+            .process_entities("source-1", "user-1", &mapper, &provider)
+            .await?;
+
+        let first = entities.first().expect("first entity");
+        let attributes = EntityAttribute::from_metadata(first.entity.metadata.as_ref());
+        assert_eq!(attributes.len(), 1);
+        let deadline = attributes.first().expect("deadline");
+        assert_eq!(deadline.kind, EntityAttributeKind::Deadline);
+        assert_eq!(deadline.value, "2026-05-01");
+        assert!(
+            entities
+                .get(1)
+                .expect("second entity")
+                .entity
+                .metadata
+                .is_none()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn merge_sections_drops_repeated_action_items() {
+        let item = |description: &str, due_date: Option<&str>| LLMActionItem {
+            description: description.to_string(),
+            owner: None,
+            due_date: due_date.map(ToString::to_string),
+        };
+        let first = LLMEnrichmentResult {
+            knowledge_entities: Vec::new(),
+            relationships: Vec::new(),
+            tags: Vec::new(),
+            action_items: vec![item("Book the venue", Some("2026-05-01"))],
+        };
+        let second = LLMEnrichmentResult {
+            knowledge_entities: Vec::new(),
+            relationships: Vec::new(),
+            tags: Vec::new(),
+            action_items: vec![item("book the venue ", None), item("  ", None)],
+        };
+
+        let merged = LLMEnrichmentResult::merge_sections(vec![first, second]);
+        assert_eq!(merged.action_items.len(), 2);
+
+        let stored = merged.to_action_items("source-1", "user-1");
+        assert_eq!(stored.len(), 1);
+        let venue = stored.first().expect("one action item");
+        assert_eq!(venue.description, "Book the venue");
+        assert_eq!(venue.due_date.as_deref(), Some("2026-05-01"));
+        assert_eq!(venue.source_id, "source-1");
+        assert!(!venue.done);
+    }
+
     #[test]
     fn process_relationships_errors_on_unknown_endpoint() {
         let result = LLMEnrichmentResult {
             knowledge_entities: vec![entity("k1")],
             relationships: vec![relationship("relates_to", "k1", "missing-key")],
+            tags: Vec::new(),
+            action_items: Vec::new(),
         };
         let mapper = result.create_mapper();
 
@@ -271,4 +711,43 @@ mod tests {
             Err(AppError::GraphMapper(_))
         ));
     }
+
+    #[test]
+    fn constrain_relationship_types_maps_onto_the_catalog() {
+        let mut weighted = relationship("referenced by", "k1", "k2");
+        weighted.weight = Some(1.5);
+        let mut result = LLMEnrichmentResult {
+            knowledge_entities: vec![entity("k1"), entity("k2")],
+            relationships: vec![
+                relationship("is related to", "k1", "k2"),
+                weighted,
+                relationship("works at", "k2", "k1"),
+            ],
+            tags: Vec::new(),
+            action_items: Vec::new(),
+        };
+
+        result.constrain_relationship_types(&RelationshipTypeCatalog::builtin());
+
+        let summary: Vec<(&str, &str, &str, Option<f32>)> = result
+            .relationships
+            .iter()
+            .map(|rel| {
+                (
+                    rel.type_.as_str(),
+                    rel.source.as_str(),
+                    rel.target.as_str(),
+                    rel.weight,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("RelatedTo", "k1", "k2", None),
+                ("References", "k2", "k1", Some(1.0)),
+                ("RelatedTo", "k2", "k1", None),
+            ]
+        );
+    }
 }
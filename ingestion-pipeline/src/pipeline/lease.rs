@@ -0,0 +1,133 @@
+//! Lease heartbeat for a task while its pipeline runs.
+//!
+//! A claim only holds the task for [`DEFAULT_LEASE_SECS`]; slow payloads (PDFs
+//! through the vision path, long audio) would otherwise be reclaimed mid-flight
+//! by another worker. The heartbeat extends the lease to the payload estimate and
+//! keeps renewing it, and records when renewal finds the task owned by someone
+//! else so the stale run can stop before persisting.
+//!
+//! [`DEFAULT_LEASE_SECS`]: common::storage::types::ingestion_task::DEFAULT_LEASE_SECS
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+use chrono::Utc;
+use common::{
+    error::AppError,
+    storage::{db::SurrealDbClient, types::ingestion_task::IngestionTask},
+};
+use tokio::{
+    task::JoinHandle,
+    time::{Duration, sleep},
+};
+use tracing::{debug, warn};
+
+/// Leases are renewed this many times per lease period.
+const RENEWALS_PER_LEASE: u32 = 3;
+
+pub(super) struct LeaseHeartbeat {
+    db: Arc<SurrealDbClient>,
+    task_id: String,
+    worker_id: String,
+    lease: Duration,
+    lost: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+impl LeaseHeartbeat {
+    /// Extends the lease to at least `lease` and starts renewing it in the background.
+    pub(super) async fn start(
+        db: Arc<SurrealDbClient>,
+        task: &IngestionTask,
+        lease: Duration,
+    ) -> Result<Self, AppError> {
+        let task_id = task.id.clone();
+        let worker_id = task.worker_id.clone().unwrap_or_default();
+        let lease = lease.max(task.lease_duration());
+        let lost = Arc::new(AtomicBool::new(false));
+
+        let held = IngestionTask::renew_lease(&db, &task_id, &worker_id, Utc::now(), lease).await?;
+        if !held {
+            lost.store(true, Ordering::SeqCst);
+        }
+
+        let handle = tokio::spawn(renew_until_lost(
+            Arc::clone(&db),
+            task_id.clone(),
+            worker_id.clone(),
+            lease,
+            Arc::clone(&lost),
+        ));
+
+        Ok(Self {
+            db,
+            task_id,
+            worker_id,
+            lease,
+            lost,
+            handle,
+        })
+    }
+
+    pub(super) fn is_lost(&self) -> bool {
+        self.lost.load(Ordering::SeqCst)
+    }
+
+    /// Renews once more and reports whether this worker still owns the task.
+    ///
+    /// Called right before persisting so a run whose lease lapsed in between
+    /// heartbeats does not write artifacts alongside the new owner.
+    pub(super) async fn confirm(&self) -> Result<bool, AppError> {
+        if self.is_lost() {
+            return Ok(false);
+        }
+
+        let held = IngestionTask::renew_lease(
+            &self.db,
+            &self.task_id,
+            &self.worker_id,
+            Utc::now(),
+            self.lease,
+        )
+        .await?;
+        if !held {
+            self.lost.store(true, Ordering::SeqCst);
+        }
+        Ok(held)
+    }
+}
+
+impl Drop for LeaseHeartbeat {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+async fn renew_until_lost(
+    db: Arc<SurrealDbClient>,
+    task_id: String,
+    worker_id: String,
+    lease: Duration,
+    lost: Arc<AtomicBool>,
+) {
+    let interval = lease
+        .checked_div(RENEWALS_PER_LEASE)
+        .unwrap_or(lease)
+        .max(Duration::from_secs(1));
+
+    while !lost.load(Ordering::SeqCst) {
+        sleep(interval).await;
+        match IngestionTask::renew_lease(&db, &task_id, &worker_id, Utc::now(), lease).await {
+            Ok(true) => debug!(%task_id, %worker_id, "renewed ingestion task lease"),
+            Ok(false) => {
+                warn!(%task_id, %worker_id, "ingestion task lease taken by another worker");
+                lost.store(true, Ordering::SeqCst);
+            }
+            // A transient failure is retried on the next beat; the lease still has
+            // two thirds of its duration left.
+            Err(err) => warn!(%task_id, %worker_id, error = %err, "failed to renew task lease"),
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! Concurrency caps shared by every task a worker processes in parallel.
+//!
+//! A worker may run several tasks at once (`ingest_worker_concurrency`), but the
+//! LLM endpoint and the local CPU saturate at different points. Each stage takes a
+//! permit from the matching pool so one kind of work cannot starve the other.
+
+use std::sync::Arc;
+
+use common::{error::AppError, utils::config::AppConfig};
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+#[derive(Debug, Clone)]
+pub struct StageLimits {
+    llm: Arc<Semaphore>,
+    cpu: Arc<Semaphore>,
+}
+
+impl StageLimits {
+    /// Builds limits with at least one permit per pool.
+    pub fn new(llm: usize, cpu: usize) -> Self {
+        Self {
+            llm: Arc::new(Semaphore::new(llm.max(1))),
+            cpu: Arc::new(Semaphore::new(cpu.max(1))),
+        }
+    }
+
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self::new(config.ingest_llm_concurrency, config.ingest_cpu_concurrency)
+    }
+
+    /// Waits for a slot in the LLM-bound pool (enrichment calls).
+    pub async fn llm(&self) -> Result<SemaphorePermit<'_>, AppError> {
+        self.llm
+            .acquire()
+            .await
+            .map_err(|_| AppError::internal("LLM stage limiter closed"))
+    }
+
+    /// Waits for a slot in the CPU-bound pool (content extraction, embedding).
+    pub async fn cpu(&self) -> Result<SemaphorePermit<'_>, AppError> {
+        self.cpu
+            .acquire()
+            .await
+            .map_err(|_| AppError::internal("CPU stage limiter closed"))
+    }
+
+    pub fn available_llm(&self) -> usize {
+        self.llm.available_permits()
+    }
+
+    pub fn available_cpu(&self) -> usize {
+        self.cpu.available_permits()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StageLimits;
+
+    #[tokio::test]
+    async fn pools_are_independent_and_never_empty() -> anyhow::Result<()> {
+        let limits = StageLimits::new(0, 2);
+        assert_eq!(limits.available_llm(), 1);
+        assert_eq!(limits.available_cpu(), 2);
+
+        let llm_permit = limits.llm().await?;
+        assert_eq!(limits.available_llm(), 0);
+        assert_eq!(limits.available_cpu(), 2);
+
+        let _cpu_permit = limits.cpu().await?;
+        assert_eq!(limits.available_cpu(), 1);
+
+        drop(llm_permit);
+        assert_eq!(limits.available_llm(), 1);
+        Ok(())
+    }
+}
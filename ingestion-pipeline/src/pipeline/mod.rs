@@ -1,8 +1,11 @@
 mod config;
 mod context;
 mod enrichment_result;
+mod lease;
+mod limits;
 mod persistence;
 mod preparation;
+mod resolution;
 mod services;
 mod stages;
 mod state;
@@ -11,6 +14,7 @@ pub use config::{IngestionConfig, IngestionTuning};
 #[allow(clippy::module_name_repetitions)]
 pub use context::{EmbeddedKnowledgeEntity, EmbeddedTextChunk, PipelineArtifacts};
 pub use enrichment_result::{LLMEnrichmentResult, LLMKnowledgeEntity, LLMRelationship};
+pub use limits::StageLimits;
 #[allow(clippy::module_name_repetitions)]
 pub use persistence::persist_artifacts;
 #[allow(clippy::module_name_repetitions)]
@@ -21,7 +25,6 @@ use std::{
     time::{Duration, Instant},
 };
 
-use async_openai::Client;
 use common::{
     error::AppError,
     storage::{
@@ -33,7 +36,7 @@ use common::{
             text_content::TextContent,
         },
     },
-    utils::config::AppConfig,
+    utils::{config::AppConfig, llm::LlmProviders},
 };
 use retrieval_pipeline::reranking::RerankerPool;
 use tokio::time::sleep;
@@ -41,8 +44,9 @@ use tracing::{debug, info, warn};
 
 use self::{
     context::PipelineContext,
-    stages::{enrich, persist, prepare_content, retrieve_related},
-    state::{Enriched, IngestionMachine, ready},
+    lease::LeaseHeartbeat,
+    stages::{enrich, persist, prepare_content, retrieve_related, summarize},
+    state::{IngestionMachine, Summarized, ready},
 };
 
 /// Wall-clock duration of each pre-persistence pipeline stage.
@@ -50,6 +54,7 @@ struct StageTimings {
     prepare: Duration,
     retrieve: Duration,
     enrich: Duration,
+    summarize: Duration,
 }
 
 #[allow(clippy::module_name_repetitions)]
@@ -62,7 +67,7 @@ pub struct IngestionPipeline {
 impl IngestionPipeline {
     pub fn new(
         db: Arc<SurrealDbClient>,
-        openai_client: Arc<Client<async_openai::config::OpenAIConfig>>,
+        llm_providers: Arc<LlmProviders>,
         config: AppConfig,
         reranker_pool: Option<Arc<RerankerPool>>,
         storage: StorageManager,
@@ -70,7 +75,7 @@ impl IngestionPipeline {
     ) -> Result<Self, AppError> {
         Self::new_with_config(
             db,
-            openai_client,
+            llm_providers,
             config,
             reranker_pool,
             storage,
@@ -81,7 +86,7 @@ impl IngestionPipeline {
 
     pub fn new_with_config(
         db: Arc<SurrealDbClient>,
-        openai_client: Arc<Client<async_openai::config::OpenAIConfig>>,
+        llm_providers: Arc<LlmProviders>,
         config: AppConfig,
         reranker_pool: Option<Arc<RerankerPool>>,
         storage: StorageManager,
@@ -90,12 +95,12 @@ impl IngestionPipeline {
     ) -> Result<Self, AppError> {
         let services = DefaultPipelineServices::new(
             Arc::clone(&db),
-            openai_client,
+            llm_providers,
             config,
             reranker_pool,
             storage,
             embedding_provider,
-            pipeline_config.tuning.embedding_query_char_limit,
+            pipeline_config.tuning.clone(),
         );
 
         Self::with_services(db, pipeline_config, Arc::new(services))
@@ -125,16 +130,24 @@ impl IngestionPipeline {
     pub async fn process_task(&self, task: IngestionTask) -> Result<(), AppError> {
         let mut processing_task = task.mark_processing(&self.db).await?;
 
-        let pipeline_result = if self.artifacts_persisted(&processing_task.id).await? {
+        let (pipeline_result, lease_lost) = if self.artifacts_persisted(&processing_task.id).await?
+        {
             info!(
                 task_id = %processing_task.id,
                 attempt = processing_task.attempts,
                 "ingestion artifacts already persisted; skipping pipeline"
             );
-            Ok(())
+            (Ok(()), false)
         } else {
             let payload = processing_task.take_content();
-            self.drive_pipeline(&processing_task, payload)
+            let lease = LeaseHeartbeat::start(
+                Arc::clone(&self.db),
+                &processing_task,
+                IngestionTask::estimated_lease(&payload),
+            )
+            .await?;
+            let result = self
+                .drive_pipeline(&processing_task, payload, &lease)
                 .await
                 .map_err(|err| {
                     debug!(
@@ -144,9 +157,20 @@ impl IngestionPipeline {
                         "ingestion pipeline failed"
                     );
                     err
-                })
+                });
+            (result, lease.is_lost())
         };
 
+        // Another worker owns the task now; its bookkeeping is theirs to update.
+        if lease_lost && pipeline_result.is_err() {
+            warn!(
+                task_id = %processing_task.id,
+                attempt = processing_task.attempts,
+                "ingestion task lease lost; abandoning stale run"
+            );
+            return pipeline_result;
+        }
+
         match pipeline_result {
             Ok(()) => self.finalize_succeeded(&processing_task).await,
             Err(err) => {
@@ -241,16 +265,17 @@ impl IngestionPipeline {
         u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
     }
 
-    /// Runs the shared `prepare → retrieve → enrich` stages, recording per-stage timings.
+    /// Runs the shared `prepare → retrieve → enrich → summarize` stages, recording
+    /// per-stage timings.
     ///
     /// Both the full task path ([`Self::drive_pipeline`]) and the artifact-only path
     /// ([`Self::produce_artifacts`]) share this prefix; only the terminal step differs
     /// (persist vs. return artifacts).
-    async fn run_through_enrichment(
+    async fn run_until_persist(
         &self,
         ctx: &mut PipelineContext<'_>,
         payload: IngestionPayload,
-    ) -> Result<(IngestionMachine<(), Enriched>, StageTimings), AppError> {
+    ) -> Result<(IngestionMachine<(), Summarized>, StageTimings), AppError> {
         let machine = ready();
 
         let stage_start = Instant::now();
@@ -269,12 +294,19 @@ impl IngestionPipeline {
         let machine = enrich(machine, ctx).await.map_err(|err| ctx.abort(err))?;
         let enrich = stage_start.elapsed();
 
+        let stage_start = Instant::now();
+        let machine = summarize(machine, ctx)
+            .await
+            .map_err(|err| ctx.abort(err))?;
+        let summarize = stage_start.elapsed();
+
         Ok((
             machine,
             StageTimings {
                 prepare,
                 retrieve,
                 enrich,
+                summarize,
             },
         ))
     }
@@ -287,6 +319,7 @@ impl IngestionPipeline {
         &self,
         task: &IngestionTask,
         payload: IngestionPayload,
+        lease: &LeaseHeartbeat,
     ) -> Result<(), AppError> {
         let mut ctx = PipelineContext::new(
             task,
@@ -296,7 +329,13 @@ impl IngestionPipeline {
         );
 
         let pipeline_started = Instant::now();
-        let (machine, timings) = self.run_through_enrichment(&mut ctx, payload).await?;
+        let (machine, timings) = self.run_until_persist(&mut ctx, payload).await?;
+
+        if !lease.confirm().await.map_err(|err| ctx.abort(err))? {
+            return Err(ctx.abort(AppError::Processing(
+                "ingestion task lease was taken by another worker before persisting".into(),
+            )));
+        }
 
         let stage_start = Instant::now();
         let _machine = persist(machine, &mut ctx)
@@ -312,6 +351,7 @@ impl IngestionPipeline {
             prepare_ms = Self::duration_millis(timings.prepare),
             retrieve_ms = Self::duration_millis(timings.retrieve),
             enrich_ms = Self::duration_millis(timings.enrich),
+            summarize_ms = Self::duration_millis(timings.summarize),
             persist_ms = Self::duration_millis(persist_duration),
             "ingestion pipeline finished"
         );
@@ -332,7 +372,7 @@ impl IngestionPipeline {
             self.services.as_ref(),
         );
 
-        let (_machine, _timings) = self.run_through_enrichment(&mut ctx, payload).await?;
+        let (_machine, _timings) = self.run_until_persist(&mut ctx, payload).await?;
 
         ctx.build_artifacts().await.map_err(|err| ctx.abort(err))
     }
@@ -12,8 +12,11 @@ use common::{
     storage::{
         db::SurrealDbClient,
         types::{
-            EmbeddingRecord, StoredObject, knowledge_entity::KnowledgeEntity,
-            knowledge_entity_embedding::KnowledgeEntityEmbedding, text_chunk::TextChunk,
+            EmbeddingRecord, StoredObject, action_item::ActionItem,
+            knowledge_entity::KnowledgeEntity,
+            knowledge_entity_embedding::KnowledgeEntityEmbedding,
+            knowledge_entity_mention::KnowledgeEntityMention,
+            knowledge_suggestion::KnowledgeSuggestion, text_chunk::TextChunk,
             text_chunk_embedding::TextChunkEmbedding, text_content::TextContent,
         },
     },
@@ -32,6 +35,7 @@ pub struct PersistCounts {
     pub chunk_count: usize,
     pub entity_count: usize,
     pub relationship_count: usize,
+    pub suggestion_count: usize,
 }
 
 /// Persists all pipeline artifacts in one database transaction.
@@ -46,6 +50,9 @@ pub async fn persist_artifacts(
         entities,
         relationships,
         chunks,
+        mut mentions,
+        action_items,
+        suggestions,
     } = artifacts;
 
     let source_id = text_content.id.clone();
@@ -53,9 +60,24 @@ pub async fn persist_artifacts(
     let chunk_count = chunks.len();
     let entity_count = entities.len();
     let relationship_count = relationships.len();
+    let suggestion_count = suggestions.len();
 
     let (entities, entity_embeddings) = prepare_entity_rows(entities, embedding_dimensions)?;
+    mentions.extend(entities.iter().map(|entity| {
+        KnowledgeEntityMention::new(
+            entity.id.clone(),
+            entity.source_id.clone(),
+            entity.user_id.clone(),
+            entity.name.clone(),
+        )
+    }));
     let (chunks, chunk_embeddings) = prepare_chunk_rows(chunks, embedding_dimensions)?;
+    for embedding in suggestions
+        .iter()
+        .filter_map(|item| item.embedding.as_ref())
+    {
+        KnowledgeEntityEmbedding::validate_dimension(embedding, embedding_dimensions)?;
+    }
 
     let payload = PersistPayload {
         source_id: Arc::from(source_id),
@@ -66,6 +88,9 @@ pub async fn persist_artifacts(
         chunks: Arc::from(chunks.into_boxed_slice()),
         chunk_embeddings: Arc::from(chunk_embeddings.into_boxed_slice()),
         relationships: relationships.into(),
+        mentions: Arc::from(mentions.into_boxed_slice()),
+        action_items: Arc::from(action_items.into_boxed_slice()),
+        suggestions: Arc::from(suggestions.into_boxed_slice()),
     };
 
     let mut backoff_ms = tuning.persist_initial_backoff_ms;
@@ -80,6 +105,7 @@ pub async fn persist_artifacts(
                     chunk_count,
                     entity_count,
                     relationship_count,
+                    suggestion_count,
                 });
             }
             Err(err) => {
@@ -115,6 +141,9 @@ struct PersistPayload {
     chunks: Arc<[TextChunk]>,
     chunk_embeddings: Arc<[TextChunkEmbedding]>,
     relationships: Arc<[common::storage::types::knowledge_relationship::KnowledgeRelationship]>,
+    mentions: Arc<[KnowledgeEntityMention]>,
+    action_items: Arc<[ActionItem]>,
+    suggestions: Arc<[KnowledgeSuggestion]>,
 }
 
 async fn execute_persist_transaction(
@@ -139,10 +168,19 @@ async fn execute_persist_transaction(
         query.push_str("\nINSERT INTO knowledge_entity $entities;");
         query.push_str("\nINSERT INTO knowledge_entity_embedding $entity_embeddings;");
     }
+    if !payload.mentions.is_empty() {
+        query.push_str("\nINSERT INTO knowledge_entity_mention $mentions;");
+    }
     if !payload.chunks.is_empty() {
         query.push_str("\nINSERT INTO text_chunk $chunks;");
         query.push_str("\nINSERT INTO text_chunk_embedding $chunk_embeddings;");
     }
+    if !payload.action_items.is_empty() {
+        query.push_str("\nINSERT INTO action_item $action_items;");
+    }
+    if !payload.suggestions.is_empty() {
+        query.push_str("\nINSERT INTO knowledge_suggestion $suggestions;");
+    }
     if !payload.relationships.is_empty() {
         query.push_str(
             r#"
@@ -177,6 +215,15 @@ FOR $relationship IN $relationships {
             .bind(("chunks", Arc::clone(&payload.chunks)))
             .bind(("chunk_embeddings", Arc::clone(&payload.chunk_embeddings)));
     }
+    if !payload.mentions.is_empty() {
+        request = request.bind(("mentions", Arc::clone(&payload.mentions)));
+    }
+    if !payload.action_items.is_empty() {
+        request = request.bind(("action_items", Arc::clone(&payload.action_items)));
+    }
+    if !payload.suggestions.is_empty() {
+        request = request.bind(("suggestions", Arc::clone(&payload.suggestions)));
+    }
     if !payload.relationships.is_empty() {
         request = request.bind(("relationships", Arc::clone(&payload.relationships)));
     }
@@ -374,6 +421,98 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn persist_records_mentions_for_new_and_linked_entities() -> anyhow::Result<()> {
+        let db = setup_db().await?;
+        let source_a = uuid::Uuid::new_v4().to_string();
+        let source_b = uuid::Uuid::new_v4().to_string();
+        let user_id = "persist-mentions";
+
+        let first = sample_artifacts(&source_a, user_id);
+        let shared_id = first
+            .entities
+            .first()
+            .map(|item| item.entity.id.clone())
+            .ok_or_else(|| anyhow::anyhow!("sample artifacts have an entity"))?;
+        persist(&db, first).await?;
+
+        let mut second = large_artifacts(&source_b, user_id, 1, 0, 0, TEST_EMBEDDING_DIM);
+        second.mentions.push(KnowledgeEntityMention::new(
+            shared_id.clone(),
+            source_b.clone(),
+            user_id.to_string(),
+            "Entity Alias".to_string(),
+        ));
+        persist(&db, second).await?;
+
+        let mentions = KnowledgeEntityMention::for_entity(&shared_id, user_id, &db).await?;
+        assert_eq!(mentions.len(), 2);
+
+        TextContent::clear_ingested_children(&source_a, user_id, &db).await?;
+        assert_eq!(count_entities_for_source(&db, &source_a).await?, 0);
+        assert_eq!(count_entities_for_source(&db, &source_b).await?, 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn persist_replaces_action_items_on_reingest() -> anyhow::Result<()> {
+        let db = setup_db().await?;
+        let source_id = uuid::Uuid::new_v4().to_string();
+        let user_id = "persist-action-items";
+
+        let mut first = sample_artifacts(&source_id, user_id);
+        first.action_items = ["Book the venue", "Send the agenda"]
+            .into_iter()
+            .filter_map(|description| {
+                ActionItem::new(
+                    source_id.clone(),
+                    user_id.to_string(),
+                    description,
+                    None,
+                    None,
+                )
+            })
+            .collect();
+        persist(&db, first).await?;
+        assert_eq!(
+            ActionItem::list_for_source(&source_id, user_id, &db)
+                .await?
+                .len(),
+            2
+        );
+
+        persist(&db, sample_artifacts(&source_id, user_id)).await?;
+        assert!(
+            ActionItem::list_for_source(&source_id, user_id, &db)
+                .await?
+                .is_empty()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn persist_stages_reviewed_artifacts_as_suggestions() -> anyhow::Result<()> {
+        let db = setup_db().await?;
+        let source_id = uuid::Uuid::new_v4().to_string();
+        let user_id = "persist-review";
+
+        let staged = large_artifacts(&source_id, user_id, 1, 2, 1, TEST_EMBEDDING_DIM);
+        persist(&db, staged.into_review()).await?;
+
+        assert_eq!(count_chunks_for_source(&db, &source_id).await?, 1);
+        assert_eq!(count_entities_for_source(&db, &source_id).await?, 0);
+        assert_eq!(count_relationships_for_source(&db, &source_id).await?, 0);
+        assert_eq!(KnowledgeSuggestion::pending_count(user_id, &db).await?, 3);
+
+        let restaged = large_artifacts(&source_id, user_id, 1, 1, 0, TEST_EMBEDDING_DIM);
+        persist(&db, restaged.into_review()).await?;
+        assert_eq!(KnowledgeSuggestion::pending_count(user_id, &db).await?, 1);
+
+        Ok(())
+    }
+
     #[test]
     fn is_retryable_conflict_matches_surreal_transaction_conflict() {
         let err = AppError::InternalError(
@@ -8,7 +8,7 @@ use common::{
             text_content::{TextContent, UrlInfo},
         },
     },
-    utils::config::AppConfig,
+    utils::{config::AppConfig, llm::LlmProviders},
 };
 
 use crate::utils::{
@@ -19,7 +19,7 @@ pub(crate) async fn to_text_content(
     ingestion_payload: IngestionPayload,
     db: &SurrealDbClient,
     config: &AppConfig,
-    openai_client: &async_openai::Client<async_openai::config::OpenAIConfig>,
+    llm_providers: &LlmProviders,
     storage: &StorageManager,
 ) -> Result<TextContent, AppError> {
     match ingestion_payload {
@@ -63,7 +63,7 @@ pub(crate) async fn to_text_content(
             user_id,
         } => {
             let text =
-                extract_text_from_file(&file_info, db, openai_client, config, storage).await?;
+                extract_text_from_file(&file_info, db, llm_providers, config, storage).await?;
             Ok(TextContent::new(
                 text,
                 Some(context),
@@ -73,5 +73,29 @@ pub(crate) async fn to_text_content(
                 user_id,
             ))
         }
+        IngestionPayload::Reingest {
+            text_content_id,
+            user_id,
+            ..
+        } => {
+            let content = db
+                .get_item::<TextContent>(&text_content_id)
+                .await?
+                .ok_or_else(|| {
+                    AppError::Validation(format!("text content {text_content_id} no longer exists"))
+                })?;
+
+            if content.user_id != user_id {
+                return Err(AppError::Validation(format!(
+                    "text content {text_content_id} does not belong to the task owner"
+                )));
+            }
+
+            // Drop the previous snapshot first so retrieval does not match the
+            // document's own stale entities.
+            TextContent::clear_ingested_children(&content.id, &content.user_id, db).await?;
+
+            Ok(content)
+        }
     }
 }
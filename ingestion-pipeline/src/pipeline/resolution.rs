@@ -0,0 +1,432 @@
+//! Cross-document entity resolution.
+//!
+//! Enrichment proposes entities per document, so the same person or technology would
+//! otherwise become a new node for every source that mentions it. After
+//! `convert_analysis`, each proposed entity is matched against the user's existing
+//! entities — first by normalized name, then by embedding similarity within the same
+//! type — and either kept as a new canonical entity or folded into the existing one.
+//! Folded entities become a [`KnowledgeEntityMention`] of the canonical entity (an
+//! alias when the names differ) and their relationships are re-pointed at it.
+
+use std::collections::{HashMap, HashSet};
+
+use common::{
+    error::AppError,
+    storage::{
+        db::SurrealDbClient,
+        types::{
+            knowledge_entity::KnowledgeEntity, knowledge_entity_mention::KnowledgeEntityMention,
+            knowledge_relationship::KnowledgeRelationship,
+        },
+    },
+};
+use tracing::{debug, warn};
+
+use super::{config::IngestionTuning, context::EmbeddedKnowledgeEntity};
+
+#[derive(Debug, Default)]
+pub struct ResolvedEntities {
+    /// Entities that are new to the graph and will be stored.
+    pub entities: Vec<EmbeddedKnowledgeEntity>,
+    /// Relationships with endpoints re-pointed at canonical entities.
+    pub relationships: Vec<KnowledgeRelationship>,
+    /// Mentions of existing entities that proposed entities were folded into.
+    pub mentions: Vec<KnowledgeEntityMention>,
+}
+
+/// Lowercases and strips punctuation so "Rust-lang" and "rust lang" compare equal.
+pub fn normalize_entity_name(name: &str) -> String {
+    name.chars()
+        .map(|ch| {
+            if ch.is_alphanumeric() {
+                ch.to_lowercase().collect::<String>()
+            } else {
+                " ".to_string()
+            }
+        })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Matches proposed entities for `source_id` against the user's existing entities.
+pub async fn resolve_entities(
+    db: &SurrealDbClient,
+    tuning: &IngestionTuning,
+    source_id: &str,
+    user_id: &str,
+    proposed: Vec<EmbeddedKnowledgeEntity>,
+    relationships: Vec<KnowledgeRelationship>,
+) -> Result<ResolvedEntities, AppError> {
+    if proposed.is_empty() {
+        return Ok(ResolvedEntities {
+            relationships,
+            ..ResolvedEntities::default()
+        });
+    }
+
+    let name_matches = existing_by_name(db, user_id, &proposed).await?;
+
+    let mut similar: HashMap<String, Vec<KnowledgeEntity>> = HashMap::new();
+    for item in &proposed {
+        if name_matches.contains_key(&normalize_entity_name(&item.entity.name)) {
+            continue;
+        }
+        let hits = match KnowledgeEntity::vector_search(
+            tuning.entity_resolution_candidates,
+            &item.embedding,
+            db,
+            user_id,
+        )
+        .await
+        {
+            Ok(hits) => hits,
+            // The vector index is built at runtime and may be missing or rebuilding;
+            // name matching still applies, so degrade instead of failing the task.
+            Err(err) => {
+                warn!(error = %err, "entity similarity search failed; resolving by name only");
+                break;
+            }
+        };
+        let candidates = hits
+            .into_iter()
+            .filter(|hit| hit.score >= tuning.entity_resolution_min_similarity)
+            .filter(|hit| hit.entity.entity_type == item.entity.entity_type)
+            .map(|hit| hit.entity)
+            .collect();
+        similar.insert(item.entity.id.clone(), candidates);
+    }
+
+    // Entities from an earlier snapshot of this source are cleared before the new one is
+    // written, unless another source also mentions them; only those are safe to link to.
+    let same_source_ids: Vec<String> = name_matches
+        .values()
+        .flatten()
+        .chain(similar.values().flatten())
+        .filter(|entity| entity.source_id == source_id)
+        .map(|entity| entity.id.clone())
+        .collect();
+    let mention_sources =
+        KnowledgeEntityMention::sources_by_entity(&same_source_ids, user_id, db).await?;
+    let survives = |entity: &KnowledgeEntity| {
+        entity.source_id != source_id
+            || mention_sources
+                .get(&entity.id)
+                .is_some_and(|sources| sources.iter().any(|s| s != source_id))
+    };
+
+    let mut resolved = ResolvedEntities::default();
+    let mut canonical_ids: HashMap<String, String> = HashMap::new();
+    let mut batch_names: HashMap<String, String> = HashMap::new();
+
+    for item in proposed {
+        let normalized = normalize_entity_name(&item.entity.name);
+
+        if let Some(kept_id) = batch_names.get(&normalized) {
+            canonical_ids.insert(item.entity.id, kept_id.clone());
+            continue;
+        }
+
+        let canonical = name_matches
+            .get(&normalized)
+            .into_iter()
+            .flatten()
+            .chain(similar.get(&item.entity.id).into_iter().flatten())
+            .find(|&candidate| survives(candidate));
+
+        if let Some(canonical) = canonical {
+            debug!(
+                proposed = %item.entity.name,
+                canonical = %canonical.name,
+                canonical_id = %canonical.id,
+                "resolved ingested entity to existing entity"
+            );
+            canonical_ids.insert(item.entity.id.clone(), canonical.id.clone());
+            batch_names.insert(normalized, canonical.id.clone());
+            resolved.mentions.push(KnowledgeEntityMention::new(
+                canonical.id.clone(),
+                source_id.to_string(),
+                user_id.to_string(),
+                item.entity.name,
+            ));
+        } else {
+            batch_names.insert(normalized, item.entity.id.clone());
+            resolved.entities.push(item);
+        }
+    }
+
+    resolved.relationships = repoint_relationships(relationships, &canonical_ids);
+    Ok(resolved)
+}
+
+/// Existing entities keyed by normalized name, same-type and oldest first.
+async fn existing_by_name(
+    db: &SurrealDbClient,
+    user_id: &str,
+    proposed: &[EmbeddedKnowledgeEntity],
+) -> Result<HashMap<String, Vec<KnowledgeEntity>>, AppError> {
+    let lowered: Vec<String> = proposed
+        .iter()
+        .map(|item| item.entity.name.trim().to_lowercase())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+
+    let mut existing: Vec<KnowledgeEntity> = db
+        .client
+        .query(
+            "SELECT * FROM knowledge_entity
+             WHERE user_id = $user_id AND string::lowercase(string::trim(name)) IN $names
+             ORDER BY created_at ASC",
+        )
+        .bind(("user_id", user_id.to_string()))
+        .bind(("names", lowered))
+        .await?
+        .take(0)?;
+
+    let proposed_types: HashMap<String, &_> = proposed
+        .iter()
+        .map(|item| {
+            (
+                normalize_entity_name(&item.entity.name),
+                &item.entity.entity_type,
+            )
+        })
+        .collect();
+    // Stable sort keeps the oldest entity first within each type group.
+    existing.sort_by_key(|entity| {
+        proposed_types.get(&normalize_entity_name(&entity.name)) != Some(&&entity.entity_type)
+    });
+
+    let mut by_name: HashMap<String, Vec<KnowledgeEntity>> = HashMap::new();
+    for entity in existing {
+        by_name
+            .entry(normalize_entity_name(&entity.name))
+            .or_default()
+            .push(entity);
+    }
+    Ok(by_name)
+}
+
+fn repoint_relationships(
+    relationships: Vec<KnowledgeRelationship>,
+    canonical_ids: &HashMap<String, String>,
+) -> Vec<KnowledgeRelationship> {
+    let mut seen = HashSet::new();
+    relationships
+        .into_iter()
+        .filter_map(|mut relationship| {
+            let was_loop = relationship.in_ == relationship.out;
+            if let Some(id) = canonical_ids.get(&relationship.in_) {
+                relationship.in_.clone_from(id);
+            }
+            if let Some(id) = canonical_ids.get(&relationship.out) {
+                relationship.out.clone_from(id);
+            }
+            // Two proposed entities folded into the same canonical one would link it to itself.
+            let collapsed = !was_loop && relationship.in_ == relationship.out;
+            let key = (
+                relationship.in_.clone(),
+                relationship.out.clone(),
+                relationship.metadata.relationship_type.clone(),
+            );
+            (!collapsed && seen.insert(key)).then_some(relationship)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used)]
+    use chrono::Utc;
+    use common::{
+        storage::types::knowledge_entity::KnowledgeEntityType,
+        test_utils::prepare_knowledge_entity_test_db,
+    };
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::pipeline::test_support::{TEST_EMBEDDING_DIM, tuning};
+
+    async fn setup_db() -> anyhow::Result<SurrealDbClient> {
+        prepare_knowledge_entity_test_db(3).await
+    }
+
+    fn proposed(
+        name: &str,
+        entity_type: KnowledgeEntityType,
+        embedding: Vec<f32>,
+        source_id: &str,
+        user_id: &str,
+    ) -> EmbeddedKnowledgeEntity {
+        let now = Utc::now();
+        EmbeddedKnowledgeEntity {
+            entity: KnowledgeEntity {
+                id: Uuid::new_v4().to_string(),
+                created_at: now,
+                updated_at: now,
+                source_id: source_id.to_string(),
+                name: name.to_string(),
+                description: format!("about {name}"),
+                entity_type,
+                metadata: None,
+                user_id: user_id.to_string(),
+                tags: Vec::new(),
+            },
+            embedding,
+        }
+    }
+
+    async fn store(db: &SurrealDbClient, item: &EmbeddedKnowledgeEntity) -> anyhow::Result<()> {
+        KnowledgeEntity::store_with_embedding(
+            item.entity.clone(),
+            item.embedding.clone(),
+            TEST_EMBEDDING_DIM,
+            db,
+        )
+        .await?;
+        Ok(())
+    }
+
+    #[test]
+    fn normalize_entity_name_ignores_case_and_punctuation() {
+        assert_eq!(normalize_entity_name("  Rust-Lang "), "rust lang");
+        assert_eq!(normalize_entity_name("rust   lang"), "rust lang");
+        assert_eq!(normalize_entity_name("Ada Lovelace."), "ada lovelace");
+    }
+
+    #[tokio::test]
+    async fn resolve_links_name_matches_and_repoints_relationships() -> anyhow::Result<()> {
+        let db = setup_db().await?;
+        let user_id = "resolve-user";
+        let existing = proposed(
+            "Rust",
+            KnowledgeEntityType::Idea,
+            vec![1.0, 0.0, 0.0],
+            "doc-old",
+            user_id,
+        );
+        store(&db, &existing).await?;
+
+        let rust = proposed(
+            "rust",
+            KnowledgeEntityType::Idea,
+            vec![0.0, 1.0, 0.0],
+            "doc-new",
+            user_id,
+        );
+        let cargo = proposed(
+            "Cargo",
+            KnowledgeEntityType::Project,
+            vec![0.0, 0.0, 1.0],
+            "doc-new",
+            user_id,
+        );
+        let relationship = KnowledgeRelationship::new(
+            cargo.entity.id.clone(),
+            rust.entity.id.clone(),
+            user_id.to_string(),
+            "doc-new".to_string(),
+            "RelatedTo".to_string(),
+        );
+
+        let resolved = resolve_entities(
+            &db,
+            &tuning(),
+            "doc-new",
+            user_id,
+            vec![rust, cargo.clone()],
+            vec![relationship],
+        )
+        .await?;
+
+        assert_eq!(resolved.entities.len(), 1);
+        assert_eq!(
+            resolved.entities.first().map(|e| e.entity.id.clone()),
+            Some(cargo.entity.id.clone())
+        );
+        assert_eq!(resolved.mentions.len(), 1);
+        let mention = resolved.mentions.first().expect("mention");
+        assert_eq!(mention.entity_id, existing.entity.id);
+        assert_eq!(mention.surface_name, "rust");
+        let relationship = resolved.relationships.first().expect("relationship");
+        assert_eq!(relationship.in_, cargo.entity.id);
+        assert_eq!(relationship.out, existing.entity.id);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn resolve_links_similar_entities_of_the_same_type_only() -> anyhow::Result<()> {
+        let db = setup_db().await?;
+        let user_id = "resolve-similar";
+        let existing = proposed(
+            "Ada Lovelace",
+            KnowledgeEntityType::Custom("Person".to_string()),
+            vec![1.0, 0.0, 0.0],
+            "doc-old",
+            user_id,
+        );
+        store(&db, &existing).await?;
+
+        let alias = proposed(
+            "Countess of Lovelace",
+            KnowledgeEntityType::Custom("Person".to_string()),
+            vec![0.99, 0.05, 0.0],
+            "doc-new",
+            user_id,
+        );
+        let other_type = proposed(
+            "Lovelace Notes",
+            KnowledgeEntityType::Document,
+            vec![0.99, 0.05, 0.0],
+            "doc-new",
+            user_id,
+        );
+
+        let resolved = resolve_entities(
+            &db,
+            &tuning(),
+            "doc-new",
+            user_id,
+            vec![alias, other_type],
+            Vec::new(),
+        )
+        .await?;
+
+        assert_eq!(resolved.entities.len(), 1);
+        assert_eq!(
+            resolved.mentions.first().map(|m| m.surface_name.clone()),
+            Some("Countess of Lovelace".to_string())
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn resolve_ignores_entities_only_this_source_mentions() -> anyhow::Result<()> {
+        let db = setup_db().await?;
+        let user_id = "resolve-reingest";
+        let previous = proposed(
+            "Rust",
+            KnowledgeEntityType::Idea,
+            vec![1.0, 0.0, 0.0],
+            "doc-a",
+            user_id,
+        );
+        store(&db, &previous).await?;
+
+        let again = proposed(
+            "Rust",
+            KnowledgeEntityType::Idea,
+            vec![1.0, 0.0, 0.0],
+            "doc-a",
+            user_id,
+        );
+        let resolved =
+            resolve_entities(&db, &tuning(), "doc-a", user_id, vec![again], Vec::new()).await?;
+
+        assert_eq!(resolved.entities.len(), 1);
+        assert!(resolved.mentions.is_empty());
+        Ok(())
+    }
+}
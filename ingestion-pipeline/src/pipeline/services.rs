@@ -1,39 +1,64 @@
 use std::{
+    fmt::Write as _,
     ops::Range,
     sync::{Arc, OnceLock},
 };
 
-use async_openai::types::chat::{
-    ChatCompletionRequestSystemMessage, ChatCompletionRequestUserMessage,
-    CreateChatCompletionRequest, CreateChatCompletionRequestArgs, ResponseFormat,
-    ResponseFormatJsonSchema,
-};
 use async_trait::async_trait;
+use chrono::Utc;
 use common::{
     error::AppError,
     storage::{
         db::SurrealDbClient,
         store::StorageManager,
         types::{
-            StoredObject, ingestion_payload::IngestionPayload,
-            knowledge_relationship::KnowledgeRelationship, system_settings::SystemSettings,
-            text_chunk::TextChunk, text_content::TextContent,
+            StoredObject,
+            entity_type_definition::EntityTypeDefinition,
+            ingestion_payload::IngestionPayload,
+            knowledge_entity::KnowledgeEntityType,
+            knowledge_relationship::KnowledgeRelationship,
+            relationship_type_definition::RelationshipTypeCatalog,
+            system_prompts::DEFAULT_CONTENT_SUMMARY_SYSTEM_PROMPT,
+            system_settings::{ModelRole, SystemSettings},
+            tag::Tag,
+            text_chunk::TextChunk,
+            text_content::{ContentSummary, TextContent},
+            user::User,
         },
     },
-    utils::{config::AppConfig, embedding::EmbeddingProvider},
+    utils::{
+        config::AppConfig,
+        embedding::EmbeddingProvider,
+        llm::{ChatMessage, LlmProviders, StructuredOutput},
+        prompt_template::{PromptVariables, render_prompt},
+    },
 };
+use futures::future::try_join_all;
 use retrieval_pipeline::{RetrievedEntity, reranking::RerankerPool, retrieved_entities_to_json};
 use text_splitter::{ChunkCapacity, ChunkConfig, TextSplitter};
 
-use super::{enrichment_result::LLMEnrichmentResult, preparation::to_text_content};
+use super::{
+    config::IngestionTuning, enrichment_result::LLMEnrichmentResult, limits::StageLimits,
+    preparation::to_text_content,
+};
 use crate::pipeline::context::{EmbeddedKnowledgeEntity, EmbeddedTextChunk};
-use crate::utils::llm_instructions::get_ingress_analysis_schema;
+use crate::utils::llm_instructions::{get_content_summary_schema, get_ingress_analysis_schema};
+
+/// Enrichment prompt for one document or section, with the settings that pick its model.
+struct AnalysisRequest {
+    settings: SystemSettings,
+    category: String,
+    messages: Vec<ChatMessage>,
+    structured_output: StructuredOutput,
+}
 
 #[async_trait]
 pub trait PipelineServices: Send + Sync {
+    /// Builds the content to ingest; `source_id` is the id it will be stored under.
     async fn prepare_text_content(
         &self,
         payload: IngestionPayload,
+        source_id: &str,
     ) -> Result<TextContent, AppError>;
 
     async fn retrieve_similar_entities(
@@ -47,6 +72,8 @@ pub trait PipelineServices: Send + Sync {
         similar_entities: &[RetrievedEntity],
     ) -> Result<LLMEnrichmentResult, AppError>;
 
+    async fn summarize_content(&self, content: &TextContent) -> Result<ContentSummary, AppError>;
+
     async fn convert_analysis(
         &self,
         content: &TextContent,
@@ -63,90 +90,190 @@ pub trait PipelineServices: Send + Sync {
 
 pub struct DefaultPipelineServices {
     db: Arc<SurrealDbClient>,
-    openai_client: Arc<async_openai::Client<async_openai::config::OpenAIConfig>>,
+    llm_providers: Arc<LlmProviders>,
     config: AppConfig,
     reranker_pool: Option<Arc<RerankerPool>>,
     storage: StorageManager,
     embedding_provider: Arc<EmbeddingProvider>,
-    embedding_query_char_limit: usize,
+    tuning: IngestionTuning,
+    limits: StageLimits,
 }
 
 impl DefaultPipelineServices {
     pub fn new(
         db: Arc<SurrealDbClient>,
-        openai_client: Arc<async_openai::Client<async_openai::config::OpenAIConfig>>,
+        llm_providers: Arc<LlmProviders>,
         config: AppConfig,
         reranker_pool: Option<Arc<RerankerPool>>,
         storage: StorageManager,
         embedding_provider: Arc<EmbeddingProvider>,
-        embedding_query_char_limit: usize,
+        tuning: IngestionTuning,
     ) -> Self {
+        let limits = StageLimits::from_config(&config);
         Self {
             db,
-            openai_client,
+            llm_providers,
             config,
             reranker_pool,
             storage,
             embedding_provider,
-            embedding_query_char_limit,
+            tuning,
+            limits,
         }
     }
 
     async fn prepare_llm_request(
         &self,
+        user_id: &str,
         category: &str,
         context: Option<&str>,
         text: &str,
         similar_entities: &[RetrievedEntity],
-    ) -> Result<CreateChatCompletionRequest, AppError> {
+        tag_vocabulary: &[String],
+    ) -> Result<AnalysisRequest, AppError> {
         let settings = SystemSettings::get_current(&self.db).await?;
 
         let entities_json = retrieved_entities_to_json(similar_entities);
 
-        let user_message = format!(
-            "Category:\n{category}\ncontext:\n{context:?}\nContent:\n{text}\nExisting KnowledgeEntities in database:\n{entities_json}"
-        );
+        let custom_types = EntityTypeDefinition::list_for_user(user_id, &self.db).await?;
+        let mut entity_types: Vec<String> = KnowledgeEntityType::variants()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        let mut type_guide = entity_types.join(", ");
+        for definition in &custom_types {
+            entity_types.push(definition.name.clone());
+            let _ = write!(
+                type_guide,
+                "\n- {}: {}",
+                definition.name, definition.description
+            );
+        }
 
-        let response_format = ResponseFormat::JsonSchema {
-            json_schema: ResponseFormatJsonSchema {
-                description: Some("Structured analysis of the submitted content".into()),
-                name: "content_analysis".into(),
-                schema: get_ingress_analysis_schema(),
-                strict: Some(true),
-            },
+        let relationship_catalog = RelationshipTypeCatalog::for_user(user_id, &self.db).await?;
+        let relationship_guide = relationship_catalog
+            .types()
+            .iter()
+            .map(|kind| {
+                let direction = if kind.directed {
+                    "directed, source to target"
+                } else {
+                    "undirected"
+                };
+                format!("- {} ({direction}): {}", kind.name, kind.description)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let tag_guide = if tag_vocabulary.is_empty() {
+            "(none yet)".to_string()
+        } else {
+            tag_vocabulary.join(", ")
         };
+        let max_new_tags = self.tuning.max_new_tags_per_document;
 
-        let request = CreateChatCompletionRequestArgs::default()
-            .model(&settings.processing_model)
-            .messages([
-                ChatCompletionRequestSystemMessage::from(settings.ingestion_system_prompt.as_str())
-                    .into(),
-                ChatCompletionRequestUserMessage::from(user_message).into(),
-            ])
-            .response_format(response_format)
-            .build()?;
+        let user_message = format!(
+            "Category:\n{category}\ncontext:\n{context:?}\nContent:\n{text}\nEntity types:\n{type_guide}\nRelationship types:\n{relationship_guide}\nExisting tags (prefer these; propose at most {max_new_tags} new ones):\n{tag_guide}\nExisting KnowledgeEntities in database:\n{entities_json}"
+        );
 
-        Ok(request)
+        let structured_output = StructuredOutput::new(
+            "content_analysis",
+            "Structured analysis of the submitted content",
+            get_ingress_analysis_schema(&entity_types, &relationship_catalog.names()),
+        );
+        let timezone = self
+            .db
+            .get_item::<User>(user_id)
+            .await?
+            .map_or_else(|| "UTC".to_string(), |user| user.timezone);
+        let variables = PromptVariables::new(&timezone, Utc::now())
+            .with_category(category)
+            .with_entity_types(entity_types.iter().map(String::as_str));
+        let system_prompt = render_prompt(settings.ingestion_prompt_for(category), &variables)?;
+
+        let messages = vec![
+            ChatMessage::system(system_prompt),
+            ChatMessage::user(user_message),
+        ];
+
+        Ok(AnalysisRequest {
+            settings,
+            category: category.to_string(),
+            messages,
+            structured_output,
+        })
     }
 
     async fn perform_analysis(
         &self,
-        request: CreateChatCompletionRequest,
+        content: &TextContent,
+        request: AnalysisRequest,
     ) -> Result<LLMEnrichmentResult, AppError> {
-        let response = self.openai_client.chat().create(request).await?;
+        let completion = self
+            .llm_providers
+            .scoped(&content.user_id, Some(&content.id))
+            .cached()
+            .complete_for_category(
+                &request.settings,
+                ModelRole::Processing,
+                &request.category,
+                request.messages,
+                Some(request.structured_output),
+            )
+            .await?;
 
-        let content = response
-            .choices
-            .first()
-            .and_then(|choice| choice.message.content.as_ref())
-            .ok_or(AppError::LLMParsing(
+        if completion.content.is_empty() {
+            return Err(AppError::LLMParsing(
                 "No content found in LLM response".into(),
-            ))?;
+            ));
+        }
 
-        serde_json::from_str::<LLMEnrichmentResult>(content).map_err(|e| {
+        serde_json::from_str::<LLMEnrichmentResult>(&completion.content).map_err(|e| {
             AppError::LLMParsing(format!("Failed to parse LLM response into analysis: {e}"))
         })
     }
+
+    /// Map-reduce enrichment for long documents: analyses each section on its own,
+    /// bounded by the LLM stage limit, then merges the results.
+    async fn run_sectioned_enrichment(
+        &self,
+        content: &TextContent,
+        sections: &[String],
+        similar_entities: &[RetrievedEntity],
+        tag_vocabulary: &[String],
+    ) -> Result<LLMEnrichmentResult, AppError> {
+        let total = sections.len();
+        tracing::info!(
+            source_id = %content.id,
+            sections = total,
+            "Enriching long document section by section"
+        );
+
+        let analyses = try_join_all(sections.iter().enumerate().map(|(index, section)| {
+            let text = format!(
+                "[Section {} of {total}]\n{section}",
+                index.saturating_add(1)
+            );
+            async move {
+                let request = self
+                    .prepare_llm_request(
+                        &content.user_id,
+                        &content.category,
+                        content.context.as_deref(),
+                        &text,
+                        similar_entities,
+                        tag_vocabulary,
+                    )
+                    .await?;
+
+                let _permit = self.limits.llm().await?;
+                self.perform_analysis(content, request).await
+            }
+        }))
+        .await?;
+
+        Ok(LLMEnrichmentResult::merge_sections(analyses))
+    }
 }
 
 #[async_trait]
@@ -154,12 +281,24 @@ impl PipelineServices for DefaultPipelineServices {
     async fn prepare_text_content(
         &self,
         payload: IngestionPayload,
+        source_id: &str,
     ) -> Result<TextContent, AppError> {
+        // File extraction (PDF rendering, OCR) and URL rendering are the heavy paths.
+        let _permit = match payload {
+            IngestionPayload::File { .. } | IngestionPayload::Url { .. } => {
+                Some(self.limits.cpu().await?)
+            }
+            IngestionPayload::Text { .. } | IngestionPayload::Reingest { .. } => None,
+        };
+
+        let llm_providers = self
+            .llm_providers
+            .scoped(payload.user_id(), Some(source_id));
         to_text_content(
             payload,
             &self.db,
             &self.config,
-            &self.openai_client,
+            &llm_providers,
             &self.storage,
         )
         .await
@@ -169,7 +308,8 @@ impl PipelineServices for DefaultPipelineServices {
         &self,
         content: &TextContent,
     ) -> Result<Vec<RetrievedEntity>, AppError> {
-        let truncated_body = truncate_for_embedding(&content.text, self.embedding_query_char_limit);
+        let truncated_body =
+            truncate_for_embedding(&content.text, self.tuning.embedding_query_char_limit);
         let input_text = format!(
             "content: {}\n[truncated={}], category: {}, user_context: {:?}",
             truncated_body,
@@ -214,15 +354,85 @@ impl PipelineServices for DefaultPipelineServices {
         content: &TextContent,
         similar_entities: &[RetrievedEntity],
     ) -> Result<LLMEnrichmentResult, AppError> {
-        let request = self
-            .prepare_llm_request(
+        let sections = split_into_sections(
+            &content.text,
+            self.tuning.long_document_chars,
+            self.tuning.enrichment_section_chars,
+        );
+        let vocabulary = Tag::names_for_user(&content.user_id, &self.db).await?;
+
+        let mut analysis = if sections.len() > 1 {
+            self.run_sectioned_enrichment(content, &sections, similar_entities, &vocabulary)
+                .await?
+        } else {
+            let request = self
+                .prepare_llm_request(
+                    &content.user_id,
+                    &content.category,
+                    content.context.as_deref(),
+                    &content.text,
+                    similar_entities,
+                    &vocabulary,
+                )
+                .await?;
+
+            let _permit = self.limits.llm().await?;
+            self.perform_analysis(content, request).await?
+        };
+
+        analysis.constrain_tags(&vocabulary, self.tuning.max_new_tags_per_document);
+        analysis.constrain_relationship_types(
+            &RelationshipTypeCatalog::for_user(&content.user_id, &self.db).await?,
+        );
+        Ok(analysis)
+    }
+
+    async fn summarize_content(&self, content: &TextContent) -> Result<ContentSummary, AppError> {
+        let settings = SystemSettings::get_current(&self.db).await?;
+        let limit = self.tuning.summary_input_chars;
+        let body = truncate_for_embedding(&content.text, limit);
+        let user_message = format!(
+            "Category:\n{}\ncontext:\n{:?}\n[truncated={}]\nContent:\n{body}",
+            content.category,
+            content.context,
+            content.text.chars().count() > limit,
+        );
+
+        let messages = vec![
+            ChatMessage::system(DEFAULT_CONTENT_SUMMARY_SYSTEM_PROMPT),
+            ChatMessage::user(user_message),
+        ];
+        let structured_output = StructuredOutput::new(
+            "content_summary",
+            "Title, abstract and key points of the submitted content",
+            get_content_summary_schema(),
+        );
+
+        let _permit = self.limits.llm().await?;
+        let completion = self
+            .llm_providers
+            .scoped(&content.user_id, Some(&content.id))
+            .complete_for_category(
+                &settings,
+                ModelRole::Processing,
                 &content.category,
-                content.context.as_deref(),
-                &content.text,
-                similar_entities,
+                messages,
+                Some(structured_output),
             )
             .await?;
-        self.perform_analysis(request).await
+
+        let raw = completion.content.as_str();
+        if raw.is_empty() {
+            return Err(AppError::LLMParsing(
+                "No content found in LLM summary response".into(),
+            ));
+        }
+
+        let summary = serde_json::from_str::<ContentSummary>(raw).map_err(|e| {
+            AppError::LLMParsing(format!("Failed to parse LLM response into summary: {e}"))
+        })?;
+
+        clean_summary(&summary)
     }
 
     async fn convert_analysis(
@@ -230,6 +440,7 @@ impl PipelineServices for DefaultPipelineServices {
         content: &TextContent,
         analysis: &LLMEnrichmentResult,
     ) -> Result<(Vec<EmbeddedKnowledgeEntity>, Vec<KnowledgeRelationship>), AppError> {
+        let _permit = self.limits.cpu().await?;
         analysis
             .to_database_entities(content.id(), &content.user_id, &self.embedding_provider)
             .await
@@ -241,6 +452,7 @@ impl PipelineServices for DefaultPipelineServices {
         token_range: Range<usize>,
         overlap_tokens: usize,
     ) -> Result<Vec<EmbeddedTextChunk>, AppError> {
+        let _permit = self.limits.cpu().await?;
         let chunk_candidates = split_text_into_chunks(
             &content.text,
             token_range.start,
@@ -287,6 +499,33 @@ impl PipelineServices for DefaultPipelineServices {
     }
 }
 
+const MAX_SUMMARY_KEY_POINTS: usize = 6;
+
+/// Trims the model's summary, drops blank key points and caps their number.
+fn clean_summary(summary: &ContentSummary) -> Result<ContentSummary, AppError> {
+    let overview = summary.overview.trim().to_string();
+    if overview.is_empty() {
+        return Err(AppError::LLMParsing(
+            "LLM summary has an empty overview".into(),
+        ));
+    }
+
+    let key_points = summary
+        .key_points
+        .iter()
+        .map(|point| point.trim().trim_start_matches(['-', '*', '•']).trim())
+        .filter(|point| !point.is_empty())
+        .take(MAX_SUMMARY_KEY_POINTS)
+        .map(str::to_string)
+        .collect();
+
+    Ok(ContentSummary {
+        title: summary.title.trim().to_string(),
+        overview,
+        key_points,
+    })
+}
+
 fn split_text_into_chunks(
     text: &str,
     min_tokens: usize,
@@ -325,6 +564,19 @@ fn split_text_into_chunks(
     Ok(chunks)
 }
 
+/// Splits `text` into sections of about `section_chars` characters, preferring
+/// paragraph and sentence boundaries. Text up to `threshold_chars` stays whole.
+fn split_into_sections(text: &str, threshold_chars: usize, section_chars: usize) -> Vec<String> {
+    if section_chars == 0 || text.chars().count() <= threshold_chars {
+        return vec![text.to_string()];
+    }
+
+    TextSplitter::new(ChunkConfig::new(section_chars))
+        .chunks(text)
+        .map(str::to_owned)
+        .collect()
+}
+
 fn get_tokenizer() -> Result<&'static tokenizers::Tokenizer, AppError> {
     static TOKENIZER: OnceLock<Result<tokenizers::Tokenizer, String>> = OnceLock::new();
 
@@ -358,34 +610,44 @@ mod tests {
     use std::sync::Arc;
 
     use anyhow::Context;
-    use async_openai::{Client, config::OpenAIConfig, types::chat::ChatCompletionRequestMessage};
+    use async_openai::{Client, config::OpenAIConfig};
     use common::{
         storage::{
-            db::SurrealDbClient, store::StorageManager, types::system_settings::SystemSettingsPatch,
+            db::SurrealDbClient,
+            store::StorageManager,
+            types::{
+                entity_type_definition::EntityTypeDefinition, system_settings::SystemSettingsPatch,
+                text_content::ContentSummary,
+            },
         },
         utils::{
             config::{AppConfig, StorageKind},
             embedding::EmbeddingProvider,
+            llm::{ChatRole, LlmProviders},
         },
     };
     use uuid::Uuid;
 
-    use super::DefaultPipelineServices;
+    use super::{AnalysisRequest, DefaultPipelineServices};
     use crate::pipeline::IngestionTuning;
     use common::error::AppError;
 
-    fn system_prompt_from_request(
-        request: &async_openai::types::chat::CreateChatCompletionRequest,
-    ) -> anyhow::Result<String> {
-        let Some(ChatCompletionRequestMessage::System(system)) = request.messages.first() else {
-            anyhow::bail!("expected first message to be system");
+    fn system_prompt_from_request(request: &AnalysisRequest) -> anyhow::Result<String> {
+        let Some(system) = request.messages.first() else {
+            anyhow::bail!("expected a system message");
         };
-        let async_openai::types::chat::ChatCompletionRequestSystemMessageContent::Text(text) =
-            &system.content
-        else {
-            anyhow::bail!("unexpected system message content: {:?}", system.content);
-        };
-        Ok(text.clone())
+        anyhow::ensure!(
+            system.role == ChatRole::System,
+            "expected first message to be system"
+        );
+        Ok(system.content.clone())
+    }
+
+    fn llm_providers(config: &AppConfig) -> Arc<LlmProviders> {
+        Arc::new(LlmProviders::from_config(
+            config,
+            Arc::new(Client::with_config(OpenAIConfig::default())),
+        ))
     }
 
     #[tokio::test]
@@ -414,21 +676,20 @@ mod tests {
         let storage = StorageManager::new(&config)
             .await
             .context("storage manager")?;
-        let openai_client = Arc::new(Client::with_config(OpenAIConfig::default()));
         let embedding_provider = Arc::new(EmbeddingProvider::new_hashed(384)?);
 
         let services = DefaultPipelineServices::new(
             db,
-            openai_client,
+            llm_providers(&config),
             config,
             None,
             storage,
             embedding_provider,
-            IngestionTuning::default().embedding_query_char_limit,
+            IngestionTuning::default(),
         );
 
         let request = services
-            .prepare_llm_request("notes", None, "hello world", &[])
+            .prepare_llm_request("user-1", "notes", None, "hello world", &[], &[])
             .await
             .context("prepare llm request")?;
 
@@ -436,6 +697,61 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn prepare_llm_request_offers_user_defined_entity_types() -> anyhow::Result<()> {
+        let db = Arc::new(
+            SurrealDbClient::memory("test_ns", &Uuid::new_v4().to_string())
+                .await
+                .context("start in-memory db")?,
+        );
+        db.apply_migrations().await.context("apply migrations")?;
+        EntityTypeDefinition::create("user-1", "Person", "A human being", None, &db).await?;
+
+        let config = AppConfig {
+            storage: StorageKind::Memory,
+            ..Default::default()
+        };
+        let storage = StorageManager::new(&config)
+            .await
+            .context("storage manager")?;
+        let services = DefaultPipelineServices::new(
+            db,
+            llm_providers(&config),
+            config,
+            None,
+            storage,
+            Arc::new(EmbeddingProvider::new_hashed(384)?),
+            IngestionTuning::default(),
+        );
+
+        let request = services
+            .prepare_llm_request("user-1", "notes", None, "hello world", &[], &[])
+            .await
+            .context("prepare llm request")?;
+
+        let Some(user) = request.messages.get(1) else {
+            anyhow::bail!("expected second message to be the user message");
+        };
+        assert!(user.content.contains("- Person: A human being"));
+
+        let allowed = request
+            .structured_output
+            .schema
+            .pointer("/properties/knowledge_entities/items/properties/entity_type/enum");
+        assert_eq!(
+            allowed,
+            Some(&serde_json::json!([
+                "Idea",
+                "Project",
+                "Document",
+                "Page",
+                "TextSnippet",
+                "Person"
+            ]))
+        );
+        Ok(())
+    }
+
     #[test]
     fn split_text_into_chunks_rejects_zero_bounds() {
         assert!(matches!(
@@ -468,6 +784,65 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn split_into_sections_keeps_short_text_whole() {
+        let sections = super::split_into_sections("short text", 100, 10);
+        assert_eq!(sections, vec!["short text".to_string()]);
+    }
+
+    #[test]
+    fn split_into_sections_splits_long_text_on_paragraphs() {
+        let paragraph = "word ".repeat(40);
+        let text = [paragraph.trim(), paragraph.trim(), paragraph.trim()].join("\n\n");
+
+        let sections = super::split_into_sections(&text, 100, 250);
+
+        assert_eq!(sections.len(), 3);
+        assert!(
+            sections
+                .iter()
+                .all(|section| section.chars().count() <= 250)
+        );
+        assert!(sections.iter().all(|section| section.starts_with("word")));
+    }
+
+    #[test]
+    fn clean_summary_trims_and_caps_key_points() -> anyhow::Result<()> {
+        let raw = ContentSummary {
+            title: "  Tidal energy  ".into(),
+            overview: " Tides can be harnessed. ".into(),
+            key_points: ["- first", "  ", "* second", "third", "4", "5", "6", "7"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+        };
+
+        let summary = super::clean_summary(&raw)?;
+
+        assert_eq!(summary.title, "Tidal energy");
+        assert_eq!(summary.overview, "Tides can be harnessed.");
+        assert_eq!(summary.key_points.len(), super::MAX_SUMMARY_KEY_POINTS);
+        assert_eq!(
+            summary.key_points.get(0..3),
+            Some(&["first", "second", "third"].map(String::from)[..])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn clean_summary_rejects_empty_overview() {
+        let raw = ContentSummary {
+            title: "Title".into(),
+            overview: "   ".into(),
+            key_points: Vec::new(),
+        };
+
+        assert!(matches!(
+            super::clean_summary(&raw),
+            Err(AppError::LLMParsing(_))
+        ));
+    }
+
     #[test]
     fn truncate_for_embedding_returns_short_text_unchanged() {
         assert_eq!(super::truncate_for_embedding("hello", 10), "hello");
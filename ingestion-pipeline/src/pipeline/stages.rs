@@ -1,21 +1,23 @@
 //! State-machine stages of the ingestion pipeline.
 //!
 //! Each function advances the `IngestionMachine` by one transition
-//! (`prepare → retrieve → enrich → persist`), mutating the shared
+//! (`prepare → retrieve → enrich → summarize → persist`), mutating the shared
 //! [`PipelineContext`]. Low-level database writes live in [`super::persistence`].
 
 use common::{
     error::AppError,
-    storage::types::{ingestion_payload::IngestionPayload, system_settings::SystemSettings},
+    storage::types::{
+        ingestion_payload::IngestionPayload, system_settings::SystemSettings, tag::Tag, user::User,
+    },
 };
 use state_machines::core::GuardError;
-use tracing::{debug, instrument};
+use tracing::{debug, instrument, warn};
 
 use super::{
     context::PipelineContext,
     enrichment_result::LLMEnrichmentResult,
     persistence::persist_artifacts,
-    state::{ContentPrepared, Enriched, IngestionMachine, Persisted, Ready, Retrieved},
+    state::{ContentPrepared, Enriched, IngestionMachine, Persisted, Ready, Retrieved, Summarized},
 };
 
 #[instrument(
@@ -28,8 +30,21 @@ pub async fn prepare_content(
     ctx: &mut PipelineContext<'_>,
     payload: IngestionPayload,
 ) -> Result<IngestionMachine<(), ContentPrepared>, AppError> {
-    let mut text_content = ctx.services.prepare_text_content(payload).await?;
-    text_content.id.clone_from(&ctx.task_id);
+    // Re-ingests update the stored content in place; new content takes the task id.
+    let reingested_id = match &payload {
+        IngestionPayload::Reingest {
+            text_content_id, ..
+        } => Some(text_content_id.clone()),
+        _ => None,
+    };
+    let source_id = reingested_id.as_deref().unwrap_or(ctx.task_id.as_str());
+    let mut text_content = ctx
+        .services
+        .prepare_text_content(payload, source_id)
+        .await?;
+    if reingested_id.is_none() {
+        text_content.id.clone_from(&ctx.task_id);
+    }
 
     let text_len = text_content.text.chars().count();
     let preview: String = text_content.text.chars().take(120).collect();
@@ -111,6 +126,8 @@ pub async fn enrich(
         ctx.analysis = Some(LLMEnrichmentResult {
             knowledge_entities: Vec::new(),
             relationships: Vec::new(),
+            tags: Vec::new(),
+            action_items: Vec::new(),
         });
         return machine
             .enrich()
@@ -128,9 +145,17 @@ pub async fn enrich(
         attempt = ctx.attempt,
         entity_suggestions = analysis.knowledge_entities.len(),
         relationship_suggestions = analysis.relationships.len(),
+        tag_suggestions = analysis.tags.len(),
         "ingestion enrichment completed"
     );
 
+    if let Some(content) = ctx.text_content.as_mut() {
+        for tag in &analysis.tags {
+            if !content.tags.contains(tag) {
+                content.tags.push(tag.clone());
+            }
+        }
+    }
     ctx.analysis = Some(analysis);
 
     machine
@@ -138,21 +163,77 @@ pub async fn enrich(
         .map_err(|(_, guard)| map_guard_error("enrich", &guard))
 }
 
+/// Attaches an abstract, key points and suggested title to the content.
+///
+/// Summaries are a convenience: a failed summary is logged and the content is stored
+/// without one rather than failing the task.
 #[instrument(
     level = "trace",
     skip_all,
     fields(task_id = %ctx.task_id, attempt = ctx.attempt, user_id = %ctx.task.user_id)
 )]
-pub async fn persist(
+pub async fn summarize(
     machine: IngestionMachine<(), Enriched>,
     ctx: &mut PipelineContext<'_>,
+) -> Result<IngestionMachine<(), Summarized>, AppError> {
+    if !ctx.pipeline_config.chunk_only {
+        let result = ctx.services.summarize_content(ctx.text_content()?).await;
+        let summary = match result {
+            Ok(summary) => {
+                debug!(
+                    task_id = %ctx.task_id,
+                    attempt = ctx.attempt,
+                    key_points = summary.key_points.len(),
+                    "ingestion summary generated"
+                );
+                Some(summary)
+            }
+            Err(err) => {
+                warn!(
+                    task_id = %ctx.task_id,
+                    attempt = ctx.attempt,
+                    error = %err,
+                    "ingestion summary failed; storing content without one"
+                );
+                None
+            }
+        };
+
+        if let Some(content) = ctx.text_content.as_mut() {
+            content.summary = summary;
+        }
+    }
+
+    machine
+        .summarize()
+        .map_err(|(_, guard)| map_guard_error("summarize", &guard))
+}
+
+#[instrument(
+    level = "trace",
+    skip_all,
+    fields(task_id = %ctx.task_id, attempt = ctx.attempt, user_id = %ctx.task.user_id)
+)]
+pub async fn persist(
+    machine: IngestionMachine<(), Summarized>,
+    ctx: &mut PipelineContext<'_>,
 ) -> Result<IngestionMachine<(), Persisted>, AppError> {
-    let artifacts = ctx.build_artifacts().await?;
+    let mut artifacts = ctx.build_artifacts().await?;
+    let owner: Option<User> = ctx.db.get_item(&artifacts.text_content.user_id).await?;
+    if owner.is_some_and(|user| user.requires_suggestion_review(&artifacts.text_content.category)) {
+        artifacts = artifacts.into_review();
+    }
     let settings = SystemSettings::get_current(ctx.db).await?;
     let embedding_dimensions = usize::try_from(settings.embedding_dimensions).map_err(|_| {
         AppError::InternalError("system_settings.embedding_dimensions exceeds usize::MAX".into())
     })?;
 
+    let mut tags = artifacts.text_content.tags.clone();
+    for entity in &artifacts.entities {
+        tags.extend(entity.entity.tags.iter().cloned());
+    }
+    Tag::ensure(&artifacts.text_content.user_id, &tags, ctx.db).await?;
+
     let counts = persist_artifacts(
         ctx.db,
         &ctx.pipeline_config.tuning,
@@ -166,6 +247,7 @@ pub async fn persist(
         attempt = ctx.attempt,
         entity_count = counts.entity_count,
         relationship_count = counts.relationship_count,
+        suggestion_count = counts.suggestion_count,
         chunk_count = counts.chunk_count,
         "ingestion persistence flushed to database"
     );
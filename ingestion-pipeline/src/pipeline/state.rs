@@ -4,17 +4,19 @@ state_machine! {
     name: IngestionMachine,
     state: IngestionState,
     initial: Ready,
-    states: [Ready, ContentPrepared, Retrieved, Enriched, Persisted, Failed],
+    states: [Ready, ContentPrepared, Retrieved, Enriched, Summarized, Persisted, Failed],
     events {
         prepare { transition: { from: Ready, to: ContentPrepared } }
         retrieve { transition: { from: ContentPrepared, to: Retrieved } }
         enrich { transition: { from: Retrieved, to: Enriched } }
-        persist { transition: { from: Enriched, to: Persisted } }
+        summarize { transition: { from: Enriched, to: Summarized } }
+        persist { transition: { from: Summarized, to: Persisted } }
         abort {
             transition: { from: Ready, to: Failed }
             transition: { from: ContentPrepared, to: Failed }
             transition: { from: Retrieved, to: Failed }
             transition: { from: Enriched, to: Failed }
+            transition: { from: Summarized, to: Failed }
             transition: { from: Persisted, to: Failed }
         }
     }
@@ -72,6 +72,7 @@ pub fn large_artifacts(
                 source_id: source_id.to_string(),
                 metadata: None,
                 user_id: user_id.to_string(),
+                tags: Vec::new(),
             },
             embedding: embedding.clone(),
         });
@@ -126,10 +127,15 @@ pub fn large_artifacts(
             context: None,
             category: "notes".to_string(),
             user_id: user_id.to_string(),
+            summary: None,
+            tags: Vec::new(),
         },
         entities,
         relationships,
         chunks,
+        mentions: Vec::new(),
+        action_items: Vec::new(),
+        suggestions: Vec::new(),
     }
 }
 
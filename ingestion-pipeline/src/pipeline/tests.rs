@@ -4,7 +4,7 @@ use super::{
     IngestionPipeline,
     config::{IngestionConfig, IngestionTuning},
     enrichment_result::LLMEnrichmentResult,
-    services::PipelineServices,
+    services::{DefaultPipelineServices, PipelineServices},
     test_support::{
         count_chunks_for_source, count_entities_for_source, count_relationships_for_source,
         persist, sample_artifacts, setup_db,
@@ -12,21 +12,29 @@ use super::{
 };
 use crate::pipeline::context::{EmbeddedKnowledgeEntity, EmbeddedTextChunk};
 use anyhow::{self, Context};
+use async_openai::{Client, config::OpenAIConfig};
 use async_trait::async_trait;
 use chrono::{Duration as ChronoDuration, Utc};
 use common::{
     error::AppError,
     storage::{
         db::SurrealDbClient,
+        store::StorageManager,
         types::{
             ingestion_payload::IngestionPayload,
             ingestion_task::{IngestionTask, TaskState},
             knowledge_entity::{KnowledgeEntity, KnowledgeEntityType},
             knowledge_relationship::KnowledgeRelationship,
+            system_settings::SystemSettings,
             text_chunk::TextChunk,
-            text_content::TextContent,
+            text_content::{ContentSummary, TextContent},
         },
     },
+    utils::{
+        config::{AppConfig, StorageKind},
+        embedding::EmbeddingProvider,
+        llm::LlmProviders,
+    },
 };
 use retrieval_pipeline::{RetrievedChunk, RetrievedEntity};
 use tokio::sync::Mutex;
@@ -35,6 +43,8 @@ pub(crate) struct MockServices {
     text_content: TextContent,
     similar_entities: Vec<RetrievedEntity>,
     analysis: LLMEnrichmentResult,
+    /// Returned by `summarize_content`; `None` makes the summary stage fail.
+    summary: Option<ContentSummary>,
     chunk_embedding: Vec<f32>,
     graph_entities: Vec<EmbeddedKnowledgeEntity>,
     graph_relationships: Vec<KnowledgeRelationship>,
@@ -70,6 +80,8 @@ impl MockServices {
         let analysis = LLMEnrichmentResult {
             knowledge_entities: Vec::new(),
             relationships: Vec::new(),
+            tags: Vec::new(),
+            action_items: Vec::new(),
         };
 
         let graph_entity = KnowledgeEntity::new(
@@ -99,6 +111,11 @@ impl MockServices {
                 }]),
             }],
             analysis,
+            summary: Some(ContentSummary {
+                title: "Example document".into(),
+                overview: "A document used to exercise the pipeline.".into(),
+                key_points: vec!["Pipelines ingest documents".into()],
+            }),
             chunk_embedding: vec![0.3; TEST_EMBEDDING_DIM],
             graph_entities: vec![EmbeddedKnowledgeEntity {
                 entity: graph_entity,
@@ -119,6 +136,7 @@ impl PipelineServices for MockServices {
     async fn prepare_text_content(
         &self,
         _payload: IngestionPayload,
+        _source_id: &str,
     ) -> Result<TextContent, AppError> {
         self.record("prepare").await;
         Ok(self.text_content.clone())
@@ -141,6 +159,13 @@ impl PipelineServices for MockServices {
         Ok(self.analysis.clone())
     }
 
+    async fn summarize_content(&self, _content: &TextContent) -> Result<ContentSummary, AppError> {
+        self.record("summarize").await;
+        self.summary
+            .clone()
+            .ok_or_else(|| AppError::LLMParsing("mock summary failure".to_string()))
+    }
+
     async fn convert_analysis(
         &self,
         content: &TextContent,
@@ -197,8 +222,9 @@ impl PipelineServices for FailingServices {
     async fn prepare_text_content(
         &self,
         payload: IngestionPayload,
+        source_id: &str,
     ) -> Result<TextContent, AppError> {
-        self.inner.prepare_text_content(payload).await
+        self.inner.prepare_text_content(payload, source_id).await
     }
 
     async fn retrieve_similar_entities(
@@ -216,6 +242,10 @@ impl PipelineServices for FailingServices {
         Err(AppError::Processing("mock enrichment failure".to_string()))
     }
 
+    async fn summarize_content(&self, content: &TextContent) -> Result<ContentSummary, AppError> {
+        self.inner.summarize_content(content).await
+    }
+
     async fn convert_analysis(
         &self,
         content: &TextContent,
@@ -241,6 +271,7 @@ impl PipelineServices for ValidationServices {
     async fn prepare_text_content(
         &self,
         _payload: IngestionPayload,
+        _source_id: &str,
     ) -> Result<TextContent, AppError> {
         Err(AppError::Validation("unsupported".to_string()))
     }
@@ -260,6 +291,10 @@ impl PipelineServices for ValidationServices {
         unreachable!("run_enrichment should not be called after validation failure")
     }
 
+    async fn summarize_content(&self, _content: &TextContent) -> Result<ContentSummary, AppError> {
+        unreachable!("summarize_content should not be called after validation failure")
+    }
+
     async fn convert_analysis(
         &self,
         _content: &TextContent,
@@ -278,6 +313,67 @@ impl PipelineServices for ValidationServices {
     }
 }
 
+/// Hands the task to another worker during enrichment, as a lapsed lease would.
+struct LeaseStealingServices {
+    inner: MockServices,
+    db: SurrealDbClient,
+    task_id: String,
+}
+
+#[async_trait]
+impl PipelineServices for LeaseStealingServices {
+    async fn prepare_text_content(
+        &self,
+        payload: IngestionPayload,
+        source_id: &str,
+    ) -> Result<TextContent, AppError> {
+        self.inner.prepare_text_content(payload, source_id).await
+    }
+
+    async fn retrieve_similar_entities(
+        &self,
+        content: &TextContent,
+    ) -> Result<Vec<RetrievedEntity>, AppError> {
+        self.inner.retrieve_similar_entities(content).await
+    }
+
+    async fn run_enrichment(
+        &self,
+        content: &TextContent,
+        similar_entities: &[RetrievedEntity],
+    ) -> Result<LLMEnrichmentResult, AppError> {
+        self.db
+            .client
+            .query("UPDATE type::thing('ingestion_task', $id) SET worker_id = 'other-worker';")
+            .bind(("id", self.task_id.clone()))
+            .await?;
+        self.inner.run_enrichment(content, similar_entities).await
+    }
+
+    async fn summarize_content(&self, content: &TextContent) -> Result<ContentSummary, AppError> {
+        self.inner.summarize_content(content).await
+    }
+
+    async fn convert_analysis(
+        &self,
+        content: &TextContent,
+        analysis: &LLMEnrichmentResult,
+    ) -> Result<(Vec<EmbeddedKnowledgeEntity>, Vec<KnowledgeRelationship>), AppError> {
+        self.inner.convert_analysis(content, analysis).await
+    }
+
+    async fn prepare_chunks(
+        &self,
+        content: &TextContent,
+        token_range: std::ops::Range<usize>,
+        overlap_tokens: usize,
+    ) -> Result<Vec<EmbeddedTextChunk>, AppError> {
+        self.inner
+            .prepare_chunks(content, token_range, overlap_tokens)
+            .await
+    }
+}
+
 pub(crate) fn pipeline_config() -> IngestionConfig {
     IngestionConfig {
         tuning: IngestionTuning {
@@ -314,10 +410,10 @@ async fn retry_delay_grows_exponentially_and_caps() -> anyhow::Result<()> {
     // Defaults: base = 30s, cap exponent = 5, max = 900s.
     assert_eq!(pipeline.retry_delay(0), Duration::from_secs(30));
     assert_eq!(pipeline.retry_delay(1), Duration::from_secs(30));
-    assert_eq!(pipeline.retry_delay(2), Duration::from_secs(60));
-    assert_eq!(pipeline.retry_delay(3), Duration::from_secs(120));
+    assert_eq!(pipeline.retry_delay(2), Duration::from_mins(1));
+    assert_eq!(pipeline.retry_delay(3), Duration::from_mins(2));
     // Beyond the cap exponent the delay clamps at the configured maximum.
-    assert_eq!(pipeline.retry_delay(7), Duration::from_secs(900));
+    assert_eq!(pipeline.retry_delay(7), Duration::from_mins(15));
     Ok(())
 }
 
@@ -396,23 +492,100 @@ async fn ingestion_pipeline_happy_path_persists_artifacts() -> anyhow::Result<()
         "graph relationships should be persisted"
     );
 
+    assert_eq!(
+        text_content.summary, services.summary,
+        "the generated summary should be stored on the content"
+    );
+
     let call_log = services.calls.lock().await.clone();
     assert!(
-        call_log.len() >= 5,
+        call_log.len() >= 6,
         "expected at least one chunk embedding call"
     );
     assert_eq!(
-        call_log.get(0..4),
-        Some(&["prepare", "retrieve", "enrich", "convert"][..])
+        call_log.get(0..5),
+        Some(&["prepare", "retrieve", "enrich", "summarize", "convert"][..])
     );
     assert!(
         call_log
-            .get(4..)
+            .get(5..)
             .is_some_and(|tail| tail.iter().all(|entry| *entry == "chunk"))
     );
     Ok(())
 }
 
+#[tokio::test]
+async fn ingestion_pipeline_stores_content_when_summary_fails() -> anyhow::Result<()> {
+    let db = setup_db().await?;
+    let worker_id = "worker-summary-failure";
+    let user_id = "user-summary-failure";
+    let mut services = MockServices::new(user_id);
+    services.summary = None;
+    let services = Arc::new(services);
+    let services_clone: Arc<dyn PipelineServices> = Arc::<MockServices>::clone(&services);
+    let pipeline =
+        IngestionPipeline::with_services(Arc::new(db.clone()), pipeline_config(), services_clone)?;
+
+    let task = reserve_task(
+        &db,
+        worker_id,
+        IngestionPayload::Text {
+            text: "Example payload".into(),
+            context: "Context".into(),
+            category: "notes".into(),
+            user_id: user_id.into(),
+        },
+        user_id,
+    )
+    .await?;
+
+    pipeline.process_task(task.clone()).await?;
+
+    let stored_task: IngestionTask = db.get_item(&task.id).await?.context("task present")?;
+    assert_eq!(stored_task.state, TaskState::Succeeded);
+
+    let text_content: TextContent = db.get_item(&task.id).await?.context("text content")?;
+    assert!(text_content.summary.is_none());
+    assert_eq!(count_chunks_for_source(&db, &task.id).await?, 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn reingest_payload_updates_existing_content_in_place() -> anyhow::Result<()> {
+    let db = setup_db().await?;
+    let worker_id = "worker-reingest";
+    let user_id = "user-reingest";
+    let services = Arc::new(MockServices::new(user_id));
+    let content_id = services.text_content.id.clone();
+    let services_clone: Arc<dyn PipelineServices> = Arc::<MockServices>::clone(&services);
+    let pipeline =
+        IngestionPipeline::with_services(Arc::new(db.clone()), pipeline_config(), services_clone)?;
+
+    let task = reserve_task(
+        &db,
+        worker_id,
+        IngestionPayload::reingest(&services.text_content),
+        user_id,
+    )
+    .await?;
+    assert_ne!(task.id, content_id);
+
+    pipeline.process_task(task.clone()).await?;
+
+    let stored_task: IngestionTask = db.get_item(&task.id).await?.context("task present")?;
+    assert_eq!(stored_task.state, TaskState::Succeeded);
+
+    let text_content: TextContent = db.get_item(&content_id).await?.context("text content")?;
+    assert_eq!(text_content.id, content_id);
+    assert!(
+        db.get_item::<TextContent>(&task.id).await?.is_none(),
+        "re-ingest must not create content under the task id"
+    );
+    assert_eq!(count_chunks_for_source(&db, &content_id).await?, 1);
+    assert_eq!(count_entities_for_source(&db, &content_id).await?, 1);
+    Ok(())
+}
+
 #[tokio::test]
 async fn ingestion_pipeline_chunk_only_skips_analysis() -> anyhow::Result<()> {
     let db = setup_db().await?;
@@ -554,3 +727,126 @@ async fn ingestion_pipeline_validation_failure_dead_letters_task() -> anyhow::Re
     assert_eq!(stored_task.state, TaskState::DeadLetter);
     Ok(())
 }
+
+#[tokio::test]
+async fn process_task_extends_lease_for_slow_payloads() -> anyhow::Result<()> {
+    let db = setup_db().await?;
+    let worker_id = "worker-lease-extend";
+    let user_id = "user-lease-extend";
+    let services = Arc::new(FailingServices {
+        inner: MockServices::new(user_id),
+    });
+    let pipeline =
+        IngestionPipeline::with_services(Arc::new(db.clone()), pipeline_config(), services)?;
+
+    let task = reserve_task(
+        &db,
+        worker_id,
+        IngestionPayload::Url {
+            url: "https://example.com/slow".into(),
+            context: String::new(),
+            category: "notes".into(),
+            user_id: user_id.into(),
+        },
+        user_id,
+    )
+    .await?;
+    assert_eq!(task.lease_duration().as_secs(), 300);
+
+    let result = pipeline.process_task(task.clone()).await;
+    assert!(result.is_err(), "failing services should bubble error");
+
+    let stored_task: IngestionTask = db.get_item(&task.id).await?.context("task present")?;
+    assert_eq!(stored_task.lease_duration().as_secs(), 600);
+    Ok(())
+}
+
+#[tokio::test]
+async fn process_task_abandons_run_when_lease_is_stolen() -> anyhow::Result<()> {
+    let db = setup_db().await?;
+    let worker_id = "worker-lease-stale";
+    let user_id = "user-lease-stale";
+
+    let task = reserve_task(
+        &db,
+        worker_id,
+        IngestionPayload::Text {
+            text: "Stolen lease payload".into(),
+            context: "Context".into(),
+            category: "notes".into(),
+            user_id: user_id.into(),
+        },
+        user_id,
+    )
+    .await?;
+
+    let services = Arc::new(LeaseStealingServices {
+        inner: MockServices::new(user_id),
+        db: db.clone(),
+        task_id: task.id.clone(),
+    });
+    let pipeline =
+        IngestionPipeline::with_services(Arc::new(db.clone()), pipeline_config(), services)?;
+
+    let result = pipeline.process_task(task.clone()).await;
+    assert!(result.is_err(), "stale run should report the lost lease");
+
+    assert_eq!(count_chunks_for_source(&db, &task.id).await?, 0);
+    assert_eq!(count_entities_for_source(&db, &task.id).await?, 0);
+    let content: Option<TextContent> = db.get_item(&task.id).await?;
+    assert!(content.is_none(), "stale run must not persist content");
+
+    let stored_task: IngestionTask = db.get_item(&task.id).await?.context("task present")?;
+    assert_eq!(stored_task.state, TaskState::Processing);
+    assert_eq!(stored_task.worker_id.as_deref(), Some("other-worker"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn mock_llm_answers_every_llm_stage_offline() -> anyhow::Result<()> {
+    let db = Arc::new(setup_db().await?);
+    let config = AppConfig {
+        storage: StorageKind::Memory,
+        llm_mock: true,
+        ..Default::default()
+    };
+    let llm_providers = Arc::new(LlmProviders::from_config(
+        &config,
+        Arc::new(Client::with_config(OpenAIConfig::default())),
+    ));
+    let dimensions = SystemSettings::get_current(&db).await?.embedding_dimensions;
+    let embedding_provider = Arc::new(EmbeddingProvider::new_hashed(usize::try_from(dimensions)?)?);
+    let services = DefaultPipelineServices::new(
+        Arc::clone(&db),
+        llm_providers,
+        config.clone(),
+        None,
+        StorageManager::new(&config).await?,
+        embedding_provider,
+        IngestionTuning::default(),
+    );
+
+    let content = services
+        .prepare_text_content(
+            IngestionPayload::Text {
+                text: "Ada Lovelace wrote the first program for the Analytical Engine.".into(),
+                context: "History notes".into(),
+                category: "notes".into(),
+                user_id: "user-offline".into(),
+            },
+            "offline-source",
+        )
+        .await?;
+
+    let analysis = services.run_enrichment(&content, &[]).await?;
+    let (entities, relationships) = services.convert_analysis(&content, &analysis).await?;
+    assert_eq!(entities.len(), 3);
+    assert_eq!(relationships.len(), 2);
+
+    let summary = services.summarize_content(&content).await?;
+    assert!(
+        !summary.overview.is_empty(),
+        "the mock also answers the summary stage"
+    );
+    Ok(())
+}
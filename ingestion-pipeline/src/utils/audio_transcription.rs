@@ -1,29 +1,19 @@
-use async_openai::types::audio::{AudioResponseFormat, CreateTranscriptionRequestArgs};
 use common::{
     error::AppError,
     storage::{db::SurrealDbClient, types::system_settings::SystemSettings},
+    utils::llm::LlmProviders,
 };
 
-/// Transcribes an audio file using the configured `OpenAI` Whisper model.
+/// Transcribes an audio file using the configured voice processing (Whisper) model.
 pub async fn transcribe_audio_file(
     file_path: &str,
     db_client: &SurrealDbClient,
-    openai_client: &async_openai::Client<async_openai::config::OpenAIConfig>,
+    llm_providers: &LlmProviders,
 ) -> Result<String, AppError> {
     let system_settings = SystemSettings::get_current(db_client).await?;
-    let model = system_settings.voice_processing_model;
 
-    let request = CreateTranscriptionRequestArgs::default()
-        .file(file_path)
-        .model(model)
-        .response_format(AudioResponseFormat::Json)
-        .build()?;
-
-    let response = openai_client
-        .audio()
-        .transcription()
-        .create(request)
+    llm_providers
+        .transcribe(&system_settings, file_path)
         .await
-        .map_err(|e| AppError::Processing(format!("audio transcription failed: {e}")))?;
-    Ok(response.text)
+        .map_err(|e| AppError::Processing(format!("audio transcription failed: {e}")))
 }
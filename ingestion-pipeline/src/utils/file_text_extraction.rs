@@ -1,7 +1,7 @@
 use common::{
     error::AppError,
     storage::{db::SurrealDbClient, store::StorageManager, types::file_info::FileInfo},
-    utils::config::AppConfig,
+    utils::{config::AppConfig, llm::LlmProviders},
 };
 use std::{
     env,
@@ -70,7 +70,7 @@ fn infer_extension(file_info: &FileInfo) -> Option<String> {
 pub async fn extract_text_from_file(
     file_info: &FileInfo,
     db_client: &SurrealDbClient,
-    openai_client: &async_openai::Client<async_openai::config::OpenAIConfig>,
+    llm_providers: &LlmProviders,
     config: &AppConfig,
     storage: &StorageManager,
 ) -> Result<String, AppError> {
@@ -88,7 +88,7 @@ pub async fn extract_text_from_file(
                 return extract_pdf_content(
                     path,
                     db_client,
-                    openai_client,
+                    llm_providers,
                     &config.pdf_ingest_mode,
                 )
                 .await;
@@ -98,7 +98,7 @@ pub async fn extract_text_from_file(
             let result = extract_pdf_content(
                 temp_guard.as_path(),
                 db_client,
-                openai_client,
+                llm_providers,
                 &config.pdf_ingest_mode,
             )
             .await;
@@ -107,7 +107,7 @@ pub async fn extract_text_from_file(
         }
         "image/png" | "image/jpeg" => {
             let content =
-                extract_text_from_image(file_bytes.as_ref(), db_client, openai_client).await?;
+                extract_text_from_image(file_bytes.as_ref(), db_client, llm_providers).await?;
             Ok(content)
         }
         "audio/mpeg" | "audio/mp3" | "audio/wav" | "audio/x-wav" | "audio/webm" | "audio/mp4"
@@ -119,7 +119,7 @@ pub async fn extract_text_from_file(
                         file_info.id
                     ))
                 })?;
-                return transcribe_audio_file(path_str, db_client, openai_client).await;
+                return transcribe_audio_file(path_str, db_client, llm_providers).await;
             }
 
             let extension = infer_extension(file_info);
@@ -131,7 +131,7 @@ pub async fn extract_text_from_file(
                     file_info.id
                 ))
             })?;
-            let result = transcribe_audio_file(path_str, db_client, openai_client).await;
+            let result = transcribe_audio_file(path_str, db_client, llm_providers).await;
             drop(temp_guard);
             result
         }
@@ -142,6 +142,8 @@ pub async fn extract_text_from_file(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::Arc;
+
     use async_openai::{Client, config::OpenAIConfig};
     use bytes::Bytes;
     use chrono::Utc;
@@ -182,10 +184,13 @@ mod tests {
         let database = &Uuid::new_v4().to_string();
         let db = SurrealDbClient::memory(namespace, database).await?;
 
-        let openai_client = Client::with_config(OpenAIConfig::default());
+        let llm_providers = LlmProviders::from_config(
+            &config,
+            Arc::new(Client::with_config(OpenAIConfig::default())),
+        );
 
         let text =
-            extract_text_from_file(&file_info, &db, &openai_client, &config, &storage).await?;
+            extract_text_from_file(&file_info, &db, &llm_providers, &config, &storage).await?;
 
         assert_eq!(text, String::from_utf8_lossy(contents));
         Ok(())
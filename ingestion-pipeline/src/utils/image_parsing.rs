@@ -1,55 +1,38 @@
-use async_openai::types::chat::{
-    ChatCompletionRequestMessageContentPartImageArgs,
-    ChatCompletionRequestMessageContentPartTextArgs, ChatCompletionRequestUserMessageArgs,
-    CreateChatCompletionRequestArgs, ImageDetail, ImageUrlArgs,
-};
 use base64::{Engine as _, engine::general_purpose::STANDARD};
 use common::{
     error::AppError,
-    storage::{db::SurrealDbClient, types::system_settings::SystemSettings},
+    storage::{
+        db::SurrealDbClient,
+        types::system_settings::{ModelRole, SystemSettings},
+    },
+    utils::llm::{ChatMessage, LlmProviders},
 };
 
 pub async fn extract_text_from_image(
     image_bytes: &[u8],
     db: &SurrealDbClient,
-    client: &async_openai::Client<async_openai::config::OpenAIConfig>,
+    llm_providers: &LlmProviders,
 ) -> Result<String, AppError> {
     let system_settings = SystemSettings::get_current(db).await?;
 
     let base64_image = STANDARD.encode(image_bytes);
 
-    let image_url = format!("data:image/png;base64,{base64_image}");
-
-    let request = CreateChatCompletionRequestArgs::default()
-        .model(system_settings.image_processing_model)
-        .messages([ChatCompletionRequestUserMessageArgs::default()
-            .content(vec![
-                ChatCompletionRequestMessageContentPartTextArgs::default()
-                    .text(system_settings.image_processing_prompt)
-                    .build()?
-                    .into(),
-                ChatCompletionRequestMessageContentPartImageArgs::default()
-                    .image_url(
-                        ImageUrlArgs::default()
-                            .url(image_url)
-                            .detail(ImageDetail::High)
-                            .build()?,
-                    )
-                    .build()?
-                    .into(),
-            ])
-            .build()?
-            .into()])
-        .build()?;
+    let message = ChatMessage::user(system_settings.image_processing_prompt.as_str())
+        .with_images(vec![base64_image]);
 
-    let response = client.chat().create(request).await?;
+    let completion = llm_providers
+        .cached()
+        .complete(
+            &system_settings,
+            ModelRole::ImageProcessing,
+            vec![message],
+            None,
+        )
+        .await?;
 
-    let description = response
-        .choices
-        .first()
-        .and_then(|c| c.message.content.as_ref())
-        .cloned()
-        .unwrap_or_else(|| "No description found.".to_string());
+    if completion.content.is_empty() {
+        return Ok("No description found.".to_string());
+    }
 
-    Ok(description)
+    Ok(completion.content)
 }
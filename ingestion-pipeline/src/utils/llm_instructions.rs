@@ -1,6 +1,13 @@
+use common::storage::types::entity_fact::EntityAttributeKind;
 use serde_json::json;
 
-pub fn get_ingress_analysis_schema() -> serde_json::Value {
+/// Structured-output schema for enrichment. `entity_types` restricts the
+/// `entity_type` of every extracted entity to the user's built-in and custom types,
+/// and `relationship_types` does the same for relationships.
+pub fn get_ingress_analysis_schema(
+    entity_types: &[String],
+    relationship_types: &[String],
+) -> serde_json::Value {
     json!({
         "type": "object",
         "properties": {
@@ -12,9 +19,30 @@ pub fn get_ingress_analysis_schema() -> serde_json::Value {
                         "key": { "type": "string" },
                         "name": { "type": "string" },
                         "description": { "type": "string" },
-                        "entity_type": { "type": "string" }
+                        "entity_type": { "type": "string", "enum": entity_types },
+                        "tags": {
+                            "type": "array",
+                            "items": { "type": "string" }
+                        },
+                        "attributes": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "kind": {
+                                        "type": "string",
+                                        "enum": EntityAttributeKind::variants()
+                                    },
+                                    "label": { "type": "string" },
+                                    "value": { "type": "string" },
+                                    "unit": { "type": ["string", "null"] }
+                                },
+                                "required": ["kind", "label", "value", "unit"],
+                                "additionalProperties": false
+                            }
+                        }
                     },
-                    "required": ["key", "name", "description", "entity_type"],
+                    "required": ["key", "name", "description", "entity_type", "tags", "attributes"],
                     "additionalProperties": false
                 }
             },
@@ -23,16 +51,81 @@ pub fn get_ingress_analysis_schema() -> serde_json::Value {
                 "items": {
                     "type": "object",
                     "properties": {
-                        "type": { "type": "string" },
+                        "type": {
+                            "type": "string",
+                            "enum": relationship_types
+                        },
                         "source": { "type": "string" },
-                        "target": { "type": "string" }
+                        "target": { "type": "string" },
+                        "weight": { "type": "number" }
                     },
-                    "required": ["type", "source", "target"],
+                    "required": ["type", "source", "target", "weight"],
+                    "additionalProperties": false
+                }
+            },
+            "tags": {
+                "type": "array",
+                "items": { "type": "string" }
+            },
+            "action_items": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "description": { "type": "string" },
+                        "owner": { "type": ["string", "null"] },
+                        "due_date": { "type": ["string", "null"] }
+                    },
+                    "required": ["description", "owner", "due_date"],
                     "additionalProperties": false
                 }
             }
         },
-        "required": ["knowledge_entities", "relationships"],
+        "required": ["knowledge_entities", "relationships", "tags", "action_items"],
         "additionalProperties": false
     })
 }
+
+/// Structured-output schema for the ingestion summary stage.
+pub fn get_content_summary_schema() -> serde_json::Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "title": { "type": "string" },
+            "overview": { "type": "string" },
+            "key_points": {
+                "type": "array",
+                "items": { "type": "string" }
+            }
+        },
+        "required": ["title", "overview", "key_points"],
+        "additionalProperties": false
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schema_restricts_entity_types_to_given_names() {
+        let types = vec!["Idea".to_string(), "Person".to_string()];
+        let schema = get_ingress_analysis_schema(&types, &[]);
+
+        assert_eq!(
+            schema.pointer("/properties/knowledge_entities/items/properties/entity_type/enum"),
+            Some(&json!(["Idea", "Person"]))
+        );
+    }
+
+    #[test]
+    fn schema_restricts_relationship_types_to_given_names() {
+        let relationship_types = vec!["RelatedTo".to_string(), "HasPart".to_string()];
+        let schema = get_ingress_analysis_schema(&[], &relationship_types);
+
+        assert_eq!(
+            schema.pointer("/properties/relationships/items/properties/type/enum"),
+            Some(&json!(["RelatedTo", "HasPart"]))
+        );
+    }
+}
@@ -4,7 +4,11 @@ mod vision;
 
 use std::path::Path;
 
-use common::{error::AppError, storage::db::SurrealDbClient, utils::config::PdfIngestMode};
+use common::{
+    error::AppError,
+    storage::db::SurrealDbClient,
+    utils::{config::PdfIngestMode, llm::LlmProviders},
+};
 
 use self::{
     render::{load_page_numbers, render_pdf_pages},
@@ -20,7 +24,7 @@ const MAX_VISION_PAGES: usize = 50;
 pub async fn extract_pdf_content(
     file_path: &Path,
     db: &SurrealDbClient,
-    client: &async_openai::Client<async_openai::config::OpenAIConfig>,
+    llm_providers: &LlmProviders,
     mode: &PdfIngestMode,
 ) -> Result<String, AppError> {
     let pdf_bytes = tokio::fs::read(file_path).await?;
@@ -49,7 +53,7 @@ pub async fn extract_pdf_content(
     }
 
     let rendered_pages = render_pdf_pages(file_path, &page_numbers).await?;
-    let combined_markdown = vision_markdown(rendered_pages, db, client).await?;
+    let combined_markdown = vision_markdown(rendered_pages, db, llm_providers).await?;
 
     Ok(post_process(&combined_markdown))
 }
@@ -1,16 +1,15 @@
 //! Vision-LLM transcription of rendered PDF pages into Markdown.
 
-use async_openai::types::chat::{
-    ChatCompletionRequestMessageContentPartImageArgs,
-    ChatCompletionRequestMessageContentPartTextArgs, ChatCompletionRequestUserMessageArgs,
-    CreateChatCompletionRequest, CreateChatCompletionRequestArgs, ImageDetail, ImageUrlArgs,
-};
 use base64::{Engine as _, engine::general_purpose::STANDARD};
 use tracing::{debug, warn};
 
 use common::{
     error::AppError,
-    storage::{db::SurrealDbClient, types::system_settings::SystemSettings},
+    storage::{
+        db::SurrealDbClient,
+        types::system_settings::{ModelRole, SystemSettings},
+    },
+    utils::llm::{ChatMessage, LlmProviders},
 };
 
 const PAGES_PER_VISION_CHUNK: usize = 4;
@@ -23,10 +22,9 @@ const PDF_MARKDOWN_PROMPT_RETRY: &str = "You must transcribe the provided PDF pa
 pub(super) async fn vision_markdown(
     rendered_pages: Vec<Vec<u8>>,
     db: &SurrealDbClient,
-    client: &async_openai::Client<async_openai::config::OpenAIConfig>,
+    llm_providers: &LlmProviders,
 ) -> Result<String, AppError> {
     let settings = SystemSettings::get_current(db).await?;
-    let model = settings.image_processing_model;
 
     debug!(
         pages = rendered_pages.len(),
@@ -37,7 +35,8 @@ pub(super) async fn vision_markdown(
 
     for (batch_idx, chunk) in rendered_pages.chunks(PAGES_PER_VISION_CHUNK).enumerate() {
         let encoded_images = encode_batch(batch_idx, chunk);
-        let markdown = transcribe_batch(client, &model, batch_idx, &encoded_images).await?;
+        let markdown =
+            transcribe_batch(llm_providers, &settings, batch_idx, &encoded_images).await?;
         markdown_sections.push(markdown);
     }
 
@@ -74,32 +73,29 @@ fn encode_batch(batch_idx: usize, chunk: &[Vec<u8>]) -> Vec<String> {
 
 /// Requests Markdown for a single batch, retrying with a stronger prompt on low-quality output.
 async fn transcribe_batch(
-    client: &async_openai::Client<async_openai::config::OpenAIConfig>,
-    model: &str,
+    llm_providers: &LlmProviders,
+    settings: &SystemSettings,
     batch_idx: usize,
     encoded_images: &[String],
 ) -> Result<String, AppError> {
     let last_attempt = MAX_VISION_ATTEMPTS.saturating_sub(1);
 
     for attempt in 0..MAX_VISION_ATTEMPTS {
-        let request = build_request(model, prompt_for_attempt(attempt), encoded_images)?;
-
-        let response = client.chat().create(request).await?;
-        let Some(choice) = response.choices.first() else {
+        let message =
+            ChatMessage::user(prompt_for_attempt(attempt)).with_images(encoded_images.to_vec());
+
+        let completion = llm_providers
+            .cached()
+            .complete(settings, ModelRole::ImageProcessing, vec![message], None)
+            .await?;
+        let content = completion.content.as_str();
+        if content.is_empty() {
             warn!(
                 batch = batch_idx,
-                attempt, "Vision response contained zero choices"
+                attempt, "Vision response missing content"
             );
             continue;
-        };
-
-        let Some(content) = choice.message.content.as_ref() else {
-            warn!(
-                batch = batch_idx,
-                attempt, "Vision response missing content field"
-            );
-            continue;
-        };
+        }
 
         debug!(
             batch = batch_idx,
@@ -130,46 +126,6 @@ async fn transcribe_batch(
     ))
 }
 
-/// Builds the chat-completion request carrying the prompt and the batch's images.
-fn build_request(
-    model: &str,
-    prompt_text: &str,
-    encoded_images: &[String],
-) -> Result<CreateChatCompletionRequest, AppError> {
-    let mut content_parts = Vec::with_capacity(encoded_images.len().saturating_add(1));
-    content_parts.push(
-        ChatCompletionRequestMessageContentPartTextArgs::default()
-            .text(prompt_text)
-            .build()?
-            .into(),
-    );
-
-    for encoded in encoded_images {
-        let image_url = format!("data:image/png;base64,{encoded}");
-        content_parts.push(
-            ChatCompletionRequestMessageContentPartImageArgs::default()
-                .image_url(
-                    ImageUrlArgs::default()
-                        .url(image_url)
-                        .detail(ImageDetail::High)
-                        .build()?,
-                )
-                .build()?
-                .into(),
-        );
-    }
-
-    let request = CreateChatCompletionRequestArgs::default()
-        .model(model)
-        .messages([ChatCompletionRequestUserMessageArgs::default()
-            .content(content_parts)
-            .build()?
-            .into()])
-        .build()?;
-
-    Ok(request)
-}
-
 /// Logs a truncated preview of a model response at debug level.
 fn log_preview(batch_idx: usize, attempt: usize, content: &str) {
     let preview: String = if content.len() > 500 {
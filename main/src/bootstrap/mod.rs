@@ -12,6 +12,7 @@ use common::{
     utils::{
         config::{AppConfig, get_config},
         embedding::{EmbeddingProvider, align_fastembed_system_settings},
+        llm::{LlmProviders, ResponseCache},
     },
 };
 use retrieval_pipeline::reranking::RerankerPool;
@@ -20,6 +21,7 @@ use tracing_subscriber::{EnvFilter, fmt, prelude::*};
 pub struct SharedServices {
     pub db: Arc<SurrealDbClient>,
     pub openai_client: Arc<Client<async_openai::config::OpenAIConfig>>,
+    pub llm_providers: Arc<LlmProviders>,
     pub embedding_provider: Arc<EmbeddingProvider>,
     pub storage: StorageManager,
     pub reranker_pool: Option<Arc<RerankerPool>>,
@@ -64,6 +66,13 @@ pub(crate) async fn init_with_config(config: AppConfig) -> anyhow::Result<Shared
             .with_api_base(&config.openai_base_url),
     ));
 
+    let mut llm_providers = LlmProviders::from_config(&config, Arc::clone(&openai_client))
+        .with_usage_log(Arc::clone(&db));
+    if let Some(cache) = ResponseCache::from_config(&config, Arc::clone(&db)) {
+        llm_providers = llm_providers.with_response_cache(Arc::new(cache));
+    }
+    let llm_providers = Arc::new(llm_providers);
+
     let embedding_provider = Arc::new(
         EmbeddingProvider::from_system_settings(
             &settings,
@@ -83,6 +92,7 @@ pub(crate) async fn init_with_config(config: AppConfig) -> anyhow::Result<Shared
     Ok(SharedServices {
         db,
         openai_client,
+        llm_providers,
         embedding_provider,
         storage,
         reranker_pool,
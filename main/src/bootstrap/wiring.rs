@@ -31,6 +31,7 @@ pub fn build_api_state(services: &SharedServices) -> ApiState {
         db: Arc::clone(&services.db),
         config: services.config.clone(),
         storage: services.storage.clone(),
+        embedding_provider: Arc::clone(&services.embedding_provider),
     }
 }
 
@@ -47,6 +48,7 @@ pub async fn build_html_state(services: &SharedServices) -> anyhow::Result<HtmlS
     Ok(HtmlState::new_with_resources(StateResources {
         db: Arc::clone(&services.db),
         openai_client: Arc::clone(&services.openai_client),
+        llm_providers: Arc::clone(&services.llm_providers),
         session_store,
         storage: services.storage.clone(),
         config: services.config.clone(),
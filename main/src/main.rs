@@ -7,7 +7,7 @@ use bootstrap::{
     EmbeddingRuntimeRole, init, prepare_embedding_runtime,
     wiring::{build_api_state, build_html_state, minne_routes},
 };
-use ingestion_pipeline::{pipeline::IngestionPipeline, run_worker_loop};
+use ingestion_pipeline::{WorkerOptions, pipeline::IngestionPipeline, run_worker_loop};
 use tracing::info;
 
 #[tokio::main]
@@ -39,10 +39,10 @@ async fn main() -> anyhow::Result<()> {
     let listener = tokio::net::TcpListener::bind(serve_address).await?;
 
     let worker_db = Arc::clone(&services.db);
-    let worker_openai = Arc::clone(&services.openai_client);
+    let worker_llm = Arc::clone(&services.llm_providers);
     let worker_embedding = Arc::clone(&services.embedding_provider);
     let worker_config = services.config.clone();
-    let index_rebuild_interval_secs = worker_config.index_rebuild_interval_secs;
+    let worker_options = WorkerOptions::from_config(&worker_config);
     let worker_reranker = services.reranker_pool.clone();
     let worker_storage = services.storage.clone();
 
@@ -52,14 +52,14 @@ async fn main() -> anyhow::Result<()> {
 
         let ingestion_pipeline = Arc::new(IngestionPipeline::new(
             Arc::clone(&worker_db),
-            worker_openai,
+            worker_llm,
             worker_config,
             worker_reranker,
             worker_storage,
             worker_embedding,
         )?);
 
-        run_worker_loop(worker_db, ingestion_pipeline, index_rebuild_interval_secs).await
+        run_worker_loop(worker_db, ingestion_pipeline, worker_options).await
     });
 
     tokio::select! {
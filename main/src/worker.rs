@@ -3,7 +3,7 @@ mod bootstrap;
 use std::sync::Arc;
 
 use bootstrap::{EmbeddingRuntimeRole, init, prepare_embedding_runtime};
-use ingestion_pipeline::{pipeline::IngestionPipeline, run_worker_loop};
+use ingestion_pipeline::{WorkerOptions, pipeline::IngestionPipeline, run_worker_loop};
 use tracing::info;
 
 #[tokio::main]
@@ -19,19 +19,15 @@ async fn main() -> anyhow::Result<()> {
 
     let ingestion_pipeline = Arc::new(IngestionPipeline::new(
         Arc::clone(&services.db),
-        Arc::clone(&services.openai_client),
+        Arc::clone(&services.llm_providers),
         services.config.clone(),
         services.reranker_pool.clone(),
         services.storage,
         Arc::clone(&services.embedding_provider),
     )?);
 
-    run_worker_loop(
-        services.db,
-        ingestion_pipeline,
-        services.config.index_rebuild_interval_secs,
-    )
-    .await
+    let options = WorkerOptions::from_config(&services.config);
+    run_worker_loop(services.db, ingestion_pipeline, options).await
 }
 
 #[cfg(test)]
@@ -51,7 +47,7 @@ mod tests {
 
         let pipeline = IngestionPipeline::new(
             Arc::clone(&services.db),
-            Arc::clone(&services.openai_client),
+            Arc::clone(&services.llm_providers),
             services.config.clone(),
             services.reranker_pool.clone(),
             services.storage,
@@ -73,8 +69,14 @@ mod tests {
 
         let db = Arc::clone(&services.db);
         let pipeline = Arc::new(pipeline);
-        let worker =
-            tokio::spawn(async move { ingestion_pipeline::run_worker_loop(db, pipeline, 0).await });
+        let worker = tokio::spawn(async move {
+            ingestion_pipeline::run_worker_loop(
+                db,
+                pipeline,
+                ingestion_pipeline::WorkerOptions::default(),
+            )
+            .await
+        });
 
         tokio::time::sleep(Duration::from_millis(250)).await;
         assert!(
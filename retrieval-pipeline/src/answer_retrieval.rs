@@ -1,16 +1,14 @@
 //! Chat answer assembly: retrieval context formatting and structured LLM request/response types.
 
-use async_openai::{
-    error::OpenAIError,
-    types::chat::{
-        ChatCompletionRequestSystemMessage, ChatCompletionRequestUserMessage,
-        CreateChatCompletionRequest, CreateChatCompletionRequestArgs, ResponseFormat,
-        ResponseFormatJsonSchema,
+use std::collections::HashMap;
+
+use common::{
+    storage::types::{
+        entity_fact::SourceExtractions,
+        message::{Message, format_history},
+        text_content::ContentSummary,
     },
-};
-use common::storage::types::{
-    message::{Message, format_history},
-    system_settings::SystemSettings,
+    utils::llm::{ChatMessage, StructuredOutput},
 };
 use serde::Deserialize;
 use serde_json::{Value, json};
@@ -59,23 +57,79 @@ impl LLMResponseFormat {
 }
 
 /// Convert chunk-based retrieval results to JSON format for LLM context.
-pub fn chunks_to_chat_context(chunks: &[crate::RetrievedChunk]) -> Value {
+///
+/// Chunks whose source document has an ingestion summary in `source_summaries` (keyed
+/// by source id) also carry that document's title and abstract. Facts and open action
+/// items extracted from the document (`source_extractions`) are attached the same way.
+#[allow(clippy::implicit_hasher)]
+pub fn chunks_to_chat_context(
+    chunks: &[crate::RetrievedChunk],
+    source_summaries: &HashMap<String, ContentSummary>,
+    source_extractions: &HashMap<String, SourceExtractions>,
+) -> Value {
     use crate::round_score;
 
     serde_json::json!(
         chunks
             .iter()
             .map(|chunk| {
-                serde_json::json!({
-                    "id": chunk.chunk.id,
-                    "content": chunk.chunk.chunk,
-                    "score": round_score(chunk.score),
-                })
+                let mut entry = serde_json::Map::new();
+                entry.insert("id".into(), json!(chunk.chunk.id));
+                entry.insert("content".into(), json!(chunk.chunk.chunk));
+                entry.insert("score".into(), json!(round_score(chunk.score)));
+                let source_id = &chunk.chunk.source_id;
+                let mut source = serde_json::Map::new();
+                if let Some(summary) = source_summaries.get(source_id) {
+                    source.insert("title".into(), json!(summary.title));
+                    source.insert("summary".into(), json!(summary.overview));
+                }
+                if let Some(extractions) = source_extractions.get(source_id) {
+                    if !extractions.facts.is_empty() {
+                        source.insert("facts".into(), facts_to_json(extractions));
+                    }
+                    if !extractions.action_items.is_empty() {
+                        source.insert("action_items".into(), action_items_to_json(extractions));
+                    }
+                }
+                if !source.is_empty() {
+                    entry.insert("source".into(), Value::Object(source));
+                }
+                Value::Object(entry)
             })
             .collect::<Vec<_>>()
     )
 }
 
+fn facts_to_json(extractions: &SourceExtractions) -> Value {
+    json!(
+        extractions
+            .facts
+            .iter()
+            .map(|fact| json!({
+                "entity": fact.entity_name,
+                "kind": fact.attribute.kind.as_str(),
+                "label": fact.attribute.label,
+                "value": fact.attribute.value,
+                "unit": fact.attribute.unit,
+            }))
+            .collect::<Vec<_>>()
+    )
+}
+
+fn action_items_to_json(extractions: &SourceExtractions) -> Value {
+    json!(
+        extractions
+            .action_items
+            .iter()
+            .map(|item| json!({
+                "description": item.description,
+                "owner": item.owner,
+                "due_date": item.due_date,
+            }))
+            .collect::<Vec<_>>()
+    )
+}
+
 pub fn create_user_message_with_history(
     context_json: &Value,
     history: &[Message],
@@ -101,25 +155,97 @@ pub fn create_user_message_with_history(
     )
 }
 
-pub fn create_chat_request(
-    user_message: String,
-    settings: &SystemSettings,
-) -> Result<CreateChatCompletionRequest, OpenAIError> {
-    let response_format = ResponseFormat::JsonSchema {
-        json_schema: ResponseFormatJsonSchema {
-            description: Some("Query answering AI".into()),
-            name: "query_answering_with_uuids".into(),
-            schema: get_query_response_schema(),
-            strict: Some(true),
-        },
-    };
-
-    CreateChatCompletionRequestArgs::default()
-        .model(&settings.query_model)
-        .messages([
-            ChatCompletionRequestSystemMessage::from(settings.query_system_prompt.clone()).into(),
-            ChatCompletionRequestUserMessage::from(user_message).into(),
-        ])
-        .response_format(response_format)
-        .build()
+/// System and user messages for a chat answer; `system_prompt` is already rendered.
+pub fn create_chat_messages(user_message: String, system_prompt: String) -> Vec<ChatMessage> {
+    vec![
+        ChatMessage::system(system_prompt),
+        ChatMessage::user(user_message),
+    ]
+}
+
+/// Structured output carrying the answer and the ids it references.
+pub fn chat_response_format() -> StructuredOutput {
+    StructuredOutput::new(
+        "query_answering_with_uuids",
+        "Query answering AI",
+        get_query_response_schema(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::expect_used)]
+    use std::sync::Arc;
+
+    use common::storage::types::{action_item::ActionItem, text_chunk::TextChunk};
+
+    use super::*;
+    use crate::RetrievedChunk;
+
+    #[test]
+    #[allow(clippy::indexing_slicing)]
+    fn chat_context_attaches_source_summaries() {
+        let summarized = TextChunk::new("doc-1".into(), "first".into(), "user".into());
+        let plain = TextChunk::new("doc-2".into(), "second".into(), "user".into());
+        let chunks = vec![
+            RetrievedChunk {
+                chunk: Arc::new(summarized),
+                score: 0.9,
+            },
+            RetrievedChunk {
+                chunk: Arc::new(plain),
+                score: 0.5,
+            },
+        ];
+        let summaries = HashMap::from([(
+            "doc-1".to_string(),
+            ContentSummary {
+                title: "Tidal energy".into(),
+                overview: "An essay on tidal power.".into(),
+                key_points: Vec::new(),
+            },
+        )]);
+
+        let context = chunks_to_chat_context(&chunks, &summaries, &HashMap::new());
+
+        assert_eq!(context[0]["source"]["title"], "Tidal energy");
+        assert_eq!(context[0]["source"]["summary"], "An essay on tidal power.");
+        assert!(context[0]["source"].get("action_items").is_none());
+        assert!(context[1].get("source").is_none());
+    }
+
+    #[test]
+    #[allow(clippy::indexing_slicing)]
+    fn chat_context_attaches_open_action_items() {
+        let chunks = vec![RetrievedChunk {
+            chunk: Arc::new(TextChunk::new(
+                "doc-1".into(),
+                "meeting notes".into(),
+                "user".into(),
+            )),
+            score: 0.8,
+        }];
+        let item = ActionItem::new(
+            "doc-1".into(),
+            "user".into(),
+            "Send the budget",
+            Some("Ada"),
+            Some("2026-03-31"),
+        )
+        .expect("valid action item");
+        let extractions = HashMap::from([(
+            "doc-1".to_string(),
+            SourceExtractions {
+                facts: Vec::new(),
+                action_items: vec![item],
+            },
+        )]);
+
+        let context = chunks_to_chat_context(&chunks, &HashMap::new(), &extractions);
+
+        let items = &context[0]["source"]["action_items"];
+        assert_eq!(items[0]["description"], "Send the budget");
+        assert_eq!(items[0]["due_date"], "2026-03-31");
+        assert!(context[0]["source"].get("title").is_none());
+    }
 }
@@ -86,6 +86,7 @@ mod tests {
     use anyhow::{self};
     use common::storage::indexes::ensure_runtime;
     use common::storage::types::knowledge_entity::{KnowledgeEntity, KnowledgeEntityType};
+    use common::storage::types::knowledge_relationship::KnowledgeRelationship;
     use common::storage::types::system_settings::SystemSettings;
     use common::utils::embedding::EmbeddingProvider;
     use uuid::Uuid;
@@ -272,4 +273,97 @@ mod tests {
         );
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_graph_expansion_pulls_in_related_entities() -> anyhow::Result<()> {
+        let db = setup_test_db().await?;
+        let user_id = "graph_user";
+
+        let seed_chunk = TextChunk::new(
+            "seed_source".into(),
+            "Tokio drives asynchronous Rust programs.".into(),
+            user_id.into(),
+        );
+        let neighbor_chunk = TextChunk::new(
+            "neighbor_source".into(),
+            "Mio wraps operating system event queues.".into(),
+            user_id.into(),
+        );
+        TextChunk::store_with_embedding(seed_chunk, chunk_embedding_primary(), 3, &db).await?;
+        TextChunk::store_with_embedding(neighbor_chunk, chunk_embedding_secondary(), 3, &db)
+            .await?;
+
+        let seed = KnowledgeEntity::new(
+            "seed_source".into(),
+            "Tokio".into(),
+            "Async runtime for Rust".into(),
+            KnowledgeEntityType::Document,
+            None,
+            user_id.into(),
+        );
+        let neighbor = KnowledgeEntity::new(
+            "neighbor_source".into(),
+            "Mio".into(),
+            "Low-level I/O library".into(),
+            KnowledgeEntityType::Document,
+            None,
+            user_id.into(),
+        );
+        db.store_item(seed.clone()).await?;
+        db.store_item(neighbor.clone()).await?;
+        KnowledgeRelationship::new(
+            seed.id.clone(),
+            neighbor.id.clone(),
+            user_id.into(),
+            "seed_source".into(),
+            "depends_on".into(),
+        )
+        .store_relationship(&db)
+        .await?;
+
+        let embedding_provider = test_embedding_provider();
+        let mut config = RetrievalConfig::with_entities().with_graph_expansion();
+        // Only the seed chunk is found by search; the neighbor must come from the graph.
+        config.tuning.chunk_vector_take = 1;
+        config.tuning.chunk_fts_take = 0;
+
+        let run = |config: RetrievalConfig| {
+            let params = pipeline::RetrievalParams {
+                db_client: &db,
+                embedding_provider: &embedding_provider,
+                input_text: "async runtime",
+                user_id,
+                config,
+                reranker: None,
+            };
+            pipeline::run_with_embedding(params, test_embedding())
+        };
+
+        let RetrievalOutput::WithEntities { entities, .. } = run(config.clone()).await? else {
+            anyhow::bail!("expected WithEntities output");
+        };
+        let names: Vec<&str> = entities.iter().map(|e| e.entity.name.as_str()).collect();
+        assert_eq!(names, vec!["Tokio", "Mio"]);
+        let mio = entities
+            .iter()
+            .find(|e| e.entity.name == "Mio")
+            .ok_or_else(|| anyhow::anyhow!("neighbor missing"))?;
+        assert!(mio.score < entities.first().map_or(0.0, |e| e.score));
+        assert!(
+            mio.chunks
+                .iter()
+                .any(|c| c.chunk.source_id == "neighbor_source"),
+            "Neighbor should carry its own chunks"
+        );
+
+        config.tuning.graph_relationship_types = vec!["mentions".into()];
+        let RetrievalOutput::WithEntities { entities, .. } = run(config).await? else {
+            anyhow::bail!("expected WithEntities output");
+        };
+        assert!(
+            entities.iter().all(|e| e.entity.name != "Mio"),
+            "Relationships of other types are not followed"
+        );
+        Ok(())
+    }
 }
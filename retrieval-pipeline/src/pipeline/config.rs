@@ -94,6 +94,18 @@ pub struct RetrievalTuning {
     pub chunk_rrf_vector_weight: f32,
     /// Weight applied to chunk FTS ranks in RRF.
     pub chunk_rrf_fts_weight: f32,
+    /// Top resolved entities graph expansion starts from.
+    pub graph_seed_entities: usize,
+    /// Relationship hops followed from the seed entities.
+    pub graph_hops: usize,
+    /// Score multiplier applied per hop, on top of the relationship weight.
+    pub graph_decay: f32,
+    /// Relationship types followed during expansion; empty follows every type.
+    pub graph_relationship_types: Vec<String>,
+    /// Maximum neighbor entities pulled in by graph expansion.
+    pub graph_max_neighbors: usize,
+    /// Weight of the graph neighbor ranking when fused with the chunk ranking.
+    pub graph_rrf_weight: f32,
     pub flags: RetrievalTuningFlags,
 }
 
@@ -109,6 +121,12 @@ impl Default for RetrievalTuning {
             chunk_rrf_k: 60.0,
             chunk_rrf_vector_weight: 1.0,
             chunk_rrf_fts_weight: 1.0,
+            graph_seed_entities: 3,
+            graph_hops: 1,
+            graph_decay: 0.5,
+            graph_relationship_types: Vec::new(),
+            graph_max_neighbors: 10,
+            graph_rrf_weight: 1.0,
             flags: RetrievalTuningFlags::default(),
         }
     }
@@ -118,11 +136,13 @@ impl Default for RetrievalTuning {
 ///
 /// The pipeline always performs chunk-first hybrid retrieval. Set `resolve_entities`
 /// when a caller additionally needs the `KnowledgeEntity` rows that own retrieved
-/// chunks (search, ingestion linking).
+/// chunks (search, ingestion linking), and `expand_graph` to also follow the
+/// relationships of the best entities to their neighbors and the neighbors' chunks.
 #[derive(Debug, Clone, Default)]
 pub struct RetrievalConfig {
     pub tuning: RetrievalTuning,
     pub resolve_entities: bool,
+    pub expand_graph: bool,
 }
 
 impl RetrievalConfig {
@@ -131,6 +151,14 @@ impl RetrievalConfig {
         Self {
             tuning: RetrievalTuning::default(),
             resolve_entities: true,
+            expand_graph: false,
         }
     }
+
+    /// Enables the graph expansion stage, see [`RetrievalTuning::graph_hops`].
+    #[must_use]
+    pub fn with_graph_expansion(mut self) -> Self {
+        self.expand_graph = true;
+        self
+    }
 }
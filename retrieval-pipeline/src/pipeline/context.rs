@@ -11,7 +11,7 @@ use crate::{RetrievedChunk, RetrievedEntity, reranking::RerankerLease};
 use super::{
     RetrievalParams, StageKind, StageTimings,
     config::RetrievalConfig,
-    diagnostics::{AssembleStats, Diagnostics, SearchStats},
+    diagnostics::{AssembleStats, Diagnostics, GraphStats, SearchStats},
 };
 
 /// Mutable working state threaded through every retrieval stage.
@@ -78,6 +78,12 @@ impl<'a> PipelineContext<'a> {
         }
     }
 
+    pub(crate) fn record_graph(&mut self, stats: GraphStats) {
+        if let Some(diag) = self.diagnostics.as_mut() {
+            diag.graph = Some(stats);
+        }
+    }
+
     pub(crate) fn record_assemble(&mut self, stats: AssembleStats) {
         if let Some(diag) = self.diagnostics.as_mut() {
             diag.assemble = Some(stats);
@@ -4,6 +4,7 @@ use serde::Serialize;
 #[derive(Debug, Clone, Default, Serialize)]
 pub struct Diagnostics {
     pub search: Option<SearchStats>,
+    pub graph: Option<GraphStats>,
     pub assemble: Option<AssembleStats>,
 }
 
@@ -15,6 +16,13 @@ pub struct SearchStats {
     pub fts_chunk_scores: Vec<f32>,
 }
 
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GraphStats {
+    pub seed_entities: usize,
+    pub neighbor_entities: usize,
+    pub neighbor_chunks: usize,
+}
+
 #[derive(Debug, Clone, Default, Serialize)]
 pub struct AssembleStats {
     pub chunks_selected: usize,
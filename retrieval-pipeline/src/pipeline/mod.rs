@@ -13,7 +13,8 @@ use std::time::{Duration, Instant};
 use tracing::info;
 
 use stages::{
-    ChunkAssembleStage, ChunkRerankStage, ChunkSearchStage, EmbedStage, ResolveEntitiesStage,
+    ChunkAssembleStage, ChunkRerankStage, ChunkSearchStage, EmbedStage, GraphExpandStage,
+    ResolveEntitiesStage,
 };
 
 /// Identifies a retrieval stage for timing and instrumentation.
@@ -27,16 +28,18 @@ pub enum StageKind {
     Search,
     Rerank,
     ResolveEntities,
+    ExpandGraph,
     Assemble,
 }
 
 impl StageKind {
     /// Every stage kind in canonical pipeline order.
-    pub const ALL: [StageKind; 5] = [
+    pub const ALL: [StageKind; 6] = [
         StageKind::Embed,
         StageKind::Search,
         StageKind::Rerank,
         StageKind::ResolveEntities,
+        StageKind::ExpandGraph,
         StageKind::Assemble,
     ];
 
@@ -47,6 +50,7 @@ impl StageKind {
             StageKind::Search => "search",
             StageKind::Rerank => "rerank",
             StageKind::ResolveEntities => "resolve_entities",
+            StageKind::ExpandGraph => "expand_graph",
             StageKind::Assemble => "assemble",
         }
     }
@@ -106,6 +110,9 @@ fn build_stages(config: &RetrievalConfig) -> Vec<BoxedStage> {
     if config.resolve_entities {
         stages.push(Box::new(ResolveEntitiesStage));
     }
+    if config.expand_graph {
+        stages.push(Box::new(GraphExpandStage));
+    }
     stages.push(Box::new(ChunkAssembleStage));
     stages
 }
@@ -125,6 +132,7 @@ async fn run(
         preview_truncated = input_chars > preview_len,
         preview = %input_preview_clean,
         resolve_entities = params.config.resolve_entities,
+        expand_graph = params.config.expand_graph,
         "Starting retrieval pipeline"
     );
 
@@ -1,23 +1,33 @@
 use async_trait::async_trait;
 use common::{
     error::AppError,
-    storage::types::{knowledge_entity::KnowledgeEntity, text_chunk::TextChunk},
+    storage::types::{
+        knowledge_entity::KnowledgeEntity, knowledge_relationship::KnowledgeRelationship,
+        text_chunk::TextChunk,
+    },
 };
 use fastembed::RerankResult;
-use std::{collections::HashMap, fmt::Write, sync::Arc};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    sync::Arc,
+};
 use tracing::{debug, instrument, warn};
 
 use crate::{
     RetrievedChunk, RetrievedEntity,
     query::normalize_fts_terms,
-    scoring::{RrfConfig, Scored, clamp_unit, min_max_normalize, reciprocal_rank_fusion},
+    scoring::{
+        RrfConfig, Scored, clamp_unit, fuse_ranked, min_max_normalize, reciprocal_rank_fusion,
+    },
 };
 
 use super::{
     Stage, StageKind,
     config::RetrievalTuning,
     context::PipelineContext,
-    diagnostics::{AssembleStats, SearchStats},
+    diagnostics::{AssembleStats, GraphStats, SearchStats},
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -76,6 +86,20 @@ impl Stage for ResolveEntitiesStage {
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+pub struct GraphExpandStage;
+
+#[async_trait]
+impl Stage for GraphExpandStage {
+    fn kind(&self) -> StageKind {
+        StageKind::ExpandGraph
+    }
+
+    async fn execute(&self, ctx: &mut PipelineContext<'_>) -> Result<(), AppError> {
+        expand_graph(ctx).await
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct ChunkAssembleStage;
 
@@ -319,6 +343,279 @@ pub async fn resolve_entities(ctx: &mut PipelineContext<'_>) -> Result<(), AppEr
     Ok(())
 }
 
+/// Follow the relationships of the best entities to their neighbors.
+///
+/// Seeds are the top resolved entities, or the entities owning the top chunks when entity
+/// resolution is off. Each hop scores a neighbor as `parent score * decay * relationship
+/// weight`, keeping the best path. The neighbors' own chunks closest to the query are then
+/// fused with the chunk ranking by reciprocal rank, and the neighbors are added to the
+/// entity results when those were requested.
+#[instrument(level = "trace", skip_all)]
+pub async fn expand_graph(ctx: &mut PipelineContext<'_>) -> Result<(), AppError> {
+    let tuning = ctx.config.tuning.clone();
+    let seeds = graph_seeds(ctx, tuning.graph_seed_entities).await?;
+    if seeds.is_empty() || tuning.graph_hops == 0 || tuning.graph_max_neighbors == 0 {
+        return Ok(());
+    }
+
+    let neighbor_scores = walk_graph(ctx, &seeds, &tuning).await?;
+
+    let mut ranked: Vec<(String, f32)> = neighbor_scores.into_iter().collect();
+    ranked.sort_by(|a, b| {
+        b.1.partial_cmp(&a.1)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| a.0.cmp(&b.0))
+    });
+    ranked.truncate(tuning.graph_max_neighbors);
+    if ranked.is_empty() {
+        debug!(seeds = seeds.len(), "Graph expansion found no neighbors");
+        return Ok(());
+    }
+
+    let neighbor_ids: Vec<String> = ranked.iter().map(|(id, _)| id.clone()).collect();
+    let mut loaded: HashMap<String, KnowledgeEntity> =
+        KnowledgeEntity::find_by_ids(ctx.db_client, &neighbor_ids, &ctx.user_id)
+            .await?
+            .into_iter()
+            .map(|entity| (entity.id.clone(), entity))
+            .collect();
+    let neighbors: Vec<(KnowledgeEntity, f32)> = ranked
+        .into_iter()
+        .filter_map(|(id, score)| loaded.remove(&id).map(|entity| (entity, score)))
+        .collect();
+
+    let source_scores = best_score_per_source(neighbors.iter().map(|(e, s)| (e, *s)));
+    let chunks_by_source = chunks_for_sources(ctx, &source_scores).await?;
+    let graph_ranked = rank_source_chunks(&chunks_by_source, &source_scores);
+    let neighbor_chunks = graph_ranked.len();
+
+    if !graph_ranked.is_empty() {
+        let chunk_values = std::mem::take(&mut ctx.chunk_values);
+        ctx.chunk_values = fuse_ranked(
+            chunk_values,
+            graph_ranked,
+            tuning.chunk_rrf_k,
+            tuning.graph_rrf_weight,
+        );
+    }
+
+    if ctx.config.resolve_entities {
+        let known: HashSet<String> = ctx
+            .entity_results
+            .iter()
+            .map(|retrieved| retrieved.entity.id.clone())
+            .collect();
+        let chunks_by_source: HashMap<String, Arc<Vec<RetrievedChunk>>> = chunks_by_source
+            .into_iter()
+            .map(|(source, chunks)| (source, Arc::new(chunks)))
+            .collect();
+        for (entity, score) in &neighbors {
+            if known.contains(&entity.id) {
+                continue;
+            }
+            let chunks = chunks_by_source
+                .get(&entity.source_id)
+                .cloned()
+                .unwrap_or_else(|| Arc::new(Vec::new()));
+            ctx.entity_results.push(RetrievedEntity {
+                entity: entity.clone(),
+                score: *score,
+                chunks,
+            });
+        }
+    }
+
+    debug!(
+        seeds = seeds.len(),
+        neighbors = neighbors.len(),
+        neighbor_chunks,
+        "Expanded retrieval along the knowledge graph"
+    );
+
+    if ctx.diagnostics_enabled() {
+        ctx.record_graph(GraphStats {
+            seed_entities: seeds.len(),
+            neighbor_entities: neighbors.len(),
+            neighbor_chunks,
+        });
+    }
+
+    Ok(())
+}
+
+/// Best score of the given entities per source id.
+fn best_score_per_source<'e>(
+    entities: impl Iterator<Item = (&'e KnowledgeEntity, f32)>,
+) -> HashMap<String, f32> {
+    let mut source_scores: HashMap<String, f32> = HashMap::new();
+    for (entity, score) in entities {
+        let best = source_scores
+            .entry(entity.source_id.clone())
+            .or_insert(score);
+        if score > *best {
+            *best = score;
+        }
+    }
+    source_scores
+}
+
+/// The chunks of each source closest to the query, at most `max_chunks_per_entity` each.
+async fn chunks_for_sources(
+    ctx: &PipelineContext<'_>,
+    source_scores: &HashMap<String, f32>,
+) -> Result<HashMap<String, Vec<RetrievedChunk>>, AppError> {
+    let sources: Vec<String> = source_scores.keys().cloned().collect();
+    let max_chunks = ctx.config.tuning.max_chunks_per_entity.max(1);
+    let embedding = ctx.ensure_embedding().map_err(|e| *e)?;
+    let rows = TextChunk::vector_search_in_sources(
+        sources.len().saturating_mul(max_chunks),
+        embedding,
+        &sources,
+        ctx.db_client,
+        &ctx.user_id,
+    )
+    .await?;
+
+    let mut chunks_by_source: HashMap<String, Vec<RetrievedChunk>> = HashMap::new();
+    for row in rows {
+        let attached = chunks_by_source
+            .entry(row.chunk.source_id.clone())
+            .or_default();
+        if attached.len() < max_chunks {
+            attached.push(RetrievedChunk {
+                chunk: Arc::new(row.chunk),
+                score: row.score,
+            });
+        }
+    }
+    Ok(chunks_by_source)
+}
+
+/// Ranks chunks by the score of their source, then by similarity to the query.
+fn rank_source_chunks(
+    chunks_by_source: &HashMap<String, Vec<RetrievedChunk>>,
+    source_scores: &HashMap<String, f32>,
+) -> Vec<Scored<Arc<TextChunk>>> {
+    let mut ranked: Vec<(f32, &RetrievedChunk)> = chunks_by_source
+        .iter()
+        .flat_map(|(source, chunks)| {
+            let score = source_scores.get(source).copied().unwrap_or(0.0);
+            chunks.iter().map(move |chunk| (score, chunk))
+        })
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.0.partial_cmp(&a.0)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| b.1.score.partial_cmp(&a.1.score).unwrap_or(Ordering::Equal))
+            .then_with(|| a.1.chunk.id.cmp(&b.1.chunk.id))
+    });
+    ranked
+        .into_iter()
+        .map(|(_, chunk)| Scored::new(Arc::clone(&chunk.chunk)).with_vector_score(chunk.score))
+        .collect()
+}
+
+/// Scores the entities within `graph_hops` of `seeds`, keeping each one's best path score.
+async fn walk_graph(
+    ctx: &PipelineContext<'_>,
+    seeds: &[(String, f32)],
+    tuning: &RetrievalTuning,
+) -> Result<HashMap<String, f32>, AppError> {
+    let decay = clamp_unit(tuning.graph_decay);
+    let mut visited: HashSet<String> = seeds.iter().map(|(id, _)| id.clone()).collect();
+    let mut frontier: HashMap<String, f32> = seeds.iter().cloned().collect();
+    let mut neighbor_scores: HashMap<String, f32> = HashMap::new();
+
+    for _ in 0..tuning.graph_hops {
+        if frontier.is_empty() {
+            break;
+        }
+        let frontier_ids: Vec<String> = frontier.keys().cloned().collect();
+        let edges = KnowledgeRelationship::find_adjacent(
+            &frontier_ids,
+            &ctx.user_id,
+            &tuning.graph_relationship_types,
+            ctx.db_client,
+        )
+        .await?;
+
+        let mut next: HashMap<String, f32> = HashMap::new();
+        for edge in &edges {
+            for (from, to) in [(&edge.in_, &edge.out), (&edge.out, &edge.in_)] {
+                let Some(parent) = frontier.get(from) else {
+                    continue;
+                };
+                if visited.contains(to) {
+                    continue;
+                }
+                let score = parent * decay * edge.metadata.weight;
+                let best = next.entry(to.clone()).or_insert(score);
+                if score > *best {
+                    *best = score;
+                }
+            }
+        }
+
+        visited.extend(next.keys().cloned());
+        neighbor_scores.extend(next.iter().map(|(id, score)| (id.clone(), *score)));
+        frontier = next;
+    }
+
+    Ok(neighbor_scores)
+}
+
+/// Entity ids and scores graph expansion starts from, best first.
+async fn graph_seeds(
+    ctx: &PipelineContext<'_>,
+    take: usize,
+) -> Result<Vec<(String, f32)>, AppError> {
+    if !ctx.entity_results.is_empty() {
+        let mut seen = HashSet::new();
+        return Ok(ctx
+            .entity_results
+            .iter()
+            .filter(|retrieved| seen.insert(retrieved.entity.id.clone()))
+            .take(take)
+            .map(|retrieved| (retrieved.entity.id.clone(), retrieved.score))
+            .collect());
+    }
+
+    let mut source_order: Vec<String> = Vec::new();
+    let mut best_score: HashMap<String, f32> = HashMap::new();
+    for scored in &ctx.chunk_values {
+        if !best_score.contains_key(&scored.item.source_id) {
+            best_score.insert(scored.item.source_id.clone(), scored.fused);
+            source_order.push(scored.item.source_id.clone());
+        }
+    }
+    if source_order.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut entities_by_source: HashMap<String, Vec<String>> = HashMap::new();
+    for entity in
+        KnowledgeEntity::find_by_source_ids(ctx.db_client, &source_order, &ctx.user_id).await?
+    {
+        entities_by_source
+            .entry(entity.source_id)
+            .or_default()
+            .push(entity.id);
+    }
+
+    Ok(source_order
+        .iter()
+        .flat_map(|source| {
+            let score = best_score.get(source).copied().unwrap_or(0.0);
+            entities_by_source
+                .remove(source)
+                .unwrap_or_default()
+                .into_iter()
+                .map(move |id| (id, score))
+        })
+        .take(take)
+        .collect())
+}
+
 #[instrument(level = "trace", skip_all)]
 #[allow(clippy::result_large_err)]
 pub fn assemble_chunks(ctx: &mut PipelineContext<'_>) -> Result<(), AppError> {
@@ -415,7 +712,7 @@ fn apply_chunk_rerank_results(
     };
 
     let mut reranked: Vec<Scored<Arc<TextChunk>>> = Vec::with_capacity(remaining.len());
-    for (result, normalized) in results.into_iter().zip(normalized_scores.into_iter()) {
+    for (result, normalized) in results.into_iter().zip(normalized_scores) {
         if let Some(slot) = remaining.get_mut(result.index) {
             if let Some(mut candidate) = slot.take() {
                 let original = candidate.fused;
@@ -245,3 +245,59 @@ where
     sort_by_fused_desc(&mut fused);
     fused
 }
+
+/// Fuse an already ranked list with a second ranking using reciprocal rank fusion.
+///
+/// Unlike [`reciprocal_rank_fusion`] both lists are taken in the order given, which suits
+/// rankings without a subscore of their own (e.g. reranked chunks and graph neighbors).
+/// Items in `primary` contribute `1 / (k + rank + 1)`, items in `secondary`
+/// `secondary_weight / (k + rank + 1)`; subscores of items present in both are merged.
+pub fn fuse_ranked<T>(
+    primary: Vec<Scored<T>>,
+    secondary: Vec<Scored<T>>,
+    k: f32,
+    secondary_weight: f32,
+) -> Vec<Scored<T>>
+where
+    T: RetrievalCandidate,
+{
+    let k = if k <= 0.0 { 60.0 } else { k };
+    let secondary_weight = if secondary_weight.is_finite() {
+        secondary_weight.max(0.0)
+    } else {
+        0.0
+    };
+
+    let mut merged: HashMap<String, Scored<T>> = HashMap::new();
+    let ranked = [(primary, 1.0), (secondary, secondary_weight)];
+    for (list, weight) in ranked {
+        for (rank, candidate) in list.into_iter().enumerate() {
+            let rank_f32: f32 = u16::try_from(rank).map_or(f32::MAX, f32::from);
+            let contribution = weight / (k + rank_f32 + 1.0);
+            match merged.entry(candidate.item.candidate_id().to_owned()) {
+                Entry::Occupied(mut occupied) => {
+                    let entry = occupied.get_mut();
+                    entry.scores.vector = max_option(entry.scores.vector, candidate.scores.vector);
+                    entry.scores.fts = max_option(entry.scores.fts, candidate.scores.fts);
+                    entry.fused += contribution;
+                }
+                Entry::Vacant(vacant) => {
+                    let mut scored = candidate;
+                    scored.fused = contribution;
+                    vacant.insert(scored);
+                }
+            }
+        }
+    }
+
+    let mut fused: Vec<Scored<T>> = merged.into_values().collect();
+    sort_by_fused_desc(&mut fused);
+    fused
+}
+
+fn max_option(a: Option<f32>, b: Option<f32>) -> Option<f32> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    }
+}